@@ -0,0 +1,70 @@
+#!/usr/bin/env python3
+"""A bot for `--bot`, heading for the nearest food it can reach without running into anything.
+
+Reads a board as a line of JSON before every tick, and answers with a line of its own: the
+direction to turn to, or an empty line to go straight on. Run it with
+
+    cargo run -- --bot "python3 examples/bot.py"
+
+or against the built-in bots with
+
+    cargo run --release -- tournament --bot "python3 examples/bot.py"
+"""
+
+import json
+import sys
+from collections import deque
+
+STEPS = {"up": (0, -1), "down": (0, 1), "left": (-1, 0), "right": (1, 0)}
+
+
+def neighbour(board, x, y, step):
+    x, y = x + step[0], y + step[1]
+    if board["wall_mode"] == "Wrap":
+        return x % board["width"], y % board["height"]
+    if 0 <= x < board["width"] and 0 <= y < board["height"]:
+        return x, y
+    return None
+
+
+def blocked_cells(board):
+    blocked = {(cell["x"], cell["y"]) for cell in board["obstacles"]}
+    for snake in board["snakes"]:
+        if snake["alive"]:
+            # The tail moves out of the way on the next tick.
+            blocked.update((cell["x"], cell["y"]) for cell in snake["cells"][:-1])
+    blocked.update((food["x"], food["y"]) for food in board["foods"] if food["poison"])
+    return blocked
+
+
+def answer(board):
+    me = board["snakes"][board["you"]]
+    head = me["cells"][0]
+    start = (head["x"], head["y"])
+    blocked = blocked_cells(board)
+    foods = {(food["x"], food["y"]) for food in board["foods"] if not food["poison"]}
+
+    # Breadth first from the head, remembering the first step taken to get to every cell.
+    first_step = {}
+    queue = deque()
+    for name, step in STEPS.items():
+        cell = neighbour(board, *start, step)
+        if cell is not None and cell not in blocked and cell not in first_step:
+            first_step[cell] = name
+            queue.append(cell)
+    while queue:
+        cell = queue.popleft()
+        if cell in foods:
+            return first_step[cell]
+        for step in STEPS.values():
+            next_cell = neighbour(board, *cell, step)
+            if next_cell is not None and next_cell not in blocked and next_cell not in first_step:
+                first_step[next_cell] = first_step[cell]
+                queue.append(next_cell)
+
+    # No food in reach: any free cell next to the head will do, or straight on if there is none.
+    return next(iter(first_step.values()), "")
+
+
+for line in sys.stdin:
+    print(answer(json.loads(line)), flush=True)
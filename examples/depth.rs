@@ -0,0 +1,82 @@
+//! Draws a small board without a window with a quad over the whole of it, drawn before the
+//! board but in front of it, and checks that the quad is what shows: that the depth buffer,
+//! rather than the order they are drawn in, puts the text over the board. Exits with an error
+//! if the board shows instead.
+
+use std::process;
+
+use image::RgbaImage;
+use snake_game::game::{Game, GameConfig};
+use snake_game::headless::HeadlessRenderer;
+use snake_game::mesh::{Mesh, MeshKind};
+use snake_game::palette::Palette;
+
+/// How big the frame is, in pixels.
+const SIZE: [u32; 2] = [96, 80];
+
+/// The color of the quad, which nothing on the board is drawn in. Its channels are all either
+/// off or on, so they come out the same whether they are encoded for the screen or not.
+const MAGENTA: [f32; 4] = [1.0, 0.0, 1.0, 1.0];
+
+/// How far apart a channel of the middle of the frame can be from the quad's with the quad
+/// still counted as showing.
+const TOLERANCE: u8 = 8;
+
+fn main() {
+    let game = Game::with_config(GameConfig::new(8, 6).with_seed(7));
+    let [width, height] = [game.width() as f32, game.height() as f32];
+
+    let mut failed = false;
+    for mesh_kind in MeshKind::ALL {
+        let mut renderer = match HeadlessRenderer::new(SIZE, mesh_kind, Palette::default()) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("Failed to draw without a window: {}", e);
+                process::exit(1);
+            }
+        };
+        let mut hud = Mesh::new(mesh_kind);
+        hud.push_quad(
+            [[0.0, 0.0], [width, 0.0], [width, height], [0.0, height]],
+            MAGENTA,
+        );
+
+        let without = middle(&renderer.render_frame(&game));
+        let with = middle(&renderer.render_frame_with_hud(&game, &hud));
+        if is_magenta(without) {
+            eprintln!(
+                "{:?}: the board itself is magenta, {:?}",
+                mesh_kind, without
+            );
+            failed = true;
+        } else if !is_magenta(with) {
+            eprintln!(
+                "{:?}: the board shows over the quad, {:?} rather than magenta",
+                mesh_kind, with
+            );
+            failed = true;
+        } else {
+            println!("{:?}: ok", mesh_kind);
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// The pixel in the middle of `frame`, over the middle of the board.
+fn middle(frame: &RgbaImage) -> [u8; 4] {
+    let [width, height] = SIZE;
+    let at = ((height / 2 * width + width / 2) * 4) as usize;
+    let mut pixel = [0; 4];
+    pixel.copy_from_slice(&frame.as_raw()[at..at + 4]);
+    pixel
+}
+
+/// Whether `pixel` is no further than [`TOLERANCE`] from [`MAGENTA`] in any color channel.
+fn is_magenta(pixel: [u8; 4]) -> bool {
+    pixel[..3]
+        .iter()
+        .zip(MAGENTA)
+        .all(|(&channel, wanted)| channel.abs_diff((wanted * 255.0) as u8) <= TOLERANCE)
+}
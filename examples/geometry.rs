@@ -0,0 +1,89 @@
+//! Draws a thousand frames of a big board without a window, every one of them a step further
+//! into a game, and checks that the pools the meshes are drawn from stop growing once the
+//! first frames were drawn. Pools that grow after that exit with an error, saying by how much
+//! and on which frame they first did.
+//!
+//! This is the stress test of the pools `FrameGeometry::upload` in `src/vulkan.rs` takes from,
+//! which needs a GPU and so can't run with the rest of the tests.
+
+use std::process;
+
+use snake_game::game::{Game, GameConfig};
+use snake_game::headless::HeadlessRenderer;
+use snake_game::mesh::MeshKind;
+use snake_game::palette::Palette;
+
+/// How big the frame is, in pixels.
+const SIZE: [u32; 2] = [640, 480];
+
+/// How many cells the board is across and down.
+const BOARD: [u32; 2] = [64, 48];
+
+const FRAMES: usize = 1000;
+
+/// How many frames are drawn before the pools are taken to be as big as they need to be.
+const WARM_UP: usize = 100;
+
+fn main() {
+    let mut failed = false;
+    for mesh_kind in MeshKind::ALL {
+        let mut renderer = match HeadlessRenderer::new(SIZE, mesh_kind, Palette::default()) {
+            Ok(renderer) => renderer,
+            Err(e) => {
+                eprintln!("Failed to draw without a window: {}", e);
+                process::exit(1);
+            }
+        };
+
+        let mut seed = 0;
+        let mut game = new_game(seed);
+        let mut warmed_up = 0;
+        // The first frame after the warm up that found the pools bigger than the one before.
+        let mut grew_on = None;
+        for frame in 0..FRAMES {
+            // Games that are over start again with other food and obstacles, so the meshes
+            // keep changing size.
+            if game.is_over() {
+                seed += 1;
+                game = new_game(seed);
+            }
+            game.step();
+            let before = renderer.geometry_capacity();
+            renderer.render_frame(&game);
+            let after = renderer.geometry_capacity();
+            if frame + 1 == WARM_UP {
+                warmed_up = after;
+            } else if frame >= WARM_UP && after > before && grew_on.is_none() {
+                grew_on = Some(frame);
+            }
+        }
+
+        let capacity = renderer.geometry_capacity();
+        if let Some(frame) = grew_on {
+            eprintln!(
+                "{:?}: the pools grew from {} to {} bytes after the first {} frames, first on \
+                 frame {}",
+                mesh_kind, warmed_up, capacity, WARM_UP, frame
+            );
+            failed = true;
+        } else {
+            println!(
+                "{:?}: ok, {} frames drawn from {} bytes",
+                mesh_kind, FRAMES, capacity
+            );
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
+
+/// A game on the big board, with obstacles and plenty of food to draw.
+fn new_game(seed: u64) -> Game {
+    let [width, height] = BOARD;
+    let config = GameConfig::new(width, height)
+        .with_seed(seed)
+        .with_obstacle_density(0.1)
+        .with_food_count(40);
+    Game::with_config(config)
+}
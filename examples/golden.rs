@@ -0,0 +1,112 @@
+//! Draws a small board without a window and checks it against the image it looked like last
+//! time, `examples/golden/tiny-board-seed-7.png` for the board of [`SEED`], saying how far apart
+//! they are. A board that looks any different exits with an error, having saved what it looks
+//! like now next to the reference as `tiny-board-seed-7.actual.png`.
+//!
+//! `--bless` saves the board as the reference instead, for when it is meant to look different.
+//! The reference is named after the seed, so a board played from another seed is never checked
+//! against the one blessed for the last.
+
+use std::env;
+use std::process;
+
+use image::RgbaImage;
+use snake_game::game::{Direction, Game, GameConfig};
+use snake_game::headless::HeadlessRenderer;
+use snake_game::mesh::MeshKind;
+use snake_game::palette::Palette;
+
+/// The seed the board is played from: the food is in the same places every time.
+const SEED: u64 = 7;
+
+/// How big the frame is, in pixels: a few for each cell, and not a square, so the board
+/// doesn't fill it.
+const SIZE: [u32; 2] = [96, 80];
+
+/// How far apart a channel of two pixels can be with the pixels still counted the same. GPUs
+/// don't all blend and filter to the same bit.
+const TOLERANCE: u8 = 8;
+
+/// How many of the pixels can be counted different with the boards still the same, out of a
+/// thousand. GPUs don't all cover the pixels on the edge of a sprite the same either.
+const MAX_DIFFERENT: usize = 5;
+
+fn main() {
+    let bless = env::args().any(|arg| arg == "--bless");
+    let reference_path = format!("examples/golden/tiny-board-seed-{}.png", SEED);
+    let actual_path = format!("examples/golden/tiny-board-seed-{}.actual.png", SEED);
+
+    // A seeded game, a few steps in.
+    let config = GameConfig::new(8, 6).with_seed(SEED);
+    let mut game = Game::with_config(config);
+    game.step();
+    game.turn(Direction::Up);
+    game.step();
+
+    // Instanced, every cell is drawn from the square uploaded to the GPU's own memory through a
+    // staging copy, so a square that didn't make it there shows too.
+    let mut renderer = match HeadlessRenderer::new(SIZE, MeshKind::Instanced, Palette::default()) {
+        Ok(renderer) => renderer,
+        Err(e) => {
+            eprintln!("Failed to draw without a window: {}", e);
+            process::exit(1);
+        }
+    };
+    let frame = renderer.render_frame(&game);
+
+    if bless {
+        frame.save(&reference_path).unwrap();
+        println!("Saved {}", reference_path);
+        return;
+    }
+    let reference = match image::open(&reference_path) {
+        Ok(reference) => reference.into_rgba8(),
+        Err(e) => {
+            eprintln!(
+                "Failed to read {}: {} (run with --bless to save it)",
+                reference_path, e
+            );
+            process::exit(1);
+        }
+    };
+    match different_pixels(&frame, &reference) {
+        Some(different) if different * 1000 <= frame.as_raw().len() / 4 * MAX_DIFFERENT => {
+            println!("ok: {} pixels differ", different);
+        }
+        different => {
+            frame.save(&actual_path).unwrap();
+            match different {
+                Some(different) => eprintln!("{} pixels differ", different),
+                None => eprintln!(
+                    "the board is {}x{}, but {} is {}x{}",
+                    frame.width(),
+                    frame.height(),
+                    reference_path,
+                    reference.width(),
+                    reference.height()
+                ),
+            }
+            eprintln!("The board drawn now is in {}", actual_path);
+            process::exit(1);
+        }
+    }
+}
+
+/// How many pixels of `a` are further than [`TOLERANCE`] from the same pixels of `b` in any
+/// channel, or `None` if the images aren't the same size.
+fn different_pixels(a: &RgbaImage, b: &RgbaImage) -> Option<usize> {
+    if (a.width(), a.height()) != (b.width(), b.height()) {
+        return None;
+    }
+    let different = a
+        .as_raw()
+        .chunks(4)
+        .zip(b.as_raw().chunks(4))
+        .filter(|(a, b)| {
+            a.iter()
+                .zip(b.iter())
+                .any(|(a, b)| a.abs_diff(*b) > TOLERANCE)
+        })
+        .count();
+    Some(different)
+}
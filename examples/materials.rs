@@ -0,0 +1,74 @@
+//! Builds the pipeline of every material the first GPU draws, for every kind of mesh, without
+//! opening a window, checking that each material gets a pipeline of its own and keeps getting
+//! the same one until they are all built again. Building one that doesn't work panics.
+
+use std::process;
+use std::sync::Arc;
+
+use snake_game::mesh::MeshKind;
+use snake_game::pipelines::PipelineRegistry;
+use snake_game::renderer::MaterialKind;
+use snake_game::vulkan::{choose_depth_format, create_render_pass};
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::{Device, DeviceExtensions, Features};
+use vulkano::format::Format;
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::Version;
+
+fn main() {
+    let instance = match Instance::new(None, Version::V1_1, &InstanceExtensions::none(), None) {
+        Ok(instance) => instance,
+        Err(e) => {
+            eprintln!("Failed to start Vulkan: {}", e);
+            process::exit(1);
+        }
+    };
+    let (physical, family) = match PhysicalDevice::enumerate(&instance).find_map(|p| {
+        p.queue_families()
+            .find(|q| q.supports_graphics())
+            .map(|q| (p, q))
+    }) {
+        Some(found) => found,
+        None => {
+            eprintln!("No GPU draws graphics");
+            process::exit(1);
+        }
+    };
+    // Wireframes are only drawn by devices that can, as in the game.
+    let features = Features {
+        fill_mode_non_solid: physical.supported_features().fill_mode_non_solid,
+        ..Features::none()
+    };
+    let (device, _queues) = Device::new(
+        physical,
+        &features,
+        &DeviceExtensions::none(),
+        [(family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+    let depth_format = choose_depth_format(physical);
+    let render_pass = create_render_pass(&device, Format::B8G8R8A8_SRGB, 1, depth_format);
+
+    for mesh_kind in MeshKind::ALL {
+        let mut pipelines = PipelineRegistry::new(device.clone(), render_pass.clone(), mesh_kind);
+        let materials: Vec<_> = MaterialKind::ALL
+            .into_iter()
+            .filter(|&material| pipelines.supports(material))
+            .collect();
+        let built: Vec<_> = materials.iter().map(|&m| pipelines.get(m)).collect();
+        for (i, pipeline) in built.iter().enumerate() {
+            assert!(
+                built[..i].iter().all(|other| !Arc::ptr_eq(other, pipeline)),
+                "{:?} shares a pipeline",
+                materials[i]
+            );
+            assert!(Arc::ptr_eq(&pipelines.get(materials[i]), pipeline));
+        }
+
+        pipelines.rebuild_all().unwrap();
+        for (&material, pipeline) in materials.iter().zip(&built) {
+            assert!(!Arc::ptr_eq(&pipelines.get(material), pipeline));
+        }
+        println!("{:?}: ok, drawing {:?}", mesh_kind, materials);
+    }
+}
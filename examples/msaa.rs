@@ -0,0 +1,54 @@
+//! Builds the render pass the board is drawn with at every number of samples a pixel the first
+//! GPU takes, without opening a window, saying which it built. Building one that doesn't work
+//! panics.
+
+use std::process;
+
+use snake_game::renderer::SAMPLE_COUNTS;
+use snake_game::vulkan::{choose_depth_format, create_render_pass, max_samples};
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::{Device, DeviceExtensions, Features};
+use vulkano::format::Format;
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::Version;
+
+fn main() {
+    let instance = match Instance::new(None, Version::V1_1, &InstanceExtensions::none(), None) {
+        Ok(instance) => instance,
+        Err(e) => {
+            eprintln!("Failed to start Vulkan: {}", e);
+            process::exit(1);
+        }
+    };
+    let (physical, family) = match PhysicalDevice::enumerate(&instance).find_map(|p| {
+        p.queue_families()
+            .find(|q| q.supports_graphics())
+            .map(|q| (p, q))
+    }) {
+        Some(found) => found,
+        None => {
+            eprintln!("No GPU draws graphics");
+            process::exit(1);
+        }
+    };
+    let (device, _queues) = Device::new(
+        physical,
+        &Features::none(),
+        &DeviceExtensions::none(),
+        [(family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+
+    let max = max_samples(physical);
+    let depth_format = choose_depth_format(physical);
+    println!(
+        "{} takes up to {} samples a pixel, with a {:?} depth buffer",
+        physical.properties().device_name,
+        max,
+        depth_format
+    );
+    for samples in SAMPLE_COUNTS.into_iter().filter(|&samples| samples <= max) {
+        create_render_pass(&device, Format::B8G8R8A8_SRGB, samples, depth_format);
+        println!("{}x: ok", samples);
+    }
+}
@@ -0,0 +1,45 @@
+//! Watches a game started with `--spectate`, printing the score and length of each snake after
+//! every tick. Takes the address to connect to, `127.0.0.1:7879` by default.
+
+use std::io::{BufRead, BufReader};
+use std::net::TcpStream;
+use std::process;
+
+use snake_game::spectate::{Snapshot, DEFAULT_SPECTATE_PORT};
+
+fn main() {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| format!("127.0.0.1:{}", DEFAULT_SPECTATE_PORT));
+    let stream = match TcpStream::connect(&address) {
+        Ok(stream) => stream,
+        Err(e) => {
+            eprintln!("Failed to connect to {}: {}", address, e);
+            process::exit(1);
+        }
+    };
+
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        let snapshot: Snapshot = match serde_json::from_str(&line) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                eprintln!("Failed to read a snapshot: {}", e);
+                continue;
+            }
+        };
+        let snakes = snapshot
+            .snakes
+            .iter()
+            .enumerate()
+            .map(|(i, snake)| {
+                format!(
+                    "player {}: score {}, length {}",
+                    i + 1,
+                    snake.score,
+                    snake.cells.len()
+                )
+            })
+            .collect::<Vec<_>>();
+        println!("tick {}  {}", snapshot.tick, snakes.join("  "));
+    }
+}
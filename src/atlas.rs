@@ -0,0 +1,284 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+
+use serde::Deserialize;
+
+use crate::game::{Direction, FoodKind, GridPos};
+use crate::mesh::TexRect;
+
+/// Where the sprites are in the bundled atlas, `sprites/atlas.png`.
+const BUNDLED: &str = include_str!("../sprites/atlas.json");
+
+/// Every sprite the game draws packed into a single image, with the rectangle each takes in it
+/// by name, as read from a description like this one, which goes beside the image:
+///
+/// ```json
+/// {"width": 128, "height": 48,
+///  "sprites": {"head-up": [0, 0, 16, 16], "body-vertical": [0, 16, 16, 16]}}
+/// ```
+///
+/// `width` and `height` are the size of the image, and each sprite is given as the `x` and `y`
+/// of its top left corner, then its width and height, all in texels.
+///
+/// Sampling a texture blends in the texels around the spot sampled, so a sprite's rectangle
+/// stops half a texel short of each of its edges: right next to another sprite, that keeps
+/// them from bleeding into each other however small the cells are drawn.
+///
+/// ```
+/// use snake_game::atlas::Atlas;
+/// use snake_game::mesh::TexRect;
+///
+/// let atlas = Atlas::from_json(
+///     r#"{"width": 32, "height": 16, "sprites": {"segment": [16, 0, 16, 16]}}"#,
+/// )
+/// .unwrap();
+/// let segment = atlas.get("segment").unwrap();
+/// assert_eq!(segment.origin, [16.5 / 32.0, 0.5 / 16.0]);
+/// assert_eq!(segment.extent, [15.0 / 32.0, 15.0 / 16.0]);
+///
+/// // A sprite that isn't there is drawn without one.
+/// assert_eq!(atlas.get("head-up"), None);
+/// assert_eq!(atlas.sprite("head-up"), TexRect::NONE);
+///
+/// assert!(Atlas::from_json(r#"{"width": 8, "height": 8, "sprites": {"big": [0, 0, 16, 16]}}"#)
+///     .is_err());
+/// assert!(Atlas::bundled().get("food-golden").is_some());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Atlas {
+    width: u32,
+    height: u32,
+    sprites: BTreeMap<String, TexRect>,
+}
+
+#[derive(Debug)]
+pub enum AtlasError {
+    Format(serde_json::Error),
+    /// The image has no texels at all.
+    Empty,
+    /// The sprite called `name` has no texels, or doesn't fit in the image.
+    BadRegion {
+        name: String,
+    },
+}
+
+impl fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtlasError::Format(err) => write!(f, "could not read atlas: {}", err),
+            AtlasError::Empty => write!(f, "atlas image is empty"),
+            AtlasError::BadRegion { name } => write!(
+                f,
+                "sprite {:?} is empty or doesn't fit in the atlas image",
+                name
+            ),
+        }
+    }
+}
+
+impl Error for AtlasError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AtlasError::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for AtlasError {
+    fn from(err: serde_json::Error) -> AtlasError {
+        AtlasError::Format(err)
+    }
+}
+
+/// The atlas as it is written down.
+#[derive(Deserialize)]
+struct Description {
+    width: u32,
+    height: u32,
+    sprites: BTreeMap<String, [u32; 4]>,
+}
+
+impl Atlas {
+    /// Reads the description of an atlas.
+    pub fn from_json(json: &str) -> Result<Atlas, AtlasError> {
+        let description: Description = serde_json::from_str(json)?;
+        let (width, height) = (description.width, description.height);
+        if width == 0 || height == 0 {
+            return Err(AtlasError::Empty);
+        }
+
+        let mut sprites = BTreeMap::new();
+        for (name, [x, y, w, h]) in description.sprites {
+            let fits = |start: u32, size: u32, limit: u32| {
+                size > 0 && start.checked_add(size).is_some_and(|end| end <= limit)
+            };
+            if !fits(x, w, width) || !fits(y, h, height) {
+                return Err(AtlasError::BadRegion { name });
+            }
+            let (width, height) = (width as f32, height as f32);
+            let rect = TexRect {
+                origin: [(x as f32 + 0.5) / width, (y as f32 + 0.5) / height],
+                extent: [(w as f32 - 1.0) / width, (h as f32 - 1.0) / height],
+                transposed: false,
+            };
+            sprites.insert(name, rect);
+        }
+        Ok(Atlas {
+            width,
+            height,
+            sprites,
+        })
+    }
+
+    /// The atlas the game comes with.
+    pub fn bundled() -> Atlas {
+        Atlas::from_json(BUNDLED).expect("the bundled atlas is well formed")
+    }
+
+    /// The size of the image the atlas describes, which the image it is drawn with must have.
+    pub fn size(&self) -> [u32; 2] {
+        [self.width, self.height]
+    }
+
+    /// Where the sprite called `name` is, if there is one.
+    pub fn get(&self, name: &str) -> Option<TexRect> {
+        self.sprites.get(name).copied()
+    }
+
+    /// Where the sprite called `name` is, or [`TexRect::NONE`] to draw without one when the
+    /// atlas has no such sprite.
+    pub fn sprite(&self, name: &str) -> TexRect {
+        self.get(name).unwrap_or(TexRect::NONE)
+    }
+}
+
+/// The sprite the heads of snakes are drawn with, facing up. It is turned to face the other
+/// ways with [`TexRect::facing`].
+pub const HEAD_SPRITE: &str = "head-up";
+
+/// The name of the sprite food of `kind` is drawn with.
+pub fn food_sprite(kind: FoodKind) -> &'static str {
+    match kind {
+        FoodKind::Normal => "food-normal",
+        FoodKind::Golden => "food-golden",
+        FoodKind::Mega => "food-mega",
+    }
+}
+
+/// The name of the sprite the segment at `index` of a snake is drawn with, given all its
+/// `cells` from the head to the tail, and where it is `heading`.
+///
+/// The head faces away from the segment behind it and the tail tapers away from the one ahead,
+/// while every other segment is straight or turns a corner, joining the sides of its cell the
+/// segments on either side are on. Segments that wrap around the edge of the board join the
+/// side they wrap through, and so do the ones that go through a portal, as far as can be told.
+/// Anything else, such as a diagonal step or segments stacked on the same cell, is drawn as a
+/// plain `segment`.
+///
+/// ```
+/// use snake_game::atlas::segment_sprite;
+/// use snake_game::game::{Direction, GridPos};
+///
+/// // Going right, then turning up: the head is at the top of an L.
+/// let cells = [(3, 1), (3, 2), (3, 3), (2, 3), (1, 3)].map(|(x, y)| GridPos::new(x, y));
+/// let sprites: Vec<_> = (0..cells.len())
+///     .map(|i| segment_sprite(&cells, i, Direction::Up))
+///     .collect();
+/// assert_eq!(
+///     sprites,
+///     ["head-up", "body-vertical", "corner-left-up", "body-horizontal", "tail-right"]
+/// );
+///
+/// // Wrapping around from the right edge of a 10 cell wide board to the left one.
+/// let wrapped = [GridPos::new(0, 4), GridPos::new(9, 4)];
+/// assert_eq!(segment_sprite(&wrapped, 0, Direction::Right), "head-right");
+/// assert_eq!(segment_sprite(&wrapped, 1, Direction::Right), "tail-right");
+///
+/// // A snake of one cell faces where it heads, and diagonal steps have no sprite of their own.
+/// assert_eq!(segment_sprite(&cells[..1], 0, Direction::Left), "head-left");
+/// let diagonal = [GridPos::new(2, 2), GridPos::new(1, 1)];
+/// assert_eq!(segment_sprite(&diagonal, 0, Direction::DownRight), "segment");
+/// ```
+pub fn segment_sprite(cells: &[GridPos], index: usize, heading: Direction) -> &'static str {
+    let cell = cells[index];
+    let ahead = index.checked_sub(1).map(|i| side(cell, cells[i]));
+    let behind = cells.get(index + 1).map(|&next| side(cell, next));
+
+    match (ahead, behind) {
+        (None, _) => head_facing(cells, heading).map_or("segment", head_sprite),
+        (Some(Some(ahead)), None) => match ahead {
+            Direction::Up => "tail-up",
+            Direction::Down => "tail-down",
+            Direction::Left => "tail-left",
+            Direction::Right => "tail-right",
+            _ => "segment",
+        },
+        (Some(Some(ahead)), Some(Some(behind))) => body_sprite(ahead, behind),
+        _ => "segment",
+    }
+}
+
+/// Which way the head of a snake with `cells`, from the head to the tail, faces: away from the
+/// segment behind it, or where the snake is `heading` when there is none. `None` when that
+/// isn't straight towards a side of its cell, after a diagonal step or on a stack of cells.
+///
+/// ```
+/// use snake_game::atlas::head_facing;
+/// use snake_game::game::{Direction, GridPos};
+///
+/// let cells = [GridPos::new(3, 1), GridPos::new(3, 2)];
+/// assert_eq!(head_facing(&cells, Direction::Up), Some(Direction::Up));
+/// assert_eq!(head_facing(&cells[..1], Direction::Left), Some(Direction::Left));
+/// assert_eq!(head_facing(&cells[..1], Direction::UpLeft), None);
+/// ```
+pub fn head_facing(cells: &[GridPos], heading: Direction) -> Option<Direction> {
+    match cells {
+        [head, behind, ..] => side(*head, *behind).map(Direction::opposite),
+        _ => Some(heading).filter(|heading| !heading.is_diagonal()),
+    }
+}
+
+fn head_sprite(facing: Direction) -> &'static str {
+    match facing {
+        Direction::Up => "head-up",
+        Direction::Down => "head-down",
+        Direction::Left => "head-left",
+        Direction::Right => "head-right",
+        _ => "segment",
+    }
+}
+
+/// The body segment joining the sides `a` and `b` of its cell.
+fn body_sprite(a: Direction, b: Direction) -> &'static str {
+    use Direction::{Down, Left, Right, Up};
+
+    match (a, b) {
+        (Up, Down) | (Down, Up) => "body-vertical",
+        (Left, Right) | (Right, Left) => "body-horizontal",
+        (Up, Right) | (Right, Up) => "corner-up-right",
+        (Right, Down) | (Down, Right) => "corner-right-down",
+        (Down, Left) | (Left, Down) => "corner-down-left",
+        (Left, Up) | (Up, Left) => "corner-left-up",
+        _ => "segment",
+    }
+}
+
+/// The side of `cell` that `neighbour` is on, if it is straight along one of them. A neighbour
+/// further away than the next cell is taken to be across an edge of the board the snake
+/// wrapped around, so it is on the side towards that edge.
+fn side(cell: GridPos, neighbour: GridPos) -> Option<Direction> {
+    let step = |from: i32, to: i32| {
+        let delta = to - from;
+        if delta.abs() > 1 {
+            -delta.signum()
+        } else {
+            delta
+        }
+    };
+    let delta = (step(cell.x, neighbour.x), step(cell.y, neighbour.y));
+    Direction::ALL
+        .into_iter()
+        .find(|direction| direction.delta() == delta)
+}
@@ -0,0 +1,151 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::palette::Palette;
+
+/// How long each flash of the background lasts once the board is filled, and the gap between
+/// two of them.
+const WON_FLASH: Duration = Duration::from_millis(250);
+
+/// How long the background takes to fade back from red after a snake dies.
+pub const DEATH_FLASH: Duration = Duration::from_millis(300);
+
+/// How long a drifting background takes to go all the way around the hues.
+pub const DRIFT_PERIOD: Duration = Duration::from_secs(60);
+
+/// How far a drifting background strays from the palette's, as a share of its brightness: a
+/// tint, rather than a color of its own. A black background stays black.
+const DRIFT_STRENGTH: f32 = 0.5;
+
+/// Whether the background stays still or slowly changes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BackgroundMode {
+    /// The palette's background, as it is.
+    #[default]
+    Still,
+    /// The palette's background, tinted with a hue that slowly goes around the color wheel.
+    Drift,
+}
+
+impl BackgroundMode {
+    pub const ALL: [BackgroundMode; 2] = [BackgroundMode::Still, BackgroundMode::Drift];
+
+    /// The names the modes go by on the command line, in the order of [`BackgroundMode::ALL`].
+    pub const NAMES: [&'static str; 2] = ["still", "drift"];
+
+    /// The mode called `name`, one of the [`BackgroundMode::NAMES`].
+    pub fn named(name: &str) -> Option<BackgroundMode> {
+        BackgroundMode::NAMES
+            .iter()
+            .position(|&other| other == name)
+            .map(|i| BackgroundMode::ALL[i])
+    }
+
+    pub fn name(self) -> &'static str {
+        BackgroundMode::NAMES[self as usize]
+    }
+}
+
+/// What the window is cleared to, frame after frame: the palette's background, drifting if it
+/// does, flashing red for a moment when a snake dies and flashing on and off once the board is
+/// filled.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snake_game::background::{Background, BackgroundMode, DEATH_FLASH, DRIFT_PERIOD};
+/// use snake_game::palette::Palette;
+///
+/// let palette = Palette::Default;
+/// let mut background = Background::new(BackgroundMode::Still);
+/// assert_eq!(background.color(palette, false), palette.background());
+///
+/// // A death turns it red, fading back to the background.
+/// background.flash_death();
+/// assert_eq!(background.color(palette, false), palette.death_flash());
+/// background.advance(DEATH_FLASH / 2);
+/// assert!(background.color(palette, false)[0] > palette.background()[0]);
+/// background.advance(DEATH_FLASH);
+/// assert_eq!(background.color(palette, false), palette.background());
+///
+/// // Filling the board flashes it on and off.
+/// let mut background = Background::new(BackgroundMode::Still);
+/// assert_eq!(background.color(palette, true), palette.won_flash());
+/// background.advance(Duration::from_millis(250));
+/// assert_eq!(background.color(palette, true), palette.background());
+///
+/// // Drifting, it strays a little from the background, going around the hues and back.
+/// let mut background = Background::new(BackgroundMode::Drift);
+/// let start = background.color(palette, false);
+/// let close =
+///     |a: [f32; 4], b: [f32; 4], by: f32| a.iter().zip(b).all(|(a, b)| (a - b).abs() < by);
+/// assert!(close(start, palette.background(), 0.051));
+/// background.advance(DRIFT_PERIOD / 3);
+/// let drifted = background.color(palette, false);
+/// assert!(!close(drifted, start, 0.01));
+/// assert!(close(drifted, palette.background(), 0.051));
+/// background.advance(DRIFT_PERIOD * 2 / 3);
+/// assert!(close(background.color(palette, false), start, 0.001));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Background {
+    mode: BackgroundMode,
+    /// How long it has been going, which the drift and the flashes of a filled board follow.
+    elapsed: Duration,
+    /// How much is left of the flash after a death.
+    death_flash: Duration,
+}
+
+impl Background {
+    pub fn new(mode: BackgroundMode) -> Background {
+        Background {
+            mode,
+            elapsed: Duration::ZERO,
+            death_flash: Duration::ZERO,
+        }
+    }
+
+    pub fn mode(&self) -> BackgroundMode {
+        self.mode
+    }
+
+    /// Moves the drift and the flashes on by `elapsed`.
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.elapsed += elapsed;
+        self.death_flash = self.death_flash.saturating_sub(elapsed);
+    }
+
+    /// Starts flashing red, for a snake that just died.
+    pub fn flash_death(&mut self) {
+        self.death_flash = DEATH_FLASH;
+    }
+
+    /// The color to clear to now with `palette`, in linear light like the palette's, flashing
+    /// on and off if the board is `won`.
+    pub fn color(&self, palette: Palette, won: bool) -> [f32; 4] {
+        let flashes = self.elapsed.as_millis() / WON_FLASH.as_millis();
+        if won && flashes.is_multiple_of(2) {
+            return palette.won_flash();
+        }
+
+        let mut color = palette.background();
+        if self.mode == BackgroundMode::Drift {
+            let turn = (self.elapsed.as_secs_f64() / DRIFT_PERIOD.as_secs_f64()).fract() as f32;
+            let brightness = (color[0] + color[1] + color[2]) / 3.0;
+            for (i, channel) in color.iter_mut().take(3).enumerate() {
+                let hue = (turn - i as f32 / 3.0) * TAU;
+                *channel = (*channel + DRIFT_STRENGTH * brightness * hue.cos()).max(0.0);
+            }
+        }
+        if !self.death_flash.is_zero() {
+            let amount = self.death_flash.as_secs_f32() / DEATH_FLASH.as_secs_f32();
+            let flash = palette.death_flash();
+            for (channel, flash) in color.iter_mut().zip(flash) {
+                *channel = *channel * (1.0 - amount) + flash * amount;
+            }
+        }
+        color
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use crate::atlas::{food_sprite, head_facing, segment_sprite, Atlas, HEAD_SPRITE};
+use crate::death::{segment_state, SegmentState};
+use crate::game::{FoodKind, Game, GridPos, RoundEnd, Snake};
+use crate::mesh::{head_notch, Mesh, MeshKind, QuadKind, TexRect, Vertex, WHITE};
+use crate::motion::{corners, slide, Trails};
+use crate::palette::{mix, Look, Palette, Tile};
+
+/// How much of the color of its latest effect a snake takes on while under it.
+const EFFECT_TINT: f32 = 0.5;
+
+/// How far towards white a dying snake is lit up while it flashes.
+const DYING_FLASH: f32 = 0.7;
+
+/// The board of `game` as the renderer draws it while nothing moves, as two triangles for each
+/// cell anything is on, in the colors of the default [`Palette`] and with the sprites of the
+/// [bundled](Atlas::bundled) atlas.
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use snake_game::board::build_board_mesh;
+/// use snake_game::game::{Game, GameConfig, GridPos};
+/// use snake_game::mesh::{Mesh, MeshKind};
+///
+/// let game = Game::with_config(GameConfig::new(8, 6).with_seed(3));
+/// let vertices = build_board_mesh(&game);
+///
+/// // Six vertices for each cell with something on it: the snake, and the food.
+/// let foods = game.foods().iter().map(|food| food.pos);
+/// let occupied: HashSet<GridPos> = game.snake().cells().copied().chain(foods).collect();
+/// assert_eq!(vertices.len(), 6 * occupied.len());
+///
+/// // Each quad is within its cell, starting from its top left corner.
+/// let drawn: HashSet<GridPos> = vertices
+///     .chunks(6)
+///     .map(|quad| {
+///         let [x, y] = quad[0].position;
+///         let cell = GridPos::new(x.floor() as i32, y.floor() as i32);
+///         let within = |v: f32, start: i32| v >= start as f32 && v <= (start + 1) as f32;
+///         assert!(quad.iter().all(|v| within(v.position[0], cell.x)));
+///         assert!(quad.iter().all(|v| within(v.position[1], cell.y)));
+///         cell
+///     })
+///     .collect();
+/// assert_eq!(drawn, occupied);
+///
+/// // The body of the snake, down to its tail, leaves a thin border around its cells. The head
+/// // is drawn last, over it, and fills the whole of its cell.
+/// let quad = |n: usize| &vertices[vertices.len() - 6 * n..][..6];
+/// let tail = game.snake().cells().last().unwrap();
+/// let (x, y) = (tail.x as f32, tail.y as f32);
+/// assert_eq!(quad(2)[0].position, [x + 0.04, y + 0.04]);
+/// assert_eq!(quad(2)[2].position, [x + 0.96, y + 0.96]);
+/// let head = game.snake().head();
+/// let (x, y) = (head.x as f32, head.y as f32);
+/// assert_eq!(quad(1)[0].position, [x, y]);
+/// assert_eq!(quad(1)[2].position, [x + 1.0, y + 1.0]);
+///
+/// // A mesh with nothing in it is never drawn, rather than submitting a draw of no vertices.
+/// let empty = Mesh::new(MeshKind::Triangles);
+/// assert_eq!(empty.draw_count(), None);
+/// ```
+pub fn build_board_mesh(game: &Game) -> Vec<Vertex> {
+    let mut mesh = Mesh::new(MeshKind::Triangles);
+    board_vertices(
+        game,
+        None,
+        None,
+        None,
+        Palette::default(),
+        &Atlas::bundled(),
+        &mut mesh,
+    );
+    mesh.vertices().to_vec()
+}
+
+/// Writes the cells of the board into `mesh`, drawn the way `palette` says with the sprites in
+/// `atlas`. The snakes still alive in `ghost` come first, so the live game is drawn over them.
+/// With `motion`, the snakes of the game are drawn sliding from the cells the trails kept to
+/// the ones they are on, the fraction of the way through the tick it gives.
+///
+/// The mesh is cleared first so the same allocations can be reused every frame.
+pub(crate) fn board_vertices(
+    game: &Game,
+    ghost: Option<&Game>,
+    motion: Option<(&Trails, f32)>,
+    dying: Option<Duration>,
+    palette: Palette,
+    atlas: &Atlas,
+    mesh: &mut Mesh,
+) {
+    mesh.clear();
+
+    // Takes the mesh rather than holding on to it, so the snakes can be drawn straight into
+    // it in between.
+    let push = |mesh: &mut Mesh,
+                cells: &mut dyn Iterator<Item = (GridPos, TexRect)>,
+                tile: Tile,
+                color: [f32; 4]| {
+        let inset = palette.look(tile).inset;
+        for (cell, sprite) in cells {
+            mesh.push_sprite(cell, inset, color, sprite);
+        }
+    };
+    // Pairs the cells of a tile that looks the same on each of them with its sprite.
+    let plain = |tile| {
+        let sprite = tile_sprite(atlas, tile);
+        move |cell| (cell, sprite)
+    };
+    let color = |tile| palette.look(tile).color;
+
+    if let Some(ghost) = ghost {
+        for player in ghost.players().iter().filter(|player| player.is_alive()) {
+            push(
+                mesh,
+                &mut segments(atlas, player.snake()),
+                Tile::Ghost,
+                color(Tile::Ghost),
+            );
+        }
+    }
+    push(
+        mesh,
+        &mut game.obstacles().iter().copied().map(plain(Tile::Obstacle)),
+        Tile::Obstacle,
+        color(Tile::Obstacle),
+    );
+    push(
+        mesh,
+        &mut game
+            .portals()
+            .iter()
+            .flat_map(|&(a, b)| [a, b])
+            .map(plain(Tile::Portal)),
+        Tile::Portal,
+        color(Tile::Portal),
+    );
+    push(
+        mesh,
+        &mut game
+            .config()
+            .checkpoints
+            .iter()
+            .copied()
+            .map(plain(Tile::Checkpoint)),
+        Tile::Checkpoint,
+        color(Tile::Checkpoint),
+    );
+    mesh.set_quad_kind(QuadKind::Food);
+    for kind in FoodKind::ALL {
+        push(
+            mesh,
+            &mut game
+                .foods()
+                .iter()
+                .filter(|food| !food.is_poison() && food.kind == kind)
+                // Food about to rot flashes, showing on every other tick only.
+                .filter(|food| !game.is_rotting(food) || game.ticks().is_multiple_of(2))
+                .map(|food| food.pos)
+                .map(plain(Tile::Food(kind))),
+            Tile::Food(kind),
+            color(Tile::Food(kind)),
+        );
+    }
+    mesh.set_quad_kind(QuadKind::Plain);
+    push(
+        mesh,
+        &mut game
+            .foods()
+            .iter()
+            .filter(|food| food.is_poison())
+            .map(|food| food.pos)
+            .map(plain(Tile::Poison)),
+        Tile::Poison,
+        color(Tile::Poison),
+    );
+    for power_up in game.power_ups() {
+        let tile = Tile::PowerUp(power_up.effect);
+        push(
+            mesh,
+            &mut std::iter::once(power_up.pos).map(plain(tile)),
+            tile,
+            color(tile),
+        );
+    }
+    for (i, player) in game.players().iter().enumerate() {
+        let tile = if game.round_end() == Some(RoundEnd::Won) {
+            Tile::WonSnake
+        } else if player.is_alive() && !game.is_over() {
+            Tile::Snake(i)
+        } else {
+            Tile::DeadSnake
+        };
+        let tint = match (tile, player.effects().last()) {
+            (Tile::Snake(_), Some(active)) => mix(
+                color(tile),
+                color(Tile::PowerUp(active.effect)),
+                EFFECT_TINT,
+            ),
+            _ => color(tile),
+        };
+        let look = Look {
+            color: tint,
+            inset: palette.look(tile).inset,
+        };
+        let cells: Vec<GridPos> = player.snake().cells().copied().collect();
+        let sprites: Vec<(GridPos, TexRect)> = segments(atlas, player.snake()).collect();
+        let previous = motion.and_then(|(trails, t)| Some((trails.previous(i)?, t)));
+        // Where each segment is drawn: sliding from where it was when the snakes move smoothly,
+        // right on its cell otherwise.
+        let position = |index: usize| {
+            let cell = cells[index];
+            match previous {
+                Some((previous, t)) => {
+                    let from = previous.get(index).or(previous.last()).unwrap_or(&cell);
+                    slide(*from, cell, t)
+                }
+                None => [cell.x as f32, cell.y as f32],
+            }
+        };
+        if let Some((previous, _)) = previous {
+            for corner in corners(previous, &cells) {
+                let (cell, sprite) = sprites[corner];
+                mesh.push_sprite(cell, look.inset, look.color, sprite);
+            }
+        }
+        // How each segment looks, or `None` once it is gone, as a snake that died flashes and
+        // crumbles away.
+        let dying = dying.filter(|_| !player.is_alive());
+        let shade = |look: Look, index: usize| match dying
+            .map(|elapsed| segment_state(index, cells.len(), elapsed))
+        {
+            Some(SegmentState::Gone) => None,
+            Some(SegmentState::Flashed) => Some(Look {
+                color: mix(look.color, WHITE, DYING_FLASH),
+                ..look
+            }),
+            Some(SegmentState::Shown) | None => Some(look),
+        };
+        for (index, &(_, sprite)) in sprites.iter().enumerate().skip(1) {
+            if let Some(look) = shade(look, index) {
+                mesh.push_sprite_at(position(index), look.inset, look.color, sprite);
+            }
+        }
+        // The head is drawn last, over the neck it spills onto. Without a sprite to show where
+        // it faces, a notch cut into it points the way.
+        if let (Some(&(cell, sprite)), Some(head)) = (sprites.first(), shade(look.head(), 0)) {
+            let at = position(0);
+            mesh.push_sprite_at(at, head.inset, head.color, sprite);
+            if sprite == TexRect::NONE {
+                let shift = [at[0] - cell.x as f32, at[1] - cell.y as f32];
+                let notch = head_notch(cell, player.snake().direction())
+                    .map(|[x, y]| [x + shift[0], y + shift[1]]);
+                mesh.push_triangle(notch, palette.background());
+            }
+        }
+    }
+    // Drawn over the snakes, so a chaser that caught one shows on top of its head.
+    push(
+        mesh,
+        &mut game
+            .chasers()
+            .iter()
+            .map(|chaser| chaser.pos)
+            .map(plain(Tile::Chaser)),
+        Tile::Chaser,
+        color(Tile::Chaser),
+    );
+}
+
+/// The cells of `snake` from its head to its tail, each with the sprite in `atlas` it is drawn
+/// with: the head, the tail, or a straight or a corner of its body.
+fn segments<'a>(atlas: &'a Atlas, snake: &Snake) -> impl Iterator<Item = (GridPos, TexRect)> + 'a {
+    let cells: Vec<GridPos> = snake.cells().copied().collect();
+    let heading = snake.direction();
+    (0..cells.len()).map(move |i| {
+        // Heads are one sprite, turned to face the way they do.
+        let head = atlas.sprite(HEAD_SPRITE);
+        let sprite = match head_facing(&cells, heading) {
+            Some(facing) if i == 0 && head != TexRect::NONE => head.facing(facing),
+            _ => atlas.sprite(segment_sprite(&cells, i, heading)),
+        };
+        (cells[i], sprite)
+    })
+}
+
+/// The sprite in `atlas` that `tile` is drawn with, on every cell it covers. Snakes have one
+/// for each of their segments instead, see [`segments`].
+pub(crate) fn tile_sprite(atlas: &Atlas, tile: Tile) -> TexRect {
+    match tile {
+        Tile::Food(kind) => atlas.sprite(food_sprite(kind)),
+        Tile::Poison => atlas.sprite("poison"),
+        _ => TexRect::NONE,
+    }
+}
@@ -0,0 +1,75 @@
+use std::time::Duration;
+
+/// How many times a snake flashes once it died, and how long each flash lasts, as does the gap
+/// after it.
+pub const DEATH_FLASHES: u32 = 3;
+pub const DEATH_FLASH_LENGTH: Duration = Duration::from_millis(80);
+
+/// How long a snake takes to crumble away once it is done flashing, tail first.
+pub const CRUMBLING: Duration = Duration::from_millis(700);
+
+/// How long the whole of the animation of a snake dying lasts: the flashes, then the crumbling.
+pub const DEATH_ANIMATION: Duration = Duration::from_millis(
+    DEATH_FLASHES as u64 * 2 * DEATH_FLASH_LENGTH.as_millis() as u64 + CRUMBLING.as_millis() as u64,
+);
+
+/// How a segment of a dying snake is drawn at some point of its animation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentState {
+    /// As it was before it died.
+    Shown,
+    /// Lit up, during a flash.
+    Flashed,
+    /// Not at all, once it crumbled away.
+    Gone,
+}
+
+/// How the segment at `index` of a dying snake of `len` segments, counted from the head, is
+/// drawn `elapsed` into the animation. The animation goes by the time it took, not by ticks,
+/// so it looks the same whatever speed the game runs at.
+///
+/// The whole snake flashes [`DEATH_FLASHES`] times, then its segments go one after the other
+/// from the tail, evenly over [`CRUMBLING`], the head last as it ends.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snake_game::death::{segment_state, SegmentState, CRUMBLING, DEATH_ANIMATION};
+///
+/// let ms = Duration::from_millis;
+/// let states = |elapsed| (0..4).map(|i| segment_state(i, 4, elapsed)).collect::<Vec<_>>();
+/// let all = |state| vec![state; 4];
+///
+/// // It flashes on and off first.
+/// assert_eq!(states(ms(0)), all(SegmentState::Flashed));
+/// assert_eq!(states(ms(100)), all(SegmentState::Shown));
+/// assert_eq!(states(ms(170)), all(SegmentState::Flashed));
+///
+/// // Then it crumbles from the tail, a quarter of the snake every quarter of the way.
+/// let crumbling = DEATH_ANIMATION - CRUMBLING;
+/// assert_eq!(states(crumbling), all(SegmentState::Shown));
+/// use SegmentState::{Gone, Shown};
+/// assert_eq!(states(crumbling + CRUMBLING / 4), [Shown, Shown, Shown, Gone]);
+/// assert_eq!(states(crumbling + CRUMBLING * 3 / 4), [Shown, Gone, Gone, Gone]);
+/// assert_eq!(states(DEATH_ANIMATION), all(Gone));
+/// assert_eq!(states(DEATH_ANIMATION * 2), all(Gone));
+/// ```
+pub fn segment_state(index: usize, len: usize, elapsed: Duration) -> SegmentState {
+    let flashing = DEATH_FLASH_LENGTH * 2 * DEATH_FLASHES;
+    if elapsed < flashing {
+        let flash = elapsed.as_nanos() / DEATH_FLASH_LENGTH.as_nanos();
+        return if flash.is_multiple_of(2) {
+            SegmentState::Flashed
+        } else {
+            SegmentState::Shown
+        };
+    }
+
+    // How many of the segments crumbled away so far, from the tail.
+    let crumbled = (elapsed - flashing).as_secs_f64() / CRUMBLING.as_secs_f64() * len as f64;
+    if index as f64 >= len as f64 - crumbled {
+        SegmentState::Gone
+    } else {
+        SegmentState::Shown
+    }
+}
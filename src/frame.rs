@@ -0,0 +1,243 @@
+/// The layers a frame is drawn in. Each is recorded into a secondary command buffer of its
+/// own, and they are executed in the order of [`Layer::ALL`]. Each is drawn over the ones
+/// before it, at a [depth](Layer::depth) in front of theirs, so it stays over them whatever
+/// order they are drawn in.
+///
+/// ```
+/// use snake_game::frame::Layer;
+///
+/// for pair in Layer::ALL.windows(2) {
+///     assert!(pair[1].depth() < pair[0].depth());
+/// }
+/// assert!(Layer::ALL.iter().all(|layer| (0.0..1.0).contains(&layer.depth())));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Layer {
+    /// The board, in cells: the grid, then everything on it.
+    Board,
+    /// The particles flying over the board, in cells like it. They change every frame while
+    /// there are any.
+    Particles,
+    /// The text over the board, in pixels. It changes far less often than the board.
+    Ui,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 3] = [Layer::Board, Layer::Particles, Layer::Ui];
+
+    /// How far back the layer is drawn, from 0 at the front to 1 at the back, where the depth
+    /// buffer is cleared to. Within a layer, what is drawn later goes over what was drawn
+    /// before, as without one.
+    pub fn depth(self) -> f32 {
+        match self {
+            Layer::Board => 0.75,
+            Layer::Particles => 0.5,
+            Layer::Ui => 0.25,
+        }
+    }
+}
+
+/// The batches drawn in a frame, sorted by the [`Layer`] they are drawn in. Whatever order
+/// they are pushed in, the batches of a layer are drawn over those of the layers before it,
+/// and in the order they were pushed within it.
+///
+/// ```
+/// use snake_game::frame::{FrameRecorder, Layer};
+///
+/// let mut frame = FrameRecorder::new();
+/// frame.push(Layer::Ui, "score");
+/// frame.push(Layer::Board, "grid");
+/// frame.push(Layer::Board, "cells");
+/// assert_eq!(frame.layer(Layer::Board), ["grid", "cells"]);
+/// assert_eq!(frame.layer(Layer::Ui), ["score"]);
+/// assert_eq!(frame.batches().copied().collect::<Vec<_>>(), ["grid", "cells", "score"]);
+///
+/// // The next frame starts from nothing.
+/// frame.clear();
+/// frame.push(Layer::Board, "cells");
+/// assert_eq!(frame.batches().copied().collect::<Vec<_>>(), ["cells"]);
+/// assert!(frame.layer(Layer::Ui).is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrameRecorder<B> {
+    /// The batches of each of the [`Layer::ALL`], in the order they were pushed.
+    layers: [Vec<B>; Layer::ALL.len()],
+}
+
+impl<B> FrameRecorder<B> {
+    pub fn new() -> FrameRecorder<B> {
+        FrameRecorder {
+            layers: Default::default(),
+        }
+    }
+
+    /// Draws `batch` in `layer`, over the batches pushed into it before.
+    pub fn push(&mut self, layer: Layer, batch: B) {
+        self.layers[layer as usize].push(batch);
+    }
+
+    /// The batches drawn in `layer`, in the order they are drawn.
+    pub fn layer(&self, layer: Layer) -> &[B] {
+        &self.layers[layer as usize]
+    }
+
+    /// Every batch of the frame, in the order they are drawn.
+    pub fn batches(&self) -> impl Iterator<Item = &B> {
+        self.layers.iter().flatten()
+    }
+
+    /// Forgets every batch, keeping the allocations for the next frame.
+    pub fn clear(&mut self) {
+        for batches in &mut self.layers {
+            batches.clear();
+        }
+    }
+}
+
+impl<B> Default for FrameRecorder<B> {
+    fn default() -> FrameRecorder<B> {
+        FrameRecorder::new()
+    }
+}
+
+/// Something recorded once and used for as many frames as what it was recorded from stays
+/// the same, such as the command buffer of a layer that rarely changes. It is recorded again
+/// only once it is asked for with another key.
+///
+/// ```
+/// use snake_game::frame::RecordedCache;
+///
+/// let mut cache = RecordedCache::new();
+/// assert_eq!(cache.get_or_record("12 points", || 1), 1);
+/// // The same key gets what was recorded for it, without recording anything.
+/// assert_eq!(cache.get_or_record("12 points", || unreachable!()), 1);
+/// assert_eq!(cache.get_or_record("13 points", || 2), 2);
+/// assert_eq!(cache.recordings(), 2);
+///
+/// // Once cleared, even the same key is recorded again.
+/// cache.clear();
+/// assert_eq!(cache.get_or_record("13 points", || 3), 3);
+/// assert_eq!(cache.recordings(), 3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RecordedCache<K, C> {
+    recorded: Option<(K, C)>,
+    recordings: u64,
+}
+
+impl<K: PartialEq, C: Clone> RecordedCache<K, C> {
+    pub fn new() -> RecordedCache<K, C> {
+        RecordedCache {
+            recorded: None,
+            recordings: 0,
+        }
+    }
+
+    /// What was recorded for `key`, recorded with `record` if the last thing recorded was
+    /// for another key, or nothing was.
+    pub fn get_or_record(&mut self, key: K, record: impl FnOnce() -> C) -> C {
+        match &self.recorded {
+            Some((recorded_for, recorded)) if *recorded_for == key => recorded.clone(),
+            _ => {
+                let recorded = record();
+                self.recorded = Some((key, recorded.clone()));
+                self.recordings += 1;
+                recorded
+            }
+        }
+    }
+
+    /// Forgets what was recorded, so it is recorded again the next time it is asked for.
+    pub fn clear(&mut self) {
+        self.recorded = None;
+    }
+
+    /// How many times something was recorded, for telling how often the cache helps.
+    pub fn recordings(&self) -> u64 {
+        self.recordings
+    }
+}
+
+impl<K: PartialEq, C: Clone> Default for RecordedCache<K, C> {
+    fn default() -> RecordedCache<K, C> {
+        RecordedCache::new()
+    }
+}
+
+/// The command buffers recorded for each swapchain image, each kept for as long as what is
+/// drawn stays the same, so frames that don't change, such as those of a paused game, aren't
+/// recorded again.
+///
+/// What is drawn is counted in generations: whenever it changes, the generation goes up, and
+/// what was recorded in an earlier one is no longer used. Everything is forgotten when the
+/// swapchain is made again, as its images are new.
+///
+/// ```
+/// use snake_game::frame::FrameCache;
+///
+/// let mut cache = FrameCache::new(2);
+/// assert_eq!(cache.get_or_record(0, || "first"), "first");
+/// // Each image has a buffer of its own...
+/// assert_eq!(cache.get_or_record(1, || "second"), "second");
+/// // ...used again for as long as the generation stays the same.
+/// assert_eq!(cache.get_or_record(0, || unreachable!()), "first");
+/// assert_eq!(cache.get_or_record(1, || unreachable!()), "second");
+///
+/// // Once what is drawn changes, each image is recorded again.
+/// cache.bump();
+/// assert_eq!(cache.generation(), 1);
+/// assert_eq!(cache.get_or_record(0, || "third"), "third");
+/// assert_eq!(cache.get_or_record(0, || unreachable!()), "third");
+/// assert_eq!(cache.get_or_record(1, || "fourth"), "fourth");
+///
+/// // A new swapchain, of three images this time, starts from nothing, in the same generation.
+/// cache.invalidate(3);
+/// assert_eq!(cache.generation(), 1);
+/// assert_eq!(cache.get_or_record(0, || "fifth"), "fifth");
+/// assert_eq!(cache.get_or_record(2, || "sixth"), "sixth");
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrameCache<C> {
+    generation: u64,
+    /// For each image, what was last recorded for it and in which generation.
+    recorded: Vec<Option<(u64, C)>>,
+}
+
+impl<C: Clone> FrameCache<C> {
+    /// A cache for a swapchain of `images` images, with nothing recorded yet.
+    pub fn new(images: usize) -> FrameCache<C> {
+        FrameCache {
+            generation: 0,
+            recorded: vec![None; images],
+        }
+    }
+
+    /// The generation of what is drawn, counting from 0.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Starts the next generation, as what is drawn changed.
+    pub fn bump(&mut self) {
+        self.generation += 1;
+    }
+
+    /// What was recorded for the swapchain image `image` in this generation, recorded with
+    /// `record` if nothing was.
+    pub fn get_or_record(&mut self, image: usize, record: impl FnOnce() -> C) -> C {
+        match &self.recorded[image] {
+            Some((generation, recorded)) if *generation == self.generation => recorded.clone(),
+            _ => {
+                let recorded = record();
+                self.recorded[image] = Some((self.generation, recorded.clone()));
+                recorded
+            }
+        }
+    }
+
+    /// Forgets everything recorded, for a new swapchain of `images` images.
+    pub fn invalidate(&mut self, images: usize) {
+        self.recorded.clear();
+        self.recorded.resize(images, None);
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How many of the latest frames the statistics are taken over.
+pub const FRAME_WINDOW: usize = 240;
+
+/// How the latest frames went: how long each took from the start of one to the start of the
+/// next, and how long the CPU spent putting the last one together.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snake_game::frame_stats::FrameStats;
+///
+/// let ms = Duration::from_millis;
+/// let mut stats = FrameStats::new();
+/// assert_eq!(stats.average_fps(), 0.0);
+///
+/// // 99 frames at 10ms and a single one at 110ms: 1.1 seconds all told.
+/// for _ in 0..99 {
+///     stats.record_frame(ms(10));
+/// }
+/// stats.record_frame(ms(110));
+/// stats.record_cpu_time(ms(3));
+/// assert!((stats.average_fps() - 100.0 / 1.1).abs() < 1e-9);
+/// // The slowest percent of the frames is that one frame.
+/// assert_eq!(stats.low_frame_time(), ms(110));
+/// assert_eq!(stats.cpu_time(), ms(3));
+/// assert_eq!(stats.summary(), "91 fps, 1% low 110.0 ms, CPU 3.0 ms");
+///
+/// // Only the latest frames count.
+/// for _ in 0..1000 {
+///     stats.record_frame(ms(20));
+/// }
+/// assert_eq!(stats.average_fps(), 50.0);
+/// assert_eq!(stats.low_frame_time(), ms(20));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FrameStats {
+    /// The latest frame times, the oldest first.
+    frames: VecDeque<Duration>,
+    total: Duration,
+    cpu_time: Duration,
+}
+
+impl FrameStats {
+    pub fn new() -> FrameStats {
+        FrameStats::default()
+    }
+
+    /// Adds a frame that took `frame_time`, forgetting the oldest once there are more than
+    /// [`FRAME_WINDOW`].
+    pub fn record_frame(&mut self, frame_time: Duration) {
+        self.frames.push_back(frame_time);
+        self.total += frame_time;
+        if self.frames.len() > FRAME_WINDOW {
+            if let Some(oldest) = self.frames.pop_front() {
+                self.total -= oldest;
+            }
+        }
+    }
+
+    /// Sets how long the CPU spent on the last frame.
+    pub fn record_cpu_time(&mut self, cpu_time: Duration) {
+        self.cpu_time = cpu_time;
+    }
+
+    /// The frames a second over the latest frames, or zero before there are any.
+    pub fn average_fps(&self) -> f64 {
+        if self.total.is_zero() {
+            return 0.0;
+        }
+        self.frames.len() as f64 / self.total.as_secs_f64()
+    }
+
+    /// How long the slowest percent of the latest frames took on average, at least the single
+    /// slowest one: the stutters an average hides.
+    pub fn low_frame_time(&self) -> Duration {
+        let mut sorted: Vec<Duration> = self.frames.iter().copied().collect();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        let slowest = &sorted[..sorted.len().div_ceil(100)];
+        if slowest.is_empty() {
+            return Duration::ZERO;
+        }
+        slowest.iter().sum::<Duration>() / slowest.len() as u32
+    }
+
+    pub fn cpu_time(&self) -> Duration {
+        self.cpu_time
+    }
+
+    /// The statistics on a line, for the title bar.
+    pub fn summary(&self) -> String {
+        format!(
+            "{:.0} fps, 1% low {:.1} ms, CPU {:.1} ms",
+            self.average_fps(),
+            self.low_frame_time().as_secs_f64() * 1000.0,
+            self.cpu_time.as_secs_f64() * 1000.0
+        )
+    }
+}
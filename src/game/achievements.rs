@@ -0,0 +1,192 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::config::WallMode;
+use super::event::GameEvent;
+use super::food::FoodKind;
+use super::{Game, MAX_MULTIPLIER};
+
+/// Something worth doing in a round, unlocked the first time a player does it.
+#[derive(Debug)]
+pub struct Achievement {
+    /// What unlocked achievements are stored as. Never rename one.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether `event`, just emitted by `game`, unlocks the achievement for `player`.
+    pub unlocked_by: fn(game: &Game, player: usize, event: &GameEvent) -> bool,
+}
+
+/// Every achievement there is. Adding one here is all it takes.
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "length-20",
+        name: "Length 20",
+        description: "Grow a snake 20 segments long",
+        unlocked_by: |game, player, event| {
+            matches!(event, GameEvent::TickCompleted { .. })
+                && game.players()[player].snake().len() >= 20
+        },
+    },
+    Achievement {
+        id: "no-wall-run",
+        name: "No-wall run",
+        description: "Score 25 points with solid walls before losing a life",
+        unlocked_by: |game, player, event| {
+            let config = game.config();
+            let state = &game.players()[player];
+            matches!(event, GameEvent::Ate { player: p, .. } if *p == player)
+                && config.wall_mode == WallMode::Solid
+                && state.lives() == config.lives.max(1)
+                && state.score() >= 25
+        },
+    },
+    Achievement {
+        id: "combo-5",
+        name: "5x combo",
+        description: "Get the score multiplier all the way up",
+        unlocked_by: |game, player, event| {
+            matches!(event, GameEvent::Ate { player: p, .. } if *p == player)
+                && game.players()[player].multiplier() >= MAX_MULTIPLIER
+        },
+    },
+    Achievement {
+        id: "golden-10",
+        name: "Gold rush",
+        description: "Eat 10 pieces of golden food in a round",
+        unlocked_by: |game, player, event| {
+            matches!(
+                event,
+                GameEvent::Ate { player: p, kind: FoodKind::Golden, .. } if *p == player
+            ) && game.players()[player].eaten(FoodKind::Golden) >= 10
+        },
+    },
+    Achievement {
+        id: "survive-3-minutes",
+        name: "Survive 3 minutes",
+        description: "Stay in a round for three minutes of game time",
+        unlocked_by: |game, player, event| {
+            matches!(event, GameEvent::TickCompleted { .. })
+                && game.players()[player].survived() >= Duration::from_secs(180)
+        },
+    },
+];
+
+impl Achievement {
+    /// The achievement with id `id`, if there is one.
+    pub fn find(id: &str) -> Option<&'static Achievement> {
+        ACHIEVEMENTS.iter().find(|achievement| achievement.id == id)
+    }
+}
+
+/// The ids of the achievements unlocked so far, kept in a file next to the high scores.
+///
+/// Like with [`HighScores`](super::HighScores), problems with the file are only warned about.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Achievements {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    unlocked: BTreeSet<String>,
+}
+
+impl Achievements {
+    /// Loads the unlocked achievements from the user's data directory.
+    pub fn load() -> Achievements {
+        match dirs::data_dir() {
+            Some(dir) => Achievements::load_from(dir.join("snake_game").join("achievements.json")),
+            None => {
+                eprintln!("warning: no data directory, achievements won't be kept");
+                Achievements::default()
+            }
+        }
+    }
+
+    /// Loads the unlocked achievements from `path`, which is also where
+    /// [`Achievements::save`] writes them. A missing file means none are unlocked.
+    pub fn load_from(path: impl Into<PathBuf>) -> Achievements {
+        let path = path.into();
+        let mut achievements = match read(&path) {
+            Ok(achievements) => achievements,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Achievements::default(),
+            Err(e) => {
+                eprintln!(
+                    "warning: could not read achievements from {}: {}",
+                    path.display(),
+                    e
+                );
+                Achievements::default()
+            }
+        };
+
+        achievements.path = Some(path);
+        achievements
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(id)
+    }
+
+    /// The achievements still to unlock, in the order they are registered in.
+    pub fn locked(&self) -> impl Iterator<Item = &'static Achievement> + '_ {
+        ACHIEVEMENTS
+            .iter()
+            .filter(move |achievement| !self.is_unlocked(achievement.id))
+    }
+
+    /// Marks achievement `id` unlocked, and returns whether it wasn't already.
+    ///
+    /// ```
+    /// use snake_game::game::Achievements;
+    ///
+    /// let dir = std::env::temp_dir().join(format!("snake-achievements-{}", std::process::id()));
+    /// let path = dir.join("achievements.json");
+    /// let mut achievements = Achievements::load_from(&path);
+    /// assert!(!achievements.is_unlocked("combo-5"));
+    /// assert!(achievements.unlock("combo-5"));
+    /// assert!(!achievements.unlock("combo-5"));
+    /// achievements.save();
+    ///
+    /// // Once saved and loaded back, it stays unlocked, and unlocking it again is no news.
+    /// let mut loaded = Achievements::load_from(&path);
+    /// assert!(loaded.is_unlocked("combo-5"));
+    /// assert!(!loaded.unlock("combo-5"));
+    /// assert!(loaded.locked().all(|achievement| achievement.id != "combo-5"));
+    /// assert!(loaded.unlock("length-20"));
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn unlock(&mut self, id: &str) -> bool {
+        self.unlocked.insert(id.to_string())
+    }
+
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Err(e) = write(self, path) {
+            eprintln!(
+                "warning: could not save achievements to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<Achievements> {
+    let source = fs::read_to_string(path)?;
+    serde_json::from_str(&source).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write(achievements: &Achievements, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(achievements)?)
+}
@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+
+use super::controller::{BoardView, Controller};
+use super::grid::{Direction, GridPos};
+
+/// Heads for the nearest food along a shortest path, as long as that doesn't cut the snake off
+/// from its own tail. When there is no such path it wanders towards its tail instead, which
+/// keeps it alive until a path opens up.
+///
+/// ```
+/// use snake_game::game::{Controller, GameConfig, GreedyAi, Simulation, StepResult};
+///
+/// for seed in 0..3 {
+///     let mut simulation = Simulation::new(GameConfig::new(20, 20), seed);
+///     let mut controllers: Vec<Box<dyn Controller>> = vec![Box::new(GreedyAi)];
+///     for _ in 0..1000 {
+///         assert_ne!(simulation.tick_with(&mut controllers), StepResult::Died);
+///     }
+///     assert!(!simulation.state().is_over());
+///     assert!(simulation.state().snake().len() > 3);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GreedyAi;
+
+struct Candidate {
+    direction: Direction,
+    /// Whether the tail can still be reached after making the move.
+    safe: bool,
+    food_distance: Option<u32>,
+    tail_distance: Option<u32>,
+}
+
+impl Controller for GreedyAi {
+    fn next_move(&mut self, view: &BoardView) -> Option<Direction> {
+        let snake = view.snake();
+        let head = snake.head();
+        let tail = snake.tail();
+
+        let mut blocked = CellSet::new(view);
+        for &cell in view.obstacles() {
+            blocked.insert(cell);
+        }
+        for other in view.players().iter().filter(|other| other.is_alive()) {
+            for &cell in other.snake().cells() {
+                blocked.insert(cell);
+            }
+        }
+        for chaser in view.chasers() {
+            blocked.insert(chaser.pos);
+        }
+        let mut foods = CellSet::new(view);
+        for food in view.foods() {
+            if food.is_poison() {
+                blocked.insert(food.pos);
+                continue;
+            }
+            foods.insert(food.pos);
+        }
+
+        let candidates = view.directions().iter().filter_map(|&direction| {
+            if snake.len() > 1 && direction.is_reversal_of(snake.direction()) {
+                return None;
+            }
+
+            let next = view.neighbour(head, direction)?;
+            // The tail moves out of the way unless the snake is about to grow.
+            let eats = foods.contains(next);
+            if blocked.contains(next) && (next != tail || eats || snake.len() == 1) {
+                return None;
+            }
+
+            let mut after = blocked.clone();
+            after.insert(next);
+            let new_tail = if eats || snake.len() == 1 {
+                tail
+            } else {
+                after.remove(tail);
+                *snake.cells().nth(snake.len() - 2).unwrap_or(&next)
+            };
+
+            let tail_distance = distance(view, next, &after, |cell| cell == new_tail);
+            let food_distance = if eats {
+                Some(0)
+            } else {
+                distance(view, next, &after, |cell| foods.contains(cell))
+            };
+
+            Some(Candidate {
+                direction,
+                safe: snake.len() == 1 || tail_distance.is_some(),
+                food_distance,
+                tail_distance,
+            })
+        });
+
+        let candidates = candidates.collect::<Vec<_>>();
+        let towards_food = candidates
+            .iter()
+            .filter(|c| c.safe && c.food_distance.is_some())
+            .min_by_key(|c| c.food_distance);
+        let towards_tail = candidates
+            .iter()
+            .filter(|c| c.safe)
+            .max_by_key(|c| c.tail_distance);
+
+        // The game maps the direction asked for when the controls are reversed or mirrored, so
+        // the bot maps it first to end up going the way it picked.
+        towards_food
+            .or(towards_tail)
+            .or_else(|| candidates.first())
+            .map(|c| view.input_direction(c.direction))
+    }
+}
+
+/// The length of the shortest path from `from` to a cell matching `goal`, going around
+/// `blocked` cells. Goal cells are reachable even when blocked.
+fn distance(
+    view: &BoardView,
+    from: GridPos,
+    blocked: &CellSet,
+    goal: impl Fn(GridPos) -> bool,
+) -> Option<u32> {
+    let mut visited = CellSet::new(view);
+    let mut queue = VecDeque::new();
+    visited.insert(from);
+    queue.push_back((from, 0));
+
+    while let Some((cell, steps)) = queue.pop_front() {
+        for &direction in view.directions() {
+            let next = match view.neighbour(cell, direction) {
+                Some(next) => next,
+                None => continue,
+            };
+
+            if goal(next) {
+                return Some(steps + 1);
+            }
+            if !blocked.contains(next) && visited.insert(next) {
+                queue.push_back((next, steps + 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// A set of cells of the board, stored as one flag per cell since the searches above hammer it.
+#[derive(Clone)]
+struct CellSet {
+    width: i32,
+    cells: Vec<bool>,
+}
+
+impl CellSet {
+    fn new(view: &BoardView) -> CellSet {
+        CellSet {
+            width: view.width() as i32,
+            cells: vec![false; (view.width() * view.height()) as usize],
+        }
+    }
+
+    fn index(&self, cell: GridPos) -> Option<usize> {
+        let height = self.cells.len() as i32 / self.width.max(1);
+        if (0..self.width).contains(&cell.x) && (0..height).contains(&cell.y) {
+            Some((cell.y * self.width + cell.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn contains(&self, cell: GridPos) -> bool {
+        self.index(cell).is_some_and(|i| self.cells[i])
+    }
+
+    /// Returns whether the cell wasn't in the set yet.
+    fn insert(&mut self, cell: GridPos) -> bool {
+        match self.index(cell) {
+            Some(i) => !std::mem::replace(&mut self.cells[i], true),
+            None => false,
+        }
+    }
+
+    fn remove(&mut self, cell: GridPos) {
+        if let Some(i) = self.index(cell) {
+            self.cells[i] = false;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use super::grid::{Direction, GridPos};
+
+/// How many cells, across and down added up, a chaser spawns at least from every snake's head.
+pub const CHASER_DISTANCE: u32 = 8;
+
+/// Chasers move on every tick that is a multiple of this, so a snake can always outrun them.
+pub const CHASER_MOVES_EVERY: u64 = 2;
+
+/// An enemy taking up a single cell, that hunts down the nearest snake. A snake running into
+/// it dies, and so does one it catches up with.
+///
+/// The snakes move before the chasers do, so a snake and a chaser heading into each other never
+/// swap places: the snake dies wherever they meet.
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// use snake_game::game::{
+///     DeathCause, Direction, Game, GameConfig, GameEvent, GridPos, SnakeStart,
+/// };
+///
+/// // A single row, the snake heading right from one end and the chaser left from the other.
+/// let start = SnakeStart::new(GridPos::new(0, 0), Direction::Right, 1);
+/// let mut met = Vec::new();
+/// for seed in 0..20 {
+///     let config = GameConfig::new(10, 1)
+///         .with_starts(vec![start])
+///         .with_food_count(0)
+///         .with_chasers(1)
+///         .with_seed(seed);
+///     let mut game = Game::with_config(config);
+///     let deaths = Rc::new(RefCell::new(Vec::new()));
+///     let seen = Rc::clone(&deaths);
+///     game.on_event(move |event| {
+///         if let GameEvent::Died { cause, .. } = event {
+///             seen.borrow_mut().push(cause);
+///         }
+///     });
+///     let spawned = game.chasers()[0].pos;
+///     while !game.is_over() {
+///         game.step();
+///         assert!(game.snake().head().x <= game.chasers()[0].pos.x);
+///     }
+///     assert_eq!(*deaths.borrow(), [DeathCause::Chaser]);
+///     met.push((spawned.x, game.snake().head().x, game.chasers()[0].pos.x));
+/// }
+/// // From (8, 0), the chaser is right next to the head on a tick it moves on, and the head
+/// // dies running into it rather than passing it. From (9, 0), it steps onto the head.
+/// assert!(met.contains(&(8, 5, 6)) && met.contains(&(9, 6, 6)));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chaser {
+    pub pos: GridPos,
+}
+
+/// The cell to step to from `from` to get to the nearest of `targets` the fastest, moving with
+/// `neighbour` and never through a `blocked` cell, targets aside. `None` if no target can be
+/// reached, or `from` is one.
+pub fn step_towards(
+    from: GridPos,
+    targets: &HashSet<GridPos>,
+    neighbour: impl Fn(GridPos, Direction) -> Option<GridPos>,
+    blocked: impl Fn(GridPos) -> bool,
+) -> Option<GridPos> {
+    // The first step of the way to every cell reached so far.
+    let mut first = HashMap::new();
+    first.insert(from, from);
+    let mut queue = VecDeque::from([from]);
+    while let Some(cell) = queue.pop_front() {
+        let step = first[&cell];
+        for direction in Direction::ALL {
+            let next = match neighbour(cell, direction) {
+                Some(next) if !first.contains_key(&next) => next,
+                _ => continue,
+            };
+            let step = if cell == from { next } else { step };
+            if targets.contains(&next) {
+                return Some(step);
+            }
+            if !blocked(next) {
+                first.insert(next, step);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use super::food::Food;
+use super::grid::GridPos;
+use super::power_up::PowerUp;
+use super::rng::Rng;
+use super::score::Score;
+use super::snake::Snake;
+
+/// Where a snake got to when it last crossed a checkpoint, and the board around it, to pick
+/// up from once it loses a life.
+///
+/// The food, the power-ups and the random number generator are kept along with the snake, so
+/// that coming back plays out the way it did the first time round. They are only put back in a
+/// single player round, as the other snakes go on with the board as it is.
+///
+/// ```
+/// use snake_game::game::{BoardView, Controller, Direction, FoodKind, Game, GameConfig};
+/// use snake_game::game::{GreedyAi, GridPos, SnakeStart, StepResult};
+///
+/// // Crosses the checkpoint, then chases food for a while, spawning more in its place, before
+/// // running into a wall.
+/// fn die_after_checkpoint(chasing: u64) -> Game {
+///     let start = SnakeStart::new(GridPos::new(2, 1), Direction::Right, 3);
+///     let mut config = GameConfig::new(12, 8)
+///         .with_starts(vec![start])
+///         .with_food_count(3)
+///         .with_lives(2)
+///         .with_seed(9);
+///     config.checkpoints = vec![GridPos::new(5, 1)];
+///     let mut game = Game::with_config(config);
+///     while game.players()[0].checkpoint().is_none() {
+///         game.step();
+///     }
+///     for _ in 0..chasing {
+///         for direction in GreedyAi.next_moves(&BoardView::new(&game, 0)) {
+///             game.turn(direction);
+///         }
+///         game.step();
+///     }
+///     while game.step() != StepResult::LostLife {}
+///     while !game.players()[0].is_alive() {
+///         game.step();
+///     }
+///     game
+/// }
+///
+/// let mut early = die_after_checkpoint(0);
+/// let mut late = die_after_checkpoint(40);
+/// assert!(early.ticks() < late.ticks());
+/// let eaten = |game: &Game| game.players()[0].eaten(FoodKind::Normal);
+/// assert!(eaten(&early) < eaten(&late));
+///
+/// // Back from the checkpoint, the food spawns the same way however long after it they died.
+/// let foods = |game: &Game| {
+///     let foods = game.foods().iter().map(|food| (food.pos, food.kind));
+///     foods.collect::<Vec<(GridPos, FoodKind)>>()
+/// };
+/// for _ in 0..50 {
+///     assert_eq!(foods(&early), foods(&late));
+///     for game in [&mut early, &mut late] {
+///         for direction in GreedyAi.next_moves(&BoardView::new(game, 0)) {
+///             game.turn(direction);
+///         }
+///         game.step();
+///     }
+/// }
+/// assert_eq!(early.score(), late.score());
+/// assert!(early.snake().cells().eq(late.snake().cells()));
+/// ```
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub pos: GridPos,
+    /// The tick the checkpoint was crossed on.
+    pub tick: u64,
+    pub(super) snake: Snake,
+    pub(super) score: Score,
+    pub(super) pending_growth: u32,
+    pub(super) foods: Vec<Food>,
+    pub(super) power_ups: Vec<PowerUp>,
+    pub(super) rng: Rng,
+}
@@ -0,0 +1,1061 @@
+use std::error::Error;
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::food::{FoodKind, FoodKinds};
+use super::grid::{Direction, GridPos};
+use super::level::Level;
+use super::speed::SpeedCurve;
+
+/// What happens when the snake reaches the edge of the board.
+///
+/// Going off any of the four edges, or off a corner along a diagonal:
+///
+/// ```
+/// use snake_game::game::{
+///     Direction, Game, GameConfig, GridPos, SnakeStart, StepResult, WallMode,
+/// };
+///
+/// let edges = [
+///     ((1, 0), Direction::Up, (1, 2)),
+///     ((1, 2), Direction::Down, (1, 0)),
+///     ((0, 1), Direction::Left, (3, 1)),
+///     ((3, 1), Direction::Right, (0, 1)),
+///     // Each axis wraps on its own.
+///     ((0, 0), Direction::UpLeft, (3, 2)),
+///     ((3, 2), Direction::DownRight, (0, 0)),
+/// ];
+/// for ((x, y), direction, (wrapped_x, wrapped_y)) in edges {
+///     let start = SnakeStart::new(GridPos::new(x, y), direction, 1);
+///     let config = GameConfig::new(4, 3)
+///         .with_starts(vec![start])
+///         .with_diagonals(true)
+///         .with_food_count(0);
+///
+///     let mut game = Game::with_config(config.clone().with_wall_mode(WallMode::Solid));
+///     assert_eq!(game.step(), StepResult::Died);
+///
+///     let mut game = Game::with_config(config.with_wall_mode(WallMode::Wrap));
+///     assert_eq!(game.step(), StepResult::Moved);
+///     assert_eq!(game.snake().head(), GridPos::new(wrapped_x, wrapped_y));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WallMode {
+    /// The edges are walls, running into one kills the snake.
+    Solid,
+    /// The snake comes back out on the opposite edge.
+    Wrap,
+}
+
+/// What the players are after.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// The round goes on until the snakes die.
+    Classic,
+    /// The round also ends once `seconds` of game time have gone by.
+    Timed { seconds: u32 },
+    /// Snakes lose a segment every `shrink_every` seconds of game time they go without eating,
+    /// and score a point per second they stay alive instead of per food.
+    Survival { shrink_every: u32 },
+    /// Nothing kills the snakes: walls, obstacles, chasers and bodies only stand in their way,
+    /// and a snake that runs into one stays where it is until it turns somewhere free. Scores
+    /// count as in the classic mode.
+    Zen,
+}
+
+impl GameMode {
+    /// What a score counts in this mode, for showing it.
+    pub fn score_label(self) -> &'static str {
+        match self {
+            GameMode::Classic | GameMode::Timed { .. } | GameMode::Zen => "Score",
+            GameMode::Survival { .. } => "Survived",
+        }
+    }
+
+    /// `points` along with their unit.
+    pub fn format_score(self, points: u32) -> String {
+        match self {
+            GameMode::Classic | GameMode::Timed { .. } | GameMode::Zen => points.to_string(),
+            GameMode::Survival { .. } => format!("{}s", points),
+        }
+    }
+}
+
+/// What happens to snakes whose heads run into each other: two or more heading for the same
+/// cell, or two heading for the cells each other's heads are on, which would have them pass
+/// through each other. A head running into any other part of a snake, its neck included, is
+/// no clash: that snake is in the way and the head dies on it as it would on any body.
+///
+/// The heads clash however the snakes are ordered, as they all move at once.
+///
+/// ```
+/// use snake_game::game::{Direction, Game, GameConfig, GridPos, HeadOn, SnakeStart};
+///
+/// fn round(head_on: HeadOn, starts: [SnakeStart; 2]) -> Game {
+///     let config = GameConfig::new(10, 5)
+///         .with_players(2)
+///         .with_starts(starts.to_vec())
+///         .with_food_count(0)
+///         .with_head_on(head_on);
+///     let mut game = Game::with_config(config);
+///     game.step();
+///     game
+/// }
+/// let alive = |game: &Game| game.players().iter().map(|p| p.is_alive()).collect::<Vec<_>>();
+///
+/// // Both heads heading for (3, 2).
+/// let same_cell = [
+///     SnakeStart::new(GridPos::new(2, 2), Direction::Right, 3),
+///     SnakeStart::new(GridPos::new(4, 2), Direction::Left, 2),
+/// ];
+/// assert_eq!(alive(&round(HeadOn::BothDie, same_cell)), [false, false]);
+/// let game = round(HeadOn::LongerSurvives, same_cell);
+/// assert_eq!(alive(&game), [true, false]);
+/// assert_eq!(game.players()[0].snake().head(), GridPos::new(3, 2));
+/// let game = round(HeadOn::Bounce, same_cell);
+/// assert_eq!(alive(&game), [true, true]);
+/// assert_eq!(game.players()[1].snake().head(), GridPos::new(4, 2));
+///
+/// // Heads one cell long swapping cells, which would otherwise leave both of them free.
+/// let swap = [
+///     SnakeStart::new(GridPos::new(2, 2), Direction::Right, 1),
+///     SnakeStart::new(GridPos::new(3, 2), Direction::Left, 1),
+/// ];
+/// assert_eq!(alive(&round(HeadOn::BothDie, swap)), [false, false]);
+/// let game = round(HeadOn::Bounce, swap);
+/// assert_eq!(alive(&game), [true, true]);
+/// assert_eq!(game.players()[0].snake().head(), GridPos::new(2, 2));
+///
+/// // Player one's head running into the neck of player two's snake, at (2, 3).
+/// let neck = [
+///     SnakeStart::new(GridPos::new(2, 2), Direction::Down, 2),
+///     SnakeStart::new(GridPos::new(1, 3), Direction::Left, 3),
+/// ];
+/// for head_on in [HeadOn::BothDie, HeadOn::LongerSurvives, HeadOn::Bounce] {
+///     assert_eq!(alive(&round(head_on, neck)), [false, true]);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HeadOn {
+    /// Every snake in the clash dies.
+    #[default]
+    BothDie,
+    /// The longest snake in the clash moves on and the others die, unless no snake is longer
+    /// than all of the others, in which case they all die.
+    LongerSurvives,
+    /// The snakes are blocked and stay where they are for the tick, as in zen mode.
+    Bounce,
+}
+
+impl HeadOn {
+    pub const ALL: [HeadOn; 3] = [HeadOn::BothDie, HeadOn::LongerSurvives, HeadOn::Bounce];
+
+    /// The names the rules go by on the command line and in replays, in the order of
+    /// [`HeadOn::ALL`].
+    pub const NAMES: [&'static str; 3] = ["both-die", "longer-survives", "bounce"];
+
+    /// The rule called `name`, one of the [`HeadOn::NAMES`].
+    pub fn named(name: &str) -> Option<HeadOn> {
+        HeadOn::NAMES
+            .iter()
+            .position(|&other| other == name)
+            .map(|i| HeadOn::ALL[i])
+    }
+
+    pub fn name(self) -> &'static str {
+        HeadOn::NAMES[self as usize]
+    }
+}
+
+/// Where a snake's head starts, which way it is heading and how many cells long it is. The
+/// body trails behind the head, in a straight line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnakeStart {
+    pub head: GridPos,
+    pub direction: Direction,
+    pub length: usize,
+}
+
+impl SnakeStart {
+    pub const fn new(head: GridPos, direction: Direction, length: usize) -> SnakeStart {
+        SnakeStart {
+            head,
+            direction,
+            length,
+        }
+    }
+
+    /// The cells the snake starts on, from the head to the tail.
+    pub fn cells(self) -> impl Iterator<Item = GridPos> {
+        let behind = self.direction.opposite();
+        std::iter::successors(Some(self.head), move |cell| Some(cell.offset(behind)))
+            .take(self.length)
+    }
+}
+
+/// Why a config can't be played.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The board has no cells.
+    EmptyBoard { width: u32, height: u32 },
+    /// The tick interval can't start below the floor it shrinks towards.
+    FloorAboveStart { start: Duration, floor: Duration },
+    /// An exponential speed curve has to shrink the interval by a factor between 0 and 1.
+    BadSpeedFactor,
+    /// The speed curve gets slower, or its steps are out of order, at score `score`.
+    SpeedCurveRises { score: u32 },
+    /// The speed curve gets down to ticks that take no time at all, or has no steps.
+    ZeroTickInterval,
+    /// A portal end is off the board or shared with another portal.
+    InvalidPortal(GridPos),
+    /// Survival mode needs some time between two shrinks.
+    ZeroShrinkInterval,
+    /// Every kind of food has a weight of zero, so none can spawn.
+    NoFoodWeight,
+    /// The obstacle density has to be between 0 and 1.
+    BadObstacleDensity,
+    /// The minimum open area has to be between 0 and 1.
+    BadMinOpenArea,
+    /// The snake of `player` has no cells.
+    ZeroStartLength { player: usize },
+    /// The body of the snake of `player` runs off the board.
+    StartOffBoard { player: usize },
+    /// The snake of `player` starts on an obstacle or a portal, at `cell`.
+    StartOverlaps { player: usize, cell: GridPos },
+    /// The snakes of `first` and `second` start on top of each other.
+    StartsCollide { first: usize, second: usize },
+    /// The snake of `player` can't take a single step before running into something.
+    StartBlocked { player: usize },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::EmptyBoard { width, height } => {
+                write!(f, "a {}x{} board has no room to play", width, height)
+            }
+            ConfigError::FloorAboveStart { start, floor } => write!(
+                f,
+                "the shortest tick ({:?}) can't be longer than the first one ({:?})",
+                floor, start
+            ),
+            ConfigError::BadSpeedFactor => {
+                write!(f, "the speed factor must be between 0 and 1")
+            }
+            ConfigError::SpeedCurveRises { score } => write!(
+                f,
+                "the speed curve must not slow down or go back, but does at score {}",
+                score
+            ),
+            ConfigError::ZeroTickInterval => {
+                write!(
+                    f,
+                    "the speed curve must never get down to zero-length ticks"
+                )
+            }
+            ConfigError::InvalidPortal(cell) => write!(
+                f,
+                "the portal at ({}, {}) is off the board or overlaps another one",
+                cell.x, cell.y
+            ),
+            ConfigError::ZeroShrinkInterval => {
+                write!(
+                    f,
+                    "snakes in survival mode must go at least a second between shrinks"
+                )
+            }
+            ConfigError::NoFoodWeight => {
+                write!(f, "at least one kind of food must have a spawn weight")
+            }
+            ConfigError::BadObstacleDensity => {
+                write!(f, "the obstacle density must be between 0 and 1")
+            }
+            ConfigError::BadMinOpenArea => {
+                write!(f, "the minimum open area must be between 0 and 1")
+            }
+            ConfigError::ZeroStartLength { player } => {
+                write!(
+                    f,
+                    "player {}'s snake must be at least one cell long",
+                    player + 1
+                )
+            }
+            ConfigError::StartOffBoard { player } => {
+                write!(f, "player {}'s snake doesn't fit on the board", player + 1)
+            }
+            ConfigError::StartOverlaps { player, cell } => write!(
+                f,
+                "player {}'s snake starts on the obstacle or portal at ({}, {})",
+                player + 1,
+                cell.x,
+                cell.y
+            ),
+            ConfigError::StartsCollide { first, second } => write!(
+                f,
+                "the snakes of players {} and {} start on top of each other",
+                first + 1,
+                second + 1
+            ),
+            ConfigError::StartBlocked { player } => {
+                write!(f, "player {}'s snake starts with nowhere to go", player + 1)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameConfig {
+    pub width: u32,
+    pub height: u32,
+    pub wall_mode: WallMode,
+    pub mode: GameMode,
+    /// Seed for the random number generator. `None` picks a new one every round.
+    pub seed: Option<u64>,
+    pub speed: SpeedCurve,
+    /// How many pieces of food are on the board at once.
+    pub food_count: usize,
+    /// What each kind of food is worth, and how often it spawns.
+    pub food_kinds: FoodKinds,
+    /// Cells that kill the snake on contact.
+    pub obstacles: Vec<GridPos>,
+    /// The share of the board's cells, from 0 to 1, to scatter obstacles on at random at the
+    /// start of each round, on top of `obstacles`.
+    pub obstacle_density: f64,
+    /// The share of the cells left free by `obstacles`, from 0 to 1, that the snakes must be
+    /// able to reach once the random obstacles are down. Random obstacles are taken away until
+    /// they are, and whatever the snakes still can't reach is filled in.
+    pub min_open_area: f64,
+    /// Where each snake starts, by player. Players past the end of the list start on a spot
+    /// that depends on how many players there are, [`START_LENGTH`](super::START_LENGTH) cells
+    /// long.
+    pub starts: Vec<SnakeStart>,
+    /// Cells food spawns on in priority, whenever one of them is free.
+    pub food_hints: Vec<GridPos>,
+    /// Pairs of cells linked together. A snake entering either end comes out past the other
+    /// one, still heading the same way.
+    pub portals: Vec<(GridPos, GridPos)>,
+    /// How many snakes share the board.
+    pub players: usize,
+    /// How many times each snake can die before it is out of the round. Zero counts as one.
+    pub lives: u32,
+    /// One in this many pieces of regular food comes with a piece of poison. Zero turns poison
+    /// off.
+    pub poison_odds: u32,
+    /// One in this many pieces of regular food wanders around instead of staying put. Zero
+    /// keeps all food in place.
+    pub moving_food_odds: u32,
+    /// How many ticks wandering food waits between moves.
+    pub food_moves_every: u32,
+    /// How many ticks regular food can be eaten for before it rots away, new food spawning
+    /// somewhere else in its place. Zero keeps food from rotting.
+    pub food_lifetime: u64,
+    /// How many of the last ticks of its lifetime rotting food flashes for, as a warning.
+    pub food_warning: u64,
+    /// Food eaten within this many ticks of the previous piece raises the snake's score
+    /// multiplier by one, up to [`MAX_MULTIPLIER`](super::MAX_MULTIPLIER). Zero turns combos
+    /// off.
+    pub combo_window: u64,
+    /// While there is no power-up on the board, one spawns on any tick with a chance of one in
+    /// this many. Zero turns power-ups off.
+    pub power_up_odds: u32,
+    /// Whether power-ups can be debuffs too, see [`Effect::is_debuff`](super::Effect::is_debuff).
+    pub debuffs: bool,
+    /// Whether snakes can head along the diagonals too, on top of the four straight directions.
+    pub diagonals: bool,
+    /// Whether the board is drawn flipped left to right. The game plays out the same either way.
+    pub mirrored: bool,
+    /// Whether left and right swap places for the turns players make, which makes steering on a
+    /// mirrored board feel the way it looks.
+    pub mirrored_input: bool,
+    /// How many chasers hunt the snakes down, see [`Chaser`](super::Chaser).
+    pub chasers: usize,
+    /// Cells that record where a snake crossing them got to. A snake that loses a life after
+    /// crossing one comes back as it was on the last one it crossed, instead of at its start.
+    pub checkpoints: Vec<GridPos>,
+    /// The points a snake coming back to a checkpoint loses from the score it had there.
+    pub checkpoint_penalty: u32,
+    /// What happens to snakes whose heads run into each other.
+    pub head_on: HeadOn,
+}
+
+impl GameConfig {
+    pub fn new(width: u32, height: u32) -> GameConfig {
+        GameConfig {
+            width,
+            height,
+            wall_mode: WallMode::Solid,
+            mode: GameMode::Classic,
+            seed: None,
+            speed: SpeedCurve::default(),
+            food_count: 1,
+            food_kinds: FoodKinds::default(),
+            obstacles: Vec::new(),
+            obstacle_density: 0.0,
+            min_open_area: 0.9,
+            starts: Vec::new(),
+            food_hints: Vec::new(),
+            portals: Vec::new(),
+            players: 1,
+            lives: 1,
+            poison_odds: 0,
+            moving_food_odds: 0,
+            food_moves_every: 0,
+            food_lifetime: 0,
+            food_warning: 0,
+            combo_window: 0,
+            power_up_odds: 0,
+            debuffs: false,
+            diagonals: false,
+            mirrored: false,
+            mirrored_input: false,
+            chasers: 0,
+            checkpoints: Vec::new(),
+            checkpoint_penalty: 0,
+            head_on: HeadOn::BothDie,
+        }
+    }
+
+    /// Checks that a round can be played with the config.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.width == 0 || self.height == 0 {
+            return Err(ConfigError::EmptyBoard {
+                width: self.width,
+                height: self.height,
+            });
+        }
+
+        self.speed.validate()?;
+
+        if self.mode == (GameMode::Survival { shrink_every: 0 }) {
+            return Err(ConfigError::ZeroShrinkInterval);
+        }
+
+        if FoodKind::ALL
+            .iter()
+            .all(|&kind| self.food_kinds.of(kind).weight == 0)
+        {
+            return Err(ConfigError::NoFoodWeight);
+        }
+
+        if !(0.0..=1.0).contains(&self.obstacle_density) {
+            return Err(ConfigError::BadObstacleDensity);
+        }
+        if !(0.0..=1.0).contains(&self.min_open_area) {
+            return Err(ConfigError::BadMinOpenArea);
+        }
+
+        let dims = [self.width, self.height];
+        let ends = self.portals.iter().flat_map(|&(a, b)| [a, b]);
+        for (i, end) in ends.clone().enumerate() {
+            if !end.is_within(dims) || ends.clone().skip(i + 1).any(|other| other == end) {
+                return Err(ConfigError::InvalidPortal(end));
+            }
+        }
+
+        self.validate_starts()
+    }
+
+    /// Checks that every snake fits on the board, clear of obstacles, portals and the other
+    /// snakes, with a free cell in front of it.
+    fn validate_starts(&self) -> Result<(), ConfigError> {
+        let dims = [self.width, self.height];
+        let blocked = |cell: &GridPos| {
+            self.obstacles.contains(cell)
+                || self.portals.iter().any(|&(a, b)| a == *cell || b == *cell)
+        };
+
+        let starts = (0..self.players.max(1))
+            .map(|player| super::player_start(self, player))
+            .collect::<Vec<_>>();
+        for (player, start) in starts.iter().enumerate() {
+            if start.length == 0 {
+                return Err(ConfigError::ZeroStartLength { player });
+            }
+            if !start.cells().all(|cell| cell.is_within(dims)) {
+                return Err(ConfigError::StartOffBoard { player });
+            }
+            if let Some(cell) = start.cells().find(blocked) {
+                return Err(ConfigError::StartOverlaps { player, cell });
+            }
+            if let Some(first) = starts[..player]
+                .iter()
+                .position(|other| other.cells().any(|cell| start.cells().any(|c| c == cell)))
+            {
+                return Err(ConfigError::StartsCollide {
+                    first,
+                    second: player,
+                });
+            }
+
+            let ahead = start.head.offset(start.direction);
+            let ahead = match self.wall_mode {
+                WallMode::Wrap => ahead.wrap(dims),
+                WallMode::Solid => ahead,
+            };
+            let taken = starts.iter().any(|other| other.cells().any(|c| c == ahead));
+            if !ahead.is_within(dims) || self.obstacles.contains(&ahead) || taken {
+                return Err(ConfigError::StartBlocked { player });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn with_wall_mode(mut self, wall_mode: WallMode) -> GameConfig {
+        self.wall_mode = wall_mode;
+        self
+    }
+
+    pub fn with_mode(mut self, mode: GameMode) -> GameConfig {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_speed(mut self, speed: SpeedCurve) -> GameConfig {
+        self.speed = speed;
+        self
+    }
+
+    /// Sets how many ticks a second the round starts at, before the speed curve kicks in. A rate
+    /// that isn't a positive number starts the round at ticks that take no time at all, which
+    /// [`GameConfig::validate`] turns down.
+    ///
+    /// ```
+    /// use snake_game::game::{ConfigError, Game, GameConfig};
+    ///
+    /// let config = GameConfig::new(10, 10).with_tick_rate(8.0);
+    /// assert_eq!(config.validate(), Ok(()));
+    /// assert_eq!(Game::with_config(config).tick_rate(), 8.0);
+    ///
+    /// for rate in [0.0, -4.0, f64::NAN] {
+    ///     let config = GameConfig::new(10, 10).with_tick_rate(rate);
+    ///     assert_eq!(config.validate(), Err(ConfigError::ZeroTickInterval));
+    /// }
+    /// ```
+    pub fn with_tick_rate(mut self, ticks_per_second: f64) -> GameConfig {
+        let start = Duration::try_from_secs_f64(1.0 / ticks_per_second).unwrap_or(Duration::ZERO);
+        self.speed = self.speed.with_start(start);
+        self
+    }
+
+    pub fn with_food_count(mut self, food_count: usize) -> GameConfig {
+        self.food_count = food_count;
+        self
+    }
+
+    /// Makes normal, golden and mega food spawn in proportion to the given weights.
+    pub fn with_food_weights(mut self, normal: u32, golden: u32, mega: u32) -> GameConfig {
+        self.food_kinds.normal.weight = normal;
+        self.food_kinds.golden.weight = golden;
+        self.food_kinds.mega.weight = mega;
+        self
+    }
+
+    pub fn with_obstacles(mut self, obstacles: Vec<GridPos>) -> GameConfig {
+        self.obstacles = obstacles;
+        self
+    }
+
+    /// Scatters obstacles on `density` of the board's cells. Every cell they leave free is
+    /// within the snakes' reach, and there are as many as [`GameConfig::min_open_area`] asks.
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos};
+    ///
+    /// for seed in 0..100 {
+    ///     let config = GameConfig::new(20, 15)
+    ///         .with_obstacle_density(0.3)
+    ///         .with_seed(seed);
+    ///     let game = Game::with_config(config);
+    ///     let dims = [game.width(), game.height()];
+    ///     let free = (0..dims[1] as i32)
+    ///         .flat_map(|y| (0..dims[0] as i32).map(move |x| GridPos::new(x, y)))
+    ///         .filter(|cell| !game.obstacles().contains(cell))
+    ///         .collect::<HashSet<_>>();
+    ///     // Obstacles on 30% of the board would leave less open than the default 90%.
+    ///     assert!(free.len() as f64 >= 0.9 * f64::from(dims[0] * dims[1]));
+    ///
+    ///     let mut reached = HashSet::from([game.snake().head()]);
+    ///     let mut queue = vec![game.snake().head()];
+    ///     while let Some(cell) = queue.pop() {
+    ///         for direction in Direction::ALL {
+    ///             let next = cell.offset(direction);
+    ///             if free.contains(&next) && reached.insert(next) {
+    ///                 queue.push(next);
+    ///             }
+    ///         }
+    ///     }
+    ///     assert_eq!(reached, free, "seed {}", seed);
+    /// }
+    /// ```
+    pub fn with_obstacle_density(mut self, density: f64) -> GameConfig {
+        self.obstacle_density = density;
+        self
+    }
+
+    pub fn with_min_open_area(mut self, min_open_area: f64) -> GameConfig {
+        self.min_open_area = min_open_area;
+        self
+    }
+
+    /// Takes the board's size, walls, start, food hints, portals and checkpoints from `level`.
+    pub fn with_level(mut self, level: &Level) -> GameConfig {
+        self.width = level.width;
+        self.height = level.height;
+        self.obstacles = level.walls.clone();
+        let start = SnakeStart::new(level.start, level.start_direction(), level.start_length());
+        match self.starts.first_mut() {
+            Some(first) => *first = start,
+            None => self.starts.push(start),
+        }
+        self.food_hints = level.food_hints.clone();
+        self.portals = level.portals.clone();
+        self.checkpoints = level.checkpoints.clone();
+        self
+    }
+
+    /// Sets where the snakes start, player one's first.
+    pub fn with_starts(mut self, starts: Vec<SnakeStart>) -> GameConfig {
+        self.starts = starts;
+        self
+    }
+
+    /// Links `a` and `b` with a portal.
+    ///
+    /// Nothing keeps obstacles off the cells of a portal, and a cell that is both is only
+    /// taken out of the board once:
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart};
+    ///
+    /// let (a, b) = (GridPos::new(2, 0), GridPos::new(3, 0));
+    /// let config = GameConfig::new(4, 1)
+    ///     .with_starts(vec![SnakeStart::new(GridPos::new(0, 0), Direction::Right, 1)])
+    ///     .with_portal(a, b)
+    ///     .with_obstacles(vec![a, b])
+    ///     .with_food_count(0);
+    /// let mut game = Game::with_config(config);
+    /// game.step();
+    /// assert!(!game.is_over());
+    /// game.step();
+    /// assert!(game.is_over());
+    /// ```
+    ///
+    /// Food just past a portal's twin is eaten on the tick the snake goes through, and a body
+    /// there is run into like anywhere else:
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    ///
+    /// use snake_game::game::{DeathCause, Direction, Game, GameConfig, GameEvent, GridPos};
+    /// use snake_game::game::{RoundEnd, SnakeStart, StepResult};
+    ///
+    /// let start = SnakeStart::new(GridPos::new(1, 1), Direction::Right, 2);
+    /// let mut config = GameConfig::new(9, 3)
+    ///     .with_starts(vec![start])
+    ///     .with_portal(GridPos::new(3, 1), GridPos::new(6, 1))
+    ///     .with_food_count(1);
+    /// config.food_hints = vec![GridPos::new(7, 1)];
+    /// let mut game = Game::with_config(config);
+    /// assert_eq!(game.foods()[0].pos, GridPos::new(7, 1));
+    /// assert_eq!(game.step(), StepResult::Moved);
+    /// assert_eq!(game.step(), StepResult::Ate);
+    /// assert_eq!(game.snake().head(), GridPos::new(7, 1));
+    /// assert_eq!(game.score(), 1);
+    ///
+    /// // Down and right around a corner, into a portal that leads back onto the neck.
+    /// let start = SnakeStart::new(GridPos::new(3, 1), Direction::Right, 4);
+    /// let config = GameConfig::new(9, 5)
+    ///     .with_starts(vec![start])
+    ///     .with_portal(GridPos::new(6, 3), GridPos::new(4, 2))
+    ///     .with_food_count(0);
+    /// let mut game = Game::with_config(config);
+    /// let deaths = Rc::new(RefCell::new(Vec::new()));
+    /// let seen = Rc::clone(&deaths);
+    /// game.on_event(move |event| {
+    ///     if let GameEvent::Died { cause, .. } = event {
+    ///         seen.borrow_mut().push(cause);
+    ///     }
+    /// });
+    /// for turn in [None, None, Some(Direction::Down), None] {
+    ///     if let Some(direction) = turn {
+    ///         game.turn(direction);
+    ///     }
+    ///     assert_eq!(game.step(), StepResult::Moved);
+    /// }
+    /// let through = game.neighbour(GridPos::new(5, 3), Direction::Right);
+    /// assert_eq!(through, Some(GridPos::new(5, 2)));
+    /// game.turn(Direction::Right);
+    /// assert_eq!(game.step(), StepResult::Died);
+    /// assert_eq!(*deaths.borrow(), [DeathCause::SelfCollision]);
+    /// assert_eq!(game.round_end(), Some(RoundEnd::Died));
+    /// ```
+    ///
+    /// The two ends of a portal can be right next to each other. Going into either one comes
+    /// out past the other, in the direction the snake was going:
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart, WallMode};
+    ///
+    /// let (a, b) = (GridPos::new(3, 1), GridPos::new(4, 1));
+    /// let start = SnakeStart::new(GridPos::new(1, 1), Direction::Right, 2);
+    /// let config = GameConfig::new(8, 3)
+    ///     .with_wall_mode(WallMode::Wrap)
+    ///     .with_starts(vec![start])
+    ///     .with_portal(a, b)
+    ///     .with_food_count(0);
+    /// let mut game = Game::with_config(config);
+    /// for (from, direction, to) in [
+    ///     ((2, 1), Direction::Right, (5, 1)),
+    ///     ((5, 1), Direction::Left, (2, 1)),
+    ///     ((3, 0), Direction::Down, (4, 2)),
+    ///     ((4, 2), Direction::Up, (3, 0)),
+    /// ] {
+    ///     let from = GridPos::new(from.0, from.1);
+    ///     assert_eq!(game.neighbour(from, direction), Some(GridPos::new(to.0, to.1)));
+    /// }
+    ///
+    /// // Around and around the middle row, the snake skips both cells every time.
+    /// let mut heads = Vec::new();
+    /// for _ in 0..12 {
+    ///     game.step();
+    ///     assert!(!game.is_over());
+    ///     assert!(game.snake().cells().all(|&cell| cell != a && cell != b));
+    ///     heads.push(game.snake().head().x);
+    /// }
+    /// assert_eq!(heads, [2, 5, 6, 7, 0, 1, 2, 5, 6, 7, 0, 1]);
+    /// ```
+    pub fn with_portal(mut self, a: GridPos, b: GridPos) -> GameConfig {
+        self.portals.push((a, b));
+        self
+    }
+
+    pub fn with_players(mut self, players: usize) -> GameConfig {
+        self.players = players;
+        self
+    }
+
+    /// Gives each snake `lives` lives. A snake that dies with lives to spare comes back
+    /// [`RESPAWN_TICKS`](super::RESPAWN_TICKS) later, and the round only ends once it loses the
+    /// last of them.
+    ///
+    /// ```
+    /// use snake_game::game::{
+    ///     Direction, Game, GameConfig, GridPos, RoundEnd, SnakeStart, StepResult, RESPAWN_TICKS,
+    /// };
+    ///
+    /// // Runs into the end of a row two ticks after it starts, or after it comes back.
+    /// let start = SnakeStart::new(GridPos::new(2, 0), Direction::Right, 3);
+    /// let config = GameConfig::new(5, 1)
+    ///     .with_starts(vec![start])
+    ///     .with_food_count(0)
+    ///     .with_lives(2);
+    /// let mut game = Game::with_config(config);
+    /// game.step();
+    /// game.step();
+    /// assert_eq!(game.step(), StepResult::LostLife);
+    /// assert_eq!(game.players()[0].lives(), 1);
+    /// for _ in 1..RESPAWN_TICKS {
+    ///     assert_eq!(game.step(), StepResult::Died);
+    ///     assert_eq!(game.round_end(), None);
+    /// }
+    /// assert_eq!(game.step(), StepResult::Moved);
+    /// assert_eq!(game.snake().head(), GridPos::new(3, 0));
+    /// game.step();
+    /// assert_eq!(game.step(), StepResult::Died);
+    /// assert_eq!(game.players()[0].lives(), 0);
+    /// assert_eq!(game.round_end(), Some(RoundEnd::Died));
+    ///
+    /// // Back on a checkpoint it crossed heading for a wall, the snake dies on the very first
+    /// // tick it is back, and with that its last life.
+    /// let start = SnakeStart::new(GridPos::new(2, 1), Direction::Right, 3);
+    /// let mut config = GameConfig::new(5, 3)
+    ///     .with_starts(vec![start])
+    ///     .with_food_count(0)
+    ///     .with_lives(2);
+    /// config.checkpoints = vec![GridPos::new(4, 1)];
+    /// let mut game = Game::with_config(config);
+    /// game.step();
+    /// game.step();
+    /// game.turn(Direction::Down);
+    /// game.step();
+    /// game.turn(Direction::Left);
+    /// let mut result = StepResult::Moved;
+    /// while result == StepResult::Moved {
+    ///     result = game.step();
+    /// }
+    /// assert_eq!(result, StepResult::LostLife);
+    /// for _ in 1..RESPAWN_TICKS {
+    ///     assert_eq!(game.step(), StepResult::Died);
+    /// }
+    /// assert_eq!(game.round_end(), None);
+    /// assert_eq!(game.step(), StepResult::Died);
+    /// assert_eq!(game.snake().head(), GridPos::new(4, 1));
+    /// assert_eq!(game.round_end(), Some(RoundEnd::Died));
+    /// ```
+    pub fn with_lives(mut self, lives: u32) -> GameConfig {
+        self.lives = lives;
+        self
+    }
+
+    /// Makes one in `odds` pieces of regular food come with a piece of poison.
+    ///
+    /// A snake at the shortest a snake can be dies of poison, and a longer one never shrinks
+    /// past it:
+    ///
+    /// ```
+    /// use snake_game::game::{
+    ///     Direction, Game, GameConfig, GridPos, RoundEnd, SnakeStart, StepResult,
+    /// };
+    ///
+    /// // Two cells long on a single row, with food and poison on the three cells ahead. Poison
+    /// // never lands right in front of the head, so it is either on the next cell but one, or
+    /// // on the last one.
+    /// let start = SnakeStart::new(GridPos::new(1, 0), Direction::Right, 2);
+    /// let (mut died, mut shrank) = (false, false);
+    /// for seed in 0..100 {
+    ///     let config = GameConfig::new(5, 1)
+    ///         .with_starts(vec![start])
+    ///         .with_food_weights(1, 0, 0)
+    ///         .with_poison(1)
+    ///         .with_seed(seed);
+    ///     let mut game = Game::with_config(config);
+    ///     let food = |x| game.foods().iter().find(|food| food.pos == GridPos::new(x, 0));
+    ///     match (food(3).map(|food| food.is_poison()), food(4).map(|food| food.is_poison())) {
+    ///         (Some(true), Some(false)) => {
+    ///             assert_eq!(game.step(), StepResult::Moved);
+    ///             assert_eq!(game.step(), StepResult::Died);
+    ///             assert_eq!(game.round_end(), Some(RoundEnd::Died));
+    ///             died = true;
+    ///         }
+    ///         (Some(false), Some(true)) => {
+    ///             assert_eq!(game.step(), StepResult::Moved);
+    ///             assert_eq!(game.step(), StepResult::Ate);
+    ///             assert_eq!(game.snake().len(), 3);
+    ///             assert_eq!(game.step(), StepResult::Poisoned);
+    ///             assert_eq!(game.snake().len(), 2);
+    ///             shrank = true;
+    ///         }
+    ///         _ => {}
+    ///     }
+    /// }
+    /// assert!(died && shrank);
+    /// ```
+    pub fn with_poison(mut self, odds: u32) -> GameConfig {
+        self.poison_odds = odds;
+        self
+    }
+
+    /// Makes one in `odds` pieces of regular food move every `every` ticks.
+    ///
+    /// Food moves before the snakes do, so food that wanders into the cell a head is going to
+    /// is eaten on the same tick:
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart, StepResult};
+    ///
+    /// // The food has nowhere to go but towards the head: the obstacle is on its other side.
+    /// let start = SnakeStart::new(GridPos::new(2, 0), Direction::Right, 2);
+    /// let mut config = GameConfig::new(6, 1)
+    ///     .with_starts(vec![start])
+    ///     .with_obstacles(vec![GridPos::new(5, 0)])
+    ///     .with_food_count(1)
+    ///     .with_moving_food(1, 1);
+    /// config.food_hints = vec![GridPos::new(4, 0)];
+    /// let mut game = Game::with_config(config);
+    /// assert_eq!(game.foods()[0].pos, GridPos::new(4, 0));
+    /// assert_eq!(game.foods()[0].moves_every, 1);
+    ///
+    /// assert_eq!(game.step(), StepResult::Ate);
+    /// assert_eq!(game.snake().head(), GridPos::new(3, 0));
+    /// assert_eq!(game.score(), 1);
+    /// ```
+    pub fn with_moving_food(mut self, odds: u32, every: u32) -> GameConfig {
+        self.moving_food_odds = odds;
+        self.food_moves_every = every;
+        self
+    }
+
+    /// Has regular food rot away once it goes `lifetime` ticks uneaten, flashing for the last
+    /// `warning` of them.
+    ///
+    /// Food reached on its last tick is eaten, and a tick later it is gone:
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart, StepResult};
+    ///
+    /// let start = SnakeStart::new(GridPos::new(0, 0), Direction::Right, 1);
+    /// for seed in 0..10 {
+    ///     let config = GameConfig::new(8, 1)
+    ///         .with_starts(vec![start])
+    ///         .with_food_weights(1, 0, 0)
+    ///         .with_seed(seed);
+    ///     // How many ticks it takes the head to get to the food.
+    ///     let distance = Game::with_config(config.clone()).foods()[0].pos.x as u64;
+    ///     if distance < 2 {
+    ///         continue;
+    ///     }
+    ///
+    ///     for (lifetime, eaten) in [(distance, true), (distance - 1, false)] {
+    ///         let mut game = Game::with_config(config.clone().with_rotting_food(lifetime, 1));
+    ///         for _ in 1..distance {
+    ///             assert_eq!(game.step(), StepResult::Moved);
+    ///         }
+    ///         assert_eq!(game.step() == StepResult::Ate, eaten);
+    ///         assert_eq!(game.snake().head().x as u64, distance);
+    ///     }
+    /// }
+    /// ```
+    pub fn with_rotting_food(mut self, lifetime: u64, warning: u64) -> GameConfig {
+        self.food_lifetime = lifetime;
+        self.food_warning = warning;
+        self
+    }
+
+    /// Makes food eaten within `window` ticks of the food before it raise the score
+    /// multiplier, see [`GameConfig::combo_window`].
+    ///
+    /// A round on a row the snake goes around and around, checked against the rules tick by
+    /// tick:
+    ///
+    /// ```
+    /// use snake_game::game::{Game, GameConfig, StepResult, WallMode, MAX_MULTIPLIER};
+    ///
+    /// let config = GameConfig::new(24, 1)
+    ///     .with_wall_mode(WallMode::Wrap)
+    ///     .with_food_count(2)
+    ///     .with_combo(8)
+    ///     .with_seed(0);
+    /// let mut game = Game::with_config(config);
+    /// let (mut last_ate, mut multiplier) = (None, 1);
+    /// let mut gaps = Vec::new();
+    /// while !game.is_over() {
+    ///     let score = game.score();
+    ///     let ate = game.step() == StepResult::Ate;
+    ///     let tick = game.ticks();
+    ///     if ate {
+    ///         let gap = last_ate.map(|last| tick - last);
+    ///         multiplier = match gap {
+    ///             Some(gap) if gap <= 8 => (multiplier + 1).min(MAX_MULTIPLIER),
+    ///             _ => 1,
+    ///         };
+    ///         gaps.extend(gap.map(|gap| (gap, multiplier)));
+    ///         last_ate = Some(tick);
+    ///         // Filling the board wins the round, and a bonus on top.
+    ///         if !game.is_over() {
+    ///             assert_eq!(game.score() - score, multiplier);
+    ///         }
+    ///     }
+    ///     let ticks_left = last_ate.map_or(0, |last| (last + 8).saturating_sub(tick));
+    ///     assert_eq!(game.combo_ticks_left(0), ticks_left);
+    ///     if ticks_left == 0 {
+    ///         multiplier = 1;
+    ///     }
+    ///     assert_eq!(game.players()[0].multiplier(), multiplier);
+    /// }
+    ///
+    /// // Food eaten on the last tick of the window still counts, the next tick is too late, and
+    /// // the multiplier got to its cap and stayed there.
+    /// assert!(gaps.contains(&(8, 3)));
+    /// assert!(gaps.contains(&(11, 1)));
+    /// assert!(gaps.iter().filter(|&&(_, multiplier)| multiplier == MAX_MULTIPLIER).count() > 5);
+    /// ```
+    pub fn with_combo(mut self, window: u64) -> GameConfig {
+        self.combo_window = window;
+        self
+    }
+
+    pub fn with_power_ups(mut self, odds: u32) -> GameConfig {
+        self.power_up_odds = odds;
+        self
+    }
+
+    pub fn with_diagonals(mut self, diagonals: bool) -> GameConfig {
+        self.diagonals = diagonals;
+        self
+    }
+
+    /// Flips the board left to right when it is drawn if `render` is set, and the turns players
+    /// make if `input` is.
+    pub fn with_mirror(mut self, render: bool, input: bool) -> GameConfig {
+        self.mirrored = render;
+        self.mirrored_input = input;
+        self
+    }
+
+    pub fn with_debuffs(mut self, debuffs: bool) -> GameConfig {
+        self.debuffs = debuffs;
+        self
+    }
+
+    pub fn with_chasers(mut self, count: usize) -> GameConfig {
+        self.chasers = count;
+        self
+    }
+
+    /// Has snakes coming back to a checkpoint lose `penalty` points.
+    pub fn with_checkpoint_penalty(mut self, penalty: u32) -> GameConfig {
+        self.checkpoint_penalty = penalty;
+        self
+    }
+
+    pub fn with_head_on(mut self, head_on: HeadOn) -> GameConfig {
+        self.head_on = head_on;
+        self
+    }
+
+    /// Fixes the seed everything random in the round is drawn from, so that the same turns play
+    /// out the same way every time.
+    ///
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{Hash, Hasher};
+    ///
+    /// use snake_game::game::{Direction, Game, GameConfig, GameMode, WallMode};
+    ///
+    /// // Plays 500 ticks of scripted turns and hashes the state the round ends up in.
+    /// fn play(seed: u64) -> u64 {
+    ///     let config = GameConfig::new(24, 18)
+    ///         .with_mode(GameMode::Zen)
+    ///         .with_wall_mode(WallMode::Wrap)
+    ///         .with_obstacle_density(0.05)
+    ///         .with_food_count(3)
+    ///         .with_poison(3)
+    ///         .with_moving_food(4, 5)
+    ///         .with_power_ups(5)
+    ///         .with_seed(seed);
+    ///     let mut game = Game::with_config(config);
+    ///     for tick in 0..500 {
+    ///         if tick % 7 == 0 {
+    ///             game.turn(Direction::ALL[tick / 7 % 4]);
+    ///         }
+    ///         game.step();
+    ///     }
+    ///     assert_eq!(game.ticks(), 500);
+    ///     let mut hasher = DefaultHasher::new();
+    ///     serde_json::to_string(&game).unwrap().hash(&mut hasher);
+    ///     hasher.finish()
+    /// }
+    ///
+    /// assert_eq!(play(12345), play(12345));
+    /// assert_ne!(play(12345), play(54321));
+    /// ```
+    pub fn with_seed(mut self, seed: u64) -> GameConfig {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> GameConfig {
+        GameConfig::new(20, 20)
+    }
+}
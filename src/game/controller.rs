@@ -0,0 +1,339 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use super::chaser::Chaser;
+use super::config::WallMode;
+use super::food::Food;
+use super::grid::{Direction, GridPos};
+use super::player::Player;
+use super::power_up::PowerUp;
+use super::replay::{Input, Replay};
+use super::rng::Rng;
+use super::snake::Snake;
+use super::Game;
+
+/// Something that steers a snake, be it a person at the keyboard, a bot or a recording.
+///
+/// A controller that heads for the right wall and keeps going straight once there crashes on
+/// the tick it reaches it:
+///
+/// ```
+/// use snake_game::game::{BoardView, Controller, Direction, GameConfig, SnakeStart};
+/// use snake_game::game::{GridPos, RoundEnd, Simulation, StepResult};
+///
+/// struct Eastward;
+///
+/// impl Controller for Eastward {
+///     fn next_move(&mut self, view: &BoardView) -> Option<Direction> {
+///         (view.snake().direction() != Direction::Right).then_some(Direction::Right)
+///     }
+/// }
+///
+/// let start = SnakeStart::new(GridPos::new(4, 4), Direction::Up, 3);
+/// let config = GameConfig::new(10, 10).with_starts(vec![start]).with_food_count(0);
+/// let mut simulation = Simulation::new(config, 1);
+/// let mut controllers: Vec<Box<dyn Controller>> = vec![Box::new(Eastward)];
+/// let mut results = Vec::new();
+/// while !simulation.state().is_over() && simulation.state().ticks() < 100 {
+///     results.push(simulation.tick_with(&mut controllers));
+/// }
+/// controllers[0].round_ended();
+///
+/// // Five cells to the last column, and the sixth move leaves the board.
+/// assert_eq!(results.len(), 6);
+/// assert!(results[..5].iter().all(|&result| result == StepResult::Moved));
+/// assert_eq!(results[5], StepResult::Died);
+/// assert_eq!(simulation.state().round_end(), Some(RoundEnd::Died));
+/// ```
+pub trait Controller {
+    /// Called before every tick with the board as it stands. Returns the direction the snake
+    /// should turn to, or `None` to keep going the way it is heading.
+    fn next_move(&mut self, view: &BoardView) -> Option<Direction>;
+
+    /// Every turn to queue up before the coming tick, oldest first. The game applies them one
+    /// tick after the other, see [`Game::turn_player`]. Controllers that turn at most once a
+    /// tick only need [`Controller::next_move`].
+    fn next_moves(&mut self, view: &BoardView) -> Vec<Direction> {
+        self.next_move(view).into_iter().collect()
+    }
+
+    /// Passes on a direction the player asked for from the keyboard. Controllers that aren't
+    /// steered from the keyboard ignore it.
+    fn key_pressed(&mut self, _direction: Direction) {}
+
+    /// Whether a person is steering, in which case their scores go into the high score table.
+    fn is_human(&self) -> bool {
+        false
+    }
+
+    /// Why the controller broke down, if it did since the last time this was asked. Whoever
+    /// drives the controllers asks after every tick's moves, and tells the game with
+    /// [`Game::controller_failed`]. A controller that broke down leaves its snake going straight.
+    fn failure(&mut self) -> Option<String> {
+        None
+    }
+
+    /// Called once the round is over, or given up on, which may be more than once for the same
+    /// round. The next call to [`Controller::next_move`] is for a new round.
+    fn round_ended(&mut self) {}
+}
+
+/// The board as one player's controller sees it. It borrows the game, so making one costs
+/// nothing, and it only ever hands out shared references.
+#[derive(Clone, Copy)]
+pub struct BoardView<'a> {
+    game: &'a Game,
+    player: usize,
+}
+
+impl<'a> BoardView<'a> {
+    /// The board as seen by `player`, which must be one of the players of `game`.
+    pub fn new(game: &'a Game, player: usize) -> BoardView<'a> {
+        BoardView { game, player }
+    }
+
+    /// The index of the player the view is for.
+    pub fn player(&self) -> usize {
+        self.player
+    }
+
+    /// The snake of the player the view is for.
+    pub fn snake(&self) -> &'a Snake {
+        self.game.players()[self.player].snake()
+    }
+
+    /// Every player, the one the view is for included.
+    pub fn players(&self) -> &'a [Player] {
+        self.game.players()
+    }
+
+    pub fn foods(&self) -> &'a [Food] {
+        self.game.foods()
+    }
+
+    pub fn power_ups(&self) -> &'a [PowerUp] {
+        self.game.power_ups()
+    }
+
+    pub fn chasers(&self) -> &'a [Chaser] {
+        self.game.chasers()
+    }
+
+    pub fn obstacles(&self) -> &'a BTreeSet<GridPos> {
+        self.game.obstacles()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.game.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.game.height()
+    }
+
+    /// The tick about to run.
+    pub fn tick(&self) -> u64 {
+        self.game.ticks()
+    }
+
+    /// Whether snakes can head along the diagonals, see
+    /// [`GameConfig::diagonals`](super::GameConfig::diagonals).
+    pub fn diagonals(&self) -> bool {
+        self.game.config().diagonals
+    }
+
+    /// See [`GameConfig::wall_mode`](super::GameConfig::wall_mode).
+    pub fn wall_mode(&self) -> WallMode {
+        self.game.config().wall_mode
+    }
+
+    /// See [`Game::directions`].
+    pub fn directions(&self) -> &'static [Direction] {
+        self.game.directions()
+    }
+
+    /// See [`Game::input_direction`].
+    pub fn input_direction(&self, direction: Direction) -> Direction {
+        self.game.input_direction(self.player, direction)
+    }
+
+    /// See [`Game::neighbour`].
+    pub fn neighbour(&self, cell: GridPos, direction: Direction) -> Option<GridPos> {
+        self.game.neighbour(cell, direction)
+    }
+}
+
+/// Steers with the keys the player presses. Every key pressed between two ticks is passed on,
+/// so a quick up-then-left makes both turns.
+///
+/// When the game allows diagonals, two keys pressed one after the other between the same two
+/// ticks are a chord instead: up then left, or left then up, make a single turn up-left. Keys
+/// that don't make a diagonal together, like up then down, still make two turns.
+#[derive(Clone, Debug, Default)]
+pub struct KeyboardController {
+    pressed: VecDeque<Direction>,
+}
+
+impl Controller for KeyboardController {
+    fn next_move(&mut self, view: &BoardView) -> Option<Direction> {
+        let first = self.pressed.pop_front()?;
+        if !view.diagonals() {
+            return Some(first);
+        }
+        match self.pressed.front().and_then(|&next| first.combine(next)) {
+            Some(chord) => {
+                self.pressed.pop_front();
+                Some(chord)
+            }
+            None => Some(first),
+        }
+    }
+
+    fn next_moves(&mut self, view: &BoardView) -> Vec<Direction> {
+        let mut directions = Vec::new();
+        while let Some(direction) = self.next_move(view) {
+            directions.push(direction);
+        }
+        directions
+    }
+
+    fn key_pressed(&mut self, direction: Direction) {
+        self.pressed.push_back(direction);
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+}
+
+/// Makes the turns one player made in a recorded round, on the same ticks.
+///
+/// ```
+/// use snake_game::game::{BoardView, Controller, Game, GameConfig, GreedyAi, Replay};
+/// use snake_game::game::ReplayController;
+///
+/// // The AI plays for a while, then the snake is left to crash.
+/// let mut game = Game::with_config(GameConfig::new(16, 12).with_seed(5));
+/// let mut replay = Replay::new(&game);
+/// while !game.is_over() {
+///     if game.ticks() < 150 {
+///         for direction in GreedyAi.next_moves(&BoardView::new(&game, 0)) {
+///             replay.record(game.ticks(), 0, direction);
+///             game.turn(direction);
+///         }
+///     }
+///     game.step();
+/// }
+///
+/// let mut replayed = Game::with_config(replay.config().clone());
+/// let mut controller = ReplayController::new(&replay, 0);
+/// while !replayed.is_over() && replayed.ticks() < 10_000 {
+///     for direction in controller.next_moves(&BoardView::new(&replayed, 0)) {
+///         replayed.turn(direction);
+///     }
+///     replayed.step();
+/// }
+/// assert!(game.score() > 0);
+/// assert_eq!(replayed.round_end(), game.round_end());
+/// assert_eq!(replayed.ticks(), game.ticks());
+/// assert_eq!(replayed.score(), game.score());
+/// assert!(replayed.snake().cells().eq(game.snake().cells()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReplayController {
+    inputs: Vec<Input>,
+    next: usize,
+}
+
+impl ReplayController {
+    /// Plays back the turns of `player` in `replay`.
+    pub fn new(replay: &Replay, player: usize) -> ReplayController {
+        ReplayController {
+            inputs: replay
+                .inputs()
+                .iter()
+                .copied()
+                .filter(|input| input.player == player)
+                .collect(),
+            next: 0,
+        }
+    }
+}
+
+impl Controller for ReplayController {
+    fn next_move(&mut self, view: &BoardView) -> Option<Direction> {
+        let input = self.inputs.get(self.next)?;
+        if input.tick > view.tick() {
+            return None;
+        }
+        self.next += 1;
+        Some(input.direction)
+    }
+
+    fn next_moves(&mut self, view: &BoardView) -> Vec<Direction> {
+        let mut directions = Vec::new();
+        while let Some(direction) = self.next_move(view) {
+            directions.push(direction);
+        }
+        directions
+    }
+}
+
+/// Turns at random now and then, though never straight back into its own neck.
+///
+/// The same seed makes the same turns, so a round it plays can be played again:
+///
+/// ```
+/// use snake_game::game::{BoardView, Controller, Game, GameConfig, RandomController};
+///
+/// let play = |seed: u64| {
+///     let mut game = Game::with_config(GameConfig::new(20, 20).with_seed(3));
+///     let mut controller = RandomController::new(seed);
+///     while !game.is_over() && game.ticks() < 10_000 {
+///         let heading = game.snake().direction();
+///         for direction in controller.next_moves(&BoardView::new(&game, 0)) {
+///             assert!(!direction.is_reversal_of(heading));
+///             game.turn(direction);
+///         }
+///         game.step();
+///     }
+///     game
+/// };
+///
+/// let (first, again) = (play(8), play(8));
+/// assert!(first.is_over());
+/// assert_eq!(again.ticks(), first.ticks());
+/// assert_eq!(again.score(), first.score());
+/// assert!(again.snake().cells().eq(first.snake().cells()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct RandomController {
+    rng: Rng,
+}
+
+impl RandomController {
+    /// The snake turns on one tick in this many, on average.
+    const TURN_ODDS: usize = 4;
+
+    pub fn new(seed: u64) -> RandomController {
+        RandomController {
+            rng: Rng::new(seed),
+        }
+    }
+}
+
+impl Controller for RandomController {
+    fn next_move(&mut self, view: &BoardView) -> Option<Direction> {
+        if self.rng.below(Self::TURN_ODDS) != 0 {
+            return None;
+        }
+
+        let heading = view.snake().direction();
+        let turns = view
+            .directions()
+            .iter()
+            .copied()
+            .filter(|&direction| !direction.is_reversal_of(heading))
+            .collect::<Vec<_>>();
+        Some(turns[self.rng.below(turns.len())])
+    }
+}
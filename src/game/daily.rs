@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::config::{GameConfig, WallMode};
+use super::rng::Rng;
+
+/// The obstacle densities a daily challenge picks from.
+const OBSTACLE_DENSITIES: [f64; 4] = [0.0, 0.02, 0.04, 0.06];
+
+/// The weights of normal, golden and mega food a daily challenge picks from.
+const FOOD_MIXES: [[u32; 3]; 3] = [[1, 0, 0], [16, 3, 1], [8, 3, 2]];
+
+/// The poison odds a daily challenge picks from, zero being no poison.
+const POISON_ODDS: [u32; 3] = [0, 0, 4];
+
+/// A day of the calendar, in UTC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub const fn new(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    /// The current day in UTC, going by the system clock.
+    pub fn today() -> Date {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Date::from_days((seconds / 86_400) as i64)
+    }
+
+    /// The day `days` after the first of January 1970.
+    pub fn from_days(days: i64) -> Date {
+        // Counts from the first of March of year 0, so that the leap day comes last in its year.
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        Date::new(year as i32, month as u32, day as u32)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// The seed of the daily challenge of `date`: a 64-bit FNV-1a hash of the date written as
+/// `YYYY-MM-DD`, run through one round of [`Rng`] to spread it out. Everyone gets the same seed
+/// on the same day, so it must never change from one release to the next.
+///
+/// ```
+/// use snake_game::game::{daily_seed, Date};
+///
+/// assert_eq!(daily_seed(Date::new(2024, 1, 1)), 16_017_668_101_927_272_624);
+/// assert_eq!(daily_seed(Date::new(2024, 2, 29)), 13_491_153_077_399_145_335);
+/// assert_eq!(daily_seed(Date::new(2026, 10, 15)), 11_933_701_575_268_050_553);
+/// ```
+pub fn daily_seed(date: Date) -> u64 {
+    let hash = date
+        .to_string()
+        .bytes()
+        .fold(0xCBF2_9CE4_8422_2325, |hash: u64, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01B3)
+        });
+    Rng::new(hash).next_u64()
+}
+
+/// The round everyone plays on a given day: its seed and the modifiers drawn from it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DailyChallenge {
+    pub date: Date,
+    pub seed: u64,
+    pub wall_mode: WallMode,
+    pub obstacle_density: f64,
+    /// The weights of normal, golden and mega food.
+    pub food_weights: [u32; 3],
+    pub poison_odds: u32,
+}
+
+impl DailyChallenge {
+    /// The challenge of `date`. The modifiers are drawn from the seed in a fixed order, so they
+    /// come out the same for everyone too.
+    ///
+    /// ```
+    /// use snake_game::game::{DailyChallenge, Date, WallMode};
+    ///
+    /// let challenge = DailyChallenge::for_date(Date::new(2026, 10, 15));
+    /// assert_eq!(challenge.wall_mode, WallMode::Solid);
+    /// assert_eq!(challenge.obstacle_density, 0.0);
+    /// assert_eq!(challenge.food_weights, [16, 3, 1]);
+    /// assert_eq!(challenge.poison_odds, 4);
+    /// ```
+    pub fn for_date(date: Date) -> DailyChallenge {
+        let seed = daily_seed(date);
+        let mut rng = Rng::new(seed);
+        let wall_mode = match rng.below(2) {
+            0 => WallMode::Solid,
+            _ => WallMode::Wrap,
+        };
+        let obstacle_density = OBSTACLE_DENSITIES[rng.below(OBSTACLE_DENSITIES.len())];
+        let food_weights = FOOD_MIXES[rng.below(FOOD_MIXES.len())];
+        let poison_odds = POISON_ODDS[rng.below(POISON_ODDS.len())];
+        DailyChallenge {
+            date,
+            seed,
+            wall_mode,
+            obstacle_density,
+            food_weights,
+            poison_odds,
+        }
+    }
+
+    pub fn today() -> DailyChallenge {
+        DailyChallenge::for_date(Date::today())
+    }
+
+    /// `base` with the challenge's seed and modifiers on top. Everything else, like the size of
+    /// the board or the speed, comes from `base`.
+    pub fn apply(&self, base: GameConfig) -> GameConfig {
+        let [normal, golden, mega] = self.food_weights;
+        GameConfig {
+            wall_mode: self.wall_mode,
+            obstacle_density: self.obstacle_density,
+            poison_odds: self.poison_odds,
+            ..base
+        }
+        .with_food_weights(normal, golden, mega)
+        .with_seed(self.seed)
+    }
+}
+
+/// The best score of each day's challenge, kept in a file next to the high scores. Playing a
+/// day again only ever raises its record.
+///
+/// Like with [`HighScores`](super::HighScores), problems with the file are only warned about.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DailyRecords {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Keyed by the date written as `YYYY-MM-DD`.
+    best: BTreeMap<String, u32>,
+}
+
+impl DailyRecords {
+    /// Loads the records from the user's data directory.
+    pub fn load() -> DailyRecords {
+        match dirs::data_dir() {
+            Some(dir) => DailyRecords::load_from(dir.join("snake_game").join("daily.json")),
+            None => {
+                eprintln!("warning: no data directory, daily records won't be kept");
+                DailyRecords::default()
+            }
+        }
+    }
+
+    /// Loads the records from `path`, which is also where [`DailyRecords::save`] writes them. A
+    /// missing file means no day was played yet.
+    pub fn load_from(path: impl Into<PathBuf>) -> DailyRecords {
+        let path = path.into();
+        let mut records = match read(&path) {
+            Ok(records) => records,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => DailyRecords::default(),
+            Err(e) => {
+                eprintln!(
+                    "warning: could not read daily records from {}: {}",
+                    path.display(),
+                    e
+                );
+                DailyRecords::default()
+            }
+        };
+
+        records.path = Some(path);
+        records
+    }
+
+    /// The best score made on the challenge of `date`.
+    pub fn best(&self, date: Date) -> Option<u32> {
+        self.best.get(&date.to_string()).copied()
+    }
+
+    /// Keeps `score` as the record of `date` if it beats the one there, and returns whether it
+    /// did.
+    pub fn record(&mut self, date: Date, score: u32) -> bool {
+        if self.best(date).is_some_and(|best| best >= score) {
+            return false;
+        }
+        self.best.insert(date.to_string(), score);
+        true
+    }
+
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Err(e) = write(self, path) {
+            eprintln!(
+                "warning: could not save daily records to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<DailyRecords> {
+    let source = fs::read_to_string(path)?;
+    serde_json::from_str(&source).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write(records: &DailyRecords, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(records)?)
+}
@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use super::config::{ConfigError, GameConfig, WallMode};
+use super::speed::SpeedCurve;
+
+/// A bundle of the settings that make a round easier or harder: speed, walls, poison and
+/// obstacles.
+#[derive(Clone, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    /// Every setting as given.
+    Custom(Box<GameConfig>),
+}
+
+impl Difficulty {
+    pub const PRESETS: [&'static str; 3] = ["easy", "normal", "hard"];
+
+    /// One of the [`Difficulty::PRESETS`].
+    pub fn preset(name: &str) -> Option<Difficulty> {
+        match name {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    /// The name high scores are tagged with.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Custom(_) => "custom",
+        }
+    }
+
+    /// `base` with the settings of the difficulty on top. Everything else, like the size of the
+    /// board or the number of players, comes from `base`. A custom difficulty replaces `base`
+    /// altogether, once it is checked.
+    pub fn apply(&self, base: GameConfig) -> Result<GameConfig, ConfigError> {
+        let config = match self {
+            Difficulty::Easy => GameConfig {
+                wall_mode: WallMode::Wrap,
+                speed: SpeedCurve::Linear {
+                    start: Duration::from_millis(250),
+                    step: Duration::from_millis(3),
+                    floor: Duration::from_millis(100),
+                },
+                poison_odds: 0,
+                obstacle_density: 0.0,
+                ..base
+            },
+            Difficulty::Normal => GameConfig {
+                wall_mode: WallMode::Solid,
+                speed: SpeedCurve::default(),
+                poison_odds: 0,
+                obstacle_density: 0.0,
+                ..base
+            },
+            Difficulty::Hard => GameConfig {
+                wall_mode: WallMode::Solid,
+                speed: SpeedCurve::Exponential {
+                    start: Duration::from_millis(140),
+                    factor: 0.96,
+                    floor: Duration::from_millis(45),
+                },
+                poison_odds: 3,
+                obstacle_density: 0.04,
+                ..base
+            },
+            Difficulty::Custom(config) => GameConfig::clone(config),
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+
+use super::grid::GridPos;
+use super::level::{Level, LevelError};
+
+/// How many edits [`Editor::undo`] can take back. Older ones are forgotten.
+pub const UNDO_LIMIT: usize = 100;
+
+/// How many cells across and down a level started from scratch is.
+const NEW_LEVEL_SIZE: u32 = 20;
+
+/// What a cell of a level being edited holds, one for each tile of the level format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditorTile {
+    Empty,
+    Wall,
+    Start,
+    /// A segment of the snake's body, only ever there when it came with the level loaded.
+    Body,
+    FoodHint,
+    /// One end of the portal with this digit.
+    Portal(char),
+    Checkpoint,
+}
+
+impl EditorTile {
+    /// The character the tile is written as in a level file.
+    fn symbol(self) -> char {
+        match self {
+            EditorTile::Empty => '.',
+            EditorTile::Wall => '#',
+            EditorTile::Start => 'S',
+            EditorTile::Body => 's',
+            EditorTile::FoodHint => 'F',
+            EditorTile::Portal(digit) => digit,
+            EditorTile::Checkpoint => 'C',
+        }
+    }
+}
+
+/// What painting a cell puts on it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tool {
+    Wall,
+    /// Puts down the other end of the portal missing one, or the first end of a new one.
+    Portal,
+    /// Moves the snake's start, clearing the body it had.
+    Start,
+    FoodHint,
+    Checkpoint,
+    Erase,
+}
+
+impl Tool {
+    /// The toolbar, in order.
+    pub const ALL: [Tool; 6] = [
+        Tool::Wall,
+        Tool::Portal,
+        Tool::Start,
+        Tool::FoodHint,
+        Tool::Checkpoint,
+        Tool::Erase,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Wall => "Wall",
+            Tool::Portal => "Portal",
+            Tool::Start => "Start",
+            Tool::FoodHint => "Food",
+            Tool::Checkpoint => "Checkpoint",
+            Tool::Erase => "Erase",
+        }
+    }
+}
+
+/// A level being drawn cell by cell, which can be saved in the format [`Level`] loads once it
+/// is playable.
+#[derive(Clone, Debug)]
+pub struct Editor {
+    width: u32,
+    height: u32,
+    /// Row by row.
+    tiles: Vec<EditorTile>,
+    tool: Tool,
+    /// Each edit, as the cells it changed and what they held before, the latest last.
+    undo: VecDeque<Vec<(GridPos, EditorTile)>>,
+}
+
+impl Editor {
+    /// A blank `width` by `height` level, walled in, with the start in the middle.
+    pub fn new(width: u32, height: u32) -> Editor {
+        let mut editor = Editor {
+            width,
+            height,
+            tiles: vec![EditorTile::Empty; (width * height) as usize],
+            tool: Tool::Wall,
+            undo: VecDeque::new(),
+        };
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                if x == 0 || y == 0 || x == width as i32 - 1 || y == height as i32 - 1 {
+                    editor.set(GridPos::new(x, y), EditorTile::Wall);
+                }
+            }
+        }
+        let middle = GridPos::new(width as i32 / 2, height as i32 / 2);
+        editor.set(middle, EditorTile::Start);
+        editor
+    }
+
+    /// The level at `path` to go on editing, or a new one if there is no file there yet.
+    pub fn open(path: impl AsRef<Path>) -> Result<Editor, LevelError> {
+        match Level::load(path) {
+            Ok(level) => Ok(Editor::from_level(&level)),
+            Err(LevelError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok(Editor::new(NEW_LEVEL_SIZE, NEW_LEVEL_SIZE))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn from_level(level: &Level) -> Editor {
+        let mut editor = Editor {
+            width: level.width,
+            height: level.height,
+            tiles: vec![EditorTile::Empty; (level.width * level.height) as usize],
+            tool: Tool::Wall,
+            undo: VecDeque::new(),
+        };
+        for &cell in &level.walls {
+            editor.set(cell, EditorTile::Wall);
+        }
+        for &cell in &level.food_hints {
+            editor.set(cell, EditorTile::FoodHint);
+        }
+        for &cell in &level.checkpoints {
+            editor.set(cell, EditorTile::Checkpoint);
+        }
+        for &cell in &level.start_body {
+            editor.set(cell, EditorTile::Body);
+        }
+        for (&(a, b), digit) in level.portals.iter().zip('0'..='9') {
+            editor.set(a, EditorTile::Portal(digit));
+            editor.set(b, EditorTile::Portal(digit));
+        }
+        editor.set(level.start, EditorTile::Start);
+        editor
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn tool(&self) -> Tool {
+        self.tool
+    }
+
+    pub fn set_tool(&mut self, tool: Tool) {
+        self.tool = tool;
+    }
+
+    /// What `cell` holds, or `None` if it is off the board.
+    pub fn tile(&self, cell: GridPos) -> Option<EditorTile> {
+        self.index(cell).map(|i| self.tiles[i])
+    }
+
+    /// Every cell along with what it holds, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = (GridPos, EditorTile)> + '_ {
+        let width = self.width as usize;
+        self.tiles.iter().enumerate().map(move |(i, &tile)| {
+            let cell = GridPos::new((i % width) as i32, (i / width) as i32);
+            (cell, tile)
+        })
+    }
+
+    /// Uses the current tool on `cell`. Returns whether it changed anything, which is then one
+    /// edit [`Editor::undo`] can take back.
+    pub fn paint(&mut self, cell: GridPos) -> bool {
+        self.apply(cell, self.tool)
+    }
+
+    /// Empties `cell`, whatever the current tool, as one edit.
+    pub fn erase(&mut self, cell: GridPos) -> bool {
+        self.apply(cell, Tool::Erase)
+    }
+
+    fn apply(&mut self, cell: GridPos, tool: Tool) -> bool {
+        let before = match self.tile(cell) {
+            Some(tile) => tile,
+            None => return false,
+        };
+
+        let tile = match tool {
+            Tool::Wall => EditorTile::Wall,
+            Tool::FoodHint => EditorTile::FoodHint,
+            Tool::Checkpoint => EditorTile::Checkpoint,
+            Tool::Erase => EditorTile::Empty,
+            Tool::Start => EditorTile::Start,
+            // Painting over an end would leave its twin on its own.
+            Tool::Portal if matches!(before, EditorTile::Portal(_)) => return false,
+            Tool::Portal => match self.next_portal() {
+                Some(digit) => EditorTile::Portal(digit),
+                None => return false,
+            },
+        };
+        if tile == before {
+            return false;
+        }
+
+        let mut edit = vec![(cell, before)];
+        if tile == EditorTile::Start {
+            // There is only one start, and the body it had doesn't follow it.
+            for (other, old) in self.cells().collect::<Vec<_>>() {
+                if matches!(old, EditorTile::Start | EditorTile::Body) {
+                    edit.push((other, old));
+                    self.set(other, EditorTile::Empty);
+                }
+            }
+        }
+        self.set(cell, tile);
+
+        if self.undo.len() == UNDO_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(edit);
+        true
+    }
+
+    /// Takes back the latest edit still remembered. Returns whether there was one.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop_back() {
+            Some(edit) => {
+                for (cell, tile) in edit {
+                    self.set(cell, tile);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The digit the next portal end gets: that of a portal with a single end down, or else the
+    /// lowest digit no portal uses yet. `None` once every digit is taken.
+    fn next_portal(&self) -> Option<char> {
+        let count = |digit| {
+            self.tiles
+                .iter()
+                .filter(|&&tile| tile == EditorTile::Portal(digit))
+                .count()
+        };
+        ('0'..='9')
+            .find(|&digit| count(digit) == 1)
+            .or_else(|| ('0'..='9').find(|&digit| count(digit) == 0))
+    }
+
+    /// The level as it would be written to a file.
+    pub fn source(&self) -> String {
+        let mut source = String::new();
+        for row in self.tiles.chunks(self.width as usize) {
+            source.extend(row.iter().map(|tile| tile.symbol()));
+            source.push('\n');
+        }
+        source
+    }
+
+    /// The level drawn so far, checked the same way a level file is when it is loaded.
+    pub fn to_level(&self) -> Result<Level, LevelError> {
+        self.source().parse()
+    }
+
+    /// Writes the level to `path` once it checks out, and returns it so it can be played.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<Level, LevelError> {
+        let level = self.to_level()?;
+        fs::write(path, self.source())?;
+        Ok(level)
+    }
+
+    fn index(&self, cell: GridPos) -> Option<usize> {
+        cell.is_within([self.width, self.height])
+            .then(|| cell.y as usize * self.width as usize + cell.x as usize)
+    }
+
+    fn set(&mut self, cell: GridPos, tile: EditorTile) {
+        if let Some(i) = self.index(cell) {
+            self.tiles[i] = tile;
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use super::food::FoodKind;
+use super::grid::GridPos;
+use super::power_up::Effect;
+use super::RoundEnd;
+
+/// Something that happened during a tick, as told to the listeners registered with
+/// [`Game::on_event`](super::Game::on_event).
+///
+/// Everything told about a round on a row seven cells long, with a checkpoint and two lives:
+///
+/// ```
+/// use std::cell::RefCell;
+/// use std::rc::Rc;
+///
+/// use snake_game::game::{DeathCause, Direction, FoodKind, Game, GameConfig, GameEvent};
+/// use snake_game::game::{GridPos, RoundEnd, SnakeStart};
+///
+/// let start = SnakeStart::new(GridPos::new(1, 0), Direction::Right, 2);
+/// let mut config = GameConfig::new(7, 1)
+///     .with_starts(vec![start])
+///     .with_food_count(1)
+///     .with_lives(2)
+///     .with_seed(0);
+/// config.checkpoints = vec![GridPos::new(3, 0)];
+/// let mut game = Game::with_config(config);
+/// let events = Rc::new(RefCell::new(Vec::new()));
+/// let seen = Rc::clone(&events);
+/// game.on_event(move |event| seen.borrow_mut().push(event));
+/// assert_eq!(game.foods()[0].pos, GridPos::new(6, 0));
+/// while !game.is_over() {
+///     game.step();
+/// }
+///
+/// let ate = GameEvent::Ate {
+///     player: 0,
+///     pos: GridPos::new(6, 0),
+///     kind: FoodKind::Normal,
+///     value: 1,
+/// };
+/// let mut expected = Vec::new();
+/// for tick in 1..=19 {
+///     match tick {
+///         2 => expected.push(GameEvent::CheckpointReached {
+///             player: 0,
+///             pos: GridPos::new(3, 0),
+///         }),
+///         // The food at the end of the row, eaten again after coming back to the checkpoint,
+///         // since the food is put back as it was.
+///         5 | 18 => expected.push(ate),
+///         6 => expected.push(GameEvent::LifeLost {
+///             player: 0,
+///             cause: DeathCause::Wall,
+///             lives_left: 1,
+///         }),
+///         16 => expected.push(GameEvent::Respawned { player: 0 }),
+///         19 => expected.extend([
+///             GameEvent::Died {
+///                 player: 0,
+///                 cause: DeathCause::Wall,
+///             },
+///             GameEvent::RoundEnded(RoundEnd::Died),
+///         ]),
+///         _ => {}
+///     }
+///     expected.push(GameEvent::TickCompleted { tick });
+/// }
+/// assert_eq!(*events.borrow(), expected);
+///
+/// // Once the round is over, steps tell nothing more.
+/// game.step();
+/// assert_eq!(events.borrow().len(), expected.len());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameEvent {
+    /// `player` ate the food of `kind` at `pos` and scored `value` for it.
+    Ate {
+        player: usize,
+        pos: GridPos,
+        kind: FoodKind,
+        value: u32,
+    },
+    /// The food at `pos` went uneaten for too long and rotted away.
+    FoodRotted { pos: GridPos },
+    /// `player` ate the poison at `pos` and survived it.
+    Poisoned { player: usize, pos: GridPos },
+    /// `player` took a power-up.
+    PowerUpPicked { player: usize, effect: Effect },
+    /// `player` died with lives to spare, and has `lives_left` of them.
+    LifeLost {
+        player: usize,
+        cause: DeathCause,
+        lives_left: u32,
+    },
+    /// `player` came back after losing a life.
+    Respawned { player: usize },
+    /// `player` crossed the checkpoint at `pos`, and comes back there after losing a life.
+    CheckpointReached { player: usize, pos: GridPos },
+    /// `player` died on their last life and is out of the round.
+    Died { player: usize, cause: DeathCause },
+    /// The controller steering `player` broke down, see
+    /// [`Controller::failure`](super::Controller::failure). Told between two ticks, by whoever
+    /// drives the controllers, with [`Game::controller_failed`](super::Game::controller_failed).
+    ControllerFailed { player: usize },
+    /// `player` unlocked the achievement with id `id`, see [`Achievement`](super::Achievement).
+    AchievementUnlocked { player: usize, id: &'static str },
+    /// The tick ended the round.
+    RoundEnded(RoundEnd),
+    /// Tick `tick` is done. This is always the last event of a tick.
+    TickCompleted { tick: u64 },
+}
+
+/// What killed a snake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeathCause {
+    /// Ran into the edge of the board, or into portals that only lead into each other.
+    Wall,
+    Obstacle,
+    /// Ran into its own body.
+    SelfCollision,
+    /// Ran into the snake of the player at this index.
+    Snake(usize),
+    /// Ate poison while too short to lose any more segments.
+    Poison,
+    /// Went too long without eating in survival mode.
+    Starved,
+    /// Ran into a chaser, or got caught by one.
+    Chaser,
+}
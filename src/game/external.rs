@@ -0,0 +1,388 @@
+use std::collections::BTreeSet;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::config::WallMode;
+use super::controller::{BoardView, Controller};
+use super::grid::{Direction, GridPos};
+
+/// How long a bot has to answer before its snake goes straight on for the tick.
+pub const BOT_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// How long a bot has to answer the first board of a round, which it gets as soon as it is
+/// started, so that it has time to get going.
+const START_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How many boards can wait to be written to a bot behind the one being written. A bot that
+/// stops reading misses the boards past them.
+const QUEUED_BOARDS: usize = 1;
+
+/// Steers with a program of its own, run as a child process, so that bots can be written in
+/// any language.
+///
+/// Before every tick, the board goes to the program's standard input as a line of JSON:
+///
+/// ```json
+/// {"tick":12,"width":20,"height":20,"wall_mode":"Solid","you":1,
+///  "snakes":[{"cells":[{"x":5,"y":3},{"x":4,"y":3}],"direction":"Right","alive":true},
+///            {"cells":[{"x":9,"y":9},{"x":9,"y":10}],"direction":"Up","alive":true}],
+///  "foods":[{"x":12,"y":7,"poison":false}],
+///  "obstacles":[{"x":0,"y":0}]}
+/// ```
+///
+/// `you` is the index of the bot's snake in `snakes`, and every snake's `cells` go from the
+/// head to the tail. Cells are counted from the top left corner.
+///
+/// The program answers each board with a line of its own: the direction to turn to, as `up`,
+/// `down`, `left`, `right` or, when the round allows diagonals, `up-left` and the like, or an
+/// empty line to go straight on. An answer that takes longer than the timeout is skipped, the
+/// snake going straight on instead, and the late answer is thrown away once it comes. The first
+/// board of a round can take longer, as the program is only just starting.
+///
+/// Boards are written on a thread of their own, so a program that stops reading its input
+/// can't hold up the game either: its snake goes straight on while the boards it doesn't read
+/// pile up, and the ones past [`QUEUED_BOARDS`] are never sent.
+///
+/// The program is started with the first board of a round and killed once the round is over.
+/// A program that exits, or answers something that isn't a direction, has broken down: its
+/// snake goes straight on for the rest of the round, and [`Controller::failure`] tells why.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snake_game::game::{BoardView, Controller, Direction, ExternalBot, Game, GameConfig};
+///
+/// // Turns up on the first board it gets, then gives up.
+/// let mut bot = ExternalBot::new("sh", ["-c", "read board; echo up; read board; exit 3"])
+///     .with_timeout(Duration::from_secs(5));
+/// let mut game = Game::with_config(GameConfig::new(20, 20));
+/// assert_eq!(bot.next_move(&BoardView::new(&game, 0)), Some(Direction::Up));
+/// assert_eq!(bot.failure(), None);
+///
+/// game.step();
+/// assert_eq!(bot.next_move(&BoardView::new(&game, 0)), None);
+/// assert!(bot.failure().unwrap().contains("exited"));
+/// ```
+///
+/// Every board is a single line of JSON, as above, and only the bot's own snake is `you`:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snake_game::game::{BoardView, Controller, Direction, ExternalBot, Game, GameConfig};
+///
+/// // Turns left only if the board is the one it should get, and answers nonsense otherwise.
+/// let stub = r#"
+///     read board
+///     case "$board" in
+///         '{"tick":0,"width":12,"height":9,'*'"you":1,'*'"foods":'*'}') echo left ;;
+///         *) echo nonsense ;;
+///     esac
+///     read board
+///     echo
+/// "#;
+/// let game = Game::with_config(GameConfig::new(12, 9).with_players(2));
+/// let mut bot = ExternalBot::new("sh", ["-c", stub]).with_timeout(Duration::from_secs(5));
+/// assert_eq!(bot.next_move(&BoardView::new(&game, 1)), Some(Direction::Left));
+/// // An empty line goes straight on, without breaking anything.
+/// assert_eq!(bot.next_move(&BoardView::new(&game, 1)), None);
+/// assert_eq!(bot.failure(), None);
+///
+/// // Anything but a direction breaks the bot down for the rest of the round.
+/// let mut bot = ExternalBot::new("sh", ["-c", "read board; echo sideways; sleep 5"]);
+/// assert_eq!(bot.next_move(&BoardView::new(&game, 0)), None);
+/// assert!(bot.failure().unwrap().contains("sideways"));
+/// ```
+///
+/// A bot that is too slow has its snake go straight on, and its late answer is thrown away
+/// rather than taken for the next board's. A bot that doesn't read at all doesn't hold up the
+/// game, however big the boards it isn't reading:
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// use snake_game::game::{BoardView, Controller, Direction, ExternalBot, Game, GameConfig};
+///
+/// let game = Game::with_config(GameConfig::new(20, 20));
+/// let view = BoardView::new(&game, 0);
+/// // Answers the second board a second late, and every other board right away.
+/// let stub = "read board; echo up; read board; sleep 1; echo left; \
+///             while read board; do echo down; done";
+/// let mut bot = ExternalBot::new("sh", ["-c", stub]).with_timeout(Duration::from_millis(100));
+/// assert_eq!(bot.next_move(&view), Some(Direction::Up));
+/// assert_eq!(bot.next_move(&view), None);
+/// std::thread::sleep(Duration::from_millis(1500));
+/// assert_eq!(bot.next_move(&view), Some(Direction::Down));
+/// assert_eq!(bot.failure(), None);
+///
+/// // Boards far bigger than a pipe holds, to a bot that never reads them.
+/// let game = Game::with_config(GameConfig::new(300, 300).with_obstacle_density(0.3));
+/// let view = BoardView::new(&game, 0);
+/// let mut bot = ExternalBot::new("sleep", ["30"]).with_timeout(Duration::from_millis(10));
+/// let started = Instant::now();
+/// for _ in 0..20 {
+///     assert_eq!(bot.next_move(&view), None);
+/// }
+/// assert!(started.elapsed() < Duration::from_secs(10));
+/// assert_eq!(bot.failure(), None);
+/// ```
+pub struct ExternalBot {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+    running: Option<Running>,
+    /// Set once the program broke down, until the round is over.
+    broken: bool,
+    /// Why it broke down, until that is asked for.
+    failure: Option<String>,
+}
+
+/// The program while a round goes on.
+struct Running {
+    child: Child,
+    /// The boards to write to the program, written on a thread of their own so that a program
+    /// that stops reading can't hold up the game.
+    boards: SyncSender<String>,
+    /// The lines the program writes, read on a thread of their own so that waiting for one
+    /// can time out.
+    answers: Receiver<String>,
+    /// How many boards went without an answer in time, whose answers are still to come.
+    late: usize,
+}
+
+impl ExternalBot {
+    /// A bot that runs `program` with `args`.
+    pub fn new<I, S>(program: impl Into<String>, args: I) -> ExternalBot
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        ExternalBot {
+            program: program.into(),
+            args: args.into_iter().map(Into::into).collect(),
+            timeout: BOT_TIMEOUT,
+            running: None,
+            broken: false,
+            failure: None,
+        }
+    }
+
+    /// A bot that runs `command`, split into the program and its arguments at the spaces.
+    /// Returns `None` for a command with nothing in it.
+    pub fn from_command(command: &str) -> Option<ExternalBot> {
+        let mut words = command.split_whitespace();
+        let program = words.next()?;
+        Some(ExternalBot::new(program, words))
+    }
+
+    /// Gives the program `timeout` to answer each board instead of [`BOT_TIMEOUT`].
+    pub fn with_timeout(mut self, timeout: Duration) -> ExternalBot {
+        self.timeout = timeout;
+        self
+    }
+
+    fn start(&self) -> Result<Running, String> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("could not start {}: {}", self.program, e))?;
+        let mut stdin = child.stdin.take().ok_or("no standard input")?;
+        let stdout = child.stdout.take().ok_or("no standard output")?;
+
+        let (boards, queued) = mpsc::sync_channel::<String>(QUEUED_BOARDS);
+        thread::spawn(move || {
+            for board in queued {
+                if stdin.write_all(board.as_bytes()).is_err() || stdin.flush().is_err() {
+                    break;
+                }
+            }
+        });
+
+        let (sender, answers) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Running {
+            child,
+            boards,
+            answers,
+            late: 0,
+        })
+    }
+
+    /// Sends the board to the program and waits for its answer, starting the program first if
+    /// the round just started.
+    fn ask(&mut self, view: &BoardView) -> Result<Option<Direction>, String> {
+        let mut timeout = self.timeout;
+        if self.running.is_none() {
+            self.running = Some(self.start()?);
+            timeout = timeout.max(START_TIMEOUT);
+        }
+        let running = match &mut self.running {
+            Some(running) => running,
+            None => return Ok(None),
+        };
+
+        let mut board = serde_json::to_string(&Board::of(view)).map_err(|e| e.to_string())?;
+        board.push('\n');
+        match running.boards.try_send(board) {
+            Ok(()) => {}
+            // Still writing the boards before, which it isn't reading: it misses this one, and
+            // there is no answer to wait for.
+            Err(TrySendError::Full(_)) => return Ok(None),
+            Err(TrySendError::Disconnected(_)) => return Err(running.exit_status()),
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match running.answers.recv_timeout(left) {
+                Ok(_) if running.late > 0 => running.late -= 1,
+                Ok(answer) => return parse_answer(&answer),
+                Err(RecvTimeoutError::Timeout) => {
+                    running.late += 1;
+                    return Ok(None);
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(running.exit_status()),
+            }
+        }
+    }
+
+    /// Kills the program, if it is running.
+    fn stop(&mut self) {
+        if let Some(mut running) = self.running.take() {
+            running.child.kill().ok();
+            running.child.wait().ok();
+        }
+    }
+}
+
+impl Running {
+    /// Why the program stopped listening, as far as can be told.
+    fn exit_status(&mut self) -> String {
+        // It may still be on its way out.
+        for _ in 0..10 {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                return format!("the bot exited ({})", status);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        "the bot stopped reading and answering".to_string()
+    }
+}
+
+impl Controller for ExternalBot {
+    fn next_move(&mut self, view: &BoardView) -> Option<Direction> {
+        if self.broken {
+            return None;
+        }
+        match self.ask(view) {
+            Ok(direction) => direction,
+            Err(failure) => {
+                self.stop();
+                self.broken = true;
+                self.failure = Some(failure);
+                None
+            }
+        }
+    }
+
+    fn failure(&mut self) -> Option<String> {
+        self.failure.take()
+    }
+
+    fn round_ended(&mut self) {
+        self.stop();
+        self.broken = false;
+        self.failure = None;
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// A line a bot answered with, as the direction it asked for.
+fn parse_answer(answer: &str) -> Result<Option<Direction>, String> {
+    let word = answer.trim().to_ascii_lowercase().replace(['-', '_'], "");
+    if word.is_empty() || word == "straight" {
+        return Ok(None);
+    }
+    Direction::ALL_EIGHT
+        .into_iter()
+        .find(|direction| format!("{:?}", direction).to_ascii_lowercase() == word)
+        .map(Some)
+        .ok_or_else(|| format!("the bot answered {:?}, which is no direction", answer))
+}
+
+/// The board as it is sent to a bot.
+#[derive(Serialize)]
+struct Board<'a> {
+    tick: u64,
+    width: u32,
+    height: u32,
+    wall_mode: WallMode,
+    you: usize,
+    snakes: Vec<BoardSnake>,
+    foods: Vec<BoardFood>,
+    obstacles: &'a BTreeSet<GridPos>,
+}
+
+#[derive(Serialize)]
+struct BoardSnake {
+    cells: Vec<GridPos>,
+    direction: Direction,
+    alive: bool,
+}
+
+#[derive(Serialize)]
+struct BoardFood {
+    x: i32,
+    y: i32,
+    poison: bool,
+}
+
+impl<'a> Board<'a> {
+    fn of(view: &BoardView<'a>) -> Board<'a> {
+        Board {
+            tick: view.tick(),
+            width: view.width(),
+            height: view.height(),
+            wall_mode: view.wall_mode(),
+            you: view.player(),
+            snakes: view
+                .players()
+                .iter()
+                .map(|player| BoardSnake {
+                    cells: player.snake().cells().copied().collect(),
+                    direction: player.snake().direction(),
+                    alive: player.is_alive(),
+                })
+                .collect(),
+            foods: view
+                .foods()
+                .iter()
+                .map(|food| BoardFood {
+                    x: food.pos.x,
+                    y: food.pos.y,
+                    poison: food.is_poison(),
+                })
+                .collect(),
+            obstacles: view.obstacles(),
+        }
+    }
+}
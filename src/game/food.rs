@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+
+use super::grid::GridPos;
+use super::rng::Rng;
+
+/// The kinds of regular food, which differ in what they are worth, see [`FoodKinds`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FoodKind {
+    Normal,
+    Golden,
+    Mega,
+}
+
+impl FoodKind {
+    pub const ALL: [FoodKind; 3] = [FoodKind::Normal, FoodKind::Golden, FoodKind::Mega];
+}
+
+/// What one kind of food is worth, and how often it spawns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoodKindConfig {
+    /// How likely regular food is to be of this kind, relative to the weights of the others.
+    /// Zero keeps the kind from spawning at all.
+    pub weight: u32,
+    /// How many points eating it is worth, before any combo.
+    pub value: u32,
+    /// How many segments the snake gains from eating it.
+    pub growth: u32,
+}
+
+/// The settings of every kind of food.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FoodKinds {
+    pub normal: FoodKindConfig,
+    pub golden: FoodKindConfig,
+    pub mega: FoodKindConfig,
+}
+
+impl FoodKinds {
+    pub fn of(&self, kind: FoodKind) -> FoodKindConfig {
+        match kind {
+            FoodKind::Normal => self.normal,
+            FoodKind::Golden => self.golden,
+            FoodKind::Mega => self.mega,
+        }
+    }
+
+    /// Draws the kind of the next piece of food according to the weights. Nothing is drawn when
+    /// only one kind can spawn, so rounds with a single kind of food play out the same as
+    /// before there were kinds.
+    ///
+    /// ```
+    /// use snake_game::game::{FoodKind, FoodKinds, Rng};
+    ///
+    /// let mut kinds = FoodKinds::default();
+    /// kinds.normal.weight = 6;
+    /// kinds.golden.weight = 3;
+    /// kinds.mega.weight = 1;
+    ///
+    /// // Over many picks, each kind comes up in proportion to its weight, give or take 2%.
+    /// let mut rng = Rng::new(11);
+    /// let picks = (0..10_000).map(|_| kinds.pick(&mut rng)).collect::<Vec<FoodKind>>();
+    /// let shares = [(FoodKind::Normal, 0.6), (FoodKind::Golden, 0.3), (FoodKind::Mega, 0.1)];
+    /// for (kind, share) in shares {
+    ///     let count = picks.iter().filter(|&&picked| picked == kind).count();
+    ///     assert!((count as f64 / 10_000.0 - share).abs() < 0.02, "{kind:?}: {count}");
+    /// }
+    ///
+    /// // A kind with no weight never comes up.
+    /// kinds.golden.weight = 0;
+    /// assert!((0..1_000).all(|_| kinds.pick(&mut rng) != FoodKind::Golden));
+    ///
+    /// // With a single kind left, nothing is drawn.
+    /// kinds.normal.weight = 0;
+    /// let (mut picked, mut untouched) = (Rng::new(3), Rng::new(3));
+    /// assert_eq!(kinds.pick(&mut picked), FoodKind::Mega);
+    /// assert_eq!(picked.next_u64(), untouched.next_u64());
+    /// ```
+    ///
+    /// Golden food is worth five points and a segment, and mega food two points and three
+    /// segments, the first grown on the tick it is eaten and the rest on the ticks after.
+    ///
+    /// ```
+    /// use snake_game::game::{FoodKind, Game, GameConfig, StepResult, WallMode};
+    ///
+    /// let config = GameConfig::new(40, 1)
+    ///     .with_wall_mode(WallMode::Wrap)
+    ///     .with_food_weights(0, 1, 1)
+    ///     .with_seed(4);
+    /// let mut game = Game::with_config(config);
+    /// let (mut golden, mut mega, mut owed) = (0, 0, 0);
+    /// for _ in 0..200 {
+    ///     let next = game.snake().next_head();
+    ///     let food = game.foods().iter().find(|food| food.pos == next).map(|food| food.kind);
+    ///     let (len, score) = (game.snake().len(), game.score());
+    ///     let result = game.step();
+    ///     match food {
+    ///         Some(FoodKind::Golden) => {
+    ///             assert_eq!(result, StepResult::Ate);
+    ///             assert_eq!(game.score(), score + 5);
+    ///             golden += 1;
+    ///             owed += 1;
+    ///         }
+    ///         Some(FoodKind::Mega) => {
+    ///             assert_eq!(result, StepResult::Ate);
+    ///             assert_eq!(game.score(), score + 2);
+    ///             mega += 1;
+    ///             owed += 3;
+    ///         }
+    ///         _ => assert_eq!(game.score(), score),
+    ///     }
+    ///     if owed > 0 {
+    ///         assert_eq!(game.snake().len(), len + 1);
+    ///         owed -= 1;
+    ///     } else {
+    ///         assert_eq!(game.snake().len(), len);
+    ///     }
+    /// }
+    /// assert!(golden >= 2 && mega >= 2);
+    /// assert_eq!(game.snake().len(), 3 + golden + 3 * mega - owed);
+    /// ```
+    pub fn pick(&self, rng: &mut Rng) -> FoodKind {
+        let weighted = FoodKind::ALL.map(|kind| (kind, self.of(kind).weight as usize));
+        let mut spawning = weighted.iter().filter(|&&(_, weight)| weight > 0);
+        if let (Some(&(kind, _)), None) = (spawning.next(), spawning.next()) {
+            return kind;
+        }
+
+        let total = weighted.iter().map(|&(_, weight)| weight).sum::<usize>();
+        if total == 0 {
+            return FoodKind::Normal;
+        }
+        let mut roll = rng.below(total);
+        weighted
+            .into_iter()
+            .find(|&(_, weight)| {
+                let hit = roll < weight;
+                roll = roll.saturating_sub(weight);
+                hit
+            })
+            .map_or(FoodKind::Normal, |(kind, _)| kind)
+    }
+}
+
+impl Default for FoodKinds {
+    /// Only normal food, worth a point and a segment. Golden food is worth five points and
+    /// mega food three segments, should they be given a weight.
+    fn default() -> FoodKinds {
+        FoodKinds {
+            normal: FoodKindConfig {
+                weight: 1,
+                value: 1,
+                growth: 1,
+            },
+            golden: FoodKindConfig {
+                weight: 0,
+                value: 5,
+                growth: 1,
+            },
+            mega: FoodKindConfig {
+                weight: 0,
+                value: 2,
+                growth: 3,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Food {
+    pub pos: GridPos,
+    /// The kind of regular food this is. Poison is always `Normal`.
+    pub kind: FoodKind,
+    /// How many points eating this food is worth.
+    pub value: u32,
+    /// How many segments the snake gains from eating it.
+    pub growth: u32,
+    /// How many points eating this food costs.
+    pub penalty: u32,
+    /// How many segments the snake loses from eating it.
+    pub shrink: u32,
+    /// The food wanders to a cell next to it on every tick that is a multiple of this. Zero
+    /// keeps it in place.
+    pub moves_every: u32,
+    /// The tick the food spawned on, which counts towards how long it has left before it rots.
+    pub spawned_at: u64,
+}
+
+impl Food {
+    /// Regular food of `kind`, worth what `kinds` says it is.
+    pub fn new(pos: GridPos, kind: FoodKind, kinds: &FoodKinds) -> Food {
+        let config = kinds.of(kind);
+        Food {
+            pos,
+            kind,
+            value: config.value,
+            growth: config.growth,
+            penalty: 0,
+            shrink: 0,
+            moves_every: 0,
+            spawned_at: 0,
+        }
+    }
+
+    /// Like [`Food::new`], wandering every `every` ticks.
+    pub fn moving(pos: GridPos, kind: FoodKind, kinds: &FoodKinds, every: u32) -> Food {
+        Food {
+            moves_every: every,
+            ..Food::new(pos, kind, kinds)
+        }
+    }
+
+    /// Food that takes points away and shrinks the snake by two segments.
+    pub fn poison(pos: GridPos) -> Food {
+        Food {
+            pos,
+            kind: FoodKind::Normal,
+            value: 0,
+            growth: 0,
+            penalty: 1,
+            shrink: 2,
+            moves_every: 0,
+            spawned_at: 0,
+        }
+    }
+
+    pub fn is_poison(&self) -> bool {
+        self.shrink > 0
+    }
+
+    /// The last tick the food can be eaten on when it lasts `lifetime` ticks, or `None` if it
+    /// never rots. Poison doesn't.
+    pub fn last_tick(&self, lifetime: u64) -> Option<u64> {
+        if lifetime == 0 || self.is_poison() {
+            return None;
+        }
+        Some(self.spawned_at + lifetime)
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+/// A cell of the playfield. The origin is the top left corner and `y` grows downwards.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GridPos {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl GridPos {
+    pub const fn new(x: i32, y: i32) -> GridPos {
+        GridPos { x, y }
+    }
+
+    /// The neighbouring cell in the given direction. It may be off the board, which is why cells
+    /// are signed: a cell left of column zero is at `-1`, not somewhere past `u32::MAX`.
+    pub fn offset(self, direction: Direction) -> GridPos {
+        let (dx, dy) = direction.delta();
+        GridPos::new(self.x + dx, self.y + dy)
+    }
+
+    /// The cell on a `dims[0]` by `dims[1]` board that this one ends up on when the edges wrap
+    /// around. Cells on the board stay where they are. `dims` must not be zero.
+    ///
+    /// ```
+    /// use snake_game::game::GridPos;
+    ///
+    /// let dims = [5, 3];
+    /// // Off the first row and column onto the last ones, and off the last onto the first.
+    /// assert_eq!(GridPos::new(-1, -1).wrap(dims), GridPos::new(4, 2));
+    /// assert_eq!(GridPos::new(5, 3).wrap(dims), GridPos::new(0, 0));
+    /// assert_eq!(GridPos::new(-1, 3).wrap(dims), GridPos::new(4, 0));
+    /// assert_eq!(GridPos::new(5, -1).wrap(dims), GridPos::new(0, 2));
+    ///
+    /// // Every cell within a few boards of this one lands on the board, a whole number of
+    /// // boards away along each axis, and the ones on it already stay put.
+    /// for y in -9..12 {
+    ///     for x in -15..20 {
+    ///         let cell = GridPos::new(x, y);
+    ///         let wrapped = cell.wrap(dims);
+    ///         assert!(wrapped.is_within(dims));
+    ///         assert_eq!(((x - wrapped.x) % 5, (y - wrapped.y) % 3), (0, 0));
+    ///         assert_eq!(wrapped == cell, cell.is_within(dims));
+    ///     }
+    /// }
+    /// ```
+    pub fn wrap(self, dims: [u32; 2]) -> GridPos {
+        GridPos::new(
+            self.x.rem_euclid(dims[0] as i32),
+            self.y.rem_euclid(dims[1] as i32),
+        )
+    }
+
+    /// Whether the cell is on a `dims[0]` by `dims[1]` board.
+    pub fn is_within(self, dims: [u32; 2]) -> bool {
+        (0..dims[0] as i32).contains(&self.x) && (0..dims[1] as i32).contains(&self.y)
+    }
+
+    /// How many moves it takes to get from one cell to the other, not counting wrapping.
+    pub fn manhattan_distance(self, other: GridPos) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+/// Where a snake is heading. The diagonals, which move a cell along both axes at once, are only
+/// allowed when the config turns them on, see
+/// [`GameConfig::diagonals`](super::GameConfig::diagonals).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    /// The four straight directions, which are also the ways one cell neighbours another.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub const DIAGONALS: [Direction; 4] = [
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    /// The straight directions then the diagonals.
+    pub const ALL_EIGHT: [Direction; 8] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    pub fn is_diagonal(self) -> bool {
+        let (dx, dy) = self.delta();
+        dx != 0 && dy != 0
+    }
+
+    /// The direction with the steps of both of them, if that is one: up and left make up-left,
+    /// while up and down, or up and up, make nothing.
+    pub fn combine(self, other: Direction) -> Option<Direction> {
+        let (a, b) = (self.delta(), other.delta());
+        let delta = (a.0 + b.0, a.1 + b.1);
+        Direction::DIAGONALS
+            .into_iter()
+            .find(|direction| direction.delta() == delta)
+    }
+
+    pub fn delta(self) -> (i32, i32) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (1, -1),
+            Direction::DownLeft => (-1, 1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+
+    /// Whether turning to this direction while heading towards `heading` would send a snake
+    /// straight back into its own neck, which it does when that is exactly the other way. Only
+    /// snakes longer than one cell have a neck, so whoever asks decides whether that matters.
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart};
+    ///
+    /// assert!(Direction::Left.is_reversal_of(Direction::Right));
+    /// assert!(Direction::DownLeft.is_reversal_of(Direction::UpRight));
+    /// assert!(!Direction::Up.is_reversal_of(Direction::Right));
+    /// assert!(!Direction::Right.is_reversal_of(Direction::Right));
+    ///
+    /// let play = |length, turns: &[Direction]| {
+    ///     let start = SnakeStart::new(GridPos::new(5, 5), Direction::Right, length);
+    ///     let config = GameConfig::new(10, 10)
+    ///         .with_starts(vec![start])
+    ///         .with_food_count(0);
+    ///     let mut game = Game::with_config(config);
+    ///     for &direction in turns {
+    ///         game.turn(direction);
+    ///     }
+    ///     game.step();
+    ///     game.step();
+    ///     game.snake().head()
+    /// };
+    /// // A single cell has no neck, so it may turn straight back.
+    /// assert_eq!(play(1, &[Direction::Left]), GridPos::new(3, 5));
+    /// assert_eq!(play(3, &[Direction::Left]), GridPos::new(7, 5));
+    /// // Turns are checked against the one queued before them, not the heading before the tick:
+    /// // down after up is a reversal, left after up isn't.
+    /// assert_eq!(play(3, &[Direction::Up, Direction::Down]), GridPos::new(5, 3));
+    /// assert_eq!(play(3, &[Direction::Up, Direction::Left]), GridPos::new(4, 4));
+    /// ```
+    pub fn is_reversal_of(self, heading: Direction) -> bool {
+        self == heading.opposite()
+    }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::UpLeft => Direction::DownRight,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::DownRight => Direction::UpLeft,
+        }
+    }
+
+    /// The direction seen in a mirror standing along the vertical: left and right swap places,
+    /// up and down stay as they are.
+    pub fn mirrored(self) -> Direction {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::UpLeft => Direction::UpRight,
+            Direction::UpRight => Direction::UpLeft,
+            Direction::DownLeft => Direction::DownRight,
+            Direction::DownRight => Direction::DownLeft,
+            Direction::Up | Direction::Down => self,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// How many scores the table keeps.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+/// A score in the table, along with the name of the [`Difficulty`](super::Difficulty) it was
+/// made on.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u32,
+    pub difficulty: String,
+}
+
+/// The best scores ever made, best first, kept in a file so they survive restarts.
+///
+/// Problems with the file never stop the game: they are reported as warnings and the table
+/// simply lives in memory.
+///
+/// ```
+/// use snake_game::game::{HighScores, MAX_HIGH_SCORES};
+///
+/// let dir = std::env::temp_dir().join(format!("snake-high-scores-{}", std::process::id()));
+/// let path = dir.join("high_scores.json");
+/// let mut high_scores = HighScores::load_from(&path);
+/// assert_eq!(high_scores.best(), None);
+///
+/// for score in [30, 10, 50, 20, 40, 60, 5, 70, 15, 25, 35, 45] {
+///     high_scores.record(score, "normal");
+/// }
+/// // A tie goes below the score that was there first, and one too low isn't kept.
+/// assert_eq!(high_scores.record(45, "hard"), Some(4));
+/// assert_eq!(high_scores.record(1, "easy"), None);
+/// high_scores.save();
+///
+/// let loaded = HighScores::load_from(&path);
+/// let scores = loaded.scores().iter().map(|entry| entry.score).collect::<Vec<_>>();
+/// assert_eq!(scores.len(), MAX_HIGH_SCORES);
+/// assert_eq!(scores, [70, 60, 50, 45, 45, 40, 35, 30, 25, 20]);
+/// assert_eq!(loaded.scores()[4].difficulty, "hard");
+///
+/// // A damaged file leaves an empty table rather than failing.
+/// std::fs::write(&path, "{ not json").unwrap();
+/// assert!(HighScores::load_from(&path).scores().is_empty());
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    scores: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Loads the table from the user's data directory.
+    pub fn load() -> HighScores {
+        match dirs::data_dir() {
+            Some(dir) => HighScores::load_from(dir.join("snake_game").join("high_scores.json")),
+            None => {
+                eprintln!("warning: no data directory, high scores won't be kept");
+                HighScores::default()
+            }
+        }
+    }
+
+    /// Loads the table from `path`, which is also where [`HighScores::save`] writes it. A missing
+    /// file is an empty table.
+    pub fn load_from(path: impl Into<PathBuf>) -> HighScores {
+        let path = path.into();
+        let mut high_scores = match read(&path) {
+            Ok(high_scores) => high_scores,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HighScores::default(),
+            Err(e) => {
+                eprintln!(
+                    "warning: could not read high scores from {}: {}",
+                    path.display(),
+                    e
+                );
+                HighScores::default()
+            }
+        };
+
+        high_scores.scores.sort_by_key(|entry| Reverse(entry.score));
+        high_scores.scores.truncate(MAX_HIGH_SCORES);
+        high_scores.path = Some(path);
+        high_scores
+    }
+
+    /// Best first.
+    pub fn scores(&self) -> &[HighScore] {
+        &self.scores
+    }
+
+    pub fn best(&self) -> Option<u32> {
+        self.scores.first().map(|entry| entry.score)
+    }
+
+    /// The best score in the table made on `difficulty`.
+    pub fn best_on(&self, difficulty: &str) -> Option<u32> {
+        self.scores
+            .iter()
+            .find(|entry| entry.difficulty == difficulty)
+            .map(|entry| entry.score)
+    }
+
+    /// Adds `score`, made on `difficulty`, to the table and returns its rank, starting at 0, or
+    /// `None` if it didn't make the cut. Ties rank below the scores that were already there.
+    pub fn record(&mut self, score: u32, difficulty: &str) -> Option<usize> {
+        let rank = self.scores.partition_point(|other| other.score >= score);
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        let entry = HighScore {
+            score,
+            difficulty: difficulty.to_string(),
+        };
+        self.scores.insert(rank, entry);
+        self.scores.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+
+    /// Where the replay of the best round in table `table` is kept, next to the table itself.
+    /// `None` when the table lives in memory only.
+    pub fn best_run_path(&self, table: &str) -> Option<PathBuf> {
+        let name = format!("best_{}.replay", table.replace(' ', "_"));
+        self.path.as_ref().map(|path| path.with_file_name(name))
+    }
+
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Err(e) = write(self, path) {
+            eprintln!(
+                "warning: could not save high scores to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<HighScores> {
+    let source = fs::read_to_string(path)?;
+    serde_json::from_str(&source).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write(high_scores: &HighScores, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(high_scores)?)
+}
@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::grid::{Direction, GridPos};
+
+/// A board layout described as a plain-text grid, one character per cell:
+///
+/// - `#` is a wall
+/// - `.` is an empty cell
+/// - `S` is where the snake's head starts
+/// - `s` is a segment of the snake's body, which trails behind the head in a straight line;
+///   without any, the snake is [`START_LENGTH`](super::START_LENGTH) long and heads wherever
+///   there is room
+/// - `F` is a cell food likes to spawn on
+/// - a digit is one end of a portal, the other end being the only other cell with that digit
+/// - `C` is a checkpoint, which a snake that loses a life comes back to once it has crossed it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Level {
+    pub width: u32,
+    pub height: u32,
+    pub walls: Vec<GridPos>,
+    pub start: GridPos,
+    /// The snake's body, from the cell behind the head to the tail. Empty when the level leaves
+    /// the length and heading of the snake to the game.
+    pub start_body: Vec<GridPos>,
+    pub food_hints: Vec<GridPos>,
+    pub portals: Vec<(GridPos, GridPos)>,
+    pub checkpoints: Vec<GridPos>,
+}
+
+/// Why a level can't be read, or played.
+///
+/// ```
+/// use snake_game::game::{GridPos, Level, LevelError};
+///
+/// let parse = |source: &str| source.parse::<Level>().unwrap_err();
+/// assert!(matches!(parse(""), LevelError::Empty));
+/// assert!(matches!(parse("\n  \n"), LevelError::Empty));
+/// assert!(matches!(
+///     parse("S...\n..\n"),
+///     LevelError::NotRectangular { line: 2, expected: 4, found: 2 }
+/// ));
+/// assert!(matches!(
+///     parse("S.x."),
+///     LevelError::UnknownTile { line: 1, column: 3, tile: 'x' }
+/// ));
+/// assert!(matches!(parse("....\n...."), LevelError::MissingStart));
+/// assert!(matches!(
+///     parse("S..S"),
+///     LevelError::MultipleStarts { first, second }
+///         if first == GridPos::new(0, 0) && second == GridPos::new(3, 0)
+/// ));
+/// assert!(matches!(
+///     parse("#..\nS#.\n#.."),
+///     LevelError::EnclosedStart(start) if start == GridPos::new(0, 1)
+/// ));
+/// assert!(matches!(
+///     parse("S.s."),
+///     LevelError::StrayBody(cell) if cell == GridPos::new(2, 0)
+/// ));
+/// assert!(matches!(parse("S..1"), LevelError::UnpairedPortal { tile: '1', count: 1 }));
+/// // The snake takes both cells, leaving none for food.
+/// assert!(matches!(parse("Ss"), LevelError::NoFreeCell));
+/// assert!(matches!(Level::load("no/such/level.txt"), Err(LevelError::Io(_))));
+///
+/// // Every level that ships with the game reads fine.
+/// for name in Level::BUNDLED {
+///     assert!(Level::bundled(name).is_some(), "{}", name);
+/// }
+/// assert!(Level::bundled("no such level").is_none());
+/// ```
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Empty,
+    /// Line `line` has `found` cells where the first line had `expected`.
+    NotRectangular {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    UnknownTile {
+        line: usize,
+        column: usize,
+        tile: char,
+    },
+    MissingStart,
+    MultipleStarts {
+        first: GridPos,
+        second: GridPos,
+    },
+    /// The start is walled in on every side.
+    EnclosedStart(GridPos),
+    /// The body segment at this cell isn't part of a straight line behind the start.
+    StrayBody(GridPos),
+    /// Portal `tile` appears `count` times instead of twice.
+    UnpairedPortal {
+        tile: char,
+        count: usize,
+    },
+    /// Every cell is a wall, a portal or taken by the snake, leaving nowhere for food to go.
+    NoFreeCell,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(err) => write!(f, "could not read level: {}", err),
+            LevelError::Empty => write!(f, "level is empty"),
+            LevelError::NotRectangular {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} is {} cells wide, but the level is {} cells wide",
+                line, found, expected
+            ),
+            LevelError::UnknownTile { line, column, tile } => {
+                write!(
+                    f,
+                    "unknown tile {:?} at line {}, column {}",
+                    tile, line, column
+                )
+            }
+            LevelError::MissingStart => write!(f, "level has no start marker 'S'"),
+            LevelError::MultipleStarts { first, second } => write!(
+                f,
+                "level has more than one start marker, at ({}, {}) and ({}, {})",
+                first.x, first.y, second.x, second.y
+            ),
+            LevelError::EnclosedStart(start) => write!(
+                f,
+                "the start at ({}, {}) is walled in on every side",
+                start.x, start.y
+            ),
+            LevelError::StrayBody(cell) => write!(
+                f,
+                "the body segment at ({}, {}) isn't in a straight line behind the start",
+                cell.x, cell.y
+            ),
+            LevelError::UnpairedPortal { tile, count } => write!(
+                f,
+                "portal {:?} appears {} times, but portals come in pairs",
+                tile, count
+            ),
+            LevelError::NoFreeCell => write!(f, "level has no free cell for food to spawn on"),
+        }
+    }
+}
+
+impl Error for LevelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LevelError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for LevelError {
+    fn from(err: io::Error) -> LevelError {
+        LevelError::Io(err)
+    }
+}
+
+const BOX: &str = include_str!("../../levels/box.txt");
+const CROSS: &str = include_str!("../../levels/cross.txt");
+const ROOMS: &str = include_str!("../../levels/rooms.txt");
+
+impl Level {
+    /// The names of the levels that ship with the game.
+    pub const BUNDLED: [&'static str; 3] = ["box", "cross", "rooms"];
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Level, LevelError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// One of the levels listed in [`Level::BUNDLED`].
+    pub fn bundled(name: &str) -> Option<Level> {
+        let source = match name {
+            "box" => BOX,
+            "cross" => CROSS,
+            "rooms" => ROOMS,
+            _ => return None,
+        };
+
+        Some(source.parse().expect("bundled levels are valid"))
+    }
+
+    pub fn is_wall(&self, cell: GridPos) -> bool {
+        self.walls.contains(&cell)
+    }
+
+    /// The heading away from the snake's body, or without one, a heading out of the start cell
+    /// that doesn't run straight into a wall.
+    pub fn start_direction(&self) -> Direction {
+        if let Some(&neck) = self.start_body.first() {
+            return Direction::ALL
+                .into_iter()
+                .find(|&direction| neck.offset(direction) == self.start)
+                .unwrap_or(Direction::Right);
+        }
+
+        [
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+            Direction::Up,
+        ]
+        .into_iter()
+        .find(|&direction| self.is_open(self.start.offset(direction)))
+        .unwrap_or(Direction::Right)
+    }
+
+    /// How long the snake starts, its head included.
+    pub fn start_length(&self) -> usize {
+        match self.start_body.len() {
+            0 => super::START_LENGTH,
+            body => body + 1,
+        }
+    }
+
+    fn is_open(&self, cell: GridPos) -> bool {
+        (0..self.width as i32).contains(&cell.x)
+            && (0..self.height as i32).contains(&cell.y)
+            && !self.is_wall(cell)
+    }
+}
+
+impl FromStr for Level {
+    type Err = LevelError;
+
+    fn from_str(source: &str) -> Result<Level, LevelError> {
+        let lines = source.lines().map(str::trim_end).collect::<Vec<_>>();
+        let lines = match lines.iter().rposition(|line| !line.is_empty()) {
+            Some(last) => &lines[..=last],
+            None => return Err(LevelError::Empty),
+        };
+
+        let width = lines[0].chars().count();
+        let mut walls = Vec::new();
+        let mut food_hints = Vec::new();
+        let mut checkpoints = Vec::new();
+        let mut start: Option<GridPos> = None;
+        let mut body = Vec::new();
+        let mut portal_ends = BTreeMap::<char, Vec<GridPos>>::new();
+
+        for (y, line) in lines.iter().enumerate() {
+            let found = line.chars().count();
+            if found != width {
+                return Err(LevelError::NotRectangular {
+                    line: y + 1,
+                    expected: width,
+                    found,
+                });
+            }
+
+            for (x, tile) in line.chars().enumerate() {
+                let cell = GridPos::new(x as i32, y as i32);
+                match tile {
+                    '#' => walls.push(cell),
+                    '.' => {}
+                    'F' => food_hints.push(cell),
+                    'C' => checkpoints.push(cell),
+                    's' => body.push(cell),
+                    '0'..='9' => portal_ends.entry(tile).or_default().push(cell),
+                    'S' => {
+                        if let Some(first) = start {
+                            return Err(LevelError::MultipleStarts {
+                                first,
+                                second: cell,
+                            });
+                        }
+                        start = Some(cell);
+                    }
+                    tile => {
+                        return Err(LevelError::UnknownTile {
+                            line: y + 1,
+                            column: x + 1,
+                            tile,
+                        })
+                    }
+                }
+            }
+        }
+
+        let mut portals = Vec::new();
+        for (tile, ends) in portal_ends {
+            match ends[..] {
+                [a, b] => portals.push((a, b)),
+                _ => {
+                    return Err(LevelError::UnpairedPortal {
+                        tile,
+                        count: ends.len(),
+                    })
+                }
+            }
+        }
+
+        let start = start.ok_or(LevelError::MissingStart)?;
+        let level = Level {
+            width: width as u32,
+            height: lines.len() as u32,
+            walls,
+            start,
+            start_body: straight_body(start, body)?,
+            food_hints,
+            portals,
+            checkpoints,
+        };
+
+        let open = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .any(|direction| level.is_open(level.start.offset(direction)));
+        if !open {
+            return Err(LevelError::EnclosedStart(level.start));
+        }
+
+        let taken = level.walls.len() + 2 * level.portals.len() + level.start_length();
+        if taken >= (level.width * level.height) as usize {
+            return Err(LevelError::NoFreeCell);
+        }
+
+        Ok(level)
+    }
+}
+
+/// Orders the `body` segments from the neck to the tail, checking that they line up behind
+/// `start` without gaps.
+fn straight_body(start: GridPos, mut body: Vec<GridPos>) -> Result<Vec<GridPos>, LevelError> {
+    body.sort_by_key(|cell| cell.manhattan_distance(start));
+    let behind = match body.first() {
+        Some(&neck) => Direction::ALL
+            .into_iter()
+            .find(|&direction| start.offset(direction) == neck)
+            .ok_or(LevelError::StrayBody(neck))?,
+        None => return Ok(body),
+    };
+
+    let mut expected = start;
+    for &cell in &body {
+        expected = expected.offset(behind);
+        if cell != expected {
+            return Err(LevelError::StrayBody(cell));
+        }
+    }
+    Ok(body)
+}
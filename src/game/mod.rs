@@ -0,0 +1,1902 @@
+mod achievements;
+mod ai;
+mod chaser;
+mod checkpoint;
+mod config;
+mod controller;
+mod daily;
+mod difficulty;
+mod editor;
+mod event;
+mod external;
+mod food;
+mod grid;
+mod high_scores;
+mod level;
+mod obstacles;
+mod player;
+mod power_up;
+mod replay;
+mod rewind;
+mod rng;
+mod save;
+mod score;
+mod simulation;
+mod snake;
+mod speed;
+mod stats;
+mod tournament;
+mod tutorial;
+
+use std::cell::Cell;
+use std::collections::{BTreeSet, HashSet};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use rewind::{History, PlayerChanges, TickChanges};
+
+pub use achievements::{Achievement, Achievements, ACHIEVEMENTS};
+pub use ai::GreedyAi;
+pub use chaser::{Chaser, CHASER_DISTANCE, CHASER_MOVES_EVERY};
+pub use checkpoint::Checkpoint;
+pub use config::{ConfigError, GameConfig, GameMode, HeadOn, SnakeStart, WallMode};
+pub use controller::{
+    BoardView, Controller, KeyboardController, RandomController, ReplayController,
+};
+pub use daily::{daily_seed, DailyChallenge, DailyRecords, Date};
+pub use difficulty::Difficulty;
+pub use editor::{Editor, EditorTile, Tool, UNDO_LIMIT};
+pub use event::{DeathCause, GameEvent};
+pub use external::{ExternalBot, BOT_TIMEOUT};
+pub use food::{Food, FoodKind, FoodKindConfig, FoodKinds};
+pub use grid::{Direction, GridPos};
+pub use high_scores::{HighScore, HighScores, MAX_HIGH_SCORES};
+pub use level::{Level, LevelError};
+pub use player::Player;
+pub use power_up::{ActiveEffect, Effect, PowerUp};
+pub use replay::{Input, Playback, Replay, ReplayError};
+pub use rng::Rng;
+pub use save::SaveError;
+pub use score::Score;
+pub use simulation::Simulation;
+pub use snake::Snake;
+pub use speed::SpeedCurve;
+pub use stats::{Deaths, Stats};
+pub use tournament::{ControllerFactory, Standing, Tournament, TournamentResults};
+pub use tutorial::{Tutorial, TutorialProgress, TutorialStep, TUTORIAL_STEPS};
+
+/// How long snakes are when the config doesn't say.
+pub const START_LENGTH: usize = 3;
+
+/// Poison never shrinks a snake below this many segments. A snake this short that eats poison
+/// dies instead.
+const MIN_LENGTH: usize = 2;
+
+/// The score multiplier of a combo never goes past this.
+pub const MAX_MULTIPLIER: u32 = 5;
+
+/// How many cells in front of the snake's starting position are kept clear of random obstacles.
+const START_CLEARANCE: usize = 3;
+
+/// How many turns a snake can have queued up at once. Turns past that are dropped.
+pub const TURN_BUFFER: usize = 2;
+
+/// The points for filling the whole board, except in survival mode, which only counts time.
+pub const WIN_BONUS: u32 = 100;
+
+/// How many ticks a snake that lost a life waits before it comes back.
+pub const RESPAWN_TICKS: u64 = 10;
+
+/// What happened during a call to [`Game::step`].
+///
+/// A snake that ate grows by a segment that tick, its tail staying where it was:
+///
+/// ```
+/// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart, StepResult};
+///
+/// // A single row, so that the food is always somewhere ahead.
+/// let start = SnakeStart::new(GridPos::new(2, 0), Direction::Right, 3);
+/// let config = GameConfig::new(12, 1)
+///     .with_starts(vec![start])
+///     .with_food_weights(1, 0, 0)
+///     .with_seed(4);
+/// let mut game = Game::with_config(config);
+/// let mut eaten = 0;
+/// while game.snake().head().x < 11 {
+///     let (len, tail) = (game.snake().len(), game.snake().tail());
+///     match game.step() {
+///         StepResult::Ate => {
+///             eaten += 1;
+///             assert_eq!(game.snake().len(), len + 1);
+///             assert_eq!(game.snake().tail(), tail);
+///         }
+///         result => {
+///             assert_eq!(result, StepResult::Moved);
+///             assert_eq!(game.snake().len(), len);
+///             assert_ne!(game.snake().tail(), tail);
+///         }
+///     }
+/// }
+/// assert!(eaten > 0);
+/// assert_eq!(game.snake().len(), 3 + eaten);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepResult {
+    Moved,
+    Ate,
+    /// Ate poison and survived it.
+    Poisoned,
+    /// Died with lives to spare, and will be back after [`RESPAWN_TICKS`].
+    LostLife,
+    Died,
+    /// Ran into something in zen mode, and stayed where it was instead of dying.
+    Blocked,
+}
+
+/// Where a snake is headed on the current tick, and what comes of it, worked out by
+/// [`Game::plan_moves`] before any snake moves.
+#[derive(Clone, Copy, Debug)]
+struct Move {
+    /// The cell the head moves onto, `None` past a solid wall.
+    target: Option<GridPos>,
+    /// Whether the snake grows this tick, keeping its tail where it is.
+    growing: bool,
+    /// What kills the snake, if anything does.
+    cause: Option<DeathCause>,
+    /// Whether the snake is blocked and stays where it is, tail and all.
+    stays: bool,
+}
+
+/// How a head clashing with another one comes out of it.
+enum Clash {
+    Blocked,
+    /// Killed by the snake of the player at this index.
+    Killed(usize),
+}
+
+/// Why a round ended.
+///
+/// On a 3×3 board with its middle walled off, a snake going round and round the ring that is
+/// left, eating whatever food it comes across, ends up filling it and winning:
+///
+/// ```
+/// use snake_game::game::{
+///     Direction, Game, GameConfig, GridPos, RoundEnd, SnakeStart, StepResult, WIN_BONUS,
+/// };
+///
+/// let start = SnakeStart::new(GridPos::new(0, 0), Direction::Right, 1);
+/// let config = GameConfig::new(3, 3)
+///     .with_starts(vec![start])
+///     .with_obstacles(vec![GridPos::new(1, 1)])
+///     .with_food_weights(1, 0, 0)
+///     .with_seed(3);
+/// let mut game = Game::with_config(config);
+/// let mut eaten = 0;
+/// while !game.is_over() && game.ticks() < 200 {
+///     match game.snake().head() {
+///         GridPos { x: 2, y: 0 } => game.turn(Direction::Down),
+///         GridPos { x: 2, y: 2 } => game.turn(Direction::Left),
+///         GridPos { x: 0, y: 2 } => game.turn(Direction::Up),
+///         GridPos { x: 0, y: 0 } => game.turn(Direction::Right),
+///         _ => {}
+///     }
+///     if game.step() == StepResult::Ate {
+///         eaten += 1;
+///     }
+/// }
+/// assert_eq!(game.round_end(), Some(RoundEnd::Won));
+/// assert_eq!(game.snake().len(), 8);
+/// assert_eq!(eaten, 7);
+/// assert_eq!(game.score(), eaten + WIN_BONUS);
+/// assert!(game.foods().is_empty());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoundEnd {
+    /// The only snake of a single player round died.
+    Died,
+    /// Every other snake died, leaving the player at this index.
+    Winner(usize),
+    /// The last snakes standing died on the same tick.
+    Tie,
+    /// The clock of a timed round ran out with more than one snake still going, or the only one
+    /// of a single player round.
+    TimeUp,
+    /// The snake of a single player round filled the board, earning [`WIN_BONUS`] points on
+    /// top of its score.
+    Won,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Game {
+    config: GameConfig,
+    players: Vec<Player>,
+    foods: Vec<Food>,
+    power_ups: Vec<PowerUp>,
+    chasers: Vec<Chaser>,
+    /// Ordered, so that the state comes out the same way every time it is saved.
+    obstacles: BTreeSet<GridPos>,
+    rng: Rng,
+    round_end: Option<RoundEnd>,
+    ticks: u64,
+    /// Game time gone by, the sum of the intervals of the ticks run so far.
+    elapsed: Duration,
+    /// Listeners aren't part of the round, so they don't survive a save.
+    #[serde(skip)]
+    listeners: Vec<Box<dyn FnMut(GameEvent)>>,
+    /// The achievements each tracked player has yet to unlock. Like the listeners, they are
+    /// left out of saves.
+    #[serde(skip)]
+    tracked: Vec<(usize, &'static Achievement)>,
+    /// The best score and the interval the speed curve gives for it, so the curve is only
+    /// looked at again once the score changes.
+    #[serde(skip)]
+    speed: Cell<Option<(u32, Duration)>>,
+    /// The ticks that can be taken back, once [`Game::keep_history`] turned it on. Left out of
+    /// saves, so a resumed round can't be rewound past where it was saved.
+    #[serde(skip)]
+    history: Option<History>,
+    /// What the tick under way changed so far, while there is a history to keep it in.
+    #[serde(skip)]
+    changes: Option<TickChanges>,
+    /// Whether a tick of the round was ever taken back.
+    rewound: bool,
+}
+
+impl Game {
+    pub fn new(width: u32, height: u32) -> Game {
+        Game::with_config(GameConfig::new(width, height))
+    }
+
+    pub fn with_config(mut config: GameConfig) -> Game {
+        // The seed is kept in the config so the round can be replayed.
+        let seed = config.seed.unwrap_or_else(|| Rng::from_time().next_u64());
+        config.seed = Some(seed);
+        let mut rng = Rng::new(seed);
+
+        let players = (0..config.players.max(1))
+            .map(|i| {
+                let start = player_start(&config, i);
+                let snake = Snake::new(start.head, start.direction, start.length);
+                Player::new(snake, config.lives.max(1))
+            })
+            .collect::<Vec<_>>();
+
+        let mut obstacles = config.obstacles.iter().copied().collect::<BTreeSet<_>>();
+        let random_obstacles =
+            (config.obstacle_density * f64::from(config.width * config.height)).round() as usize;
+        let mut scattered = Vec::new();
+        if random_obstacles > 0 {
+            let mut reserved = obstacles.iter().copied().collect::<HashSet<_>>();
+            // Keeps the portals and a way in and out of them clear.
+            for &end in config.portals.iter().flat_map(|(a, b)| [a, b]) {
+                reserved.insert(end);
+                reserved.extend(
+                    Direction::ALL
+                        .iter()
+                        .map(|&direction| end.offset(direction)),
+                );
+            }
+            for snake in players.iter().map(Player::snake) {
+                reserved.extend(snake.cells().copied());
+                let mut ahead = snake.head();
+                for _ in 0..START_CLEARANCE {
+                    ahead = ahead.offset(snake.direction());
+                    reserved.insert(ahead);
+                }
+            }
+
+            scattered = obstacles::scatter(
+                config.width,
+                config.height,
+                random_obstacles,
+                &reserved,
+                &mut rng,
+            );
+            obstacles.extend(scattered.iter().copied());
+        }
+
+        let mut game = Game {
+            config,
+            players,
+            foods: Vec::new(),
+            power_ups: Vec::new(),
+            chasers: Vec::new(),
+            obstacles,
+            rng,
+            round_end: None,
+            ticks: 0,
+            elapsed: Duration::ZERO,
+            listeners: Vec::new(),
+            tracked: Vec::new(),
+            speed: Cell::new(None),
+            history: None,
+            changes: None,
+            rewound: false,
+        };
+        if !scattered.is_empty() {
+            game.open_up(scattered);
+        }
+        game.spawn_chasers();
+        game.refill_food();
+        game
+    }
+
+    /// Checks `config` before building a game from it, unlike [`Game::with_config`], which
+    /// takes it as it is.
+    pub fn try_with_config(config: GameConfig) -> Result<Game, ConfigError> {
+        config.validate()?;
+        Ok(Game::with_config(config))
+    }
+
+    /// A default board set up for `difficulty`.
+    pub fn with_difficulty(difficulty: Difficulty) -> Result<Game, ConfigError> {
+        Ok(Game::with_config(difficulty.apply(GameConfig::default())?))
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    /// The seed all of the round's randomness comes from. Building a game from the same config
+    /// with this seed and feeding it the same turns plays the exact same round.
+    pub fn seed(&self) -> u64 {
+        self.config
+            .seed
+            .expect("the seed is picked when the game is built")
+    }
+
+    pub fn width(&self) -> u32 {
+        self.config.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.config.height
+    }
+
+    pub fn set_wall_mode(&mut self, wall_mode: WallMode) {
+        self.config.wall_mode = wall_mode;
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// Player one's snake.
+    pub fn snake(&self) -> &Snake {
+        &self.players[0].snake
+    }
+
+    pub fn foods(&self) -> &[Food] {
+        &self.foods
+    }
+
+    pub fn power_ups(&self) -> &[PowerUp] {
+        &self.power_ups
+    }
+
+    pub fn chasers(&self) -> &[Chaser] {
+        &self.chasers
+    }
+
+    pub fn obstacles(&self) -> &BTreeSet<GridPos> {
+        &self.obstacles
+    }
+
+    /// Player one's score.
+    ///
+    /// Every piece of normal food is worth a point:
+    ///
+    /// ```
+    /// use snake_game::game::{Game, GameConfig, StepResult, WallMode};
+    ///
+    /// let config = GameConfig::new(12, 1)
+    ///     .with_wall_mode(WallMode::Wrap)
+    ///     .with_food_weights(1, 0, 0)
+    ///     .with_seed(4);
+    /// let mut game = Game::with_config(config);
+    /// assert_eq!(game.score(), 0);
+    /// let mut eaten = 0;
+    /// while eaten < 3 {
+    ///     let score = game.score();
+    ///     match game.step() {
+    ///         StepResult::Ate => {
+    ///             eaten += 1;
+    ///             assert_eq!(game.score(), score + 1);
+    ///         }
+    ///         _ => assert_eq!(game.score(), score),
+    ///     }
+    /// }
+    /// assert_eq!(game.score(), 3);
+    /// assert_eq!(game.players()[0].score(), 3);
+    /// ```
+    pub fn score(&self) -> u32 {
+        self.players[0].score()
+    }
+
+    pub fn reset_score(&mut self) {
+        for player in &mut self.players {
+            player.score.reset();
+        }
+    }
+
+    /// How long the current tick lasts, which gets shorter as the best score goes up. Speed
+    /// effects of any snake still alive stretch or shrink it, for everyone since the snakes share
+    /// the clock.
+    pub fn tick_interval(&self) -> Duration {
+        let best = self.players.iter().map(Player::score).max().unwrap_or(0);
+        let factor = self
+            .players
+            .iter()
+            .filter(|player| player.alive)
+            .flat_map(Player::effects)
+            .map(|active| active.effect.interval_factor())
+            .product::<f64>();
+        let interval = match self.speed.get() {
+            Some((score, interval)) if score == best => interval,
+            _ => {
+                let interval = self.config.speed.interval(best);
+                self.speed.set(Some((best, interval)));
+                interval
+            }
+        };
+        interval.mul_f64(factor)
+    }
+
+    /// How many ticks a second the game currently runs at.
+    pub fn tick_rate(&self) -> f64 {
+        1.0 / self.tick_interval().as_secs_f64()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.round_end.is_some()
+    }
+
+    pub fn round_end(&self) -> Option<RoundEnd> {
+        self.round_end
+    }
+
+    /// Game time left in a timed round, `None` in other modes. The clock only moves with the
+    /// ticks, so it stops whenever the game does.
+    ///
+    /// A three second round, played from 50 ms frames with a ten second pause in the middle,
+    /// during which no frame time reaches the game:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snake_game::game::{Game, GameConfig, GameMode, RoundEnd, StepResult, WallMode};
+    /// use snake_game::ticker::Ticker;
+    ///
+    /// let config = GameConfig::new(40, 1)
+    ///     .with_wall_mode(WallMode::Wrap)
+    ///     .with_mode(GameMode::Timed { seconds: 3 })
+    ///     .with_tick_rate(10.0)
+    ///     .with_food_count(0);
+    /// let mut game = Game::with_config(config);
+    /// let mut ticker = Ticker::new();
+    /// fn play(game: &mut Game, ticker: &mut Ticker, frames: u32) {
+    ///     for _ in 0..frames {
+    ///         ticker.advance(Duration::from_millis(50));
+    ///         while !game.is_over() && ticker.tick(game.tick_interval()) {
+    ///             assert_eq!(game.step(), StepResult::Moved);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// play(&mut game, &mut ticker, 24);
+    /// assert_eq!(game.time_left(), Some(Duration::from_millis(1_800)));
+    /// // Paused for 200 frames, then back with the frame time reset, as after the countdown.
+    /// ticker.reset();
+    /// play(&mut game, &mut ticker, 34);
+    /// assert_eq!(game.time_left(), Some(Duration::from_millis(100)));
+    /// assert!(!game.is_over());
+    ///
+    /// // The last tick takes the clock to exactly zero, and ends the round there.
+    /// play(&mut game, &mut ticker, 2);
+    /// assert_eq!(game.ticks(), 30);
+    /// assert_eq!(game.elapsed(), Duration::from_secs(3));
+    /// assert_eq!(game.time_left(), Some(Duration::ZERO));
+    /// assert_eq!(game.round_end(), Some(RoundEnd::TimeUp));
+    /// ```
+    pub fn time_left(&self) -> Option<Duration> {
+        match self.config.mode {
+            GameMode::Classic | GameMode::Survival { .. } | GameMode::Zen => None,
+            GameMode::Timed { seconds } => {
+                Some(Duration::from_secs(seconds.into()).saturating_sub(self.elapsed))
+            }
+        }
+    }
+
+    /// How many ticks have run since the round started.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Game time gone by since the round started.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// How many more ticks player `i` has to eat again to keep their combo going, zero when
+    /// there is no combo to keep.
+    pub fn combo_ticks_left(&self, i: usize) -> u64 {
+        self.players
+            .get(i)
+            .map_or(0, |player| player.combo_until.saturating_sub(self.ticks))
+    }
+
+    /// How many more ticks player `i` is under `effect` for, the coming one included, or zero if
+    /// they aren't.
+    pub fn effect_ticks_left(&self, i: usize, effect: Effect) -> u64 {
+        self.players
+            .get(i)
+            .and_then(|player| player.effects.iter().find(|active| active.effect == effect))
+            .map_or(0, |active| active.until.saturating_sub(self.ticks))
+    }
+
+    /// The directions snakes can head in: the four straight ones, and the diagonals after them
+    /// if the config allows them.
+    pub fn directions(&self) -> &'static [Direction] {
+        if self.config.diagonals {
+            &Direction::ALL_EIGHT
+        } else {
+            &Direction::ALL
+        }
+    }
+
+    /// Where `player` asking to turn towards `direction` sends their snake: mirrored if the
+    /// config mirrors input, and turned around while the snake is under
+    /// [`Effect::ReversedControls`]. Mapping a direction twice gives it back, so a bot can map
+    /// the direction it wants to get the one to ask for.
+    ///
+    /// Turns are checked after they are reversed, so under the effect it is the key for the way
+    /// the snake is heading that would turn it straight back, and is ignored:
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Effect, Game, GameConfig, GridPos, SnakeStart};
+    /// use snake_game::game::WallMode;
+    ///
+    /// // Heading right along the middle row until it takes a power-up that reverses controls.
+    /// let start = SnakeStart::new(GridPos::new(3, 1), Direction::Right, 3);
+    /// let config = GameConfig::new(12, 3)
+    ///     .with_wall_mode(WallMode::Wrap)
+    ///     .with_starts(vec![start])
+    ///     .with_food_count(0)
+    ///     .with_power_ups(1)
+    ///     .with_debuffs(true)
+    ///     .with_seed(6);
+    /// let mut game = Game::with_config(config);
+    /// while game.effect_ticks_left(0, Effect::ReversedControls) == 0 {
+    ///     game.step();
+    /// }
+    /// assert_eq!(game.input_direction(0, Direction::Up), Direction::Down);
+    /// assert_eq!(game.input_direction(0, Direction::Right), Direction::Left);
+    ///
+    /// // Moves the head one cell `direction`, around the edges, and checks the snake went there.
+    /// fn moved(game: &mut Game, direction: Direction) {
+    ///     let next = game.neighbour(game.snake().head(), direction);
+    ///     game.step();
+    ///     assert_eq!(Some(game.snake().head()), next);
+    ///     assert_eq!(game.snake().direction(), direction);
+    /// }
+    ///
+    /// // Left now means right, where it is heading already, and right means straight back.
+    /// game.turn(Direction::Left);
+    /// game.turn(Direction::Right);
+    /// moved(&mut game, Direction::Right);
+    /// // Up goes down, then right goes left, and left would be back right, so it is ignored.
+    /// game.turn(Direction::Up);
+    /// game.turn(Direction::Right);
+    /// game.turn(Direction::Left);
+    /// moved(&mut game, Direction::Down);
+    /// moved(&mut game, Direction::Left);
+    /// moved(&mut game, Direction::Left);
+    ///
+    /// // Once the effect wears off, keys mean what they say again.
+    /// while game.effect_ticks_left(0, Effect::ReversedControls) > 0 {
+    ///     game.step();
+    /// }
+    /// assert_eq!(game.input_direction(0, Direction::Up), Direction::Up);
+    /// game.turn(Direction::Up);
+    /// moved(&mut game, Direction::Up);
+    /// ```
+    pub fn input_direction(&self, player: usize, direction: Direction) -> Direction {
+        let direction = if self.config.mirrored_input {
+            direction.mirrored()
+        } else {
+            direction
+        };
+        let reversed = self
+            .players
+            .get(player)
+            .is_some_and(|player| player.has_effect(Effect::ReversedControls));
+        if reversed {
+            direction.opposite()
+        } else {
+            direction
+        }
+    }
+
+    /// Steers player one's snake.
+    pub fn turn(&mut self, direction: Direction) {
+        self.turn_player(0, direction);
+    }
+
+    /// Queues a turn of `player`'s snake. Each tick applies the oldest turn queued, so two turns
+    /// made between the same two ticks play out one after the other instead of the second
+    /// overriding the first. A turn that doesn't change where the snake will be heading by then
+    /// is ignored, and so is any turn past [`TURN_BUFFER`].
+    ///
+    /// So is a turn straight back, unless the snake is a single cell long and has no neck to
+    /// run into. That is checked against the last turn queued, since that is the heading the
+    /// turn will be made from. Diagonal turns are ignored too unless the config allows them.
+    ///
+    /// `direction` goes through [`Game::input_direction`] before any of that, so the checks
+    /// apply to the turn the snake actually makes.
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart};
+    ///
+    /// let start = SnakeStart::new(GridPos::new(5, 5), Direction::Right, 3);
+    /// let config = GameConfig::new(10, 10)
+    ///     .with_starts(vec![start])
+    ///     .with_food_count(0);
+    ///
+    /// // Up then left between two ticks makes a tight turn over the next two, and a third turn
+    /// // past the buffer is dropped.
+    /// let mut game = Game::with_config(config.clone());
+    /// game.turn(Direction::Up);
+    /// game.turn(Direction::Left);
+    /// game.turn(Direction::Down);
+    /// game.step();
+    /// assert_eq!(game.snake().head(), GridPos::new(5, 4));
+    /// game.step();
+    /// assert_eq!(game.snake().head(), GridPos::new(4, 4));
+    /// game.step();
+    /// assert_eq!(game.snake().head(), GridPos::new(3, 4));
+    ///
+    /// // Turning the way the last queued turn already heads is no turn at all, so it doesn't
+    /// // take up room in the buffer.
+    /// let mut game = Game::with_config(config);
+    /// game.turn(Direction::Up);
+    /// game.turn(Direction::Up);
+    /// game.turn(Direction::Left);
+    /// game.step();
+    /// game.step();
+    /// assert_eq!(game.snake().head(), GridPos::new(4, 4));
+    /// ```
+    pub fn turn_player(&mut self, player: usize, direction: Direction) {
+        let direction = self.input_direction(player, direction);
+        if direction.is_diagonal() && !self.config.diagonals {
+            return;
+        }
+        let player = match self.players.get_mut(player) {
+            Some(player) => player,
+            None => return,
+        };
+        let heading = player
+            .turns
+            .back()
+            .copied()
+            .unwrap_or_else(|| player.snake.direction());
+        let reversal = player.snake.len() > 1 && direction.is_reversal_of(heading);
+        if player.turns.len() < TURN_BUFFER && direction != heading && !reversal {
+            player.turns.push_back(direction);
+        }
+    }
+
+    /// Calls `listener` with every event from now on, after the listeners registered before it.
+    ///
+    /// Within a tick, events come in the order things happen in [`Game::step`]: snakes coming
+    /// back from a lost life first, then for each snake in player order, the power-up it took,
+    /// then the food it ate or its death; then deaths from starving, then the checkpoints
+    /// reached, then the end of the round if the tick brought it, and last
+    /// [`GameEvent::TickCompleted`].
+    pub fn on_event(&mut self, listener: impl FnMut(GameEvent) + 'static) {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// Watches `player` for the achievements `achievements` doesn't have yet. Each one unlocks
+    /// at most once per round, with a [`GameEvent::AchievementUnlocked`] right after the event
+    /// that unlocked it.
+    pub fn track_achievements(&mut self, player: usize, achievements: &Achievements) {
+        self.tracked.extend(
+            achievements
+                .locked()
+                .map(|achievement| (player, achievement)),
+        );
+    }
+
+    fn emit(&mut self, event: GameEvent) {
+        for listener in &mut self.listeners {
+            listener(event);
+        }
+
+        let (unlocked, tracked): (Vec<_>, Vec<_>) = std::mem::take(&mut self.tracked)
+            .into_iter()
+            .partition(|&(player, achievement)| (achievement.unlocked_by)(self, player, &event));
+        self.tracked = tracked;
+        for (player, achievement) in unlocked {
+            self.emit(GameEvent::AchievementUnlocked {
+                player,
+                id: achievement.id,
+            });
+        }
+    }
+
+    /// Advances the game by one tick and reports what happened to player one. Once the round is
+    /// over this does nothing but report that the snake died.
+    ///
+    /// A tick goes in this order:
+    ///
+    /// 1. food past its last tick rots, new food spawning elsewhere in its place,
+    /// 2. wandering food whose turn it is moves,
+    /// 3. snakes that lost a life come back, if their wait is over,
+    /// 4. the snakes move all at once, eating whatever is on the cell they enter by then, or
+    ///    staying where they are if they run into something in zen mode; heads running into
+    ///    each other clash as [`GameConfig::head_on`] says,
+    /// 5. chasers whose turn it is move, one after the other, catching the snakes whose heads
+    ///    they step onto,
+    /// 6. the survival clocks of the snakes still alive move on, shrinking those that went too
+    ///    long without eating,
+    /// 7. effects and combos that are over wear off,
+    /// 8. food and power-ups spawn,
+    /// 9. the snakes whose heads are on a checkpoint record it, along with the board as the
+    ///    tick leaves it,
+    /// 10. the end of the round is checked, a snake that fills every cell but obstacles,
+    ///     portals and chasers winning it.
+    ///
+    /// So food that wanders into the cell a head is entering gets eaten, and food that wanders
+    /// away from it doesn't. A snake and a chaser heading into each other never swap places:
+    /// a head moving onto the chaser dies there, and one moving anywhere else has left the cell
+    /// the chaser then steps onto. Food can still be eaten on its last tick, as it only rots on the
+    /// one after. An effect still applies on its last tick, whatever happens to the
+    /// snake during it, and is gone from the next one.
+    ///
+    /// A head may move into the cell the tail leaves on the same tick, but not into the rest of
+    /// the body, and a turn back into the neck is never made:
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart, StepResult};
+    ///
+    /// // Curls round into the cell the fourth one started on.
+    /// fn curl(length: usize) -> (Game, StepResult) {
+    ///     let start = SnakeStart::new(GridPos::new(3, 2), Direction::Right, length);
+    ///     let config = GameConfig::new(6, 5)
+    ///         .with_starts(vec![start])
+    ///         .with_food_count(0)
+    ///         .with_seed(1);
+    ///     let mut game = Game::with_config(config);
+    ///     let mut result = StepResult::Moved;
+    ///     for direction in [Direction::Up, Direction::Left, Direction::Down] {
+    ///         game.turn(direction);
+    ///         result = game.step();
+    ///     }
+    ///     (game, result)
+    /// }
+    ///
+    /// // Four cells long, that cell is the tail's, which moves out of the way.
+    /// let (game, result) = curl(4);
+    /// assert_eq!(result, StepResult::Moved);
+    /// assert_eq!(game.snake().head(), GridPos::new(2, 2));
+    /// // Five cells long, the tail is further back, and the head runs into the body.
+    /// let (game, result) = curl(5);
+    /// assert_eq!(result, StepResult::Died);
+    /// assert!(game.is_over());
+    ///
+    /// // Turning back into the neck keeps the snake going the way it was.
+    /// let mut game = curl(4).0;
+    /// game.turn(Direction::Up);
+    /// assert_eq!(game.step(), StepResult::Moved);
+    /// assert_eq!(game.snake().head(), GridPos::new(2, 3));
+    /// ```
+    pub fn step(&mut self) -> StepResult {
+        if self.is_over() {
+            return StepResult::Died;
+        }
+        if self.history.is_some() {
+            self.changes = Some(TickChanges::record(self));
+        }
+
+        let interval = self.tick_interval();
+        self.ticks += 1;
+        self.elapsed += interval;
+        self.rot_food();
+        self.wander_food();
+        for i in 0..self.players.len() {
+            if self.players[i]
+                .respawn_at
+                .is_some_and(|tick| tick <= self.ticks)
+            {
+                self.respawn(i);
+            }
+        }
+
+        // Player one's result stays `Died` while it waits to come back.
+        let mut result = StepResult::Died;
+        let moves = self.plan_moves();
+        for (i, planned) in moves.into_iter().enumerate() {
+            if let Some(planned) = planned {
+                let player_result = self.step_player(i, planned);
+                if i == 0 {
+                    result = player_result;
+                }
+            }
+        }
+        if let Some(caught) = self.move_chasers() {
+            result = caught;
+        }
+
+        for i in 0..self.players.len() {
+            if self.players[i].alive {
+                let survived = self.survive(i, interval);
+                if i == 0 && survived != StepResult::Moved {
+                    result = survived;
+                }
+            }
+        }
+
+        let tick = self.ticks;
+        for player in &mut self.players {
+            player.effects.retain(|active| active.until > tick);
+            if player.combo_until <= tick {
+                player.multiplier = 1;
+            }
+        }
+
+        // Topping up after the move means the cells the tails just left are already available.
+        self.refill_food();
+        self.maybe_spawn_power_up();
+        self.record_checkpoints();
+
+        let in_play = self
+            .players
+            .iter()
+            .filter(|player| player.is_in_play())
+            .count();
+        let filled = self.players[0].alive && self.players[0].snake.len() >= self.open_cells();
+        self.round_end = match (self.players.len(), in_play) {
+            (1, 0) => Some(RoundEnd::Died),
+            (1, _) if filled => Some(RoundEnd::Won),
+            (1, _) => None,
+            (_, 0) => Some(RoundEnd::Tie),
+            (_, 1) => self
+                .players
+                .iter()
+                .position(Player::is_in_play)
+                .map(RoundEnd::Winner),
+            _ => None,
+        };
+        if self.round_end.is_none() && self.time_left() == Some(Duration::ZERO) {
+            self.round_end = Some(RoundEnd::TimeUp);
+        }
+
+        if self.round_end == Some(RoundEnd::Won) {
+            let bonus = match self.config.mode {
+                GameMode::Survival { .. } => 0,
+                GameMode::Classic | GameMode::Timed { .. } | GameMode::Zen => WIN_BONUS,
+            };
+            self.players[0].score.add(bonus);
+        }
+
+        if let (Some(history), Some(changes)) = (&mut self.history, self.changes.take()) {
+            history.push(changes);
+        }
+
+        if let Some(end) = self.round_end {
+            self.emit(GameEvent::RoundEnded(end));
+        }
+        self.emit(GameEvent::TickCompleted { tick: self.ticks });
+        result
+    }
+
+    /// Tells the listeners that the controller steering `player` broke down.
+    pub fn controller_failed(&mut self, player: usize) {
+        self.emit(GameEvent::ControllerFailed { player });
+    }
+
+    /// Starts keeping what the ticks from now on change, up to the last `ticks` of them, so
+    /// that [`Game::rewind`] can take them back. Only the cells a tick adds to a snake or takes
+    /// off it are kept, so this costs about as much on a big board as on a small one.
+    pub fn keep_history(&mut self, ticks: usize) {
+        self.history = Some(History::new(ticks));
+    }
+
+    /// Takes back the latest tick kept, food eaten on it coming back and the points scored for
+    /// it going away. Returns whether there was one. Nothing is told to the listeners about it.
+    pub fn rewind(&mut self) -> bool {
+        let changes = match self.history.as_mut().and_then(History::pop) {
+            Some(changes) => changes,
+            None => return false,
+        };
+        changes.undo(self);
+        self.rewound = true;
+        true
+    }
+
+    /// How many ticks [`Game::rewind`] can still take back.
+    pub fn rewindable_ticks(&self) -> usize {
+        self.history.as_ref().map_or(0, History::len)
+    }
+
+    /// Whether any tick of the round was taken back.
+    pub fn was_rewound(&self) -> bool {
+        self.rewound
+    }
+
+    /// What the tick under way changed about player `i` so far, if it is being kept.
+    fn player_changes(&mut self, i: usize) -> Option<&mut PlayerChanges> {
+        self.changes.as_mut().map(|changes| &mut changes.players[i])
+    }
+
+    /// Works out where every snake still alive heads this tick and what it runs into, before
+    /// any of them moves, so that the outcome doesn't depend on the order of the players.
+    ///
+    /// A snake runs into the cells the other snakes are on as the tick starts, except for tails
+    /// that move on. Two heads going for the same cell, or for each other's cells, clash as
+    /// [`GameConfig::head_on`] says; in zen mode they are blocked whatever it says. A snake
+    /// left where it is keeps its tail, which can in turn block another snake, so this goes
+    /// over the snakes again until no more of them stay put.
+    ///
+    /// In zen mode, a snake that keeps heading into a wall stays blocked against it for as long
+    /// as it does, and goes on once it turns somewhere free:
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GameMode, GridPos, RandomController};
+    /// use snake_game::game::{BoardView, Controller, SnakeStart, StepResult};
+    ///
+    /// let start = SnakeStart::new(GridPos::new(3, 2), Direction::Right, 3);
+    /// let config = GameConfig::new(6, 5)
+    ///     .with_mode(GameMode::Zen)
+    ///     .with_starts(vec![start])
+    ///     .with_food_count(0);
+    /// let mut game = Game::with_config(config);
+    /// game.step();
+    /// game.step();
+    /// let against_wall = game.snake().cells().copied().collect::<Vec<GridPos>>();
+    /// assert_eq!(against_wall[0], GridPos::new(5, 2));
+    /// for _ in 0..50 {
+    ///     game.turn(Direction::Right);
+    ///     assert_eq!(game.step(), StepResult::Blocked);
+    ///     assert!(game.snake().cells().eq(&against_wall));
+    /// }
+    /// // Turning back into its own neck is no way out either.
+    /// game.turn(Direction::Left);
+    /// assert_eq!(game.step(), StepResult::Blocked);
+    /// game.turn(Direction::Up);
+    /// assert_eq!(game.step(), StepResult::Moved);
+    /// assert_eq!(game.snake().head(), GridPos::new(5, 1));
+    ///
+    /// // However it is steered, the snake never dies nor leaves the board.
+    /// let mut controller = RandomController::new(2);
+    /// for _ in 0..5_000 {
+    ///     for direction in controller.next_moves(&BoardView::new(&game, 0)) {
+    ///         game.turn(direction);
+    ///     }
+    ///     assert_ne!(game.step(), StepResult::Died);
+    ///     assert!(game.players()[0].is_alive() && !game.is_over());
+    ///     assert!(game.snake().cells().all(|cell| {
+    ///         (0..6).contains(&cell.x) && (0..5).contains(&cell.y)
+    ///     }));
+    /// }
+    /// ```
+    fn plan_moves(&mut self) -> Vec<Option<Move>> {
+        let zen = self.config.mode == GameMode::Zen;
+        let mut moves = Vec::with_capacity(self.players.len());
+        for i in 0..self.players.len() {
+            let player = &mut self.players[i];
+            if !player.alive {
+                moves.push(None);
+                continue;
+            }
+            if let Some(direction) = player.turns.pop_front() {
+                player.snake.set_direction(direction);
+            }
+
+            let player = &self.players[i];
+            let target = self.neighbour(player.snake.head(), player.snake.direction());
+            let growth = target
+                .and_then(|target| self.foods.iter().find(|food| food.pos == target))
+                .map_or(0, |food| food.growth);
+            moves.push(Some(Move {
+                target,
+                growing: player.pending_growth + growth > 0,
+                cause: None,
+                stays: zen && target.is_none(),
+            }));
+        }
+
+        let staying = |moves: &[Option<Move>]| moves.iter().flatten().filter(|m| m.stays).count();
+        for _ in 0..=self.players.len() {
+            let before = staying(&moves);
+            for i in 0..moves.len() {
+                let cause = match moves[i] {
+                    Some(Move { stays: true, .. }) | None => continue,
+                    Some(Move { target: None, .. }) => Some(DeathCause::Wall),
+                    Some(Move {
+                        target: Some(target),
+                        growing,
+                        ..
+                    }) => self.collision(i, target, growing, &moves),
+                };
+                if let Some(planned) = &mut moves[i] {
+                    planned.cause = cause;
+                }
+            }
+            self.clash_heads(&mut moves);
+            if zen {
+                for planned in moves.iter_mut().flatten() {
+                    if planned.cause.take().is_some() {
+                        planned.stays = true;
+                    }
+                }
+            }
+            if staying(&moves) == before {
+                break;
+            }
+        }
+        moves
+    }
+
+    /// What kills player `i` if their snake moves onto `target`, growing or not, given where
+    /// the other snakes are headed in `moves`. Heads clashing are left to
+    /// [`Game::clash_heads`].
+    fn collision(
+        &self,
+        i: usize,
+        target: GridPos,
+        growing: bool,
+        moves: &[Option<Move>],
+    ) -> Option<DeathCause> {
+        let player = &self.players[i];
+        let head = player.snake.head();
+        let hit = self
+            .players
+            .iter()
+            .zip(moves)
+            .enumerate()
+            .position(|(j, (other, planned))| {
+                let planned = match planned {
+                    Some(planned) if j != i => planned,
+                    _ => return false,
+                };
+                // A head swapping cells with this one clashes with it instead.
+                if !planned.stays && planned.target == Some(head) && other.snake.head() == target {
+                    return false;
+                }
+                let keeps_tail = planned.stays || planned.growing;
+                other.snake.collides(target, keeps_tail)
+            });
+
+        if self.obstacles.contains(&target) {
+            Some(DeathCause::Obstacle)
+        } else if self.chasers.iter().any(|chaser| chaser.pos == target) {
+            Some(DeathCause::Chaser)
+        } else if let Some(j) = hit {
+            Some(DeathCause::Snake(j))
+        } else if !player.has_effect(Effect::Invincible) && player.snake.collides(target, growing) {
+            Some(DeathCause::SelfCollision)
+        } else {
+            None
+        }
+    }
+
+    /// Settles the clashes between the snakes in `moves` heading for the same cell, or for the
+    /// cells each other's heads are on, that nothing else stands in the way of.
+    fn clash_heads(&self, moves: &mut [Option<Move>]) {
+        let moving = |planned: &Option<Move>| match planned {
+            Some(planned) if !planned.stays && planned.cause.is_none() => planned.target,
+            _ => None,
+        };
+        let zen = self.config.mode == GameMode::Zen;
+
+        let mut outcomes = Vec::new();
+        for (i, planned) in moves.iter().enumerate() {
+            let target = match moving(planned) {
+                Some(target) => target,
+                None => continue,
+            };
+            let head = self.players[i].snake.head();
+            let rivals = moves
+                .iter()
+                .enumerate()
+                .filter(|&(j, other)| {
+                    let other_target = match moving(other) {
+                        Some(other_target) if j != i => other_target,
+                        _ => return false,
+                    };
+                    let swapped = other_target == head && self.players[j].snake.head() == target;
+                    other_target == target || swapped
+                })
+                .map(|(j, _)| j)
+                .collect::<Vec<_>>();
+            if rivals.is_empty() {
+                continue;
+            }
+
+            let len = self.players[i].snake.len();
+            let outcome = match self.config.head_on {
+                _ if zen => Clash::Blocked,
+                HeadOn::Bounce => Clash::Blocked,
+                HeadOn::BothDie => Clash::Killed(rivals[0]),
+                HeadOn::LongerSurvives => {
+                    match rivals.iter().find(|&&j| self.players[j].snake.len() >= len) {
+                        Some(&j) => Clash::Killed(j),
+                        None => continue,
+                    }
+                }
+            };
+            outcomes.push((i, outcome));
+        }
+
+        for (i, outcome) in outcomes {
+            if let Some(planned) = &mut moves[i] {
+                match outcome {
+                    Clash::Blocked => planned.stays = true,
+                    Clash::Killed(j) => planned.cause = Some(DeathCause::Snake(j)),
+                }
+            }
+        }
+    }
+
+    fn step_player(&mut self, i: usize, planned: Move) -> StepResult {
+        if planned.stays {
+            return StepResult::Blocked;
+        }
+        if let Some(cause) = planned.cause {
+            return self.kill(i, cause);
+        }
+        let head = planned
+            .target
+            .expect("a snake only moves with somewhere to go");
+
+        let eaten = self
+            .foods
+            .iter()
+            .position(|food| food.pos == head)
+            .map(|f| self.foods.swap_remove(f));
+        self.players[i].pending_growth += eaten.map_or(0, |food| food.growth);
+        let taken = self
+            .power_ups
+            .iter()
+            .position(|power_up| power_up.pos == head)
+            .map(|i| self.power_ups.swap_remove(i));
+
+        let player = &mut self.players[i];
+        let dropped = if player.pending_growth > 0 {
+            player.snake.grow(head);
+            player.pending_growth -= 1;
+            None
+        } else {
+            let tail = player.snake.tail();
+            player.snake.step(head);
+            Some(tail)
+        };
+        if let Some(changes) = self.player_changes(i) {
+            changes.moved = true;
+            changes.dropped.extend(dropped);
+        }
+
+        let tick = self.ticks;
+        if let Some(power_up) = taken {
+            self.players[i].add_effect(power_up.effect, tick);
+            self.emit(GameEvent::PowerUpPicked {
+                player: i,
+                effect: power_up.effect,
+            });
+        }
+
+        let player = &mut self.players[i];
+        match eaten {
+            Some(food) if food.is_poison() => {
+                if player.snake.len() <= MIN_LENGTH && self.config.mode != GameMode::Zen {
+                    return self.kill(i, DeathCause::Poison);
+                }
+                let dropped = player.snake.shrink(food.shrink as usize, MIN_LENGTH);
+                player.score.subtract(food.penalty);
+                player.multiplier = 1;
+                player.combo_until = 0;
+                if let Some(changes) = self.player_changes(i) {
+                    changes.dropped.extend(dropped.into_iter().rev());
+                }
+                self.emit(GameEvent::Poisoned {
+                    player: i,
+                    pos: food.pos,
+                });
+                StepResult::Poisoned
+            }
+            Some(food) => {
+                player.since_shrink = Duration::ZERO;
+                let window = self.config.combo_window;
+                if window > 0 {
+                    // The combo is still going on the tick it runs out, so a hit then counts.
+                    if tick <= player.combo_until {
+                        player.multiplier = (player.multiplier + 1).min(MAX_MULTIPLIER);
+                    }
+                    player.combo_until = tick + window;
+                }
+                let value = match self.config.mode {
+                    GameMode::Survival { .. } => 0,
+                    GameMode::Classic | GameMode::Timed { .. } | GameMode::Zen => {
+                        food.value * player.multiplier
+                    }
+                };
+                player.score.add(value);
+                player.eaten[food.kind as usize] += 1;
+                self.emit(GameEvent::Ate {
+                    player: i,
+                    pos: food.pos,
+                    kind: food.kind,
+                    value,
+                });
+                StepResult::Ate
+            }
+            None => StepResult::Moved,
+        }
+    }
+
+    /// Counts `interval` of game time towards how long player `i` survived. In survival mode
+    /// this is their score, and a snake that has gone the whole shrink interval without eating
+    /// loses a segment, or a segment it was still going to grow. A snake that would end up
+    /// shorter than [`MIN_LENGTH`] dies.
+    ///
+    /// The clock only moves with the ticks, so a pause holds it, frame time left over from
+    /// before the pause included:
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snake_game::game::{Direction, Game, GameConfig, GameMode, GridPos, SnakeStart};
+    /// use snake_game::game::WallMode;
+    /// use snake_game::ticker::Ticker;
+    ///
+    /// fn survival(length: usize, seed: u64) -> GameConfig {
+    ///     let start = SnakeStart::new(GridPos::new(10, 0), Direction::Right, length);
+    ///     GameConfig::new(100, 1)
+    ///         .with_wall_mode(WallMode::Wrap)
+    ///         .with_starts(vec![start])
+    ///         .with_mode(GameMode::Survival { shrink_every: 1 })
+    ///         .with_tick_rate(10.0)
+    ///         .with_seed(seed)
+    /// }
+    ///
+    /// let mut game = Game::with_config(survival(6, 0).with_food_count(0));
+    /// let mut ticker = Ticker::new();
+    /// fn frame(game: &mut Game, ticker: &mut Ticker) {
+    ///     ticker.advance(Duration::from_millis(50));
+    ///     while ticker.tick(game.tick_interval()) {
+    ///         game.step();
+    ///     }
+    /// }
+    /// for _ in 0..19 {
+    ///     frame(&mut game, &mut ticker);
+    /// }
+    /// assert_eq!((game.ticks(), game.snake().len()), (9, 6));
+    ///
+    /// // Paused, then back with the frame time reset.
+    /// ticker.reset();
+    /// frame(&mut game, &mut ticker);
+    /// assert_eq!((game.ticks(), game.snake().len()), (9, 6));
+    /// frame(&mut game, &mut ticker);
+    /// assert_eq!((game.ticks(), game.snake().len()), (10, 5));
+    /// assert_eq!(game.score(), 1);
+    /// ```
+    ///
+    /// Eating restarts the clock, and a shrink that comes while the snake still has growing to
+    /// do takes one of the segments it was going to grow instead:
+    ///
+    /// ```
+    /// # use snake_game::game::{Direction, Game, GameConfig, GameMode, GridPos, SnakeStart};
+    /// # use snake_game::game::{StepResult, WallMode};
+    /// # fn survival(length: usize, seed: u64) -> GameConfig {
+    /// #     let start = SnakeStart::new(GridPos::new(10, 0), Direction::Right, length);
+    /// #     GameConfig::new(100, 1)
+    /// #         .with_wall_mode(WallMode::Wrap)
+    /// #         .with_starts(vec![start])
+    /// #         .with_mode(GameMode::Survival { shrink_every: 1 })
+    /// #         .with_tick_rate(10.0)
+    /// #         .with_seed(seed)
+    /// # }
+    /// // One piece of mega food at a time, worth twenty segments.
+    /// let mut config = survival(4, 3).with_food_count(1).with_food_weights(0, 0, 1);
+    /// config.food_kinds.mega.growth = 20;
+    /// let mut game = Game::with_config(config);
+    /// let mut len = game.snake().len();
+    /// while game.step() != StepResult::Ate {
+    ///     len = game.snake().len();
+    /// }
+    ///
+    /// let mut lens = vec![game.snake().len()];
+    /// for _ in 0..20 {
+    ///     assert_eq!(game.step(), StepResult::Moved);
+    ///     lens.push(game.snake().len());
+    /// }
+    /// // It grows on every tick until it is owed nothing, and the shrink ten ticks after eating
+    /// // only shows in it growing a segment less.
+    /// assert!((0..19).all(|tick| lens[tick] == len + 1 + tick));
+    /// assert_eq!(lens[18], len + 20 - 1);
+    /// // Ten ticks later, with nothing left to grow, it loses a segment.
+    /// assert_eq!(lens[19], lens[18]);
+    /// assert_eq!(lens[20], lens[18] - 1);
+    /// ```
+    fn survive(&mut self, i: usize, interval: Duration) -> StepResult {
+        let player = &mut self.players[i];
+        player.survived += interval;
+        let shrink_every = match self.config.mode {
+            GameMode::Survival { shrink_every } => Duration::from_secs(shrink_every.into()),
+            GameMode::Classic | GameMode::Timed { .. } | GameMode::Zen => return StepResult::Moved,
+        };
+
+        player.score.reset();
+        player.score.add(player.survived.as_secs() as u32);
+
+        player.since_shrink += interval;
+        if player.since_shrink < shrink_every {
+            return StepResult::Moved;
+        }
+        player.since_shrink -= shrink_every;
+
+        if player.pending_growth > 0 {
+            player.pending_growth -= 1;
+        } else if player.snake.len() <= MIN_LENGTH {
+            return self.kill(i, DeathCause::Starved);
+        } else {
+            let dropped = player.snake.shrink(1, MIN_LENGTH);
+            if let Some(changes) = self.player_changes(i) {
+                changes.dropped.extend(dropped);
+            }
+        }
+        StepResult::Moved
+    }
+
+    /// Marks player `i` dead. Its effects end with it. A player with lives to spare loses one
+    /// and comes back [`RESPAWN_TICKS`] later; one on their last life is out of the round.
+    fn kill(&mut self, i: usize, cause: DeathCause) -> StepResult {
+        let player = &mut self.players[i];
+        player.alive = false;
+        player.effects.clear();
+        player.lives -= 1;
+        if player.lives == 0 {
+            self.emit(GameEvent::Died { player: i, cause });
+            return StepResult::Died;
+        }
+
+        player.respawn_at = Some(self.ticks + RESPAWN_TICKS);
+        let lives_left = player.lives;
+        self.emit(GameEvent::LifeLost {
+            player: i,
+            cause,
+            lives_left,
+        });
+        StepResult::LostLife
+    }
+
+    /// Records a [`Checkpoint`] for each snake whose head is on one of the config's checkpoints.
+    fn record_checkpoints(&mut self) {
+        for i in 0..self.players.len() {
+            let player = &self.players[i];
+            let pos = player.snake.head();
+            if !player.alive || !self.config.checkpoints.contains(&pos) {
+                continue;
+            }
+
+            let checkpoint = Checkpoint {
+                pos,
+                tick: self.ticks,
+                snake: player.snake.clone(),
+                score: player.score,
+                pending_growth: player.pending_growth,
+                foods: self.foods.clone(),
+                power_ups: self.power_ups.clone(),
+                rng: self.rng.clone(),
+            };
+            let old = self.players[i].checkpoint.replace(checkpoint);
+            if let Some(changes) = self.player_changes(i) {
+                changes.checkpoint.get_or_insert(old);
+            }
+            self.emit(GameEvent::CheckpointReached { player: i, pos });
+        }
+    }
+
+    /// Brings player `i` back without a combo: as they were on the last checkpoint they
+    /// crossed, less [`GameConfig::checkpoint_penalty`] points, or else at their starting
+    /// position and length. Food, power-ups and obstacles on the cells the snake comes back on
+    /// are cleared away; if another snake or a chaser is in the way, the snake waits for the
+    /// tick after.
+    ///
+    /// In a single player round, the food, power-ups and randomness go back to how the
+    /// checkpoint found them too. Food keeps the ticks it had left before rotting.
+    fn respawn(&mut self, i: usize) {
+        let snake = match &self.players[i].checkpoint {
+            Some(checkpoint) => checkpoint.snake.clone(),
+            None => {
+                let start = player_start(&self.config, i);
+                Snake::new(start.head, start.direction, start.length)
+            }
+        };
+        let blocked = snake.cells().any(|cell| {
+            self.players
+                .iter()
+                .any(|other| other.alive && other.snake.cells().any(|c| c == cell))
+                || self.chasers.iter().any(|chaser| chaser.pos == *cell)
+        });
+        if blocked {
+            self.players[i].respawn_at = Some(self.ticks + 1);
+            return;
+        }
+
+        let (score, pending_growth) = match self.players[i].checkpoint.clone() {
+            Some(checkpoint) => {
+                if self.players.len() == 1 {
+                    let later = self.ticks - checkpoint.tick;
+                    self.foods = checkpoint.foods;
+                    for food in &mut self.foods {
+                        food.spawned_at += later;
+                    }
+                    self.power_ups = checkpoint.power_ups;
+                    self.rng = checkpoint.rng;
+                }
+                let mut score = checkpoint.score;
+                score.subtract(self.config.checkpoint_penalty);
+                (score, checkpoint.pending_growth)
+            }
+            None => (self.players[i].score, 0),
+        };
+
+        let cells = snake.cells().copied().collect::<HashSet<_>>();
+        self.foods.retain(|food| !cells.contains(&food.pos));
+        self.power_ups
+            .retain(|power_up| !cells.contains(&power_up.pos));
+        let cleared = self
+            .obstacles
+            .iter()
+            .filter(|cell| cells.contains(cell))
+            .copied()
+            .collect::<Vec<_>>();
+        for cell in &cleared {
+            self.obstacles.remove(cell);
+        }
+        if let Some(changes) = &mut self.changes {
+            changes.cleared.extend(cleared);
+        }
+
+        let old = std::mem::replace(&mut self.players[i].snake, snake);
+        if let Some(changes) = self.player_changes(i) {
+            changes.replaced = Some(old);
+        }
+        let player = &mut self.players[i];
+        player.alive = true;
+        player.respawn_at = None;
+        player.turns.clear();
+        player.score = score;
+        player.pending_growth = pending_growth;
+        player.since_shrink = Duration::ZERO;
+        player.multiplier = 1;
+        player.combo_until = 0;
+        self.emit(GameEvent::Respawned { player: i });
+    }
+
+    /// The cell a snake on `cell` heading in `direction` moves to, following the wall mode and
+    /// portals, or `None` if that is past a solid wall.
+    ///
+    /// Portal cells are never occupied: moving into one leads straight to the cell past its
+    /// twin, so the snake's body is split between the two sides. When that cell is a portal
+    /// too the snake goes through it as well, and portals that only lead into each other are
+    /// as deadly as a wall.
+    pub fn neighbour(&self, cell: GridPos, direction: Direction) -> Option<GridPos> {
+        let mut next = self.resolve_wall(cell.offset(direction))?;
+        for _ in 0..=self.config.portals.len() {
+            match self.portal_twin(next) {
+                Some(twin) => next = self.resolve_wall(twin.offset(direction))?,
+                None => return Some(next),
+            }
+        }
+        None
+    }
+
+    /// The other end of the portal at `cell`, if there is one.
+    fn portal_twin(&self, cell: GridPos) -> Option<GridPos> {
+        self.config.portals.iter().find_map(|&(a, b)| {
+            if cell == a {
+                Some(b)
+            } else if cell == b {
+                Some(a)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn portals(&self) -> &[(GridPos, GridPos)] {
+        &self.config.portals
+    }
+
+    /// Makes sure the obstacles scattered at random, `scattered`, don't cut the board up. The
+    /// ones bordering on the area the snakes can reach are taken away, one ring after the other,
+    /// until it covers [`GameConfig::min_open_area`] of the cells the config's own obstacles
+    /// leave free. Cells still out of reach after that are filled in, so that food never spawns
+    /// where no snake can get to it.
+    fn open_up(&mut self, mut scattered: Vec<GridPos>) {
+        let portals = self
+            .config
+            .portals
+            .iter()
+            .flat_map(|&(a, b)| [a, b])
+            .collect::<HashSet<_>>();
+        let width = self.width() as i32;
+        let cells = (0..self.height() as i32)
+            .flat_map(|y| (0..width).map(move |x| GridPos::new(x, y)))
+            .filter(|cell| !portals.contains(cell))
+            .collect::<Vec<_>>();
+        let open = cells
+            .iter()
+            .filter(|cell| !self.config.obstacles.contains(cell))
+            .count();
+        let starts = self
+            .players
+            .iter()
+            .flat_map(|player| player.snake.cells().copied())
+            .collect::<Vec<_>>();
+
+        loop {
+            let reachable = obstacles::reachable(
+                starts.iter().copied(),
+                |cell, direction| self.neighbour(cell, direction),
+                |cell| self.obstacles.contains(&cell),
+            );
+            let bordering = reachable
+                .iter()
+                .flat_map(|&cell| Direction::ALL.map(|direction| self.neighbour(cell, direction)))
+                .flatten()
+                .filter(|cell| scattered.contains(cell))
+                .collect::<HashSet<_>>();
+
+            if reachable.len() as f64 >= self.config.min_open_area * open as f64
+                || bordering.is_empty()
+            {
+                let cut_off = cells
+                    .iter()
+                    .filter(|cell| !reachable.contains(cell))
+                    .copied()
+                    .collect::<Vec<_>>();
+                self.obstacles.extend(cut_off);
+                return;
+            }
+
+            scattered.retain(|cell| !bordering.contains(cell));
+            for cell in &bordering {
+                self.obstacles.remove(cell);
+            }
+        }
+    }
+
+    /// Maps a cell the head is about to enter back onto the board according to the wall mode,
+    /// or `None` if the snake hits a wall. Each axis wraps on its own.
+    fn resolve_wall(&self, cell: GridPos) -> Option<GridPos> {
+        let dims = [self.width(), self.height()];
+        match self.config.wall_mode {
+            WallMode::Wrap => Some(cell.wrap(dims)),
+            WallMode::Solid => Some(cell).filter(|cell| cell.is_within(dims)),
+        }
+    }
+
+    /// Adds a piece of food on a random cell that isn't occupied by the snake, an obstacle or
+    /// other food, returning where it went, or `None` if there is no room left.
+    ///
+    /// The cell is drawn from the list of free cells rather than by retrying random positions,
+    /// so this stays cheap even when the board is almost full. Free food hints are preferred
+    /// over any other cell.
+    ///
+    /// When poison is turned on, the food may come with a piece of poison somewhere else.
+    ///
+    /// With a single cell left free, every seed puts the food there, and then there is no room:
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Game, GameConfig, GridPos, SnakeStart};
+    ///
+    /// // The snake fills the top row and obstacles the rest, all but the bottom right corner.
+    /// let snake = SnakeStart::new(GridPos::new(2, 0), Direction::Right, 3);
+    /// let obstacles = [(0, 1), (1, 1), (2, 1), (0, 2), (1, 2)]
+    ///     .map(|(x, y)| GridPos::new(x, y))
+    ///     .to_vec();
+    /// for seed in 0..20 {
+    ///     let config = GameConfig::new(3, 3)
+    ///         .with_starts(vec![snake])
+    ///         .with_obstacles(obstacles.clone())
+    ///         .with_food_count(0)
+    ///         .with_seed(seed);
+    ///     let mut game = Game::with_config(config);
+    ///     assert_eq!(game.spawn_food(), Some(GridPos::new(2, 2)));
+    ///     assert_eq!(game.foods()[0].pos, GridPos::new(2, 2));
+    ///     assert_eq!(game.spawn_food(), None);
+    ///     assert_eq!(game.foods().len(), 1);
+    /// }
+    /// ```
+    pub fn spawn_food(&mut self) -> Option<GridPos> {
+        let mut free = self.free_cells();
+        // A snake filling the board leaves no room at all, which is how a round is won.
+        if free.is_empty() {
+            return None;
+        }
+
+        let hinted = free
+            .iter()
+            .copied()
+            .filter(|cell| self.config.food_hints.contains(cell))
+            .collect::<Vec<_>>();
+        let candidates = if hinted.is_empty() { &free } else { &hinted };
+
+        let pos = candidates[self.rng.below(candidates.len())];
+        let kinds = self.config.food_kinds;
+        let kind = kinds.pick(&mut self.rng);
+        let moving_odds = self.config.moving_food_odds as usize;
+        let mut food = if moving_odds > 0 && self.rng.below(moving_odds) == 0 {
+            Food::moving(pos, kind, &kinds, self.config.food_moves_every)
+        } else {
+            Food::new(pos, kind, &kinds)
+        };
+        food.spawned_at = self.ticks;
+        self.foods.push(food);
+
+        let odds = self.config.poison_odds as usize;
+        if odds > 0 && self.rng.below(odds) == 0 {
+            free.retain(|&cell| cell != pos);
+            self.spawn_poison(&free);
+        }
+        Some(pos)
+    }
+
+    /// Puts a piece of poison on one of the `free` cells, but never right in front of a snake, as
+    /// running into it wouldn't leave a chance to turn away. There is never more poison than
+    /// regular food on the board.
+    fn spawn_poison(&mut self, free: &[GridPos]) {
+        if self.foods.iter().filter(|food| food.is_poison()).count() >= self.config.food_count {
+            return;
+        }
+
+        let ahead = self
+            .players
+            .iter()
+            .filter(|player| player.alive)
+            .filter_map(|player| self.neighbour(player.snake.head(), player.snake.direction()))
+            .collect::<Vec<_>>();
+        let cells = free
+            .iter()
+            .filter(|cell| !ahead.contains(cell))
+            .collect::<Vec<_>>();
+        if !cells.is_empty() {
+            let pos = *cells[self.rng.below(cells.len())];
+            self.foods.push(Food::poison(pos));
+        }
+    }
+
+    /// Spawns regular food until there are as many pieces as configured, or the board is full.
+    /// Poison doesn't count.
+    fn refill_food(&mut self) {
+        while self.foods.iter().filter(|food| !food.is_poison()).count() < self.config.food_count
+            && self.spawn_food().is_some()
+        {}
+    }
+
+    /// Whether `food` is about to rot, within [`GameConfig::food_warning`] ticks of the end of
+    /// its lifetime.
+    pub fn is_rotting(&self, food: &Food) -> bool {
+        food.last_tick(self.config.food_lifetime)
+            .is_some_and(|last| last.saturating_sub(self.ticks) < self.config.food_warning)
+    }
+
+    /// Takes away the food left uneaten past its last tick. Its replacement spawns before it
+    /// goes, so that it never lands on the same cell.
+    fn rot_food(&mut self) {
+        let lifetime = self.config.food_lifetime;
+        let tick = self.ticks;
+        while let Some(i) = self
+            .foods
+            .iter()
+            .position(|food| food.last_tick(lifetime).is_some_and(|last| last < tick))
+        {
+            let pos = self.foods[i].pos;
+            self.spawn_food();
+            self.foods.retain(|food| food.pos != pos);
+            self.emit(GameEvent::FoodRotted { pos });
+        }
+    }
+
+    /// Moves every piece of wandering food whose turn it is onto a random free cell next to
+    /// it, going around the edges in wrap mode. Food that is boxed in stays put.
+    fn wander_food(&mut self) {
+        for i in 0..self.foods.len() {
+            let every = u64::from(self.foods[i].moves_every);
+            if every == 0 || !self.ticks.is_multiple_of(every) {
+                continue;
+            }
+
+            let occupied = self.occupied_cells();
+            let from = self.foods[i].pos;
+            let cells = Direction::ALL
+                .iter()
+                .filter_map(|&direction| self.resolve_wall(from.offset(direction)))
+                .filter(|cell| !occupied.contains(cell))
+                .collect::<Vec<_>>();
+            if !cells.is_empty() {
+                self.foods[i].pos = cells[self.rng.below(cells.len())];
+            }
+        }
+    }
+
+    /// Rolls for a power-up while there is none on the board, if they are turned on.
+    fn maybe_spawn_power_up(&mut self) {
+        let odds = self.config.power_up_odds;
+        if odds == 0 || !self.power_ups.is_empty() || self.rng.below(odds as usize) != 0 {
+            return;
+        }
+
+        let free = self.free_cells();
+        if free.is_empty() {
+            return;
+        }
+
+        let pos = free[self.rng.below(free.len())];
+        let effects = Effect::ALL
+            .iter()
+            .copied()
+            .filter(|effect| self.config.debuffs || !effect.is_debuff())
+            .collect::<Vec<_>>();
+        let effect = effects[self.rng.below(effects.len())];
+        self.power_ups.push(PowerUp { pos, effect });
+    }
+
+    /// Puts the chasers of the config on free cells at least [`CHASER_DISTANCE`] away from
+    /// every head. A board too small to keep them that far away gets fewer of them.
+    fn spawn_chasers(&mut self) {
+        let heads = self
+            .players
+            .iter()
+            .map(|player| player.snake.head())
+            .collect::<Vec<_>>();
+        let mut far = self
+            .free_cells()
+            .into_iter()
+            .filter(|cell| {
+                heads
+                    .iter()
+                    .all(|&head| cell.manhattan_distance(head) >= CHASER_DISTANCE)
+            })
+            .collect::<Vec<_>>();
+        for _ in 0..self.config.chasers {
+            if far.is_empty() {
+                break;
+            }
+            let pos = far.swap_remove(self.rng.below(far.len()));
+            self.chasers.push(Chaser { pos });
+        }
+    }
+
+    /// Moves each chaser a cell closer to the nearest snake head, on the ticks they move on.
+    /// Chasers get around obstacles, snake bodies and each other, and go through portals like
+    /// snakes do; one that can't get to any head stays where it is. A chaser stepping onto a
+    /// head kills that snake. Returns what happened to player one, if it was caught.
+    fn move_chasers(&mut self) -> Option<StepResult> {
+        if !self.ticks.is_multiple_of(CHASER_MOVES_EVERY) {
+            return None;
+        }
+
+        let mut result = None;
+        for c in 0..self.chasers.len() {
+            let alive = self.players.iter().filter(|player| player.alive);
+            let heads = alive
+                .clone()
+                .map(|player| player.snake.head())
+                .collect::<HashSet<_>>();
+            if heads.is_empty() {
+                break;
+            }
+            let bodies = alive
+                .flat_map(|player| player.snake.cells().copied())
+                .collect::<HashSet<_>>();
+            let from = self.chasers[c].pos;
+            let next = chaser::step_towards(
+                from,
+                &heads,
+                |cell, direction| self.neighbour(cell, direction),
+                |cell| {
+                    self.obstacles.contains(&cell)
+                        || bodies.contains(&cell)
+                        || self.chasers.iter().any(|other| other.pos == cell)
+                },
+            );
+            let next = match next {
+                Some(next) => next,
+                None => continue,
+            };
+
+            // Nobody gets caught in zen mode, so chasers wait next to the head instead.
+            let zen = self.config.mode == GameMode::Zen;
+            let head = |player: &Player| player.alive && player.snake.head() == next;
+            if zen && self.players.iter().any(head) {
+                continue;
+            }
+
+            self.chasers[c].pos = next;
+            let caught = self
+                .players
+                .iter()
+                .position(|player| player.alive && player.snake.head() == next);
+            if let Some(i) = caught {
+                let caught = self.kill(i, DeathCause::Chaser);
+                if i == 0 {
+                    result = Some(caught);
+                }
+            }
+        }
+        result
+    }
+
+    /// Every cell of the board that nothing currently occupies, in row-major order.
+    fn free_cells(&self) -> Vec<GridPos> {
+        let occupied = self.occupied_cells();
+        let width = self.width() as i32;
+        (0..self.height() as i32)
+            .flat_map(|y| (0..width).map(move |x| GridPos::new(x, y)))
+            .filter(|cell| !occupied.contains(cell))
+            .collect()
+    }
+
+    /// How many cells a snake can be on: all of them but obstacles, portals and the cells
+    /// chasers are on. Configs can put obstacles on portals, or off the board, so the cells
+    /// taken are each counted once, and only if they are on the board.
+    fn open_cells(&self) -> usize {
+        let dims = [self.width(), self.height()];
+        let closed = self
+            .obstacles
+            .iter()
+            .copied()
+            .chain(self.config.portals.iter().flat_map(|&(a, b)| [a, b]))
+            .chain(self.chasers.iter().map(|chaser| chaser.pos))
+            .filter(|cell| cell.is_within(dims))
+            .collect::<HashSet<_>>();
+        (dims[0] * dims[1]) as usize - closed.len()
+    }
+
+    /// The cells taken by snakes, food, power-ups, chasers, obstacles and portals.
+    fn occupied_cells(&self) -> HashSet<GridPos> {
+        self.players
+            .iter()
+            .flat_map(|player| player.snake.cells().copied())
+            .chain(self.foods.iter().map(|food| food.pos))
+            .chain(self.power_ups.iter().map(|power_up| power_up.pos))
+            .chain(self.chasers.iter().map(|chaser| chaser.pos))
+            .chain(self.obstacles.iter().copied())
+            .chain(self.config.portals.iter().flat_map(|&(a, b)| [a, b]))
+            .collect()
+    }
+}
+
+/// Where snake `index` starts, and comes back after losing a life.
+fn player_start(config: &GameConfig, index: usize) -> SnakeStart {
+    match config.starts.get(index) {
+        Some(&start) => start,
+        None => default_start(index, config.players.max(1), config.width, config.height),
+    }
+}
+
+/// Where snake `index` out of `count` starts when the config doesn't say. Snakes are spread out
+/// vertically, heading in alternate directions.
+fn default_start(index: usize, count: usize, width: u32, height: u32) -> SnakeStart {
+    let (width, height) = (width as i32, height as i32);
+    if count == 1 {
+        let head = GridPos::new(width / 2, height / 2);
+        return SnakeStart::new(head, Direction::Right, START_LENGTH);
+    }
+
+    let y = (index as i32 + 1) * height / (count as i32 + 1);
+    match index % 2 {
+        0 => SnakeStart::new(GridPos::new(width / 3, y), Direction::Right, START_LENGTH),
+        _ => SnakeStart::new(
+            GridPos::new(2 * width / 3, y),
+            Direction::Left,
+            START_LENGTH,
+        ),
+    }
+}
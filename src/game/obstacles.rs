@@ -0,0 +1,59 @@
+use std::collections::{HashSet, VecDeque};
+
+use super::grid::{Direction, GridPos};
+use super::rng::Rng;
+
+/// Picks `count` distinct cells of a `width` by `height` board, none of which are `reserved`.
+///
+/// Fewer cells are returned if the board doesn't have that many to spare.
+pub fn scatter(
+    width: u32,
+    height: u32,
+    count: usize,
+    reserved: &HashSet<GridPos>,
+    rng: &mut Rng,
+) -> Vec<GridPos> {
+    let mut candidates = (0..height as i32)
+        .flat_map(|y| (0..width as i32).map(move |x| GridPos::new(x, y)))
+        .filter(|cell| !reserved.contains(cell))
+        .collect::<Vec<_>>();
+
+    // A partial Fisher-Yates shuffle: the first `count` candidates end up being a uniform sample.
+    let count = count.min(candidates.len());
+    for i in 0..count {
+        let j = i + rng.below(candidates.len() - i);
+        candidates.swap(i, j);
+    }
+
+    candidates.truncate(count);
+    candidates
+}
+
+/// Every cell that can be reached from the cells of `from`, these included, by stepping from
+/// one cell to the next with `neighbour`, without ever going through a `blocked` one.
+pub fn reachable(
+    from: impl IntoIterator<Item = GridPos>,
+    neighbour: impl Fn(GridPos, Direction) -> Option<GridPos>,
+    blocked: impl Fn(GridPos) -> bool,
+) -> HashSet<GridPos> {
+    let mut reached = HashSet::new();
+    let mut queue = VecDeque::new();
+    for cell in from {
+        if reached.insert(cell) {
+            queue.push_back(cell);
+        }
+    }
+
+    while let Some(cell) = queue.pop_front() {
+        for direction in Direction::ALL {
+            let next = match neighbour(cell, direction) {
+                Some(next) if !blocked(next) => next,
+                _ => continue,
+            };
+            if reached.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    reached
+}
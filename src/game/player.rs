@@ -0,0 +1,195 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::checkpoint::Checkpoint;
+use super::food::FoodKind;
+use super::grid::Direction;
+use super::power_up::{ActiveEffect, Effect};
+use super::score::Score;
+use super::snake::Snake;
+
+/// A snake along with everything that is tracked for it separately from the other snakes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Player {
+    pub(super) snake: Snake,
+    pub(super) score: Score,
+    /// Segments still to be added, one per tick, by not popping the tail.
+    pub(super) pending_growth: u32,
+    pub(super) alive: bool,
+    pub(super) effects: Vec<ActiveEffect>,
+    /// Game time the snake has been alive for.
+    pub(super) survived: Duration,
+    /// Game time since the snake last ate or, in survival mode, lost a segment.
+    pub(super) since_shrink: Duration,
+    /// What the value of the food the snake eats is multiplied by.
+    pub(super) multiplier: u32,
+    /// The last tick eating keeps the combo going.
+    pub(super) combo_until: u64,
+    /// Lives left, the one being played included.
+    pub(super) lives: u32,
+    /// The tick the snake comes back on after losing a life.
+    pub(super) respawn_at: Option<u64>,
+    /// Turns still to make, one per tick, oldest first.
+    pub(super) turns: VecDeque<Direction>,
+    /// How many pieces of each kind of food the snake ate, indexed by kind.
+    pub(super) eaten: [u32; 3],
+    /// The last checkpoint the snake crossed, which it comes back to after losing a life.
+    pub(super) checkpoint: Option<Checkpoint>,
+}
+
+impl Player {
+    pub(super) fn new(snake: Snake, lives: u32) -> Player {
+        Player {
+            snake,
+            score: Score::default(),
+            pending_growth: 0,
+            alive: true,
+            effects: Vec::new(),
+            survived: Duration::ZERO,
+            since_shrink: Duration::ZERO,
+            multiplier: 1,
+            combo_until: 0,
+            lives,
+            respawn_at: None,
+            turns: VecDeque::new(),
+            eaten: [0; 3],
+            checkpoint: None,
+        }
+    }
+
+    pub fn snake(&self) -> &Snake {
+        &self.snake
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score.points()
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.alive
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.lives
+    }
+
+    /// The tick the snake comes back on, while it is waiting to after losing a life.
+    pub fn respawn_at(&self) -> Option<u64> {
+        self.respawn_at
+    }
+
+    /// Whether the player is still in the round: alive, or waiting to come back.
+    pub fn is_in_play(&self) -> bool {
+        self.alive || self.respawn_at.is_some()
+    }
+
+    /// The last checkpoint the snake crossed, if it crossed one yet.
+    pub fn checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoint.as_ref()
+    }
+
+    /// How much game time the snake has been alive for.
+    pub fn survived(&self) -> Duration {
+        self.survived
+    }
+
+    /// How many pieces of food of `kind` the snake ate in the round, across all its lives.
+    pub fn eaten(&self, kind: FoodKind) -> u32 {
+        self.eaten[kind as usize]
+    }
+
+    /// The current score multiplier, 1 when there is no combo going.
+    pub fn multiplier(&self) -> u32 {
+        self.multiplier
+    }
+
+    /// The effects the snake is under, oldest first.
+    pub fn effects(&self) -> &[ActiveEffect] {
+        &self.effects
+    }
+
+    pub fn has_effect(&self, effect: Effect) -> bool {
+        self.effects.iter().any(|active| active.effect == effect)
+    }
+
+    /// Starts `effect` from the tick after `tick`. Taking an effect that is already running adds
+    /// its duration on top of what is left.
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, Effect, Game, GameConfig, GridPos, SnakeStart};
+    /// use snake_game::game::{StepResult, WallMode};
+    ///
+    /// // A row the snake goes around and around, picking up every power-up that spawns on it.
+    /// let config = GameConfig::new(12, 1)
+    ///     .with_wall_mode(WallMode::Wrap)
+    ///     .with_food_count(0)
+    ///     .with_power_ups(1)
+    ///     .with_seed(0);
+    /// let mut game = Game::with_config(config);
+    /// let mut stacked = 0;
+    /// for _ in 0..100 {
+    ///     let before = game.players()[0].effects().to_vec();
+    ///     game.step();
+    ///     for active in game.players()[0].effects() {
+    ///         match before.iter().find(|other| other.effect == active.effect) {
+    ///             // Taken again while it runs: a whole duration more, on top of what was left.
+    ///             Some(other) if other.until != active.until => {
+    ///                 assert_eq!(active.until, other.until + active.effect.duration());
+    ///                 stacked += 1;
+    ///             }
+    ///             Some(_) => {}
+    ///             None => assert_eq!(active.until, game.ticks() + active.effect.duration()),
+    ///         }
+    ///     }
+    /// }
+    /// assert!(stacked > 0);
+    ///
+    /// // Invincible, a snake curls round into its own body, running into it on the last tick of
+    /// // the effect, or on the one after.
+    /// fn curl_with(ticks_left: u64) -> (Game, StepResult) {
+    ///     let start = SnakeStart::new(GridPos::new(5, 1), Direction::Right, 6);
+    ///     let config = GameConfig::new(12, 2)
+    ///         .with_wall_mode(WallMode::Wrap)
+    ///         .with_starts(vec![start])
+    ///         .with_food_count(0)
+    ///         .with_power_ups(1)
+    ///         .with_seed(1);
+    ///     let mut game = Game::with_config(config);
+    ///     while game.effect_ticks_left(0, Effect::Invincible) != ticks_left {
+    ///         game.step();
+    ///     }
+    ///     let mut result = StepResult::Moved;
+    ///     for direction in [Direction::Up, Direction::Left, Direction::Down] {
+    ///         game.turn(direction);
+    ///         result = game.step();
+    ///     }
+    ///     (game, result)
+    /// }
+    ///
+    /// // The effect still applies on its last tick, so the snake goes through, and then it wears
+    /// // off.
+    /// let (game, result) = curl_with(3);
+    /// assert_eq!(result, StepResult::Moved);
+    /// assert_eq!(game.effect_ticks_left(0, Effect::Invincible), 0);
+    /// assert!(game.players()[0].effects().is_empty());
+    /// // A tick later it is gone, and the snake dies, under no effect at all.
+    /// let (game, result) = curl_with(2);
+    /// assert_eq!(result, StepResult::Died);
+    /// assert!(game.players()[0].effects().is_empty());
+    /// ```
+    pub(super) fn add_effect(&mut self, effect: Effect, tick: u64) {
+        match self
+            .effects
+            .iter_mut()
+            .find(|active| active.effect == effect)
+        {
+            Some(active) => active.until += effect.duration(),
+            None => self.effects.push(ActiveEffect {
+                effect,
+                until: tick + effect.duration(),
+            }),
+        }
+    }
+}
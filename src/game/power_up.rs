@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use super::grid::GridPos;
+
+/// What picking up a power-up does to the snake that took it, for a limited number of ticks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Effect {
+    /// Ticks come faster.
+    SpeedBoost,
+    /// Ticks come slower.
+    SlowDown,
+    /// The snake can move through its own body.
+    Invincible,
+    /// Up and down swap places, and so do left and right, for the turns the player makes.
+    ReversedControls,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 4] = [
+        Effect::SpeedBoost,
+        Effect::SlowDown,
+        Effect::Invincible,
+        Effect::ReversedControls,
+    ];
+
+    /// Whether the effect hinders the snake instead of helping it. Debuffs only show up when the
+    /// config allows them.
+    pub fn is_debuff(self) -> bool {
+        self == Effect::ReversedControls
+    }
+
+    /// How many ticks the effect lasts.
+    pub fn duration(self) -> u64 {
+        match self {
+            Effect::SpeedBoost => 40,
+            Effect::SlowDown => 30,
+            Effect::Invincible => 25,
+            Effect::ReversedControls => 30,
+        }
+    }
+
+    /// What the tick interval is multiplied by while the effect lasts.
+    pub fn interval_factor(self) -> f64 {
+        match self {
+            Effect::SpeedBoost => 0.6,
+            Effect::SlowDown => 1.6,
+            Effect::Invincible | Effect::ReversedControls => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerUp {
+    pub pos: GridPos,
+    pub effect: Effect,
+}
+
+/// An effect a snake is under.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveEffect {
+    pub effect: Effect,
+    /// The last tick the effect applies to.
+    pub until: u64,
+}
@@ -0,0 +1,548 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::config::{GameConfig, GameMode, HeadOn, SnakeStart, WallMode};
+use super::food::{FoodKind, FoodKindConfig, FoodKinds};
+use super::grid::{Direction, GridPos};
+use super::speed::SpeedCurve;
+use super::{Game, StepResult, START_LENGTH};
+
+/// The first line of every replay file.
+const HEADER: &str = "snake-replay 1";
+
+/// A turn made by one of the players, just before tick `tick` ran.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Input {
+    pub tick: u64,
+    pub player: usize,
+    pub direction: Direction,
+}
+
+/// A recorded round: the config it was started from, seed included, and every turn the players
+/// made. Since the game only draws randomness from the seed, playing the turns back on a game
+/// built from the same config reproduces the round exactly.
+///
+/// Replays are stored as plain text, one setting or input per line.
+///
+/// A round steered by the AI for a while, then left to crash, comes out the same once its
+/// replay is saved, loaded back and played:
+///
+/// ```
+/// use snake_game::game::{BoardView, Controller, Game, GameConfig, GreedyAi, Replay};
+///
+/// let mut game = Game::with_config(GameConfig::new(16, 12).with_seed(21));
+/// let mut replay = Replay::new(&game);
+/// let mut ai = GreedyAi;
+/// while !game.is_over() {
+///     if game.ticks() < 200 {
+///         for direction in ai.next_moves(&BoardView::new(&game, 0)) {
+///             replay.record(game.ticks(), 0, direction);
+///             game.turn(direction);
+///         }
+///     }
+///     game.step();
+/// }
+///
+/// let path = std::env::temp_dir().join(format!("snake-replay-{}", std::process::id()));
+/// replay.save(&path).unwrap();
+/// let loaded = Replay::load(&path).unwrap();
+/// std::fs::remove_file(&path).unwrap();
+/// assert_eq!(loaded.inputs().len(), replay.inputs().len());
+///
+/// let replayed = loaded.play().finish();
+/// assert!(game.score() > 0);
+/// assert_eq!(replayed.score(), game.score());
+/// assert_eq!(replayed.ticks(), game.ticks());
+/// assert!(replayed.snake().cells().eq(game.snake().cells()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Replay {
+    config: GameConfig,
+    inputs: Vec<Input>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// The file doesn't start with the replay header.
+    NotAReplay,
+    /// Line `line` couldn't be understood.
+    Malformed {
+        line: usize,
+    },
+    /// The file lacks the `size` or `seed` line.
+    Missing(&'static str),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not read replay: {}", err),
+            ReplayError::NotAReplay => write!(f, "not a replay file"),
+            ReplayError::Malformed { line } => write!(f, "line {} is malformed", line),
+            ReplayError::Missing(key) => write!(f, "replay has no {:?} line", key),
+        }
+    }
+}
+
+impl Error for ReplayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReplayError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> ReplayError {
+        ReplayError::Io(err)
+    }
+}
+
+impl Replay {
+    /// Starts recording the round `game` is about to play.
+    pub fn new(game: &Game) -> Replay {
+        Replay {
+            config: game.config().clone(),
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+    pub fn inputs(&self) -> &[Input] {
+        &self.inputs
+    }
+
+    /// Records `player` turning towards `direction` before tick `tick`. Inputs must be recorded
+    /// in the order they were given to the game.
+    pub fn record(&mut self, tick: u64, player: usize, direction: Direction) {
+        self.inputs.push(Input {
+            tick,
+            player,
+            direction,
+        });
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Replay, ReplayError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Starts playing the round back from the beginning.
+    pub fn play(&self) -> Playback {
+        Playback {
+            game: Game::with_config(self.config.clone()),
+            inputs: self.inputs.clone(),
+            next: 0,
+        }
+    }
+}
+
+/// A replay being played back, one tick at a time.
+pub struct Playback {
+    game: Game,
+    inputs: Vec<Input>,
+    next: usize,
+}
+
+impl Playback {
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.game.is_over()
+    }
+
+    /// Feeds the game the turns recorded for the coming tick, then runs it.
+    pub fn step(&mut self) -> StepResult {
+        while let Some(input) = self.inputs.get(self.next) {
+            if input.tick > self.game.ticks() {
+                break;
+            }
+            self.game.turn_player(input.player, input.direction);
+            self.next += 1;
+        }
+
+        self.game.step()
+    }
+
+    /// Plays the rest of the round and returns the finished game.
+    pub fn finish(mut self) -> Game {
+        while !self.is_over() {
+            self.step();
+        }
+        self.game
+    }
+}
+
+impl fmt::Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let config = &self.config;
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "size {} {}", config.width, config.height)?;
+        writeln!(f, "walls {}", wall_mode_name(config.wall_mode))?;
+        match config.mode {
+            GameMode::Classic => writeln!(f, "mode classic")?,
+            GameMode::Timed { seconds } => writeln!(f, "mode timed {}", seconds)?,
+            GameMode::Survival { shrink_every } => writeln!(f, "mode survival {}", shrink_every)?,
+            GameMode::Zen => writeln!(f, "mode zen")?,
+        }
+        if let Some(seed) = config.seed {
+            writeln!(f, "seed {}", seed)?;
+        }
+
+        match &config.speed {
+            SpeedCurve::Linear { start, step, floor } => writeln!(
+                f,
+                "speed linear {} {} {}",
+                start.as_micros(),
+                step.as_micros(),
+                floor.as_micros()
+            )?,
+            SpeedCurve::Exponential {
+                start,
+                factor,
+                floor,
+            } => writeln!(
+                f,
+                "speed exponential {} {} {}",
+                start.as_micros(),
+                factor,
+                floor.as_micros()
+            )?,
+            SpeedCurve::Steps(steps) => {
+                write!(f, "speed steps")?;
+                for (score, interval) in steps {
+                    write!(f, " {} {}", score, interval.as_micros())?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        writeln!(f, "food {}", config.food_count)?;
+        write!(f, "food-kinds")?;
+        for kind in FoodKind::ALL {
+            let kind = config.food_kinds.of(kind);
+            write!(f, " {} {} {}", kind.weight, kind.value, kind.growth)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "obstacle-density {}", config.obstacle_density)?;
+        writeln!(f, "min-open-area {}", config.min_open_area)?;
+        writeln!(f, "players {}", config.players)?;
+        writeln!(f, "lives {}", config.lives)?;
+        writeln!(f, "poison {}", config.poison_odds)?;
+        writeln!(
+            f,
+            "moving-food {} {}",
+            config.moving_food_odds, config.food_moves_every
+        )?;
+        writeln!(
+            f,
+            "rotting-food {} {}",
+            config.food_lifetime, config.food_warning
+        )?;
+        writeln!(f, "combo {}", config.combo_window)?;
+        writeln!(f, "power-ups {}", config.power_up_odds)?;
+        writeln!(f, "debuffs {}", config.debuffs)?;
+        writeln!(f, "diagonals {}", config.diagonals)?;
+        writeln!(f, "mirror {} {}", config.mirrored, config.mirrored_input)?;
+        writeln!(f, "chasers {}", config.chasers)?;
+        writeln!(f, "checkpoint-penalty {}", config.checkpoint_penalty)?;
+        writeln!(f, "head-on {}", config.head_on.name())?;
+        for start in &config.starts {
+            writeln!(
+                f,
+                "start {} {} {} {}",
+                start.head.x,
+                start.head.y,
+                direction_name(start.direction),
+                start.length
+            )?;
+        }
+        for cell in &config.obstacles {
+            writeln!(f, "obstacle {} {}", cell.x, cell.y)?;
+        }
+        for cell in &config.food_hints {
+            writeln!(f, "hint {} {}", cell.x, cell.y)?;
+        }
+        for (a, b) in &config.portals {
+            writeln!(f, "portal {} {} {} {}", a.x, a.y, b.x, b.y)?;
+        }
+        for cell in &config.checkpoints {
+            writeln!(f, "checkpoint {} {}", cell.x, cell.y)?;
+        }
+        for input in &self.inputs {
+            writeln!(
+                f,
+                "input {} {} {}",
+                input.tick,
+                input.player,
+                direction_name(input.direction)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Replay {
+    type Err = ReplayError;
+
+    fn from_str(source: &str) -> Result<Replay, ReplayError> {
+        let mut lines = source.lines().map(str::trim).enumerate();
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(ReplayError::NotAReplay);
+        }
+
+        let mut size = None;
+        let mut random_obstacles: Option<u32> = None;
+        let mut config = GameConfig::default();
+        let mut inputs = Vec::new();
+
+        for (i, line) in lines.filter(|(_, line)| !line.is_empty()) {
+            let malformed = || ReplayError::Malformed { line: i + 1 };
+            let mut words = line.split_whitespace();
+            let key = words.next().ok_or_else(malformed)?;
+            let args = words.collect::<Vec<_>>();
+
+            match (key, args.as_slice()) {
+                ("size", [width, height]) => {
+                    size = Some((number(width, i)?, number(height, i)?));
+                }
+                ("walls", [mode]) => {
+                    config.wall_mode = parse_wall_mode(mode).ok_or_else(malformed)?;
+                }
+                ("mode", ["classic"]) => config.mode = GameMode::Classic,
+                ("mode", ["zen"]) => config.mode = GameMode::Zen,
+                ("mode", ["timed", seconds]) => {
+                    config.mode = GameMode::Timed {
+                        seconds: number(seconds, i)?,
+                    };
+                }
+                ("mode", ["survival", shrink_every]) => {
+                    config.mode = GameMode::Survival {
+                        shrink_every: number(shrink_every, i)?,
+                    };
+                }
+                ("seed", [seed]) => config.seed = Some(number(seed, i)?),
+                ("speed", ["linear", start, step, floor]) => {
+                    config.speed = SpeedCurve::Linear {
+                        start: micros(start, i)?,
+                        step: micros(step, i)?,
+                        floor: micros(floor, i)?,
+                    };
+                }
+                ("speed", ["exponential", start, factor, floor]) => {
+                    config.speed = SpeedCurve::Exponential {
+                        start: micros(start, i)?,
+                        factor: number(factor, i)?,
+                        floor: micros(floor, i)?,
+                    };
+                }
+                ("speed", ["steps", steps @ ..]) if steps.len().is_multiple_of(2) => {
+                    config.speed = SpeedCurve::Steps(
+                        steps
+                            .chunks(2)
+                            .map(|pair| Ok((number(pair[0], i)?, micros(pair[1], i)?)))
+                            .collect::<Result<_, ReplayError>>()?,
+                    );
+                }
+                // Replays from before speed curves put the start and floor first.
+                ("speed", [start, floor, ramp @ ..]) => {
+                    let (start, floor) = (micros(start, i)?, micros(floor, i)?);
+                    config.speed = match ramp {
+                        ["linear", step] => SpeedCurve::Linear {
+                            start,
+                            step: micros(step, i)?,
+                            floor,
+                        },
+                        ["exponential", factor] => SpeedCurve::Exponential {
+                            start,
+                            factor: number(factor, i)?,
+                            floor,
+                        },
+                        ["stepped", every, step] => {
+                            stepped(start, floor, number(every, i)?, micros(step, i)?)
+                        }
+                        _ => return Err(malformed()),
+                    };
+                }
+                ("food", [count]) => config.food_count = number(count, i)?,
+                ("food-kinds", kinds) if kinds.len() == 3 * FoodKind::ALL.len() => {
+                    let kind = |at: usize| -> Result<FoodKindConfig, ReplayError> {
+                        Ok(FoodKindConfig {
+                            weight: number(kinds[at], i)?,
+                            value: number(kinds[at + 1], i)?,
+                            growth: number(kinds[at + 2], i)?,
+                        })
+                    };
+                    config.food_kinds = FoodKinds {
+                        normal: kind(0)?,
+                        golden: kind(3)?,
+                        mega: kind(6)?,
+                    };
+                }
+                ("obstacle-density", [density]) => config.obstacle_density = number(density, i)?,
+                ("min-open-area", [share]) => config.min_open_area = number(share, i)?,
+                // Replays from before obstacle densities counted the obstacles instead.
+                ("random-obstacles", [count]) => random_obstacles = Some(number(count, i)?),
+                ("players", [count]) => config.players = number(count, i)?,
+                ("lives", [count]) => config.lives = number(count, i)?,
+                ("poison", [odds]) => config.poison_odds = number(odds, i)?,
+                ("moving-food", [odds, every]) => {
+                    config.moving_food_odds = number(odds, i)?;
+                    config.food_moves_every = number(every, i)?;
+                }
+                ("rotting-food", [lifetime, warning]) => {
+                    config.food_lifetime = number(lifetime, i)?;
+                    config.food_warning = number(warning, i)?;
+                }
+                ("combo", [window]) => config.combo_window = number(window, i)?,
+                ("power-ups", [odds]) => config.power_up_odds = number(odds, i)?,
+                ("debuffs", [debuffs]) => config.debuffs = number(debuffs, i)?,
+                ("diagonals", [diagonals]) => config.diagonals = number(diagonals, i)?,
+                ("mirror", [render, input]) => {
+                    config.mirrored = number(render, i)?;
+                    config.mirrored_input = number(input, i)?;
+                }
+                ("chasers", [count]) => config.chasers = number(count, i)?,
+                ("head-on", [rule]) => {
+                    config.head_on = HeadOn::named(rule).ok_or_else(malformed)?;
+                }
+                ("checkpoint-penalty", [points]) => {
+                    config.checkpoint_penalty = number(points, i)?;
+                }
+                ("start", [x, y, direction]) => config.starts.push(SnakeStart::new(
+                    GridPos::new(number(x, i)?, number(y, i)?),
+                    parse_direction(direction).ok_or_else(malformed)?,
+                    START_LENGTH,
+                )),
+                ("start", [x, y, direction, length]) => config.starts.push(SnakeStart::new(
+                    GridPos::new(number(x, i)?, number(y, i)?),
+                    parse_direction(direction).ok_or_else(malformed)?,
+                    number(length, i)?,
+                )),
+                ("obstacle", [x, y]) => {
+                    config
+                        .obstacles
+                        .push(GridPos::new(number(x, i)?, number(y, i)?));
+                }
+                ("hint", [x, y]) => {
+                    config
+                        .food_hints
+                        .push(GridPos::new(number(x, i)?, number(y, i)?));
+                }
+                ("portal", [ax, ay, bx, by]) => config.portals.push((
+                    GridPos::new(number(ax, i)?, number(ay, i)?),
+                    GridPos::new(number(bx, i)?, number(by, i)?),
+                )),
+                ("checkpoint", [x, y]) => {
+                    config
+                        .checkpoints
+                        .push(GridPos::new(number(x, i)?, number(y, i)?));
+                }
+                ("input", [tick, player, direction]) => inputs.push(Input {
+                    tick: number(tick, i)?,
+                    player: number(player, i)?,
+                    direction: parse_direction(direction).ok_or_else(malformed)?,
+                }),
+                _ => return Err(malformed()),
+            }
+        }
+
+        let (width, height) = size.ok_or(ReplayError::Missing("size"))?;
+        config.width = width;
+        config.height = height;
+        if let (Some(count), cells @ 1..) = (random_obstacles, width * height) {
+            config.obstacle_density = f64::from(count) / f64::from(cells);
+            config.min_open_area = 0.0;
+        }
+        if config.seed.is_none() {
+            return Err(ReplayError::Missing("seed"));
+        }
+
+        Ok(Replay { config, inputs })
+    }
+}
+
+/// Parses word `word` of line index `i`.
+fn number<T: FromStr>(word: &str, i: usize) -> Result<T, ReplayError> {
+    word.parse()
+        .map_err(|_| ReplayError::Malformed { line: i + 1 })
+}
+
+fn micros(word: &str, i: usize) -> Result<Duration, ReplayError> {
+    number(word, i).map(Duration::from_micros)
+}
+
+/// The old stepped speed ramp, which took `step` off the interval every `every` points.
+fn stepped(start: Duration, floor: Duration, every: u32, step: Duration) -> SpeedCurve {
+    let every = every.max(1);
+    let mut steps = vec![(0, start.max(floor))];
+    let mut interval = start;
+    let mut score: u32 = 0;
+    while interval > floor && !step.is_zero() {
+        match score.checked_add(every) {
+            Some(next) => score = next,
+            None => break,
+        }
+        interval = interval.saturating_sub(step).max(floor);
+        steps.push((score, interval));
+    }
+    SpeedCurve::Steps(steps)
+}
+
+fn wall_mode_name(mode: WallMode) -> &'static str {
+    match mode {
+        WallMode::Solid => "solid",
+        WallMode::Wrap => "wrap",
+    }
+}
+
+fn parse_wall_mode(name: &str) -> Option<WallMode> {
+    match name {
+        "solid" => Some(WallMode::Solid),
+        "wrap" => Some(WallMode::Wrap),
+        _ => None,
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+        Direction::UpLeft => "up-left",
+        Direction::UpRight => "up-right",
+        Direction::DownLeft => "down-left",
+        Direction::DownRight => "down-right",
+    }
+}
+
+fn parse_direction(name: &str) -> Option<Direction> {
+    match name {
+        "up" => Some(Direction::Up),
+        "down" => Some(Direction::Down),
+        "left" => Some(Direction::Left),
+        "right" => Some(Direction::Right),
+        "up-left" => Some(Direction::UpLeft),
+        "up-right" => Some(Direction::UpRight),
+        "down-left" => Some(Direction::DownLeft),
+        "down-right" => Some(Direction::DownRight),
+        _ => None,
+    }
+}
@@ -0,0 +1,197 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use super::chaser::Chaser;
+use super::checkpoint::Checkpoint;
+use super::food::Food;
+use super::grid::{Direction, GridPos};
+use super::player::Player;
+use super::power_up::{ActiveEffect, PowerUp};
+use super::rng::Rng;
+use super::score::Score;
+use super::snake::Snake;
+use super::{Game, RoundEnd};
+
+/// The last few ticks of a round, kept as what each of them changed so that they can be taken
+/// back one after the other, see [`Game::rewind`].
+///
+/// A snake can get as long as the board is big, so what a tick did to it is kept as the cell
+/// it put a new head on and the cells that came off its tail, not as a copy of the snake. The
+/// same goes for the few obstacles a snake coming back clears away. Everything else a tick can
+/// change, like the food, the power-ups and the scores, is a handful of values however big the
+/// board is, and is kept as it was.
+pub(super) struct History {
+    limit: usize,
+    ticks: VecDeque<TickChanges>,
+}
+
+impl History {
+    pub(super) fn new(limit: usize) -> History {
+        History {
+            limit,
+            ticks: VecDeque::with_capacity(limit),
+        }
+    }
+
+    /// Keeps `changes` as the latest tick, forgetting the oldest one kept if there is no more
+    /// room.
+    pub(super) fn push(&mut self, changes: TickChanges) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.ticks.len() == self.limit {
+            self.ticks.pop_front();
+        }
+        self.ticks.push_back(changes);
+    }
+
+    pub(super) fn pop(&mut self) -> Option<TickChanges> {
+        self.ticks.pop_back()
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.ticks.len()
+    }
+}
+
+/// What a tick changed, filled in as the tick goes.
+pub(super) struct TickChanges {
+    ticks: u64,
+    elapsed: Duration,
+    rng: Rng,
+    round_end: Option<RoundEnd>,
+    foods: Vec<Food>,
+    power_ups: Vec<PowerUp>,
+    chasers: Vec<Chaser>,
+    /// The obstacles cleared away during the tick.
+    pub(super) cleared: Vec<GridPos>,
+    pub(super) players: Vec<PlayerChanges>,
+}
+
+impl TickChanges {
+    /// Starts keeping track of a tick of `game`, which is about to run.
+    pub(super) fn record(game: &Game) -> TickChanges {
+        TickChanges {
+            ticks: game.ticks,
+            elapsed: game.elapsed,
+            rng: game.rng.clone(),
+            round_end: game.round_end,
+            foods: game.foods.clone(),
+            power_ups: game.power_ups.clone(),
+            chasers: game.chasers.clone(),
+            cleared: Vec::new(),
+            players: game.players.iter().map(PlayerChanges::record).collect(),
+        }
+    }
+
+    /// Puts `game` back the way it was before the tick.
+    pub(super) fn undo(self, game: &mut Game) {
+        for (changes, player) in self.players.into_iter().zip(&mut game.players) {
+            changes.undo(player);
+        }
+        game.obstacles.extend(self.cleared);
+        game.ticks = self.ticks;
+        game.elapsed = self.elapsed;
+        game.rng = self.rng;
+        game.round_end = self.round_end;
+        game.foods = self.foods;
+        game.power_ups = self.power_ups;
+        game.chasers = self.chasers;
+    }
+}
+
+/// What a tick changed about a player.
+pub(super) struct PlayerChanges {
+    vitals: Vitals,
+    /// Whether the snake got a new head.
+    pub(super) moved: bool,
+    /// The cells that came off the tail, in the order they did.
+    pub(super) dropped: Vec<GridPos>,
+    /// The snake as it was before the player came back from a lost life with a new one.
+    pub(super) replaced: Option<Snake>,
+    /// The checkpoint the player had before crossing a new one.
+    pub(super) checkpoint: Option<Option<Checkpoint>>,
+}
+
+impl PlayerChanges {
+    fn record(player: &Player) -> PlayerChanges {
+        PlayerChanges {
+            vitals: Vitals::of(player),
+            moved: false,
+            dropped: Vec::new(),
+            replaced: None,
+            checkpoint: None,
+        }
+    }
+
+    fn undo(self, player: &mut Player) {
+        match self.replaced {
+            Some(snake) => player.snake = snake,
+            None => {
+                for cell in self.dropped.into_iter().rev() {
+                    player.snake.push_tail(cell);
+                }
+                if self.moved {
+                    player.snake.pop_head();
+                }
+            }
+        }
+        if let Some(checkpoint) = self.checkpoint {
+            player.checkpoint = checkpoint;
+        }
+        self.vitals.restore(player);
+    }
+}
+
+/// Everything about a player but the cells of their snake and their checkpoint.
+struct Vitals {
+    direction: Direction,
+    score: Score,
+    pending_growth: u32,
+    alive: bool,
+    effects: Vec<ActiveEffect>,
+    survived: Duration,
+    since_shrink: Duration,
+    multiplier: u32,
+    combo_until: u64,
+    lives: u32,
+    respawn_at: Option<u64>,
+    turns: VecDeque<Direction>,
+    eaten: [u32; 3],
+}
+
+impl Vitals {
+    fn of(player: &Player) -> Vitals {
+        Vitals {
+            direction: player.snake.direction(),
+            score: player.score,
+            pending_growth: player.pending_growth,
+            alive: player.alive,
+            effects: player.effects.clone(),
+            survived: player.survived,
+            since_shrink: player.since_shrink,
+            multiplier: player.multiplier,
+            combo_until: player.combo_until,
+            lives: player.lives,
+            respawn_at: player.respawn_at,
+            turns: player.turns.clone(),
+            eaten: player.eaten,
+        }
+    }
+
+    fn restore(self, player: &mut Player) {
+        player.snake.set_direction(self.direction);
+        player.score = self.score;
+        player.pending_growth = self.pending_growth;
+        player.alive = self.alive;
+        player.effects = self.effects;
+        player.survived = self.survived;
+        player.since_shrink = self.since_shrink;
+        player.multiplier = self.multiplier;
+        player.combo_until = self.combo_until;
+        player.lives = self.lives;
+        player.respawn_at = self.respawn_at;
+        player.turns = self.turns;
+        player.eaten = self.eaten;
+    }
+}
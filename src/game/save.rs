@@ -0,0 +1,145 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::Game;
+
+/// Bumped whenever the saved state changes shape. Saves from any other version are refused
+/// rather than read into something that only looks like the game that was saved.
+const SAVE_VERSION: u32 = 23;
+
+#[derive(Serialize)]
+struct SaveFile<'a> {
+    version: u32,
+    game: &'a Game,
+}
+
+/// Just enough of a save to tell which version wrote it, whatever the rest looks like.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct LoadedSave {
+    game: Game,
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    /// The file isn't a save, or it is damaged.
+    Format(serde_json::Error),
+    /// The save was written by another version of the game.
+    UnsupportedVersion {
+        found: u32,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "could not access save: {}", err),
+            SaveError::Format(err) => write!(f, "save is damaged: {}", err),
+            SaveError::UnsupportedVersion { found } => write!(
+                f,
+                "save uses format version {}, but this game only reads version {}",
+                found, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::Io(err) => Some(err),
+            SaveError::Format(err) => Some(err),
+            SaveError::UnsupportedVersion { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> SaveError {
+        SaveError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(err: serde_json::Error) -> SaveError {
+        SaveError::Format(err)
+    }
+}
+
+impl Game {
+    /// Writes the whole state of the game, random number generator included, so that
+    /// [`Game::load_from`] picks up exactly where it left off.
+    ///
+    /// ```
+    /// use snake_game::game::{BoardView, Controller, Game, GameConfig, GreedyAi, SaveError};
+    ///
+    /// fn play(game: &mut Game, ticks: u64) {
+    ///     for _ in 0..ticks {
+    ///         for direction in GreedyAi.next_moves(&BoardView::new(game, 0)) {
+    ///             game.turn(direction);
+    ///         }
+    ///         game.step();
+    ///     }
+    /// }
+    ///
+    /// let path = std::env::temp_dir().join(format!("snake-save-{}", std::process::id()));
+    /// let mut game = Game::with_config(GameConfig::new(16, 12).with_seed(5));
+    /// play(&mut game, 50);
+    /// game.save_to(&path).unwrap();
+    /// let mut loaded = Game::load_from(&path).unwrap();
+    ///
+    /// // Both go on the same way, food spawned from the saved random numbers and all.
+    /// play(&mut game, 300);
+    /// play(&mut loaded, 300);
+    /// assert_eq!(loaded.ticks(), game.ticks());
+    /// assert_eq!(loaded.score(), game.score());
+    /// assert_eq!(loaded.round_end(), game.round_end());
+    /// assert!(loaded.snake().cells().eq(game.snake().cells()));
+    /// let foods = |game: &Game| game.foods().iter().map(|food| food.pos).collect::<Vec<_>>();
+    /// assert_eq!(foods(&loaded), foods(&game));
+    ///
+    /// // Saves from another version, even a newer one, are refused, and so is anything else.
+    /// std::fs::write(&path, r#"{"version":9999,"game":{"from":"the future"}}"#).unwrap();
+    /// assert!(matches!(
+    ///     Game::load_from(&path),
+    ///     Err(SaveError::UnsupportedVersion { found: 9999 })
+    /// ));
+    /// std::fs::write(&path, "not a save").unwrap();
+    /// assert!(matches!(Game::load_from(&path), Err(SaveError::Format(_))));
+    /// std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        let save = SaveFile {
+            version: SAVE_VERSION,
+            game: self,
+        };
+        fs::write(path, serde_json::to_string(&save)?)?;
+        Ok(())
+    }
+
+    pub fn load_from(path: impl AsRef<Path>) -> Result<Game, SaveError> {
+        let source = fs::read_to_string(path)?;
+
+        // The version is checked on its own first, since a save from another version may not
+        // parse as a game at all.
+        let header: SaveHeader = serde_json::from_str(&source)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion {
+                found: header.version,
+            });
+        }
+
+        let save: LoadedSave = serde_json::from_str(&source)?;
+        Ok(save.game)
+    }
+}
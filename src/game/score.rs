@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Score {
+    points: u32,
+}
+
+impl Score {
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    pub fn add(&mut self, value: u32) {
+        self.points = self.points.saturating_add(value);
+    }
+
+    pub fn subtract(&mut self, value: u32) {
+        self.points = self.points.saturating_sub(value);
+    }
+
+    pub fn reset(&mut self) {
+        self.points = 0;
+    }
+}
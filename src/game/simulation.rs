@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+
+use super::config::GameConfig;
+use super::controller::{BoardView, Controller};
+use super::event::GameEvent;
+use super::grid::Direction;
+use super::{Game, StepResult};
+
+/// The rules of the game on their own, driven one tick at a time, with nothing to draw and no
+/// clock: a tick runs whenever [`Simulation::tick`] is called. This is what tests, benchmarks
+/// and bots use to play rounds as fast as the machine goes.
+///
+/// ```
+/// use snake_game::game::{Direction, GameConfig, Simulation, WallMode};
+///
+/// let config = GameConfig::new(20, 20).with_wall_mode(WallMode::Wrap);
+/// let mut simulation = Simulation::new(config, 7);
+///
+/// // Climbs the board diagonally, wrapping around the edges.
+/// for tick in 0..10_000 {
+///     if tick % 5 == 0 {
+///         simulation.push_input(Direction::Up);
+///         simulation.push_input(Direction::Right);
+///     }
+///     simulation.tick();
+/// }
+/// assert_eq!(simulation.state().ticks(), 10_000);
+/// ```
+pub struct Simulation {
+    game: Game,
+    inputs: VecDeque<Direction>,
+}
+
+impl Simulation {
+    /// Starts a round of `config`, with `seed` in place of whatever seed it has.
+    pub fn new(config: GameConfig, seed: u64) -> Simulation {
+        Simulation {
+            game: Game::with_config(config.with_seed(seed)),
+            inputs: VecDeque::new(),
+        }
+    }
+
+    /// Queues a turn of player one's snake. Every tick takes the oldest turn still queued, so
+    /// turns pushed together play out on consecutive ticks.
+    pub fn push_input(&mut self, direction: Direction) {
+        self.inputs.push_back(direction);
+    }
+
+    /// Turns the snake if a turn is queued, then runs a tick.
+    pub fn tick(&mut self) -> StepResult {
+        if let Some(direction) = self.inputs.pop_front() {
+            self.game.turn(direction);
+        }
+        self.game.step()
+    }
+
+    /// Lets each of `controllers`, one per player in player order, turn its snake, then runs a
+    /// tick. Turns queued with [`Simulation::push_input`] go first, and player one's controller
+    /// queues its own after them. A controller that broke down is reported to the game, see
+    /// [`Game::controller_failed`].
+    pub fn tick_with(&mut self, controllers: &mut [Box<dyn Controller>]) -> StepResult {
+        if let Some(direction) = self.inputs.pop_front() {
+            self.game.turn(direction);
+        }
+        let players = self.game.players().len();
+        for (player, controller) in controllers.iter_mut().enumerate().take(players) {
+            for direction in controller.next_moves(&BoardView::new(&self.game, player)) {
+                self.game.turn_player(player, direction);
+            }
+            if let Some(failure) = controller.failure() {
+                eprintln!(
+                    "The controller of player {} broke down: {}",
+                    player + 1,
+                    failure
+                );
+                self.game.controller_failed(player);
+            }
+        }
+        self.game.step()
+    }
+
+    /// Calls `listener` with every event of the round from now on, see [`Game::on_event`].
+    pub fn on_event(&mut self, listener: impl FnMut(GameEvent) + 'static) {
+        self.game.on_event(listener);
+    }
+
+    /// The round as it stands.
+    pub fn state(&self) -> &Game {
+        &self.game
+    }
+}
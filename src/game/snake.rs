@@ -0,0 +1,112 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::grid::{Direction, GridPos};
+
+/// The snake, as an ordered list of cells going from the head to the tail.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snake {
+    body: VecDeque<GridPos>,
+    direction: Direction,
+}
+
+impl Snake {
+    /// Creates a snake whose body trails behind `head`, opposite to `direction`.
+    pub fn new(head: GridPos, direction: Direction, length: usize) -> Snake {
+        let mut body = VecDeque::with_capacity(length);
+        let mut cell = head;
+        for _ in 0..length.max(1) {
+            body.push_back(cell);
+            cell = cell.offset(direction.opposite());
+        }
+
+        Snake { body, direction }
+    }
+
+    pub fn head(&self) -> GridPos {
+        self.body[0]
+    }
+
+    pub fn tail(&self) -> GridPos {
+        self.body[self.body.len() - 1]
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    /// Drops up to `segments` cells off the tail, without going below `min_len`. Returns the
+    /// cells dropped, from the one closest to the head to the old tail.
+    pub fn shrink(&mut self, segments: usize, min_len: usize) -> Vec<GridPos> {
+        let len = self.body.len().saturating_sub(segments).max(min_len.max(1));
+        self.body.drain(len.min(self.body.len())..).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.body.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.body.is_empty()
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &GridPos> {
+        self.body.iter()
+    }
+
+    /// The cell the head would move into on the next step.
+    pub fn next_head(&self) -> GridPos {
+        self.head().offset(self.direction)
+    }
+
+    /// Whether moving the head into `cell` runs into the body. The tail only counts when the
+    /// snake is `growing`, since it moves out of the way otherwise.
+    ///
+    /// ```
+    /// use snake_game::game::{Direction, GridPos, Snake};
+    ///
+    /// // Heading right, with the neck at (1, 0) and the tail at (0, 0).
+    /// let snake = Snake::new(GridPos::new(2, 0), Direction::Right, 3);
+    /// assert!(snake.collides(GridPos::new(1, 0), false));
+    /// assert!(snake.collides(GridPos::new(1, 0), true));
+    /// assert!(!snake.collides(GridPos::new(0, 0), false));
+    /// assert!(snake.collides(GridPos::new(0, 0), true));
+    /// assert!(!snake.collides(GridPos::new(3, 0), false));
+    /// ```
+    pub fn collides(&self, cell: GridPos, growing: bool) -> bool {
+        let len = if growing {
+            self.body.len()
+        } else {
+            self.body.len() - 1
+        };
+        self.body.iter().take(len).any(|&c| c == cell)
+    }
+
+    /// Moves the head to `head` and pops the tail.
+    pub fn step(&mut self, head: GridPos) {
+        self.body.push_front(head);
+        self.body.pop_back();
+    }
+
+    /// Moves the head to `head` but leaves the tail in place, making the snake one cell longer.
+    pub fn grow(&mut self, head: GridPos) {
+        self.body.push_front(head);
+    }
+
+    /// Takes the head off, the cell behind it becoming the head again.
+    pub(super) fn pop_head(&mut self) {
+        if self.body.len() > 1 {
+            self.body.pop_front();
+        }
+    }
+
+    /// Puts `cell` back behind the tail.
+    pub(super) fn push_tail(&mut self, cell: GridPos) {
+        self.body.push_back(cell);
+    }
+}
@@ -0,0 +1,264 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::config::ConfigError;
+
+/// The time between two ticks as a function of the score. Intervals only ever get shorter as
+/// the score goes up, see [`SpeedCurve::validate`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpeedCurve {
+    /// Starts at `start` and takes `step` off for every point, down to `floor`.
+    Linear {
+        start: Duration,
+        step: Duration,
+        floor: Duration,
+    },
+    /// Starts at `start` and is multiplied by `factor` for every point, down to `floor`.
+    Exponential {
+        start: Duration,
+        factor: f64,
+        floor: Duration,
+    },
+    /// `(score, interval)` pairs by increasing score. Each interval holds from its score up to
+    /// the next pair's, and the first one for scores below it too.
+    Steps(Vec<(u32, Duration)>),
+}
+
+impl SpeedCurve {
+    /// The curve `curve` draws for scores up to `up_to`, with the interval for `up_to` holding
+    /// for every score past it. The curve is only called while building the steps, so it can
+    /// be any code at all.
+    pub fn from_fn(up_to: u32, curve: impl Fn(u32) -> Duration) -> SpeedCurve {
+        let mut steps: Vec<(u32, Duration)> = Vec::new();
+        for score in 0..=up_to {
+            let interval = curve(score);
+            if steps.last().is_none_or(|&(_, last)| last != interval) {
+                steps.push((score, interval));
+            }
+        }
+        SpeedCurve::Steps(steps)
+    }
+
+    /// The time between two ticks for the given score.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snake_game::game::SpeedCurve;
+    ///
+    /// let ms = Duration::from_millis;
+    /// let linear = SpeedCurve::Linear {
+    ///     start: ms(200),
+    ///     step: ms(5),
+    ///     floor: ms(60),
+    /// };
+    /// // Right down to the floor at 28 points, and no further, however high the score.
+    /// assert_eq!(linear.interval(0), ms(200));
+    /// assert_eq!(linear.interval(27), ms(65));
+    /// assert_eq!(linear.interval(28), ms(60));
+    /// assert_eq!(linear.interval(29), ms(60));
+    /// assert_eq!(linear.interval(u32::MAX), ms(60));
+    ///
+    /// // A factor of 1 never speeds up, even with nothing to stop it.
+    /// let steady = SpeedCurve::Exponential {
+    ///     start: ms(100),
+    ///     factor: 1.0,
+    ///     floor: Duration::ZERO,
+    /// };
+    /// assert_eq!(steady.interval(0), ms(100));
+    /// assert_eq!(steady.interval(u32::MAX), ms(100));
+    /// // Halving each point with no floor gets down to nothing at all.
+    /// let halving = SpeedCurve::Exponential {
+    ///     start: ms(100),
+    ///     factor: 0.5,
+    ///     floor: Duration::ZERO,
+    /// };
+    /// assert_eq!(halving.interval(2), ms(25));
+    /// assert_eq!(halving.interval(u32::MAX), Duration::ZERO);
+    ///
+    /// // The first step holds before its score too, and the last one past it.
+    /// let steps = SpeedCurve::Steps(vec![(5, ms(150)), (10, ms(100)), (20, ms(80))]);
+    /// assert_eq!(steps.interval(0), ms(150));
+    /// assert_eq!(steps.interval(9), ms(150));
+    /// assert_eq!(steps.interval(10), ms(100));
+    /// assert_eq!(steps.interval(19), ms(100));
+    /// assert_eq!(steps.interval(20), ms(80));
+    /// assert_eq!(steps.interval(u32::MAX), ms(80));
+    /// ```
+    pub fn interval(&self, score: u32) -> Duration {
+        match self {
+            SpeedCurve::Linear { start, step, floor } => step
+                .checked_mul(score)
+                .map_or(Duration::ZERO, |total| start.saturating_sub(total))
+                .max(*floor),
+            SpeedCurve::Exponential {
+                start,
+                factor,
+                floor,
+            } => {
+                let points = i32::try_from(score).unwrap_or(i32::MAX);
+                start.mul_f64(factor.powi(points)).max(*floor)
+            }
+            SpeedCurve::Steps(steps) => {
+                let i = steps.partition_point(|&(from, _)| from <= score);
+                steps
+                    .get(i.saturating_sub(1))
+                    .map_or(Duration::ZERO, |&(_, interval)| interval)
+            }
+        }
+    }
+
+    /// The interval at a score of zero.
+    pub fn start(&self) -> Duration {
+        self.interval(0)
+    }
+
+    /// The same curve, starting at `start` instead. Steps are all scaled to match.
+    pub fn with_start(self, start: Duration) -> SpeedCurve {
+        match self {
+            SpeedCurve::Linear { step, floor, .. } => SpeedCurve::Linear { start, step, floor },
+            SpeedCurve::Exponential { factor, floor, .. } => SpeedCurve::Exponential {
+                start,
+                factor,
+                floor,
+            },
+            SpeedCurve::Steps(steps) => {
+                let scale = match steps.first() {
+                    Some(&(_, first)) if !first.is_zero() => {
+                        start.as_secs_f64() / first.as_secs_f64()
+                    }
+                    _ => return SpeedCurve::Steps(vec![(0, start)]),
+                };
+                SpeedCurve::Steps(
+                    steps
+                        .into_iter()
+                        .map(|(score, interval)| (score, interval.mul_f64(scale)))
+                        .collect(),
+                )
+            }
+        }
+    }
+
+    /// Checks that the interval never goes up with the score, nor down to zero.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snake_game::game::{ConfigError, SpeedCurve};
+    ///
+    /// let ms = Duration::from_millis;
+    /// assert_eq!(SpeedCurve::default().validate(), Ok(()));
+    ///
+    /// let steps = SpeedCurve::Steps(vec![(0, ms(150)), (10, ms(100)), (20, ms(120))]);
+    /// assert_eq!(steps.validate(), Err(ConfigError::SpeedCurveRises { score: 20 }));
+    /// let out_of_order = SpeedCurve::Steps(vec![(10, ms(150)), (10, ms(100))]);
+    /// assert_eq!(out_of_order.validate(), Err(ConfigError::SpeedCurveRises { score: 10 }));
+    ///
+    /// let (start, floor) = (ms(50), ms(60));
+    /// let step = ms(5);
+    /// let linear = SpeedCurve::Linear { start, step, floor };
+    /// assert_eq!(linear.validate(), Err(ConfigError::FloorAboveStart { start, floor }));
+    ///
+    /// // Ticks that take no time at all, whether from the start or once the score is high.
+    /// let floor = Duration::ZERO;
+    /// let linear = SpeedCurve::Linear { start, step, floor };
+    /// assert_eq!(linear.validate(), Err(ConfigError::ZeroTickInterval));
+    /// let halving = SpeedCurve::Exponential { start, factor: 0.5, floor };
+    /// assert_eq!(halving.validate(), Err(ConfigError::ZeroTickInterval));
+    /// let steady = SpeedCurve::Exponential { start, factor: 1.0, floor };
+    /// assert_eq!(steady.validate(), Ok(()));
+    /// let stopped = SpeedCurve::Steps(vec![(0, ms(100)), (10, Duration::ZERO)]);
+    /// assert_eq!(stopped.validate(), Err(ConfigError::ZeroTickInterval));
+    /// assert_eq!(SpeedCurve::Steps(Vec::new()).validate(), Err(ConfigError::ZeroTickInterval));
+    /// ```
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match *self {
+            SpeedCurve::Linear { start, step, floor } => {
+                if start.is_zero() {
+                    return Err(ConfigError::ZeroTickInterval);
+                }
+                if floor > start {
+                    return Err(ConfigError::FloorAboveStart { start, floor });
+                }
+                if floor.is_zero() && !step.is_zero() {
+                    return Err(ConfigError::ZeroTickInterval);
+                }
+            }
+            SpeedCurve::Exponential {
+                start,
+                factor,
+                floor,
+            } => {
+                if !(0.0..=1.0).contains(&factor) {
+                    return Err(ConfigError::BadSpeedFactor);
+                }
+                if start.is_zero() {
+                    return Err(ConfigError::ZeroTickInterval);
+                }
+                if floor > start {
+                    return Err(ConfigError::FloorAboveStart { start, floor });
+                }
+                if floor.is_zero() && factor < 1.0 {
+                    return Err(ConfigError::ZeroTickInterval);
+                }
+            }
+            SpeedCurve::Steps(ref steps) => {
+                if steps.is_empty() {
+                    return Err(ConfigError::ZeroTickInterval);
+                }
+                if steps.iter().any(|(_, interval)| interval.is_zero()) {
+                    return Err(ConfigError::ZeroTickInterval);
+                }
+                for pair in steps.windows(2) {
+                    let ((before, previous), (score, interval)) = (pair[0], pair[1]);
+                    if score <= before || interval > previous {
+                        return Err(ConfigError::SpeedCurveRises { score });
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for SpeedCurve {
+    /// Starts at 200 ms a tick and takes 5 ms off for every point, down to 60 ms.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snake_game::game::{Game, GameConfig, StepResult, WallMode};
+    ///
+    /// // A row the snake goes around and around, with one piece of normal food, worth a point.
+    /// let config = GameConfig::new(40, 1)
+    ///     .with_wall_mode(WallMode::Wrap)
+    ///     .with_food_weights(1, 0, 0)
+    ///     .with_seed(4);
+    /// let mut game = Game::with_config(config);
+    /// assert_eq!(game.tick_interval(), Duration::from_millis(200));
+    ///
+    /// let mut eaten = 0;
+    /// for _ in 0..10_000 {
+    ///     if eaten == 30 {
+    ///         break;
+    ///     }
+    ///     if game.step() == StepResult::Ate {
+    ///         eaten += 1;
+    ///         assert_eq!(game.score(), eaten);
+    ///         let expected = 200u64.saturating_sub(5 * u64::from(eaten)).max(60);
+    ///         assert_eq!(game.tick_interval(), Duration::from_millis(expected));
+    ///     }
+    /// }
+    /// // The floor is reached at 28 points, and the two after it don't go below.
+    /// assert_eq!(eaten, 30);
+    /// assert_eq!(game.tick_interval(), Duration::from_millis(60));
+    /// ```
+    fn default() -> SpeedCurve {
+        SpeedCurve::Linear {
+            start: Duration::from_millis(200),
+            step: Duration::from_millis(5),
+            floor: Duration::from_millis(60),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::event::{DeathCause, GameEvent};
+use super::Game;
+
+/// How many times snakes died of each cause. Running into another snake counts the same
+/// whichever snake it was.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deaths {
+    pub wall: u64,
+    pub obstacle: u64,
+    pub self_collision: u64,
+    pub snake: u64,
+    pub poison: u64,
+    pub starved: u64,
+    /// Missing from stats kept from before there were chasers.
+    #[serde(default)]
+    pub chaser: u64,
+}
+
+impl Deaths {
+    pub fn of(&self, cause: DeathCause) -> u64 {
+        match cause {
+            DeathCause::Wall => self.wall,
+            DeathCause::Obstacle => self.obstacle,
+            DeathCause::SelfCollision => self.self_collision,
+            DeathCause::Snake(_) => self.snake,
+            DeathCause::Poison => self.poison,
+            DeathCause::Starved => self.starved,
+            DeathCause::Chaser => self.chaser,
+        }
+    }
+
+    fn count(&mut self, cause: DeathCause) {
+        let count = match cause {
+            DeathCause::Wall => &mut self.wall,
+            DeathCause::Obstacle => &mut self.obstacle,
+            DeathCause::SelfCollision => &mut self.self_collision,
+            DeathCause::Snake(_) => &mut self.snake,
+            DeathCause::Poison => &mut self.poison,
+            DeathCause::Starved => &mut self.starved,
+            DeathCause::Chaser => &mut self.chaser,
+        };
+        *count += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.wall
+            + self.obstacle
+            + self.self_collision
+            + self.snake
+            + self.poison
+            + self.starved
+            + self.chaser
+    }
+}
+
+/// Totals over every round played, kept in a file next to the high scores.
+///
+/// Like with [`HighScores`](super::HighScores), problems with the file are only warned about.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    pub games_played: u64,
+    pub food_eaten: u64,
+    /// The most segments a snake ever had.
+    pub longest_snake: usize,
+    /// Game time played, pauses left out.
+    pub play_time: Duration,
+    /// Lost lives count as deaths too.
+    pub deaths: Deaths,
+}
+
+impl Stats {
+    /// Loads the stats from the user's data directory.
+    pub fn load() -> Stats {
+        match dirs::data_dir() {
+            Some(dir) => Stats::load_from(dir.join("snake_game").join("stats.json")),
+            None => {
+                eprintln!("warning: no data directory, stats won't be kept");
+                Stats::default()
+            }
+        }
+    }
+
+    /// Loads the stats from `path`, which is also where [`Stats::save`] writes them. A missing
+    /// file means nothing was played yet.
+    pub fn load_from(path: impl Into<PathBuf>) -> Stats {
+        let path = path.into();
+        let mut stats = match read(&path) {
+            Ok(stats) => stats,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Stats::default(),
+            Err(e) => {
+                eprintln!(
+                    "warning: could not read stats from {}: {}",
+                    path.display(),
+                    e
+                );
+                Stats::default()
+            }
+        };
+
+        stats.path = Some(path);
+        stats
+    }
+
+    /// Counts `event`, just emitted by `game`, towards the stats. Only food and deaths of the
+    /// snakes of `players` count, and only they can set the longest snake.
+    ///
+    /// Three rounds the AI plays for a while before crashing, counted, saved and loaded back:
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use std::time::Duration;
+    ///
+    /// use snake_game::game::{Controller, FoodKind, GameConfig, GreedyAi, Simulation, Stats};
+    ///
+    /// let dir = std::env::temp_dir().join(format!("snake-stats-{}", std::process::id()));
+    /// let path = dir.join("stats.json");
+    /// let mut stats = Stats::load_from(&path);
+    /// assert_eq!(stats.games_played, 0);
+    ///
+    /// let (mut eaten, mut longest, mut play_time) = (0, 0, Duration::ZERO);
+    /// for seed in [1, 2, 3] {
+    ///     let mut simulation = Simulation::new(GameConfig::new(12, 10), seed);
+    ///     let events = Rc::new(RefCell::new(Vec::new()));
+    ///     let seen = Rc::clone(&events);
+    ///     simulation.on_event(move |event| seen.borrow_mut().push(event));
+    ///     let mut controllers: Vec<Box<dyn Controller>> = vec![Box::new(GreedyAi)];
+    ///     while !simulation.state().is_over() {
+    ///         if simulation.state().ticks() < 80 {
+    ///             simulation.tick_with(&mut controllers);
+    ///         } else {
+    ///             simulation.tick();
+    ///         }
+    ///         for event in events.borrow_mut().drain(..) {
+    ///             stats.record(simulation.state(), &event, &[0]);
+    ///         }
+    ///         longest = longest.max(simulation.state().snake().len());
+    ///     }
+    ///     let player = &simulation.state().players()[0];
+    ///     eaten += FoodKind::ALL.map(|kind| player.eaten(kind)).iter().sum::<u32>();
+    ///     play_time += simulation.state().elapsed();
+    /// }
+    /// assert_eq!(stats.games_played, 3);
+    /// assert_eq!(stats.deaths.total(), 3);
+    /// assert!(eaten > 0);
+    /// assert_eq!(stats.food_eaten, u64::from(eaten));
+    /// assert_eq!(stats.longest_snake, longest);
+    /// assert_eq!(stats.play_time, play_time);
+    ///
+    /// // Saved twice, so the second write moves its file over the first, leaving nothing else
+    /// // behind.
+    /// stats.save();
+    /// stats.food_eaten += 1;
+    /// stats.save();
+    /// let files = std::fs::read_dir(&dir).unwrap().map(|entry| entry.unwrap().file_name());
+    /// assert_eq!(files.collect::<Vec<_>>(), ["stats.json"]);
+    /// assert_eq!(Stats::load_from(&path), stats);
+    ///
+    /// // A write cut short leaves the file it was to replace as it was.
+    /// std::fs::write(path.with_extension("json.tmp"), "{ \"games_played\": 9").unwrap();
+    /// assert_eq!(Stats::load_from(&path), stats);
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn record(&mut self, game: &Game, event: &GameEvent, players: &[usize]) {
+        match *event {
+            GameEvent::Ate { player, .. } if players.contains(&player) => self.food_eaten += 1,
+            GameEvent::LifeLost { player, cause, .. } | GameEvent::Died { player, cause }
+                if players.contains(&player) =>
+            {
+                self.deaths.count(cause);
+            }
+            GameEvent::TickCompleted { .. } => {
+                let longest = players
+                    .iter()
+                    .filter_map(|&player| game.players().get(player))
+                    .map(|player| player.snake().len());
+                self.longest_snake = longest.fold(self.longest_snake, usize::max);
+            }
+            GameEvent::RoundEnded(_) => {
+                self.games_played += 1;
+                self.play_time += game.elapsed();
+            }
+            _ => {}
+        }
+    }
+
+    /// The stats as a few lines of text.
+    pub fn summary(&self) -> String {
+        let minutes = self.play_time.as_secs() / 60;
+        let seconds = self.play_time.as_secs() % 60;
+        let mut summary = String::new();
+        // Writing to a string can't fail.
+        let _ = writeln!(summary, "Games played:  {}", self.games_played);
+        let _ = writeln!(summary, "Food eaten:    {}", self.food_eaten);
+        let _ = writeln!(summary, "Longest snake: {}", self.longest_snake);
+        let _ = writeln!(summary, "Play time:     {}m {:02}s", minutes, seconds);
+        let _ = writeln!(summary, "Deaths:        {}", self.deaths.total());
+        let deaths = &self.deaths;
+        for (name, count) in [
+            ("walls", deaths.wall),
+            ("obstacles", deaths.obstacle),
+            ("own tail", deaths.self_collision),
+            ("other snakes", deaths.snake),
+            ("poison", deaths.poison),
+            ("starving", deaths.starved),
+            ("chasers", deaths.chaser),
+        ] {
+            let _ = writeln!(summary, "  {:<13}{}", name, count);
+        }
+        summary
+    }
+
+    /// Writes the stats to a file next to theirs first and moves it over them after, so a
+    /// write cut short never leaves them half written.
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Err(e) = write(self, path) {
+            eprintln!("warning: could not save stats to {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<Stats> {
+    let source = fs::read_to_string(path)?;
+    serde_json::from_str(&source).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write(stats: &Stats, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, serde_json::to_string(stats)?)?;
+    fs::rename(&temp, path)
+}
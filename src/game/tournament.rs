@@ -0,0 +1,241 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use serde::Serialize;
+
+use super::config::GameConfig;
+use super::controller::Controller;
+use super::simulation::Simulation;
+use super::RoundEnd;
+
+/// How many ticks a tournament game lasts at most unless told otherwise.
+const DEFAULT_TICK_CAP: u64 = 5_000;
+
+/// Builds a fresh controller for a game of the tournament, from the seed of that game.
+pub type ControllerFactory = Box<dyn Fn(u64) -> Box<dyn Controller> + Send + Sync>;
+
+/// Plays every pair of controllers against each other, two player games on every seed, one
+/// with each of them as player one, and tallies how each controller did.
+///
+/// Games run in parallel, but every game only depends on its pairing and seed, so the results
+/// come out the same however the games end up spread over the threads.
+///
+/// Swapping seats means neither of a pair gets the better start on every board. The same
+/// controller entered twice comes out even with itself:
+///
+/// ```
+/// use snake_game::game::{ControllerFactory, GameConfig, GreedyAi, Tournament};
+///
+/// let entrants: Vec<(String, ControllerFactory)> = vec![
+///     ("one".to_string(), Box::new(|_| Box::new(GreedyAi))),
+///     ("other".to_string(), Box::new(|_| Box::new(GreedyAi))),
+/// ];
+/// let results = Tournament::new(entrants, (0..10).collect())
+///     .with_config(GameConfig::new(16, 10))
+///     .with_tick_cap(500)
+///     .run();
+///
+/// let [one, other] = &results.standings[..] else {
+///     panic!("two entrants, two standings");
+/// };
+/// assert_eq!(one.games, 20);
+/// assert_eq!((one.wins, one.draws, one.losses), (other.wins, other.draws, other.losses));
+/// assert!(one.wins > 0);
+/// assert_eq!(one.wins, one.losses);
+/// assert_eq!(one.average_length, other.average_length);
+/// assert_eq!(one.average_survival, other.average_survival);
+/// ```
+pub struct Tournament {
+    entrants: Vec<(String, ControllerFactory)>,
+    seeds: Vec<u64>,
+    config: GameConfig,
+    tick_cap: u64,
+}
+
+/// How one controller did over the whole tournament.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Standing {
+    pub name: String,
+    pub games: u32,
+    pub wins: u32,
+    /// Games where both snakes died on the same tick, or neither did before the tick cap.
+    pub draws: u32,
+    pub losses: u32,
+    /// The length of the snake at the end of the game, on average.
+    pub average_length: f64,
+    /// How many ticks the snake stayed alive, on average.
+    pub average_survival: f64,
+}
+
+/// The standings of every controller, in the order they entered.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TournamentResults {
+    pub standings: Vec<Standing>,
+}
+
+/// What happened to one snake in one game.
+#[derive(Clone, Copy)]
+struct Outcome {
+    won: bool,
+    lost: bool,
+    length: usize,
+    survival: u64,
+}
+
+impl Tournament {
+    /// A tournament between `entrants`, named controllers, over `seeds`, on a default board.
+    pub fn new(entrants: Vec<(String, ControllerFactory)>, seeds: Vec<u64>) -> Tournament {
+        Tournament {
+            entrants,
+            seeds,
+            config: GameConfig::default(),
+            tick_cap: DEFAULT_TICK_CAP,
+        }
+    }
+
+    /// Plays on boards built from `config` instead. The number of players is always two.
+    pub fn with_config(mut self, config: GameConfig) -> Tournament {
+        self.config = config;
+        self
+    }
+
+    /// Calls a game a draw once it has gone on for `ticks` ticks, so snakes that never die
+    /// don't hold the tournament up forever.
+    pub fn with_tick_cap(mut self, ticks: u64) -> Tournament {
+        self.tick_cap = ticks;
+        self
+    }
+
+    /// Plays every game, on as many threads as the machine has cores.
+    pub fn run(&self) -> TournamentResults {
+        let mut games = Vec::new();
+        for first in 0..self.entrants.len() {
+            for second in first + 1..self.entrants.len() {
+                for &seed in &self.seeds {
+                    games.push((first, second, seed));
+                    games.push((second, first, seed));
+                }
+            }
+        }
+
+        let outcomes = Mutex::new(vec![None; games.len()]);
+        let next = AtomicUsize::new(0);
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        thread::scope(|scope| {
+            for _ in 0..threads.min(games.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&(first, second, seed)) = games.get(i) else {
+                        break;
+                    };
+                    let outcome = self.play(first, second, seed);
+                    outcomes.lock().unwrap()[i] = Some(outcome);
+                });
+            }
+        });
+
+        let mut standings = self
+            .entrants
+            .iter()
+            .map(|(name, _)| Standing {
+                name: name.clone(),
+                games: 0,
+                wins: 0,
+                draws: 0,
+                losses: 0,
+                average_length: 0.0,
+                average_survival: 0.0,
+            })
+            .collect::<Vec<_>>();
+        let outcomes = outcomes.into_inner().unwrap();
+        for (&(first, second, _), outcome) in games.iter().zip(outcomes) {
+            let outcome = outcome.expect("every game is played");
+            for (entrant, outcome) in [(first, outcome[0]), (second, outcome[1])] {
+                let standing = &mut standings[entrant];
+                standing.games += 1;
+                match (outcome.won, outcome.lost) {
+                    (true, _) => standing.wins += 1,
+                    (_, true) => standing.losses += 1,
+                    _ => standing.draws += 1,
+                }
+                // Sums for now, turned into averages below.
+                standing.average_length += outcome.length as f64;
+                standing.average_survival += outcome.survival as f64;
+            }
+        }
+        for standing in &mut standings {
+            let games = f64::from(standing.games.max(1));
+            standing.average_length /= games;
+            standing.average_survival /= games;
+        }
+
+        TournamentResults { standings }
+    }
+
+    /// Plays entrant `first` as player one against entrant `second` on `seed`.
+    fn play(&self, first: usize, second: usize, seed: u64) -> [Outcome; 2] {
+        let mut controllers = [
+            (self.entrants[first].1)(seed),
+            (self.entrants[second].1)(seed),
+        ];
+        let mut simulation = Simulation::new(self.config.clone().with_players(2), seed);
+        let mut died_at = [None; 2];
+        while !simulation.state().is_over() && simulation.state().ticks() < self.tick_cap {
+            simulation.tick_with(&mut controllers);
+            let ticks = simulation.state().ticks();
+            for (player, died_at) in simulation.state().players().iter().zip(&mut died_at) {
+                if !player.is_in_play() && died_at.is_none() {
+                    *died_at = Some(ticks);
+                }
+            }
+        }
+
+        let game = simulation.state();
+        let winner = match game.round_end() {
+            Some(RoundEnd::Winner(i)) => Some(i),
+            _ => None,
+        };
+        [0, 1].map(|i| Outcome {
+            won: winner == Some(i),
+            lost: winner.is_some_and(|winner| winner != i),
+            length: game.players()[i].snake().len(),
+            survival: died_at[i].unwrap_or(game.ticks()),
+        })
+    }
+}
+
+impl TournamentResults {
+    /// Writes the results as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl fmt::Display for TournamentResults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:>6} {:>6} {:>6} {:>6} {:>8} {:>10}",
+            "controller", "games", "wins", "draws", "losses", "length", "survival"
+        )?;
+        for standing in &self.standings {
+            writeln!(
+                f,
+                "{:<12} {:>6} {:>6} {:>6} {:>6} {:>8.1} {:>10.1}",
+                standing.name,
+                standing.games,
+                standing.wins,
+                standing.draws,
+                standing.losses,
+                standing.average_length,
+                standing.average_survival
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+use std::cell::RefCell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use super::config::{GameConfig, SnakeStart, WallMode};
+use super::event::GameEvent;
+use super::food::FoodKind;
+use super::grid::{Direction, GridPos};
+use super::Game;
+
+/// How many cells across and down the tutorial boards are.
+const BOARD_SIZE: u32 = 9;
+
+/// One step of the tutorial: a board to play on, what to tell the player and what they have to
+/// do on it to move on.
+#[derive(Debug)]
+pub struct TutorialStep {
+    pub prompt: &'static str,
+    /// The arrow key to press before the board starts moving, if any. Pressing it turns the
+    /// snake too.
+    pub gate: Option<Direction>,
+    /// The board the step is played on. A snake that dies starts the step over on a new one.
+    pub config: fn() -> GameConfig,
+    /// Whether `event`, just emitted by `game`, completes the step.
+    pub done_when: fn(game: &Game, event: &GameEvent) -> bool,
+}
+
+/// Every step of the tutorial, in order. Adding one here is all it takes.
+pub const TUTORIAL_STEPS: &[TutorialStep] = &[
+    TutorialStep {
+        prompt: "Press Up to get going",
+        gate: Some(Direction::Up),
+        config: || board(WallMode::Wrap, Direction::Right),
+        done_when: |game, event| {
+            matches!(event, GameEvent::TickCompleted { tick } if *tick >= 3)
+                && game.snake().direction() == Direction::Up
+        },
+    },
+    TutorialStep {
+        prompt: "The arrow keys steer your snake, now turn left",
+        gate: None,
+        config: || board(WallMode::Wrap, Direction::Up),
+        done_when: |game, event| {
+            matches!(event, GameEvent::TickCompleted { .. })
+                && game.snake().direction() == Direction::Left
+        },
+    },
+    TutorialStep {
+        prompt: "Eat the food ahead to grow, press Right to go",
+        gate: Some(Direction::Right),
+        config: || {
+            let mut config = board(WallMode::Wrap, Direction::Right).with_food_count(1);
+            config.food_hints = vec![GridPos::new(BOARD_SIZE as i32 - 2, BOARD_SIZE as i32 / 2)];
+            config
+        },
+        done_when: |_, event| matches!(event, GameEvent::Ate { .. }),
+    },
+    TutorialStep {
+        prompt: "Walls are deadly, turn away before you hit one",
+        gate: None,
+        config: || board(WallMode::Solid, Direction::Right),
+        done_when: |_, event| matches!(event, GameEvent::TickCompleted { tick } if *tick >= 15),
+    },
+    TutorialStep {
+        prompt: "Eat three pieces of food without hitting a wall",
+        gate: None,
+        config: || board(WallMode::Solid, Direction::Right).with_food_count(1),
+        done_when: |game, event| {
+            matches!(event, GameEvent::Ate { .. }) && game.players()[0].eaten(FoodKind::Normal) >= 3
+        },
+    },
+];
+
+/// A tutorial board with a snake in the middle heading `direction`, and no food unless the
+/// step adds some. The seed is fixed so that every player gets the same tutorial.
+fn board(wall_mode: WallMode, direction: Direction) -> GameConfig {
+    let middle = GridPos::new(BOARD_SIZE as i32 / 2, BOARD_SIZE as i32 / 2);
+    GameConfig::new(BOARD_SIZE, BOARD_SIZE)
+        .with_wall_mode(wall_mode)
+        .with_seed(0)
+        .with_food_count(0)
+        .with_starts(vec![SnakeStart::new(middle, direction, 2)])
+}
+
+/// Plays through [`TUTORIAL_STEPS`] one after the other, each on a board of its own.
+pub struct Tutorial {
+    step: usize,
+    game: Game,
+    /// What `game` told its listener about during the tick, not looked at yet.
+    events: Rc<RefCell<Vec<GameEvent>>>,
+    /// Whether the step's gate key is yet to be pressed.
+    waiting: bool,
+}
+
+impl Tutorial {
+    pub fn new() -> Tutorial {
+        let events = Rc::default();
+        let (game, waiting) = start(0, &events);
+        Tutorial {
+            step: 0,
+            game,
+            events,
+            waiting,
+        }
+    }
+
+    /// The step being played, `None` once the tutorial is over.
+    pub fn current(&self) -> Option<&'static TutorialStep> {
+        TUTORIAL_STEPS.get(self.step)
+    }
+
+    /// The board of the step being played, or of the last one once the tutorial is over.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Whether the board waits for the step's gate key before it starts moving.
+    pub fn is_waiting(&self) -> bool {
+        self.waiting
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.current().is_none()
+    }
+
+    /// Steers the snake, opening the step's gate if `direction` is what it waits for. Other keys
+    /// do nothing while the gate is closed.
+    pub fn press(&mut self, direction: Direction) {
+        if self.waiting {
+            if self.current().and_then(|step| step.gate) != Some(direction) {
+                return;
+            }
+            self.waiting = false;
+        }
+        self.game.turn(direction);
+    }
+
+    /// Runs a tick of the current step, unless it is waiting for its gate key. A step that gets
+    /// done hands over to the next one, and one where the snake died starts over.
+    pub fn tick(&mut self) {
+        let step = match self.current() {
+            Some(step) if !self.waiting => step,
+            _ => return,
+        };
+
+        self.game.step();
+        let events = self.events.borrow_mut().drain(..).collect::<Vec<_>>();
+        let done = events
+            .iter()
+            .any(|event| (step.done_when)(&self.game, event));
+        if done {
+            self.step += 1;
+        }
+        if (done || self.game.is_over()) && !self.is_over() {
+            let (game, waiting) = start(self.step, &self.events);
+            self.game = game;
+            self.waiting = waiting;
+        }
+    }
+}
+
+impl Default for Tutorial {
+    fn default() -> Tutorial {
+        Tutorial::new()
+    }
+}
+
+/// A new board for step `step`, reporting to `events`, and whether it waits for a gate key.
+fn start(step: usize, events: &Rc<RefCell<Vec<GameEvent>>>) -> (Game, bool) {
+    let step = &TUTORIAL_STEPS[step];
+    let mut game = Game::with_config((step.config)());
+    let events = Rc::clone(events);
+    game.on_event(move |event| events.borrow_mut().push(event));
+    (game, step.gate.is_some())
+}
+
+/// Whether the tutorial was played through or skipped, kept in a file next to the high scores
+/// so that it only runs on its own the first time.
+///
+/// Like with [`HighScores`](super::HighScores), problems with the file are only warned about.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TutorialProgress {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    done: bool,
+}
+
+impl TutorialProgress {
+    /// Loads the progress from the user's data directory.
+    pub fn load() -> TutorialProgress {
+        match dirs::data_dir() {
+            Some(dir) => TutorialProgress::load_from(dir.join("snake_game").join("tutorial.json")),
+            None => {
+                eprintln!("warning: no data directory, the tutorial will run every time");
+                TutorialProgress::default()
+            }
+        }
+    }
+
+    /// Loads the progress from `path`, which is also where [`TutorialProgress::save`] writes
+    /// it. A missing file means the tutorial hasn't run yet.
+    pub fn load_from(path: impl Into<PathBuf>) -> TutorialProgress {
+        let path = path.into();
+        let mut progress = match read(&path) {
+            Ok(progress) => progress,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => TutorialProgress::default(),
+            Err(e) => {
+                eprintln!(
+                    "warning: could not read the tutorial progress from {}: {}",
+                    path.display(),
+                    e
+                );
+                TutorialProgress::default()
+            }
+        };
+
+        progress.path = Some(path);
+        progress
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Marks the tutorial done, played through or skipped.
+    pub fn finish(&mut self) {
+        self.done = true;
+    }
+
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Err(e) = write(self, path) {
+            eprintln!(
+                "warning: could not save the tutorial progress to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<TutorialProgress> {
+    let source = fs::read_to_string(path)?;
+    serde_json::from_str(&source).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write(progress: &TutorialProgress, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(progress)?)
+}
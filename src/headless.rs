@@ -0,0 +1,277 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use image::RgbaImage;
+use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer, CpuBufferPool};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, SubpassContents};
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::{Device, Queue};
+use vulkano::format::Format;
+use vulkano::image::view::ImageView;
+use vulkano::image::{AttachmentImage, ImageUsage};
+use vulkano::instance::{InstanceCreationError, InstanceExtensions};
+use vulkano::pipeline::graphics::viewport::Viewport;
+use vulkano::pipeline::GraphicsPipeline;
+use vulkano::render_pass::Framebuffer;
+use vulkano::sampler::Filter;
+use vulkano::sync::{self, GpuFuture};
+
+use crate::atlas::Atlas;
+use crate::board::board_vertices;
+use crate::frame::Layer;
+use crate::game::Game;
+use crate::layout::{BoardViewport, Projection};
+use crate::mesh::{Mesh, MeshKind};
+use crate::palette::{CellShape, Palette};
+use crate::pipelines::PipelineRegistry;
+use crate::renderer::MaterialKind;
+use crate::texture::Texture;
+use crate::vulkan::{
+    choose_depth_format, clear_values, create_instance, create_logical_device, create_render_pass,
+    projection_set, texture_set, Batch, Draw, FrameGeometry,
+};
+
+/// The format frames are drawn in. It is an sRGB one, like the window's whenever the window
+/// can have one, so colors come out the same as on the screen.
+const FORMAT: Format = Format::R8G8B8A8_SRGB;
+
+/// Draws the board into an image of its own rather than a window, and reads it back: for
+/// checking what the board looks like on a machine without a screen.
+///
+/// Frames are drawn with the same render pass, pipeline and meshes as the window, but only
+/// the board: nothing that changes with time, such as the countdown or the flashes once the
+/// board is filled, and no text.
+pub struct HeadlessRenderer {
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    pipeline: Arc<GraphicsPipeline>,
+    image: Arc<AttachmentImage>,
+    framebuffer: Arc<Framebuffer>,
+    geometry: FrameGeometry,
+    atlas: Atlas,
+    sprite_set: Arc<PersistentDescriptorSet>,
+    projection_pool: CpuBufferPool<Projection>,
+    projection: Option<(Projection, Arc<PersistentDescriptorSet>)>,
+    mesh: Mesh,
+    size: [u32; 2],
+    palette: Palette,
+    cells: CellShape,
+}
+
+#[derive(Debug)]
+pub enum HeadlessError {
+    /// Vulkan couldn't be started.
+    Instance(InstanceCreationError),
+    /// No GPU draws graphics.
+    NoDevice,
+}
+
+impl fmt::Display for HeadlessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeadlessError::Instance(err) => write!(f, "could not start Vulkan: {}", err),
+            HeadlessError::NoDevice => write!(f, "no GPU draws graphics"),
+        }
+    }
+}
+
+impl Error for HeadlessError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HeadlessError::Instance(err) => Some(err),
+            HeadlessError::NoDevice => None,
+        }
+    }
+}
+
+impl From<InstanceCreationError> for HeadlessError {
+    fn from(err: InstanceCreationError) -> HeadlessError {
+        HeadlessError::Instance(err)
+    }
+}
+
+impl HeadlessRenderer {
+    /// Gets ready to draw frames `size` pixels big, the board built out of `mesh_kind` meshes
+    /// in the colors of `palette`. Any GPU that draws graphics does, with no window to show
+    /// them in.
+    pub fn new(
+        size: [u32; 2],
+        mesh_kind: MeshKind,
+        palette: Palette,
+    ) -> Result<HeadlessRenderer, HeadlessError> {
+        let instance = create_instance(&InstanceExtensions::none())?;
+        let (device, queue) =
+            create_logical_device(&instance, None).ok_or(HeadlessError::NoDevice)?;
+
+        let render_pass = create_render_pass(
+            &device,
+            FORMAT,
+            1,
+            choose_depth_format(device.physical_device()),
+        );
+        let pipeline = PipelineRegistry::new(device.clone(), render_pass.clone(), mesh_kind)
+            .get(MaterialKind::Textured);
+        // Drawn into, then copied out of.
+        let usage = ImageUsage {
+            color_attachment: true,
+            transfer_source: true,
+            ..ImageUsage::none()
+        };
+        let image = AttachmentImage::with_usage(device.clone(), size, FORMAT, usage).unwrap();
+        let depth_format = render_pass.desc().attachments()[1].format;
+        let depth = AttachmentImage::transient(device.clone(), size, depth_format).unwrap();
+        let framebuffer = Framebuffer::start(render_pass)
+            .add(ImageView::new(image.clone()).unwrap())
+            .unwrap()
+            .add(ImageView::new(depth).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let (sprite_set, uploaded) =
+            texture_set(&queue, &pipeline, &Texture::atlas(), Filter::Linear);
+        uploaded
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        Ok(HeadlessRenderer {
+            geometry: FrameGeometry::new(&device, &queue),
+            projection_pool: CpuBufferPool::uniform_buffer(device.clone()),
+            device,
+            queue,
+            pipeline,
+            image,
+            framebuffer,
+            atlas: Atlas::bundled(),
+            sprite_set,
+            projection: None,
+            mesh: Mesh::new(mesh_kind),
+            size,
+            palette,
+            cells: CellShape::default(),
+        })
+    }
+
+    /// The same renderer, drawing the cells of the board in `cells`.
+    pub fn with_cell_shape(self, cells: CellShape) -> HeadlessRenderer {
+        HeadlessRenderer { cells, ..self }
+    }
+
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    /// How many bytes the pools the meshes of frames are drawn from take, free or not. It only
+    /// grows while frames need more than they ever did.
+    pub fn geometry_capacity(&self) -> u64 {
+        self.geometry.capacity()
+    }
+
+    /// Draws the board of `game` and waits for the GPU to be done, returning the frame.
+    pub fn render_frame(&mut self, game: &Game) -> RgbaImage {
+        self.render(game, None)
+    }
+
+    /// Draws the board of `game` like [`HeadlessRenderer::render_frame`], with `hud` over it,
+    /// measured in cells like the board. The `hud` is drawn first, in the [`Layer::Ui`], so
+    /// it is only the depth buffer that keeps the board from going over it.
+    pub fn render_frame_with_hud(&mut self, game: &Game, hud: &Mesh) -> RgbaImage {
+        self.render(game, Some(hud))
+    }
+
+    fn render(&mut self, game: &Game, hud: Option<&Mesh>) -> RgbaImage {
+        let [width, height] = self.size;
+        board_vertices(
+            game,
+            None,
+            None,
+            None,
+            self.palette,
+            &self.atlas,
+            &mut self.mesh,
+        );
+        let viewport = BoardViewport::new([game.width(), game.height()], self.size)
+            .with_mirror(game.config().mirrored);
+        let board_set = projection_set(
+            &mut self.projection,
+            viewport.projection(),
+            &self.projection_pool,
+            &self.pipeline,
+        );
+
+        let pixels = CpuAccessibleBuffer::from_iter(
+            self.device.clone(),
+            BufferUsage::transfer_destination(),
+            false,
+            (0..width * height * 4).map(|_| 0u8),
+        )
+        .unwrap();
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            self.device.clone(),
+            self.queue.family(),
+            CommandBufferUsage::OneTimeSubmit,
+        )
+        .unwrap();
+        builder
+            .begin_render_pass(
+                self.framebuffer.clone(),
+                SubpassContents::Inline,
+                clear_values(self.palette.background(), 1),
+            )
+            .unwrap()
+            .set_viewport(
+                0,
+                [Viewport {
+                    origin: [0.0, 0.0],
+                    dimensions: [width as f32, height as f32],
+                    depth_range: 0.0..1.0,
+                }],
+            );
+        // The hud goes first, against the order of the layers, so only the depth buffer puts it
+        // over the board.
+        let mut batches = Vec::new();
+        if let Some(hud) = hud {
+            batches.push(Batch {
+                pipeline: &self.pipeline,
+                sets: (board_set.clone(), self.sprite_set.clone()),
+                depth: Layer::Ui.depth(),
+                shape: CellShape::Sharp,
+                draw: Draw::Mesh(hud),
+            });
+        }
+        batches.push(Batch {
+            pipeline: &self.pipeline,
+            sets: (board_set, self.sprite_set.clone()),
+            depth: Layer::Board.depth(),
+            shape: self.cells,
+            draw: Draw::Mesh(&self.mesh),
+        });
+        self.geometry.record(
+            &mut builder,
+            &batches,
+            // Food is drawn as it is between two pulses, so frames come out the same.
+            0.0,
+        );
+        builder
+            .end_render_pass()
+            .unwrap()
+            .copy_image_to_buffer(self.image.clone(), pixels.clone())
+            .unwrap();
+        let command_buffer = builder.build().unwrap();
+
+        sync::now(self.device.clone())
+            .then_execute(self.queue.clone(), command_buffer)
+            .unwrap()
+            .then_signal_fence_and_flush()
+            .unwrap()
+            .wait(None)
+            .unwrap();
+
+        let pixels = pixels.read().unwrap().to_vec();
+        RgbaImage::from_raw(width, height, pixels).expect("the frame is as big as the image")
+    }
+}
@@ -0,0 +1,249 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use vulkano::device::Device;
+use vulkano::shader::{ShaderCreationError, ShaderModule};
+
+use crate::mesh::MeshKind;
+use crate::pipelines::{PipelineError, PipelineRegistry};
+use crate::vulkan::embedded_shaders;
+
+/// Where compiled shaders are looked for, from the directory the game is run in.
+pub const SHADER_DIR: &str = "assets/shaders";
+
+/// How often the shaders are looked at for changes.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The first word of every SPIR-V module, in the byte order it was written in.
+const SPIRV_MAGIC: u32 = 0x0723_0203;
+
+/// The names of the compiled shaders that draw `mesh_kind` meshes, the vertex shader then the
+/// fragment one: the names of the GLSL sources in `src/shaders` with `.spv` after them.
+pub fn shader_files(mesh_kind: MeshKind) -> [&'static str; 2] {
+    match mesh_kind {
+        MeshKind::Triangles | MeshKind::Indexed => ["board.vert.spv", "board.frag.spv"],
+        MeshKind::Instanced => ["instanced.vert.spv", "instanced.frag.spv"],
+    }
+}
+
+/// Keeps an eye on the compiled shaders in a directory, by when they were last modified,
+/// telling when they changed so the pipeline can be built again with them.
+///
+/// A shader turning up or going away is a change too, so the first look at a directory with
+/// shaders in it finds one.
+///
+/// ```
+/// use std::fs::File;
+/// use std::time::{Instant, SystemTime};
+///
+/// use snake_game::hot_reload::{ShaderWatcher, POLL_INTERVAL};
+///
+/// let dir = std::env::temp_dir().join(format!("snake-shaders-{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let mut watcher = ShaderWatcher::new(&dir, ["a.spv", "b.spv"]);
+/// let now = Instant::now();
+/// assert!(!watcher.poll(now));
+///
+/// let file = File::create(dir.join("a.spv")).unwrap();
+/// // Too soon to look again.
+/// assert!(!watcher.poll(now + POLL_INTERVAL / 2));
+/// assert!(watcher.poll(now + POLL_INTERVAL));
+/// assert!(!watcher.poll(now + POLL_INTERVAL * 2));
+///
+/// file.set_modified(SystemTime::now() + POLL_INTERVAL * 10).unwrap();
+/// assert!(watcher.poll(now + POLL_INTERVAL * 3));
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ShaderWatcher {
+    files: Vec<PathBuf>,
+    /// When each of the files was last modified, if it was there the last time it was looked
+    /// at.
+    modified: Vec<Option<SystemTime>>,
+    last_poll: Option<Instant>,
+}
+
+impl ShaderWatcher {
+    /// Watches the files named `names` in `dir`, none of which are taken to be there yet.
+    pub fn new<'a>(dir: &Path, names: impl IntoIterator<Item = &'a str>) -> ShaderWatcher {
+        let files: Vec<_> = names.into_iter().map(|name| dir.join(name)).collect();
+        ShaderWatcher {
+            modified: vec![None; files.len()],
+            files,
+            last_poll: None,
+        }
+    }
+
+    /// Looks at the files if [`POLL_INTERVAL`] went by since they were last looked at at
+    /// `now`, telling whether any of them changed.
+    pub fn poll(&mut self, now: Instant) -> bool {
+        if matches!(self.last_poll, Some(last) if now - last < POLL_INTERVAL) {
+            return false;
+        }
+        self.last_poll = Some(now);
+
+        let modified: Vec<_> = self
+            .files
+            .iter()
+            .map(|file| fs::metadata(file).and_then(|m| m.modified()).ok())
+            .collect();
+        let changed = modified != self.modified;
+        self.modified = modified;
+        changed
+    }
+}
+
+/// Why the pipeline couldn't be built again with the shaders on disk. The one built before is
+/// kept.
+#[derive(Debug)]
+pub enum ReloadError {
+    /// A shader couldn't be read.
+    Io(PathBuf, io::Error),
+    /// A shader isn't compiled SPIR-V.
+    NotSpirv(PathBuf),
+    /// The device turned a shader down.
+    Shader(PathBuf, ShaderCreationError),
+    /// A shader has no `main` function to start at.
+    NoMain(PathBuf),
+    /// The shaders didn't make pipelines the game can draw with.
+    Pipeline(PipelineError),
+}
+
+impl fmt::Display for ReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReloadError::Io(path, err) => write!(f, "could not read {}: {}", path.display(), err),
+            ReloadError::NotSpirv(path) => write!(f, "{} is not SPIR-V", path.display()),
+            ReloadError::Shader(path, err) => write!(f, "{}: {}", path.display(), err),
+            ReloadError::NoMain(path) => {
+                write!(f, "{} has no main entry point", path.display())
+            }
+            ReloadError::Pipeline(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ReloadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReloadError::Io(_, err) => Some(err),
+            ReloadError::Shader(_, err) => Some(err),
+            ReloadError::Pipeline(err) => Some(err),
+            ReloadError::NotSpirv(_) | ReloadError::NoMain(_) => None,
+        }
+    }
+}
+
+impl From<PipelineError> for ReloadError {
+    fn from(err: PipelineError) -> ReloadError {
+        ReloadError::Pipeline(err)
+    }
+}
+
+/// The words of the SPIR-V module in `bytes`, or `None` if it isn't one: a whole number of
+/// words, starting with the magic number in either byte order.
+///
+/// The bytes are read word by word rather than cast, as a file read into memory needn't be
+/// aligned for words.
+///
+/// ```
+/// use snake_game::hot_reload::spirv_words;
+///
+/// let little = [0x03, 0x02, 0x23, 0x07, 0x00, 0x00, 0x01, 0x00];
+/// assert_eq!(spirv_words(&little), Some(vec![0x0723_0203, 0x0001_0000]));
+/// let big = [0x07, 0x23, 0x02, 0x03, 0x00, 0x01, 0x00, 0x00];
+/// assert_eq!(spirv_words(&big), Some(vec![0x0723_0203, 0x0001_0000]));
+///
+/// assert_eq!(spirv_words(b"#version 450\n"), None);
+/// assert_eq!(spirv_words(&little[..6]), None);
+/// assert_eq!(spirv_words(&[]), None);
+/// ```
+pub fn spirv_words(bytes: &[u8]) -> Option<Vec<u32>> {
+    if bytes.is_empty() || !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+    let magic = [bytes[0], bytes[1], bytes[2], bytes[3]];
+    let from_bytes: fn([u8; 4]) -> u32 = if magic == SPIRV_MAGIC.to_le_bytes() {
+        u32::from_le_bytes
+    } else if magic == SPIRV_MAGIC.to_be_bytes() {
+        u32::from_be_bytes
+    } else {
+        return None;
+    };
+    let words = bytes.chunks_exact(4);
+    Some(
+        words
+            .map(|w| from_bytes([w[0], w[1], w[2], w[3]]))
+            .collect(),
+    )
+}
+
+/// The shader module made of `words`, which came from `path`.
+pub(crate) fn shader_module(
+    device: &Arc<Device>,
+    path: &Path,
+    words: &[u32],
+) -> Result<Arc<ShaderModule>, ReloadError> {
+    // Only as much of the module as vulkano needs to know what it takes is checked. The rest
+    // is down to whatever compiled it, and to the validation layers if they are on.
+    let module = unsafe { ShaderModule::from_words(device.clone(), words) }
+        .map_err(|err| ReloadError::Shader(path.to_path_buf(), err))?;
+    if module.entry_point("main").is_none() {
+        return Err(ReloadError::NoMain(path.to_path_buf()));
+    }
+    Ok(module)
+}
+
+/// The shader compiled into the file at `path`, or `embedded` if there is no such file.
+fn load_shader(
+    device: &Arc<Device>,
+    path: PathBuf,
+    embedded: Arc<ShaderModule>,
+) -> Result<Arc<ShaderModule>, ReloadError> {
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(embedded),
+        Err(err) => return Err(ReloadError::Io(path, err)),
+    };
+    let words = spirv_words(&bytes).ok_or_else(|| ReloadError::NotSpirv(path.clone()))?;
+    shader_module(device, &path, &words)
+}
+
+/// The shaders in `dir` that draw `mesh_kind` meshes, the ones built into the game standing in
+/// for any that aren't there.
+fn load_shaders(
+    device: &Arc<Device>,
+    dir: &Path,
+    mesh_kind: MeshKind,
+) -> Result<[Arc<ShaderModule>; 2], ReloadError> {
+    let [vs_file, fs_file] = shader_files(mesh_kind);
+    let [vs, fs] = embedded_shaders(device, mesh_kind);
+    Ok([
+        load_shader(device, dir.join(vs_file), vs)?,
+        load_shader(device, dir.join(fs_file), fs)?,
+    ])
+}
+
+/// Builds every pipeline in `pipelines` again with the shaders in [`SHADER_DIR`] that
+/// [`shader_files`] names. They are kept as they are, and what went wrong logged, unless all of
+/// them can be built.
+///
+/// Nothing drawn with them may still be in flight.
+pub fn reload_pipelines(pipelines: &mut PipelineRegistry) {
+    let dir = Path::new(SHADER_DIR);
+    let reloaded =
+        load_shaders(pipelines.device(), dir, pipelines.mesh_kind()).and_then(|shaders| {
+            pipelines
+                .set_shaders(Some(shaders))
+                .map_err(ReloadError::from)
+        });
+    match reloaded {
+        Ok(()) => println!("Reloaded the shaders in {}", dir.display()),
+        Err(err) => eprintln!("warning: could not reload the shaders: {}", err),
+    }
+}
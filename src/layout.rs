@@ -0,0 +1,335 @@
+use crate::game::GridPos;
+
+/// How points of the board, measured in cells from its top left corner, map onto normalized
+/// device coordinates: scaled by `scale`, then moved by `offset`. It goes to the shaders as it
+/// is, so it is laid out the way they read it.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Projection {
+    pub scale: [f32; 2],
+    pub offset: [f32; 2],
+}
+
+impl Projection {
+    /// Puts whatever is measured in pixels from the top left corner of a window `window`
+    /// pixels big where it is on that window, the way text is.
+    ///
+    /// ```
+    /// use snake_game::layout::Projection;
+    ///
+    /// let screen = Projection::screen([800, 600]);
+    /// assert_eq!(screen.apply([0.0, 0.0]), [-1.0, -1.0]);
+    /// assert_eq!(screen.apply([400.0, 600.0]), [0.0, 1.0]);
+    /// ```
+    pub fn screen(window: [u32; 2]) -> Projection {
+        Projection {
+            scale: [2.0 / window[0].max(1) as f32, 2.0 / window[1].max(1) as f32],
+            offset: [-1.0, -1.0],
+        }
+    }
+
+    /// Where `point` of the board goes, in normalized device coordinates.
+    pub fn apply(&self, point: [f32; 2]) -> [f32; 2] {
+        [
+            point[0] * self.scale[0] + self.offset[0],
+            point[1] * self.scale[1] + self.offset[1],
+        ]
+    }
+}
+
+/// How many pixels wide and high a cell is at the least, which keeps a window from getting too
+/// small to make the board out, see [`min_window_size`].
+pub const MIN_CELL_PIXELS: u32 = 8;
+
+/// The smallest a window can get with a board of `grid` cells in it, in physical pixels: big
+/// enough for cells of [`MIN_CELL_PIXELS`].
+///
+/// ```
+/// use snake_game::layout::min_window_size;
+///
+/// assert_eq!(min_window_size([20, 15]), [160, 120]);
+/// ```
+pub fn min_window_size(grid: [u32; 2]) -> [u32; 2] {
+    grid.map(|cells| cells.max(1) * MIN_CELL_PIXELS)
+}
+
+/// How big the window is, kept up to date from the events of the window system so the
+/// swapchain can be made to match it.
+///
+/// Everything drawn is sized in physical pixels, the ones the swapchain's images have, which
+/// is also how the window system measures the window and the mouse. Only what is meant to look
+/// the same size whatever the screen, such as text, is scaled up by the scale factor on top.
+/// Moving the window to a screen that scales things up more, or less, changes both at once.
+///
+/// ```
+/// use snake_game::layout::WindowSize;
+///
+/// let mut size = WindowSize::new([800, 600], 1.0);
+/// assert_eq!(size.logical(), [800.0, 600.0]);
+///
+/// // Dragged onto a screen that scales things up twice, it keeps its size as it looks, with
+/// // twice the pixels.
+/// assert!(size.scale_factor_changed(2.0, [1600, 1200]));
+/// assert_eq!(size.physical(), [1600, 1200]);
+/// assert_eq!(size.scale_factor(), 2.0);
+/// assert_eq!(size.logical(), [800.0, 600.0]);
+/// // The window system follows it up with a resize to the same size, which changes nothing.
+/// assert!(!size.resized([1600, 1200]));
+///
+/// // And back.
+/// assert!(size.scale_factor_changed(1.0, [800, 600]));
+/// assert_eq!((size.physical(), size.scale_factor()), ([800, 600], 1.0));
+///
+/// // The window system may keep the pixels as they are, for the window to look bigger.
+/// assert!(size.scale_factor_changed(2.0, [800, 600]));
+/// assert_eq!(size.logical(), [400.0, 300.0]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowSize {
+    physical: [u32; 2],
+    scale_factor: f64,
+}
+
+impl WindowSize {
+    /// A window `physical` pixels big, on a screen that scales things up by `scale_factor`.
+    pub fn new(physical: [u32; 2], scale_factor: f64) -> WindowSize {
+        WindowSize {
+            physical,
+            scale_factor,
+        }
+    }
+
+    /// Takes in that the window is now `physical` pixels big. Returns whether that is another
+    /// size than it was, which the swapchain has to be made again for.
+    pub fn resized(&mut self, physical: [u32; 2]) -> bool {
+        let changed = physical != self.physical;
+        self.physical = physical;
+        changed
+    }
+
+    /// Takes in that the window moved to a screen that scales things up by `scale_factor`,
+    /// being `physical` pixels big there. Returns whether anything changed.
+    pub fn scale_factor_changed(&mut self, scale_factor: f64, physical: [u32; 2]) -> bool {
+        let changed = scale_factor != self.scale_factor;
+        self.scale_factor = scale_factor;
+        self.resized(physical) || changed
+    }
+
+    /// The size of the window in physical pixels, which the swapchain's images have.
+    pub fn physical(&self) -> [u32; 2] {
+        self.physical
+    }
+
+    /// How much the screen the window is on scales things up.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// The size of the window as it looks, before the screen scales it up.
+    pub fn logical(&self) -> [f64; 2] {
+        self.physical.map(|size| size as f64 / self.scale_factor)
+    }
+}
+
+/// A rectangle of a window, measured in pixels from its top left corner.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Bounds {
+    pub origin: [f32; 2],
+    pub size: [f32; 2],
+}
+
+impl Bounds {
+    /// The whole of a window `window` pixels big.
+    pub fn window(window: [u32; 2]) -> Bounds {
+        Bounds {
+            origin: [0.0, 0.0],
+            size: [window[0] as f32, window[1] as f32],
+        }
+    }
+}
+
+/// The part of a window the board is drawn in: the largest rectangle as wide for its height
+/// as the board, so that cells stay square, centered in the window. It leaves bars on either
+/// side of the board or above and below it, which are left to the background.
+///
+/// Everything that goes between the window and the board goes through it: the shaders put
+/// cells where [`BoardViewport::projection`] says, the mouse picks the cell
+/// [`BoardViewport::window_to_cell`] says, and the text about the round is kept to its
+/// [`BoardViewport::bounds`]. A mirrored viewport flips the board left to right, putting
+/// column zero on the right.
+///
+/// ```
+/// use snake_game::game::GridPos;
+/// use snake_game::layout::BoardViewport;
+///
+/// let close = |a: [f32; 2], b: [f32; 2]| a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-5);
+///
+/// // A window far wider than the board has bars on either side of it.
+/// let viewport = BoardViewport::new([20, 20], [10000, 10]);
+/// assert_eq!(viewport.bounds().origin, [4995.0, 0.0]);
+/// assert_eq!(viewport.bounds().size, [10.0, 10.0]);
+/// assert_eq!(viewport.window_to_cell([4995.2, 0.2]), Some(GridPos::new(0, 0)));
+/// assert_eq!(viewport.window_to_cell([5004.8, 9.8]), Some(GridPos::new(19, 19)));
+/// assert_eq!(viewport.window_to_cell([4994.0, 5.0]), None);
+/// assert_eq!(viewport.window_to_cell([5006.0, 5.0]), None);
+/// assert!(close(viewport.cell_to_ndc([0.0, 0.0]), [-0.001, -1.0]));
+/// assert!(close(viewport.cell_to_ndc([20.0, 20.0]), [0.001, 1.0]));
+///
+/// // One far taller than the board has bars above and below it.
+/// let viewport = BoardViewport::new([40, 20], [100, 100000]);
+/// assert_eq!(viewport.bounds().origin, [0.0, 49975.0]);
+/// assert_eq!(viewport.bounds().size, [100.0, 50.0]);
+/// assert_eq!(viewport.window_to_cell([99.0, 49976.0]), Some(GridPos::new(39, 0)));
+/// assert_eq!(viewport.window_to_cell([50.0, 49974.0]), None);
+/// assert!(close(viewport.cell_to_ndc([40.0, 20.0]), [1.0, 0.0005]));
+///
+/// // A window with no room at all still puts the board somewhere.
+/// let viewport = BoardViewport::new([10, 10], [0, 0]);
+/// assert!(viewport.cell_to_ndc([5.0, 5.0]).iter().all(|v| v.is_finite()));
+///
+/// // Mirrored, the first column is on the right.
+/// let viewport = BoardViewport::new([4, 2], [400, 200]).with_mirror(true);
+/// assert_eq!(viewport.window_to_cell([399.0, 0.0]), Some(GridPos::new(0, 0)));
+/// assert!(close(viewport.cell_to_ndc([0.0, 0.0]), [1.0, -1.0]));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardViewport {
+    grid: [u32; 2],
+    window: [u32; 2],
+    bounds: Bounds,
+    mirrored: bool,
+}
+
+impl BoardViewport {
+    /// `grid` is the size of the board in cells and `window` the size of the surface in pixels.
+    pub fn new(grid: [u32; 2], window: [u32; 2]) -> BoardViewport {
+        BoardViewport::fit(grid, window, false)
+    }
+
+    /// Like [`BoardViewport::new`], with cells a whole number of pixels wide, as many as fit,
+    /// and the board on whole pixels too, so that every cell is drawn the same and no seams
+    /// shimmer between them. That leaves wider bars around the board, and a cell of a pixel in
+    /// a window too small for the board.
+    ///
+    /// ```
+    /// use snake_game::layout::BoardViewport;
+    ///
+    /// for window in [[800, 600], [801, 599], [1366, 768], [333, 1000], [30, 20], [7, 7]] {
+    ///     for grid in [[20, 15], [17, 9], [40, 40]] {
+    ///         let viewport = BoardViewport::integer(grid, window);
+    ///         let bounds = viewport.bounds();
+    ///         let cell = viewport.cell_size();
+    ///         assert!(cell >= 1.0 && cell.fract() == 0.0);
+    ///         assert!(bounds.origin.iter().all(|v| v.fract() == 0.0));
+    ///         assert_eq!(bounds.size, [grid[0] as f32 * cell, grid[1] as f32 * cell]);
+    ///     }
+    /// }
+    ///
+    /// // The board is as big as whole pixels allow, centered.
+    /// let viewport = BoardViewport::integer([20, 15], [810, 590]);
+    /// assert_eq!(viewport.cell_size(), 39.0);
+    /// assert_eq!(viewport.bounds().origin, [15.0, 2.0]);
+    /// ```
+    pub fn integer(grid: [u32; 2], window: [u32; 2]) -> BoardViewport {
+        BoardViewport::fit(grid, window, true)
+    }
+
+    fn fit(grid: [u32; 2], window: [u32; 2], integer: bool) -> BoardViewport {
+        let [width, height] = window.map(|size| size.max(1) as f32);
+        let cell = (width / grid[0].max(1) as f32).min(height / grid[1].max(1) as f32);
+        let cell = if integer { cell.floor().max(1.0) } else { cell };
+        let size = [grid[0] as f32 * cell, grid[1] as f32 * cell];
+        let mut origin = [(width - size[0]) / 2.0, (height - size[1]) / 2.0];
+        if integer {
+            origin = origin.map(f32::floor);
+        }
+        BoardViewport {
+            grid,
+            window,
+            bounds: Bounds { origin, size },
+            mirrored: false,
+        }
+    }
+
+    /// The size of the board in cells.
+    pub fn grid(&self) -> [u32; 2] {
+        self.grid
+    }
+
+    /// The part of the window the board covers.
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+
+    /// How many pixels wide and high a cell is.
+    pub fn cell_size(&self) -> f32 {
+        self.bounds.size[0] / self.grid[0].max(1) as f32
+    }
+
+    /// The same viewport, flipped left to right if `mirrored` is set.
+    pub fn with_mirror(self, mirrored: bool) -> BoardViewport {
+        BoardViewport { mirrored, ..self }
+    }
+
+    /// The position of `point` of the board, measured in cells from its top left corner, in
+    /// normalized device coordinates. Point `(0, 0)` is the top left corner of the board and
+    /// `(width, height)` the bottom right one.
+    pub fn cell_to_ndc(&self, point: [f32; 2]) -> [f32; 2] {
+        self.projection().apply(point)
+    }
+
+    /// The mapping of the whole board onto normalized device coordinates, for the shaders to
+    /// do.
+    ///
+    /// ```
+    /// use snake_game::layout::BoardViewport;
+    ///
+    /// // The window is as wide for its height as the board is, so the board fills it.
+    /// let projection = BoardViewport::new([16, 8], [800, 400]).projection();
+    /// assert_eq!(projection.apply([0.0, 0.0]), [-1.0, -1.0]);
+    /// assert_eq!(projection.apply([16.0, 8.0]), [1.0, 1.0]);
+    ///
+    /// // A square one leaves bands above and below it.
+    /// let projection = BoardViewport::new([16, 8], [400, 400]).projection();
+    /// assert_eq!(projection.apply([0.0, 0.0]), [-1.0, -0.5]);
+    /// assert_eq!(projection.apply([16.0, 8.0]), [1.0, 0.5]);
+    ///
+    /// // Mirrored, the first column is on the right.
+    /// let projection = BoardViewport::new([16, 8], [800, 400]).with_mirror(true).projection();
+    /// assert_eq!(projection.apply([0.0, 0.0]), [1.0, -1.0]);
+    /// assert_eq!(projection.apply([16.0, 8.0]), [-1.0, 1.0]);
+    /// ```
+    pub fn projection(&self) -> Projection {
+        let screen = Projection::screen(self.window);
+        let Bounds { origin, size } = self.bounds;
+        let cell = [
+            size[0] / self.grid[0].max(1) as f32,
+            size[1] / self.grid[1].max(1) as f32,
+        ];
+        let (left, flip) = if self.mirrored {
+            (origin[0] + size[0], -1.0)
+        } else {
+            (origin[0], 1.0)
+        };
+        let [x, y] = screen.apply([left, origin[1]]);
+        Projection {
+            scale: [flip * cell[0] * screen.scale[0], cell[1] * screen.scale[1]],
+            offset: [x, y],
+        }
+    }
+
+    /// The cell under the point `pixel` of the window, measured in pixels from its top left
+    /// corner, or `None` if the point is off the board, in the bars left around it.
+    pub fn window_to_cell(&self, pixel: [f32; 2]) -> Option<GridPos> {
+        let Bounds { origin, size } = self.bounds;
+        let x = (pixel[0] - origin[0]) / size[0] * self.grid[0] as f32;
+        let y = (pixel[1] - origin[1]) / size[1] * self.grid[1] as f32;
+        let x = if self.mirrored {
+            self.grid[0] as f32 - x
+        } else {
+            x
+        };
+        let cell = GridPos::new(x.floor() as i32, y.floor() as i32);
+        Some(cell).filter(|cell| cell.is_within(self.grid))
+    }
+}
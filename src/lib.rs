@@ -0,0 +1,36 @@
+pub mod atlas;
+pub mod background;
+pub mod board;
+pub mod death;
+pub mod frame;
+pub mod frame_stats;
+pub mod game;
+#[cfg(feature = "render")]
+pub mod headless;
+#[cfg(feature = "dev-tools")]
+pub mod hot_reload;
+pub mod layout;
+pub mod limiter;
+pub mod mesh;
+pub mod motion;
+pub mod net;
+pub mod palette;
+pub mod particles;
+pub mod pipeline_cache;
+#[cfg(feature = "render")]
+pub mod pipelines;
+pub mod pulse;
+pub mod renderer;
+#[cfg(feature = "render")]
+pub mod session;
+pub mod settings;
+#[cfg(feature = "runtime-shaders")]
+pub mod shader_compiler;
+pub mod spectate;
+pub mod state;
+pub mod text;
+#[cfg(feature = "render")]
+pub mod texture;
+pub mod ticker;
+#[cfg(feature = "render")]
+pub mod vulkan;
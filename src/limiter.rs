@@ -0,0 +1,139 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The lowest frame rate the limiter can be set to. The game ticks at most
+/// [`MAX_TICKS_PER_FRAME`](crate::ticker::MAX_TICKS_PER_FRAME) times a frame, and even its
+/// fastest ticks, sped up as far as they go, fit that many into a frame this long, so holding
+/// frames back never drops a tick.
+pub const MIN_TARGET_FPS: u32 = 30;
+
+/// How long before the end of a frame the limiter stops sleeping and spins instead. Sleeping
+/// can overshoot by about this much, while spinning ends right on time.
+const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+/// Where the limiter gets the time from, and how it waits. Anything other than the
+/// [`SystemClock`] is for trying the limiter out without waiting.
+pub trait Clock {
+    fn now(&self) -> Instant;
+
+    /// Waits until `deadline`, or returns right away if it is already past.
+    fn sleep_until(&self, deadline: Instant);
+}
+
+/// The time as it goes by. Waits by sleeping until just before the deadline, then spinning
+/// the rest of the way.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left > SPIN_MARGIN {
+            thread::sleep(left - SPIN_MARGIN);
+        }
+        while Instant::now() < deadline {
+            thread::yield_now();
+        }
+    }
+}
+
+/// Holds frames back to a target frame rate, when presenting doesn't already: frames wait for
+/// the screen to refresh with vsync, but without it the render loop would draw as fast as it
+/// can, keeping a CPU core and the GPU busy for frames that are never seen.
+///
+/// The frame time it measures is what the frame took all told, the wait included, so it is
+/// what the game is advanced by.
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::time::{Duration, Instant};
+///
+/// use snake_game::limiter::{Clock, FrameLimiter};
+///
+/// /// A clock that only moves when told to, or when waited on.
+/// struct Mock(Cell<Instant>);
+///
+/// impl Clock for &Mock {
+///     fn now(&self) -> Instant {
+///         self.0.get()
+///     }
+///     fn sleep_until(&self, deadline: Instant) {
+///         self.0.set(self.0.get().max(deadline));
+///     }
+/// }
+///
+/// let clock = Mock(Cell::new(Instant::now()));
+/// let mut limiter = FrameLimiter::with_clock(Some(50), &clock);
+///
+/// // A frame drawn in 5ms waits out the rest of its 20ms.
+/// clock.0.set(clock.0.get() + Duration::from_millis(5));
+/// assert_eq!(limiter.end_frame(false), Duration::from_millis(20));
+/// assert_eq!(limiter.frame_time(), Duration::from_millis(20));
+///
+/// // One that took longer goes by as it is, and the next one starts from where it ended.
+/// clock.0.set(clock.0.get() + Duration::from_millis(30));
+/// assert_eq!(limiter.end_frame(false), Duration::from_millis(30));
+///
+/// // With vsync holding frames back, nothing is waited for.
+/// clock.0.set(clock.0.get() + Duration::from_millis(5));
+/// assert_eq!(limiter.end_frame(true), Duration::from_millis(5));
+///
+/// // Targets too low for the game to keep up are raised.
+/// assert_eq!(FrameLimiter::with_clock(Some(5), &clock).target_fps(), Some(30));
+/// assert_eq!(FrameLimiter::with_clock(None, &clock).target_fps(), None);
+/// ```
+#[derive(Debug)]
+pub struct FrameLimiter<C = SystemClock> {
+    clock: C,
+    target_fps: Option<u32>,
+    frame_started: Instant,
+    frame_time: Duration,
+}
+
+impl FrameLimiter {
+    /// Holds frames back to `target_fps` frames a second, if there is one, going by the time
+    /// as it goes by.
+    pub fn new(target_fps: Option<u32>) -> FrameLimiter {
+        FrameLimiter::with_clock(target_fps, SystemClock)
+    }
+}
+
+impl<C: Clock> FrameLimiter<C> {
+    /// Holds frames back to `target_fps` frames a second, if there is one, going by `clock`.
+    /// The target is raised to [`MIN_TARGET_FPS`] if it is any lower.
+    pub fn with_clock(target_fps: Option<u32>, clock: C) -> FrameLimiter<C> {
+        FrameLimiter {
+            frame_started: clock.now(),
+            clock,
+            target_fps: target_fps.map(|fps| fps.max(MIN_TARGET_FPS)),
+            frame_time: Duration::ZERO,
+        }
+    }
+
+    pub fn target_fps(&self) -> Option<u32> {
+        self.target_fps
+    }
+
+    /// Ends a frame, once it has been handed over to be presented, waiting out whatever is left
+    /// of it first unless `vsync` holds frames back already. Returns how long it took, the
+    /// wait included, and starts the next one.
+    pub fn end_frame(&mut self, vsync: bool) -> Duration {
+        if let (false, Some(fps)) = (vsync, self.target_fps) {
+            let budget = Duration::from_secs(1) / fps;
+            self.clock.sleep_until(self.frame_started + budget);
+        }
+        let now = self.clock.now();
+        self.frame_time = now - self.frame_started;
+        self.frame_started = now;
+        self.frame_time
+    }
+
+    /// How long the last frame took, as measured by [`FrameLimiter::end_frame`].
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+}
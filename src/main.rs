@@ -1,3 +1,503 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use snake_game::background::BackgroundMode;
+use snake_game::game::{
+    Achievements, Controller, ControllerFactory, DailyChallenge, DailyRecords, Difficulty, Editor,
+    ExternalBot, Game, GameConfig, GameMode, GreedyAi, HeadOn, HighScores, KeyboardController,
+    Level, RandomController, Replay, Stats, Tournament, TutorialProgress,
+};
+use snake_game::mesh::MeshKind;
+use snake_game::net::{Connection, Host, DEFAULT_PORT};
+use snake_game::palette::{CellShape, Palette};
+use snake_game::renderer::{PresentMode, RenderConfig, ShaderSource, SAMPLE_COUNTS};
+use snake_game::session::Mode;
+use snake_game::settings::Settings;
+use snake_game::spectate::{Spectators, DEFAULT_SPECTATE_PORT};
+use snake_game::vulkan::Vulkan;
+use winit::event_loop::EventLoop;
+
+/// Where a round still running when the window closes is saved.
+const SAVE_PATH: &str = "snake.save";
+
+/// With `--power-ups`, a power-up shows up on average this many ticks after the last was taken.
+const POWER_UP_ODDS: u32 = 50;
+
+/// With `--moving-food`, one in this many pieces of food wanders, moving every
+/// `FOOD_MOVES_EVERY` ticks.
+const MOVING_FOOD_ODDS: u32 = 2;
+const FOOD_MOVES_EVERY: u32 = 4;
+
+/// With `--combo`, how many ticks a snake has to eat again to keep its combo going.
+const COMBO_WINDOW: u64 = 25;
+
+/// With `--rotting-food`, how many ticks food lasts for, and for how many of the last of them
+/// it flashes.
+const FOOD_LIFETIME: u64 = 60;
+const FOOD_WARNING: u64 = 15;
+
+/// How many chasers `--chasers` sets loose when no count follows it.
+const DEFAULT_CHASERS: usize = 1;
+
+/// How many lives each snake has with `--lives` when no count follows it.
+const DEFAULT_LIVES: u32 = 3;
+
+/// With `--poison`, this is one in how many pieces of food comes with poison.
+const POISON_ODDS: u32 = 3;
+
+/// With `--special-food`, how much more often normal food spawns than golden and mega food.
+const FOOD_WEIGHTS: [u32; 3] = [16, 3, 1];
+
+/// How many seeds each pair of bots plays on in a tournament, unless told otherwise. They play
+/// two games on each, swapping seats.
+const TOURNAMENT_SEEDS: u64 = 20;
+
 fn main() {
-    println!("This project currently doesn't show anything because I got tired of updating to work with vgl");
+    match std::env::args().nth(1).as_deref() {
+        Some("tournament") => {
+            tournament(std::env::args().skip(2));
+            return;
+        }
+        Some("stats") => {
+            print!("{}", Stats::load().summary());
+            return;
+        }
+        _ => {}
+    }
+
+    let mut config = GameConfig::default();
+    let mut players = 1;
+    let mut bots = 0;
+    let mut record = None;
+    let mut watch = None;
+    let mut difficulty = Difficulty::Normal;
+    let mut poison = false;
+    let mut mirror = false;
+    let mut mirror_input = false;
+    let mut tutorial = false;
+    let mut daily = false;
+    let mut editor = None;
+    let mut host = None;
+    let mut join = None;
+    let mut spectate = None;
+    let mut render = RenderConfig::default();
+    let mut external = Vec::new();
+    let mut settings = Settings::load();
+
+    let mut args = std::env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--players" => {
+                players = match args.next().and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=2) => n,
+                    _ => fail("--players expects 1 or 2"),
+                };
+            }
+            "--head-on" => match args.next().as_deref().and_then(HeadOn::named) {
+                Some(head_on) => config = config.with_head_on(head_on),
+                None => fail(&format!(
+                    "--head-on expects one of {}",
+                    HeadOn::NAMES.join(", ")
+                )),
+            },
+            "--bot" => match args.next().as_deref().and_then(ExternalBot::from_command) {
+                Some(bot) => external.push(bot),
+                None => fail("--bot expects the command that runs the bot"),
+            },
+            "--ai" => {
+                bots = match args.next().and_then(|n| n.parse().ok()) {
+                    Some(n @ 0..=2) => n,
+                    _ => fail("--ai expects 0, 1 or 2"),
+                };
+            }
+            "--seed" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(seed) => config = config.with_seed(seed),
+                None => fail("--seed expects a number"),
+            },
+            "--timed" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(seconds) if seconds > 0 => {
+                    config = config.with_mode(GameMode::Timed { seconds });
+                }
+                _ => fail("--timed expects a number of seconds"),
+            },
+            "--survival" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(shrink_every) if shrink_every > 0 => {
+                    config = config.with_mode(GameMode::Survival { shrink_every });
+                }
+                _ => fail("--survival expects a number of seconds"),
+            },
+            "--zen" => config = config.with_mode(GameMode::Zen),
+            "--tutorial" => tutorial = true,
+            "--daily" => daily = true,
+            "--edit" => match args.next() {
+                Some(path) => match Editor::open(&path) {
+                    Ok(opened) => editor = Some((opened, PathBuf::from(path))),
+                    Err(e) => fail(&format!("Failed to load level {}: {}", path, e)),
+                },
+                None => fail("--edit expects a path"),
+            },
+            "--renderer" => match args.next().as_deref().and_then(MeshKind::named) {
+                Some(kind) => render = render.with_mesh_kind(kind),
+                None => fail(&format!(
+                    "--renderer expects one of {}",
+                    MeshKind::NAMES.join(", ")
+                )),
+            },
+            "--msaa" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(samples) if SAMPLE_COUNTS.contains(&samples) => {
+                    render = render.with_samples(samples);
+                }
+                _ => fail("--msaa expects 1, 2, 4 or 8 samples"),
+            },
+            "--present-mode" => match args.next().as_deref().and_then(PresentMode::named) {
+                Some(mode) => render = render.with_present_mode(mode),
+                None => fail(&format!(
+                    "--present-mode expects one of {}",
+                    PresentMode::NAMES.join(", ")
+                )),
+            },
+            "--no-vsync" => render = render.with_present_mode(PresentMode::Mailbox),
+            "--fps-limit" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(fps) if fps > 0 => render = render.with_fps_limit(fps),
+                _ => fail("--fps-limit expects a number of frames a second"),
+            },
+            "--shaders" if !cfg!(feature = "runtime-shaders") => {
+                fail("--shaders needs the game built with --features runtime-shaders")
+            }
+            "--shaders" => match (args.next(), args.next()) {
+                (Some(vert), Some(frag)) => {
+                    render = render.with_shaders(ShaderSource::Files {
+                        vert: PathBuf::from(vert),
+                        frag: PathBuf::from(frag),
+                    });
+                }
+                _ => fail("--shaders expects a vertex shader and a fragment shader"),
+            },
+            "--palette" => match args.next().as_deref().and_then(Palette::named) {
+                Some(palette) => {
+                    settings.palette = palette;
+                    settings.save();
+                }
+                None => fail(&format!(
+                    "--palette expects one of {}",
+                    Palette::NAMES.join(", ")
+                )),
+            },
+            "--cells" => match args.next().as_deref().and_then(CellShape::named) {
+                Some(shape) => {
+                    settings.cells = shape;
+                    settings.save();
+                }
+                None => fail(&format!(
+                    "--cells expects one of {}",
+                    CellShape::NAMES.join(", ")
+                )),
+            },
+            "--background" => match args.next().as_deref().and_then(BackgroundMode::named) {
+                Some(mode) => {
+                    settings.background = mode;
+                    settings.save();
+                }
+                None => fail(&format!(
+                    "--background expects one of {}",
+                    BackgroundMode::NAMES.join(", ")
+                )),
+            },
+            "--fullscreen" | "--windowed" => {
+                settings.fullscreen = arg == "--fullscreen";
+                settings.save();
+            }
+            "--integer-scaling" | "--no-integer-scaling" => {
+                settings.integer_scaling = arg == "--integer-scaling";
+                settings.save();
+            }
+            "--reduced-motion" | "--no-reduced-motion" => {
+                settings.reduced_motion = arg == "--reduced-motion";
+                settings.save();
+            }
+            "--difficulty" => {
+                difficulty = match args.next().as_deref().and_then(Difficulty::preset) {
+                    Some(difficulty) => difficulty,
+                    None => fail(&format!(
+                        "--difficulty expects one of {}",
+                        Difficulty::PRESETS.join(", ")
+                    )),
+                };
+            }
+            "--lives" => {
+                let lives = match args.peek().and_then(|n| n.parse().ok()) {
+                    Some(lives) => {
+                        args.next();
+                        lives
+                    }
+                    None => DEFAULT_LIVES,
+                };
+                if lives == 0 {
+                    fail("--lives expects a positive number of lives");
+                }
+                config = config.with_lives(lives);
+            }
+            "--host" => {
+                host = match args.peek().and_then(|n| n.parse().ok()) {
+                    Some(port) => {
+                        args.next();
+                        Some(port)
+                    }
+                    None => Some(DEFAULT_PORT),
+                };
+            }
+            "--join" => match args.next() {
+                // The port can be left out when the host kept to the default one.
+                Some(address) if address.contains(':') => join = Some(address),
+                Some(address) => join = Some(format!("{}:{}", address, DEFAULT_PORT)),
+                None => fail("--join expects the address of the host"),
+            },
+            "--spectate" => {
+                spectate = match args.peek().and_then(|n| n.parse().ok()) {
+                    Some(port) => {
+                        args.next();
+                        Some(port)
+                    }
+                    None => Some(DEFAULT_SPECTATE_PORT),
+                };
+            }
+            "--checkpoint-penalty" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(penalty) => config = config.with_checkpoint_penalty(penalty),
+                None => fail("--checkpoint-penalty expects a number of points"),
+            },
+            "--chasers" => {
+                let chasers = match args.peek().and_then(|n| n.parse().ok()) {
+                    Some(chasers) => {
+                        args.next();
+                        chasers
+                    }
+                    None => DEFAULT_CHASERS,
+                };
+                config = config.with_chasers(chasers);
+            }
+            "--poison" => poison = true,
+            "--power-ups" => config = config.with_power_ups(POWER_UP_ODDS),
+            "--debuffs" => config = config.with_debuffs(true),
+            "--diagonals" => config = config.with_diagonals(true),
+            "--mirror" => mirror = true,
+            "--mirror-input" => mirror_input = true,
+            "--special-food" => {
+                let [normal, golden, mega] = FOOD_WEIGHTS;
+                config = config.with_food_weights(normal, golden, mega);
+            }
+            "--combo" => config = config.with_combo(COMBO_WINDOW),
+            "--rotting-food" => config = config.with_rotting_food(FOOD_LIFETIME, FOOD_WARNING),
+            "--moving-food" => {
+                config = config.with_moving_food(MOVING_FOOD_ODDS, FOOD_MOVES_EVERY);
+            }
+            "--record" => match args.next() {
+                Some(path) => record = Some(PathBuf::from(path)),
+                None => fail("--record expects a path"),
+            },
+            "--replay" => match args.next() {
+                Some(path) => match Replay::load(&path) {
+                    Ok(replay) => watch = Some(replay),
+                    Err(e) => fail(&format!("Failed to load replay {}: {}", path, e)),
+                },
+                None => fail("--replay expects a path"),
+            },
+            // Anything else is a level, either one of the bundled ones or a path.
+            _ => {
+                let level = match Level::bundled(&arg) {
+                    Some(level) => Ok(level),
+                    None => Level::load(&arg),
+                };
+
+                match level {
+                    Ok(level) => config = config.with_level(&level),
+                    Err(e) => fail(&format!("Failed to load level {}: {}", arg, e)),
+                }
+            }
+        }
+    }
+
+    // The guest steers player two.
+    if host.is_some() {
+        players = 2;
+    }
+    let config = config
+        .with_players(players + external.len() + bots)
+        .with_mirror(mirror, mirror_input);
+    let mut config = match difficulty.apply(config) {
+        Ok(config) => config,
+        Err(e) => fail(&format!("Can't play with these settings: {}", e)),
+    };
+    if poison {
+        config = config.with_poison(POISON_ODDS);
+    }
+    // The day's challenge goes last, so that its board wins over whatever else was asked for.
+    let daily = daily.then(DailyChallenge::today);
+    if let Some(challenge) = &daily {
+        config = challenge.apply(config);
+        println!("Daily challenge of {}", challenge.date);
+    }
+
+    // The computer snakes come after the ones played from the keyboard, the bots run as
+    // programs of their own first.
+    let mut controllers: Vec<Box<dyn Controller>> = Vec::new();
+    controllers.resize_with(players, || Box::new(KeyboardController::default()));
+    for bot in external {
+        controllers.push(Box::new(bot));
+    }
+    controllers.resize_with(controllers.len() + bots, || Box::new(GreedyAi));
+
+    let host = host.map(|port| match Host::listen(("0.0.0.0", port)) {
+        Ok(host) => {
+            println!("Waiting for a player to join on port {}", port);
+            host
+        }
+        Err(e) => fail(&format!("Failed to listen on port {}: {}", port, e)),
+    });
+
+    // Only for watching from the same machine, streaming software and the like.
+    let spectators = spectate.map(|port| match Spectators::listen(("127.0.0.1", port)) {
+        Ok(spectators) => {
+            println!("Publishing the rounds to spectators on port {}", port);
+            spectators
+        }
+        Err(e) => fail(&format!("Failed to listen on port {}: {}", port, e)),
+    });
+
+    let mode = match (watch, join) {
+        (Some(replay), _) => Mode::Watch(replay),
+        (None, Some(address)) => match Connection::join(address.as_str()) {
+            Ok(connection) => {
+                println!("Joined {}", connection.peer());
+                Mode::Join(connection)
+            }
+            Err(e) => fail(&format!("Failed to join {}: {}", address, e)),
+        },
+        (None, None) => Mode::Play {
+            config,
+            difficulty,
+            controllers,
+            record,
+            // A round with a guest can't be picked up without them.
+            resume: host
+                .is_none()
+                .then(|| offer_resume(Path::new(SAVE_PATH)))
+                .flatten(),
+            save: host.is_none().then(|| PathBuf::from(SAVE_PATH)),
+            high_scores: HighScores::load(),
+            achievements: Achievements::load(),
+            stats: Stats::load(),
+            // Someone editing a level knows their way around already.
+            tutorial: Some(TutorialProgress::load())
+                .filter(|progress| editor.is_none() && (tutorial || !progress.is_done())),
+            daily: daily.map(|challenge| (challenge, DailyRecords::load())),
+            editor,
+            host,
+            spectators,
+        },
+    };
+
+    let event_loop = EventLoop::new();
+    let vulkan = Vulkan::new(&event_loop, render);
+    vulkan.setup(event_loop, mode, settings);
+}
+
+/// Asks on the terminal whether to pick up the game saved at `path`, if there is one. The save
+/// is removed either way, a new one is written if the window closes mid-round again.
+fn offer_resume(path: &Path) -> Option<Box<Game>> {
+    if !path.exists() {
+        return None;
+    }
+
+    print!("Resume the game you left off? [Y/n] ");
+    io::stdout().flush().ok()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).ok()?;
+
+    let game = match answer.trim() {
+        "" | "y" | "Y" | "yes" => match Game::load_from(path) {
+            Ok(game) => Some(Box::new(game)),
+            Err(e) => {
+                eprintln!("Failed to resume the saved game: {}", e);
+                None
+            }
+        },
+        _ => None,
+    };
+
+    if let Err(e) = fs::remove_file(path) {
+        eprintln!("Failed to remove {}: {}", path.display(), e);
+    }
+    game
+}
+
+/// `snake_game tournament`: plays the bots against each other without opening a window, and
+/// prints how each of them did.
+fn tournament(mut args: impl Iterator<Item = String>) {
+    let mut seeds = TOURNAMENT_SEEDS;
+    let mut tick_cap = None;
+    let mut json = None;
+    let mut external = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seeds" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) if n > 0 => seeds = n,
+                _ => fail("--seeds expects a number of seeds"),
+            },
+            "--tick-cap" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) if n > 0 => tick_cap = Some(n),
+                _ => fail("--tick-cap expects a number of ticks"),
+            },
+            "--json" => match args.next() {
+                Some(path) => json = Some(PathBuf::from(path)),
+                None => fail("--json expects a path"),
+            },
+            "--bot" => match args.next() {
+                Some(command) if ExternalBot::from_command(&command).is_some() => {
+                    external.push(command)
+                }
+                _ => fail("--bot expects the command that runs the bot"),
+            },
+            _ => fail(&format!("Unknown tournament option {}", arg)),
+        }
+    }
+
+    let mut entrants: Vec<(String, ControllerFactory)> = vec![
+        ("greedy".to_string(), Box::new(|_| Box::new(GreedyAi))),
+        (
+            "random".to_string(),
+            Box::new(|seed| Box::new(RandomController::new(seed))),
+        ),
+    ];
+    // Each game starts the bot over, and it is killed once the game is done.
+    for command in external {
+        let factory: ControllerFactory = Box::new({
+            let command = command.clone();
+            move |_| {
+                Box::new(
+                    ExternalBot::from_command(&command)
+                        .expect("the command was checked when it was given"),
+                )
+            }
+        });
+        entrants.push((command, factory));
+    }
+    let mut tournament = Tournament::new(entrants, (0..seeds).collect());
+    if let Some(ticks) = tick_cap {
+        tournament = tournament.with_tick_cap(ticks);
+    }
+
+    let results = tournament.run();
+    print!("{}", results);
+    if let Some(path) = json {
+        if let Err(e) = results.save(&path) {
+            fail(&format!("Failed to write {}: {}", path.display(), e));
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
 }
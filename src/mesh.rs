@@ -0,0 +1,758 @@
+use crate::game::{Direction, GridPos};
+use crate::layout::Projection;
+use crate::palette::CellShape;
+
+/// A color that leaves whatever it tints as it is.
+pub const WHITE: [f32; 4] = [1.0; 4];
+
+/// How far the tip of the notch cut into the head of a snake is ahead of the middle of its
+/// cell, how far behind the middle its base is, and how far that goes out to either side, all
+/// in cells.
+const NOTCH_TIP: f32 = 0.3;
+const NOTCH_BACK: f32 = 0.05;
+const NOTCH_HALF_WIDTH: f32 = 0.2;
+
+/// How many quads a triangle is made of in an instanced mesh, see [`Mesh::push_triangle`].
+const TRIANGLE_STEPS: usize = 4;
+
+/// Texture coordinates that sample nothing: a corner with them is drawn in its color alone.
+/// Real ones are never negative, so the shaders tell them apart.
+pub const UNTEXTURED: [f32; 2] = [-1.0, -1.0];
+
+/// Where in its cell every corner of a quad that isn't a cell is: in the middle, as far from
+/// its edges as it gets, so that no [`CellShape`] cuts any of it off.
+pub const UNSHAPED: [f32; 2] = [0.5, 0.5];
+
+/// What a quad is, as far as the shaders go: most are drawn as they are, but food pulses, see
+/// [`Pulse`](crate::pulse::Pulse).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuadKind {
+    #[default]
+    Plain,
+    Food,
+}
+
+impl QuadKind {
+    /// The kind the way the shaders take it.
+    pub const fn id(self) -> u32 {
+        self as u32
+    }
+}
+
+/// A corner of a triangle, on the board: measured in cells from its top left corner. The
+/// shaders put it in the window with the [`Projection`](crate::layout::Projection) of the
+/// board's layout. The triangle's color is blended from the colors of its corners, and tinted
+/// by the sprite at `tex_coords`, from `(0, 0)` at its top left corner to `(1, 1)` at its bottom
+/// right, unless they are [`UNTEXTURED`].
+///
+/// The corners of a cell also say where they are in it, as `local`, going from `(0, 0)` to
+/// `(1, 1)` the same way, which the fragment shaders cut the [`CellShape`] out of the quad by.
+/// Anything else is [`UNSHAPED`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Vertex {
+    pub position: [f32; 2],
+    pub color: [f32; 4],
+    pub tex_coords: [f32; 2],
+    /// The [`QuadKind::id`] of the quad the corner is of.
+    pub quad_kind: u32,
+    pub local: [f32; 2],
+}
+
+impl Vertex {
+    /// A [`WHITE`] corner at `position`, without a sprite, for whatever only has a place to
+    /// give.
+    pub const fn new(position: [f32; 2]) -> Vertex {
+        Vertex {
+            position,
+            color: WHITE,
+            tex_coords: UNTEXTURED,
+            quad_kind: QuadKind::Plain.id(),
+            local: UNSHAPED,
+        }
+    }
+
+    /// The same corner in `color`.
+    pub const fn with_color(self, color: [f32; 4]) -> Vertex {
+        Vertex { color, ..self }
+    }
+
+    /// The same corner, showing the sprite at `tex_coords`.
+    pub const fn with_tex_coords(self, tex_coords: [f32; 2]) -> Vertex {
+        Vertex { tex_coords, ..self }
+    }
+}
+
+impl Default for Vertex {
+    fn default() -> Vertex {
+        Vertex::new([0.0, 0.0])
+    }
+}
+
+/// The part of a sprite a quad shows, in texture coordinates: from `origin` at the quad's top
+/// left corner, `extent` further at its bottom right one. A negative extent flips the sprite.
+///
+/// ```
+/// use snake_game::game::Direction;
+/// use snake_game::mesh::TexRect;
+///
+/// let sprite = TexRect::WHOLE;
+/// // Facing up, it is as it is; facing right, its top is along the quad's right side.
+/// assert_eq!(sprite.facing(Direction::Up), sprite);
+/// let right = sprite.facing(Direction::Right);
+/// assert_eq!(right.at([1.0, 0.0]), [0.0, 0.0]);
+/// assert_eq!(right.at([1.0, 1.0]), [1.0, 0.0]);
+/// assert_eq!(right.at([0.0, 0.0]), [0.0, 1.0]);
+/// let down = sprite.facing(Direction::Down);
+/// assert_eq!(down.at([0.0, 1.0]), [1.0, 0.0]);
+/// let left = sprite.facing(Direction::Left);
+/// assert_eq!(left.at([0.0, 1.0]), [0.0, 0.0]);
+/// assert_eq!(left.at([0.0, 0.0]), [1.0, 0.0]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TexRect {
+    pub origin: [f32; 2],
+    pub extent: [f32; 2],
+    /// Whether the sprite's axes are swapped: across the quad goes down the sprite, and down
+    /// the quad across it. Along with a flip, that turns the sprite a quarter.
+    pub transposed: bool,
+}
+
+impl TexRect {
+    /// The whole sprite.
+    pub const WHOLE: TexRect = TexRect {
+        origin: [0.0, 0.0],
+        extent: [1.0, 1.0],
+        transposed: false,
+    };
+
+    /// No sprite at all, every corner [`UNTEXTURED`].
+    pub const NONE: TexRect = TexRect {
+        origin: UNTEXTURED,
+        extent: [0.0, 0.0],
+        transposed: false,
+    };
+
+    /// The texture coordinates at `position` on the unit square.
+    pub fn at(&self, position: [f32; 2]) -> [f32; 2] {
+        let [x, y] = if self.transposed {
+            [position[1], position[0]]
+        } else {
+            position
+        };
+        [
+            self.origin[0] + x * self.extent[0],
+            self.origin[1] + y * self.extent[1],
+        ]
+    }
+
+    /// This sprite, drawn facing up, turned to face `facing`. Only quarter turns go, so the
+    /// diagonals leave it as it is.
+    pub fn facing(self, facing: Direction) -> TexRect {
+        let [x, y] = self.origin;
+        let [width, height] = self.extent;
+        let (origin, extent, transposed) = match facing {
+            Direction::Right => ([x, y + height], [width, -height], true),
+            Direction::Down => ([x + width, y + height], [-width, -height], false),
+            Direction::Left => ([x + width, y], [-width, height], true),
+            _ => return self,
+        };
+        TexRect {
+            origin,
+            extent,
+            transposed: transposed != self.transposed,
+        }
+    }
+}
+
+/// The square from `(0, 0)` to `(1, 1)`, as the corners of two triangles, which every
+/// [`Instance`] stretches over its own quad.
+pub const UNIT_QUAD: [[f32; 2]; 6] = [
+    [0.0, 0.0],
+    [1.0, 0.0],
+    [1.0, 1.0],
+    [0.0, 0.0],
+    [1.0, 1.0],
+    [0.0, 1.0],
+];
+
+/// A quad drawn as an instance of [`UNIT_QUAD`]: `origin` is where the unit square's `(0, 0)`
+/// corner goes, and `extent` how far its `(1, 1)` corner is from there, both measured in cells
+/// like [`Vertex`] positions. All of it is `color`, tinted by the sprite from `tex_origin` to
+/// `tex_origin + tex_extent`, the way a [`TexRect`] goes, and where it is in its cell goes from
+/// `local_origin` to `local_origin + local_extent` the same way, see [`Vertex::local`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Instance {
+    pub origin: [f32; 2],
+    pub extent: [f32; 2],
+    pub color: [f32; 4],
+    pub tex_origin: [f32; 2],
+    pub tex_extent: [f32; 2],
+    /// One if the sprite is [`TexRect::transposed`], and zero if it isn't.
+    pub tex_transposed: f32,
+    /// The [`QuadKind::id`] of the quad.
+    pub quad_kind: u32,
+    pub local_origin: [f32; 2],
+    pub local_extent: [f32; 2],
+}
+
+impl Instance {
+    /// `position` on the unit square, moved to where it is on the quad.
+    pub fn place(&self, position: [f32; 2]) -> [f32; 2] {
+        [
+            self.origin[0] + position[0] * self.extent[0],
+            self.origin[1] + position[1] * self.extent[1],
+        ]
+    }
+}
+
+/// What a [`Mesh`] is drawn with: all of it tinted by `color`, its positions scaled by `scale`
+/// and then moved by `offset`, its food pulsing along with `time`, the
+/// [`Pulse::time`](crate::pulse::Pulse::time), all of it `depth` back, the
+/// [`Layer::depth`](crate::frame::Layer::depth) of what it is drawn in, and its cells cut to
+/// the [`CellShape`] with `corner_radius` and `softness`. The renderer pushes these before
+/// drawing it.
+///
+/// ```
+/// use snake_game::mesh::{DrawParams, WHITE};
+///
+/// let params = DrawParams::new(WHITE);
+/// assert_eq!(params.transform([0.5, -0.5]), [0.5, -0.5]);
+///
+/// let params = params.with_transform([0.25, 0.0], [0.5, 2.0]);
+/// assert_eq!(params.transform([0.5, -0.5]), [0.5, -1.0]);
+///
+/// // Cells are square unless they are given a shape.
+/// use snake_game::palette::CellShape;
+/// assert_eq!(params.corner_radius, 0.0);
+/// assert!(params.with_shape(CellShape::Rounded).corner_radius > 0.0);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawParams {
+    pub color: [f32; 4],
+    pub offset: [f32; 2],
+    pub scale: [f32; 2],
+    pub time: f32,
+    pub depth: f32,
+    pub corner_radius: f32,
+    pub softness: f32,
+}
+
+impl DrawParams {
+    /// Tinted by `color`, right where the positions say.
+    pub fn new(color: [f32; 4]) -> DrawParams {
+        DrawParams {
+            color,
+            offset: [0.0, 0.0],
+            scale: [1.0, 1.0],
+            time: 0.0,
+            depth: 0.0,
+            corner_radius: 0.0,
+            softness: 0.0,
+        }
+    }
+
+    /// The same, with the positions scaled by `scale` and then moved by `offset`.
+    pub fn with_transform(self, offset: [f32; 2], scale: [f32; 2]) -> DrawParams {
+        DrawParams {
+            offset,
+            scale,
+            ..self
+        }
+    }
+
+    /// The same, with the food pulsing `time` seconds into the pulse.
+    pub fn with_time(self, time: f32) -> DrawParams {
+        DrawParams { time, ..self }
+    }
+
+    /// The same, drawn `depth` back, from 0 at the front to 1 at the back.
+    pub fn with_depth(self, depth: f32) -> DrawParams {
+        DrawParams { depth, ..self }
+    }
+
+    /// The same, with its cells drawn in `shape`.
+    pub fn with_shape(self, shape: CellShape) -> DrawParams {
+        DrawParams {
+            corner_radius: shape.corner_radius(QuadKind::Plain),
+            softness: shape.softness(),
+            ..self
+        }
+    }
+
+    /// Where `position` ends up once drawn, the way the vertex shader puts it.
+    pub fn transform(&self, position: [f32; 2]) -> [f32; 2] {
+        [
+            position[0] * self.scale[0] + self.offset[0],
+            position[1] * self.scale[1] + self.offset[1],
+        ]
+    }
+}
+
+/// The ways a [`Mesh`] can be drawn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeshKind {
+    /// Two triangles of three vertices each a quad.
+    Triangles,
+    /// The four corners of each quad, and six indices into them.
+    Indexed,
+    /// One [`Instance`] of the unit square each quad.
+    #[default]
+    Instanced,
+}
+
+impl MeshKind {
+    pub const ALL: [MeshKind; 3] = [MeshKind::Triangles, MeshKind::Indexed, MeshKind::Instanced];
+
+    /// The names the kinds go by on the command line, in the order of [`MeshKind::ALL`].
+    pub const NAMES: [&'static str; 3] = ["triangles", "indexed", "instanced"];
+
+    /// The kind called `name`, one of the [`MeshKind::NAMES`].
+    pub fn named(name: &str) -> Option<MeshKind> {
+        MeshKind::NAMES
+            .iter()
+            .position(|&other| other == name)
+            .map(|i| MeshKind::ALL[i])
+    }
+}
+
+/// Quads to draw, one after the other, built for whichever [`MeshKind`] the renderer draws.
+/// Quads are taken to have their sides along the axes, as the cells of the board do, and are
+/// measured in cells. Each has a color of its own, so the whole of a mesh is drawn at once.
+///
+/// Whatever the kind, what gets drawn is counted in [`Mesh::len`]: the vertices when drawing
+/// triangles, the indices when drawing them indexed, and the instances otherwise. A run of
+/// quads is drawn the same whichever it is.
+///
+/// ```
+/// use snake_game::game::GridPos;
+/// use snake_game::mesh::{Mesh, MeshKind, TexRect, UNTEXTURED};
+///
+/// let (red, blue) = ([1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]);
+/// let [mut triangles, mut indexed, mut instanced] = MeshKind::ALL.map(Mesh::new);
+/// for mesh in [&mut triangles, &mut indexed, &mut instanced] {
+///     mesh.push_cell(GridPos::new(0, 0), 0.0, red);
+///     mesh.push_sprite(GridPos::new(3, 2), 0.1, blue, TexRect::WHOLE);
+/// }
+///
+/// assert_eq!((triangles.vertices().len(), triangles.len()), (12, 12));
+/// assert_eq!((indexed.vertices().len(), indexed.len()), (8, 12));
+/// assert_eq!((instanced.instances().len(), instanced.len()), (2, 2));
+///
+/// assert_eq!(triangles.triangles()[0], [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]);
+/// assert_eq!(triangles.triangles(), indexed.triangles());
+/// assert_eq!(triangles.triangles(), instanced.triangles());
+///
+/// assert_eq!((triangles.vertices()[5].color, triangles.vertices()[6].color), (red, blue));
+/// assert_eq!((indexed.vertices()[3].color, indexed.vertices()[4].color), (red, blue));
+/// assert_eq!(instanced.instances()[1].color, blue);
+///
+/// assert_eq!(triangles.vertices()[0].tex_coords, UNTEXTURED);
+/// assert_eq!(triangles.vertices()[8].tex_coords, [1.0, 1.0]);
+/// assert_eq!(indexed.vertices()[7].tex_coords, [0.0, 1.0]);
+/// assert_eq!(instanced.instances()[1].tex_extent, [1.0, 1.0]);
+///
+/// // Triangles are drawn as they are, except in instanced meshes, which only have quads.
+/// let corner = [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]];
+/// for mesh in [&mut triangles, &mut indexed, &mut instanced] {
+///     mesh.clear();
+///     mesh.push_triangle(corner, red);
+/// }
+/// assert_eq!(triangles.triangles(), [corner]);
+/// assert_eq!(indexed.triangles(), [corner]);
+/// assert_eq!(instanced.instances().len(), 4);
+///
+/// // Quads are of the kind last set, which the shaders tell apart.
+/// use snake_game::mesh::QuadKind;
+/// for mesh in [&mut triangles, &mut instanced] {
+///     mesh.clear();
+///     mesh.set_quad_kind(QuadKind::Food);
+///     mesh.push_cell(GridPos::new(1, 1), 0.0, red);
+/// }
+/// assert!(triangles.vertices().iter().all(|v| v.quad_kind == QuadKind::Food.id()));
+/// assert_eq!(instanced.instances()[0].quad_kind, QuadKind::Food.id());
+///
+/// // Cells say where their corners are in them, for the shaders to cut their shape out by;
+/// // other quads don't.
+/// use snake_game::mesh::UNSHAPED;
+/// assert_eq!(triangles.vertices()[2].local, [1.0, 1.0]);
+/// assert_eq!(instanced.instances()[0].local_extent, [1.0, 1.0]);
+/// for mesh in [&mut triangles, &mut instanced] {
+///     mesh.clear();
+///     mesh.push_quad([[0.0, 0.0], [2.0, 0.0], [2.0, 1.0], [0.0, 1.0]], red);
+/// }
+/// assert!(triangles.vertices().iter().all(|v| v.local == UNSHAPED));
+/// assert_eq!(instanced.instances()[0].local_extent, [0.0, 0.0]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh {
+    kind: MeshKind,
+    vertices: Vec<Vertex>,
+    /// Only used by [`MeshKind::Indexed`] meshes.
+    indices: Vec<u32>,
+    /// Only used by [`MeshKind::Instanced`] meshes.
+    instances: Vec<Instance>,
+    /// The kind of the quads pushed from now on.
+    quad_kind: QuadKind,
+}
+
+impl Mesh {
+    /// An empty mesh, to be drawn the way `kind` says.
+    pub fn new(kind: MeshKind) -> Mesh {
+        Mesh {
+            kind,
+            ..Mesh::default()
+        }
+    }
+
+    pub fn kind(&self) -> MeshKind {
+        self.kind
+    }
+
+    /// Takes out every quad, keeping the allocations so the mesh can be built again every frame.
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+        self.indices.clear();
+        self.instances.clear();
+        self.quad_kind = QuadKind::Plain;
+    }
+
+    /// Makes the quads pushed from now on `kind`, until it is set again or the mesh cleared.
+    pub fn set_quad_kind(&mut self, kind: QuadKind) {
+        self.quad_kind = kind;
+    }
+
+    /// Adds the quad with corners `corners`, in `color`. The corners go top left, top right,
+    /// bottom right and bottom left.
+    pub fn push_quad(&mut self, corners: [[f32; 2]; 4], color: [f32; 4]) {
+        self.push_textured_quad(corners, color, TexRect::NONE);
+    }
+
+    /// Adds the quad with corners `corners`, in `color` tinted by the part `tex` of the sprite.
+    pub fn push_textured_quad(&mut self, corners: [[f32; 2]; 4], color: [f32; 4], tex: TexRect) {
+        self.push_shaped_quad(corners, color, tex, false);
+    }
+
+    /// Adds a quad the way [`Mesh::push_textured_quad`] does, cut to the shape of a cell when
+    /// it is `cell`.
+    fn push_shaped_quad(
+        &mut self,
+        corners: [[f32; 2]; 4],
+        color: [f32; 4],
+        tex: TexRect,
+        cell: bool,
+    ) {
+        // The corners of the quad are the corners of the unit square, stretched.
+        let unit = [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]];
+        let quad_kind = self.quad_kind.id();
+        let corner = |i: usize| Vertex {
+            position: corners[i],
+            color,
+            tex_coords: tex.at(unit[i]),
+            quad_kind,
+            local: if cell { unit[i] } else { UNSHAPED },
+        };
+        let [top_left, top_right, bottom_right, bottom_left] = [0, 1, 2, 3].map(corner);
+        match self.kind {
+            MeshKind::Triangles => self.vertices.extend([
+                top_left,
+                top_right,
+                bottom_right,
+                top_left,
+                bottom_right,
+                bottom_left,
+            ]),
+            MeshKind::Indexed => {
+                let first = self.vertices.len() as u32;
+                self.vertices
+                    .extend([top_left, top_right, bottom_right, bottom_left]);
+                self.indices
+                    .extend([0, 1, 2, 0, 2, 3].map(|corner| first + corner));
+            }
+            MeshKind::Instanced => {
+                let [left, top] = top_left.position;
+                let [right, bottom] = bottom_right.position;
+                self.instances.push(Instance {
+                    origin: [left, top],
+                    extent: [right - left, bottom - top],
+                    color,
+                    tex_origin: tex.origin,
+                    tex_extent: tex.extent,
+                    tex_transposed: if tex.transposed { 1.0 } else { 0.0 },
+                    quad_kind,
+                    local_origin: if cell { [0.0, 0.0] } else { UNSHAPED },
+                    local_extent: if cell { [1.0, 1.0] } else { [0.0, 0.0] },
+                });
+            }
+        }
+    }
+
+    /// Adds the triangle with corners `corners`, in `color`. An instanced mesh only has quads,
+    /// so it gets a few narrow ones side by side instead, in steps along the triangle's edges.
+    pub fn push_triangle(&mut self, corners: [[f32; 2]; 3], color: [f32; 4]) {
+        let quad_kind = self.quad_kind.id();
+        let corner = |i: usize| Vertex {
+            quad_kind,
+            ..Vertex::new(corners[i]).with_color(color)
+        };
+        match self.kind {
+            MeshKind::Triangles => self.vertices.extend([0, 1, 2].map(corner)),
+            MeshKind::Indexed => {
+                let first = self.vertices.len() as u32;
+                self.vertices.extend([0, 1, 2].map(corner));
+                self.indices.extend([0, 1, 2].map(|corner| first + corner));
+            }
+            MeshKind::Instanced => {
+                let xs = corners.map(|[x, _]| x);
+                let left = xs.into_iter().fold(f32::INFINITY, f32::min);
+                let right = xs.into_iter().fold(f32::NEG_INFINITY, f32::max);
+                let step = (right - left) / TRIANGLE_STEPS as f32;
+                for i in 0..TRIANGLE_STEPS {
+                    let (x0, x1) = (left + i as f32 * step, left + (i + 1) as f32 * step);
+                    // How far the triangle goes up and down halfway across the step.
+                    let middle = (x0 + x1) / 2.0;
+                    let ys = [(0, 1), (1, 2), (2, 0)].into_iter().filter_map(|(a, b)| {
+                        let ([ax, ay], [bx, by]) = (corners[a], corners[b]);
+                        let crosses = (ax.min(bx)..=ax.max(bx)).contains(&middle) && ax != bx;
+                        crosses.then(|| ay + (middle - ax) / (bx - ax) * (by - ay))
+                    });
+                    let (top, bottom) = ys.fold((f32::INFINITY, f32::NEG_INFINITY), |(t, b), y| {
+                        (t.min(y), b.max(y))
+                    });
+                    if top < bottom {
+                        self.push_quad([[x0, top], [x1, top], [x1, bottom], [x0, bottom]], color);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds a quad in `color` covering `cell`, `inset` of a cell in from each of its edges, in
+    /// the [`CellShape`] the cells are drawn in.
+    pub fn push_cell(&mut self, cell: GridPos, inset: f32, color: [f32; 4]) {
+        self.push_sprite(cell, inset, color, TexRect::NONE);
+    }
+
+    /// Adds a quad covering `cell` the way [`Mesh::push_cell`] does, in `color` tinted by the
+    /// part `tex` of the sprite.
+    pub fn push_sprite(&mut self, cell: GridPos, inset: f32, color: [f32; 4], tex: TexRect) {
+        self.push_sprite_at([cell.x as f32, cell.y as f32], inset, color, tex);
+    }
+
+    /// Adds a quad the way [`Mesh::push_sprite`] does, over a cell that isn't lined up with the
+    /// others: the one with its top left corner at `position`.
+    pub fn push_sprite_at(
+        &mut self,
+        position: [f32; 2],
+        inset: f32,
+        color: [f32; 4],
+        tex: TexRect,
+    ) {
+        let [x, y] = position;
+        let (left, top) = (x + inset, y + inset);
+        let (right, bottom) = (x + 1.0 - inset, y + 1.0 - inset);
+        self.push_shaped_quad(
+            [[left, top], [right, top], [right, bottom], [left, bottom]],
+            color,
+            tex,
+            true,
+        );
+    }
+
+    /// Changes the color of every quad to what `recolor` makes of it.
+    pub fn recolor(&mut self, recolor: impl Fn([f32; 4]) -> [f32; 4]) {
+        for vertex in &mut self.vertices {
+            vertex.color = recolor(vertex.color);
+        }
+        for instance in &mut self.instances {
+            instance.color = recolor(instance.color);
+        }
+    }
+
+    /// How many vertices, indices or instances there are to draw, depending on the kind.
+    pub fn len(&self) -> u32 {
+        match self.kind {
+            MeshKind::Triangles => self.vertices.len() as u32,
+            MeshKind::Indexed => self.indices.len() as u32,
+            MeshKind::Instanced => self.instances.len() as u32,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many vertices, indices or instances the draw of the mesh is of, or `None` when it has
+    /// nothing to draw: an empty buffer can't be bound, and some drivers don't take well to
+    /// drawing nothing, so no draw is submitted for it at all.
+    pub fn draw_count(&self) -> Option<u32> {
+        Some(self.len()).filter(|&len| len > 0)
+    }
+
+    /// The vertices to draw. Empty for an instanced mesh, which draws [`UNIT_QUAD`] instead.
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    /// The indices into [`Mesh::vertices`], three a triangle. Empty unless the mesh is indexed.
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// Empty unless the mesh is instanced.
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// Every triangle drawn, in order, as the positions of its corners.
+    pub fn triangles(&self) -> Vec<[[f32; 2]; 3]> {
+        let corners: Vec<[f32; 2]> = match self.kind {
+            MeshKind::Triangles => self.vertices.iter().map(|vertex| vertex.position).collect(),
+            MeshKind::Indexed => self
+                .indices
+                .iter()
+                .map(|&index| self.vertices[index as usize].position)
+                .collect(),
+            MeshKind::Instanced => self
+                .instances
+                .iter()
+                .flat_map(|instance| UNIT_QUAD.map(|corner| instance.place(corner)))
+                .collect(),
+        };
+        corners
+            .chunks_exact(3)
+            .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+            .collect()
+    }
+}
+
+/// The triangle cut into the head of a snake in `cell` heading towards `heading`, pointing
+/// that way: its tip, then the two corners of its base. It is what shows where a head faces
+/// when there is no sprite to show it.
+///
+/// ```
+/// use snake_game::game::{Direction, GridPos};
+/// use snake_game::mesh::head_notch;
+///
+/// let rounded = |corners: [[f32; 2]; 3]| corners.map(|c| c.map(|v| (v * 100.0).round() / 100.0));
+/// let cell = GridPos::new(2, 3);
+/// let notch = |heading| rounded(head_notch(cell, heading));
+/// assert_eq!(notch(Direction::Up), [[2.5, 3.2], [2.3, 3.55], [2.7, 3.55]]);
+/// assert_eq!(notch(Direction::Down), [[2.5, 3.8], [2.7, 3.45], [2.3, 3.45]]);
+/// assert_eq!(notch(Direction::Left), [[2.2, 3.5], [2.55, 3.7], [2.55, 3.3]]);
+/// assert_eq!(notch(Direction::Right), [[2.8, 3.5], [2.45, 3.3], [2.45, 3.7]]);
+///
+/// // Whichever way, it stays within the cell, with its tip furthest along the heading.
+/// for heading in Direction::ALL_EIGHT {
+///     let [tip, a, b] = head_notch(cell, heading);
+///     let (dx, dy) = heading.delta();
+///     let along = |[x, y]: [f32; 2]| x * dx as f32 + y * dy as f32;
+///     assert!(along(tip) > along(a) && along(tip) > along(b));
+///     for [x, y] in [tip, a, b] {
+///         assert!((2.0..=3.0).contains(&x) && (3.0..=4.0).contains(&y));
+///     }
+/// }
+/// ```
+pub fn head_notch(cell: GridPos, heading: Direction) -> [[f32; 2]; 3] {
+    let (dx, dy) = heading.delta();
+    let length = ((dx * dx + dy * dy) as f32).sqrt();
+    let forward = [dx as f32 / length, dy as f32 / length];
+    let side = [-forward[1], forward[0]];
+    let middle = [cell.x as f32 + 0.5, cell.y as f32 + 0.5];
+    let at = |ahead: f32, across: f32| {
+        [
+            middle[0] + forward[0] * ahead + side[0] * across,
+            middle[1] + forward[1] * ahead + side[1] * across,
+        ]
+    };
+    [
+        at(NOTCH_TIP, 0.0),
+        at(-NOTCH_BACK, -NOTCH_HALF_WIDTH),
+        at(-NOTCH_BACK, NOTCH_HALF_WIDTH),
+    ]
+}
+
+impl GridPos {
+    /// The corners of the quad [`Mesh::push_cell`] covers the cell with, in the order a
+    /// [`MeshKind::Triangles`] mesh has them, placed in normalized device coordinates as if a
+    /// board `dims` cells big filled the whole of them. That is where a board drawn without
+    /// a [`BoardViewport`](crate::layout::BoardViewport) of its own puts the cell.
+    ///
+    /// ```
+    /// use snake_game::game::GridPos;
+    /// use snake_game::mesh::{Mesh, MeshKind, WHITE};
+    ///
+    /// // Cells a quarter of the way across and half of it down, which floats hold exactly.
+    /// let dims = [8, 4];
+    /// for y in 0..4 {
+    ///     for x in 0..8 {
+    ///         let quad = GridPos::new(x, y).to_cell_quad(dims, 0.0);
+    ///         let [left, top] = [x as f32 * 0.25 - 1.0, y as f32 * 0.5 - 1.0];
+    ///         let [right, bottom] = [left + 0.25, top + 0.5];
+    ///         let corners = [
+    ///             [left, top],
+    ///             [right, top],
+    ///             [right, bottom],
+    ///             [left, top],
+    ///             [right, bottom],
+    ///             [left, bottom],
+    ///         ];
+    ///         assert_eq!(quad.map(|vertex| vertex.position), corners);
+    ///     }
+    /// }
+    ///
+    /// // The last cell reaches the bottom right corner and no further.
+    /// let quad = GridPos::new(7, 3).to_cell_quad(dims, 0.0);
+    /// assert_eq!(quad[2].position, [1.0, 1.0]);
+    ///
+    /// // Inset, it is the same quad as the mesh gets, only placed on the screen.
+    /// let mut mesh = Mesh::new(MeshKind::Triangles);
+    /// mesh.push_cell(GridPos::new(7, 3), 0.25, WHITE);
+    /// let quad = GridPos::new(7, 3).to_cell_quad(dims, 0.25);
+    /// assert_eq!(quad[0].position, [0.8125, 0.625]);
+    /// assert_eq!(quad[2].position, [0.9375, 0.875]);
+    /// for (vertex, pushed) in quad.iter().zip(mesh.vertices()) {
+    ///     assert_eq!((vertex.local, vertex.color), (pushed.local, pushed.color));
+    /// }
+    /// ```
+    pub fn to_cell_quad(self, dims: [u32; 2], cell_inset: f32) -> [Vertex; 6] {
+        let mut mesh = Mesh::new(MeshKind::Triangles);
+        mesh.push_cell(self, cell_inset, WHITE);
+        let board = Projection::screen(dims);
+        let mut quad = [Vertex::new([0.0, 0.0]); 6];
+        for (corner, vertex) in quad.iter_mut().zip(mesh.vertices()) {
+            *corner = Vertex {
+                position: board.apply(vertex.position),
+                ..*vertex
+            };
+        }
+        quad
+    }
+}
+
+/// The lines along every edge of the cells of a board `size` cells wide and high, the borders
+/// of the board included, as the two ends of each line in turn, all in `color`: the columns
+/// from left to right, then the rows from top to bottom.
+///
+/// ```
+/// use snake_game::mesh::{grid_lines, WHITE};
+///
+/// let lines = grid_lines([3, 2], WHITE);
+/// // Four lines down between and around three columns, three across for two rows.
+/// assert_eq!(lines.len(), 2 * (4 + 3));
+/// assert_eq!(lines[0].position, [0.0, 0.0]);
+/// assert_eq!(lines[1].position, [0.0, 2.0]);
+/// assert_eq!(lines[6].position, [3.0, 0.0]);
+/// assert_eq!(lines[13].position, [3.0, 2.0]);
+/// assert!(lines.iter().all(|end| end.color == WHITE));
+/// ```
+pub fn grid_lines(size: [u32; 2], color: [f32; 4]) -> Vec<Vertex> {
+    let [width, height] = size.map(|cells| cells as f32);
+    let columns = (0..=size[0]).flat_map(|x| [[x as f32, 0.0], [x as f32, height]]);
+    let rows = (0..=size[1]).flat_map(|y| [[0.0, y as f32], [width, y as f32]]);
+    columns
+        .chain(rows)
+        .map(|position| Vertex::new(position).with_color(color))
+        .collect()
+}
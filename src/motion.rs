@@ -0,0 +1,142 @@
+use std::mem;
+
+use crate::game::{Game, GridPos};
+
+/// Where a segment of a snake that moved from `from` to `to` over a tick is drawn a fraction
+/// `t` of the way through the next one, as the top left corner of its quad, in cells.
+///
+/// Segments slide to a neighbouring cell, diagonal ones included. Anything further, wrapping
+/// around an edge of the board, going through a portal or coming back to life, snaps straight
+/// to where it went rather than sliding across the board.
+///
+/// ```
+/// use snake_game::game::GridPos;
+/// use snake_game::motion::slide;
+///
+/// // Straight to the right.
+/// let (from, to) = (GridPos::new(2, 3), GridPos::new(3, 3));
+/// assert_eq!(slide(from, to, 0.0), [2.0, 3.0]);
+/// assert_eq!(slide(from, to, 0.25), [2.25, 3.0]);
+/// assert_eq!(slide(from, to, 1.0), [3.0, 3.0]);
+///
+/// // Around from the right edge of a 10 cell wide board to the left one.
+/// assert_eq!(slide(GridPos::new(9, 3), GridPos::new(0, 3), 0.25), [0.0, 3.0]);
+/// ```
+pub fn slide(from: GridPos, to: GridPos, t: f32) -> [f32; 2] {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    if dx.abs() > 1 || dy.abs() > 1 {
+        return [to.x as f32, to.y as f32];
+    }
+    [from.x as f32 + dx as f32 * t, from.y as f32 + dy as f32 * t]
+}
+
+/// The indices of the cells of a snake where its body turns, given its `previous` cells before
+/// the last tick and its `current` ones, both from the head to the tail. The tail, which
+/// slides in from where it was, turns if it came from another way than the body goes.
+///
+/// Sliding along the path of the body, the segments on either side of a turn each go their
+/// own way, one along the cell before the turn, the other along the cell after it, leaving a
+/// gap on the inside of the corner. Drawing the cell of the turn where it is, under them,
+/// bends the body along an L instead.
+///
+/// ```
+/// use snake_game::game::GridPos;
+/// use snake_game::motion::{corners, slide};
+///
+/// // Heading right along the top, then turning down.
+/// let cells = |cells: &[(i32, i32)]| cells.iter().map(|&(x, y)| GridPos::new(x, y)).collect();
+/// let previous: Vec<GridPos> = cells(&[(1, 1), (0, 1)]);
+/// let current: Vec<GridPos> = cells(&[(1, 2), (1, 1)]);
+/// assert_eq!(corners(&previous, &current).collect::<Vec<_>>(), [1]);
+///
+/// // Halfway through, the head is halfway down and the tail halfway along, with the cell of
+/// // the turn joining them.
+/// assert_eq!(slide(previous[0], current[0], 0.5), [1.0, 1.5]);
+/// assert_eq!(slide(previous[1], current[1], 0.5), [0.5, 1.0]);
+///
+/// // Going straight, there is nothing to fill.
+/// let straight: Vec<GridPos> = cells(&[(2, 1), (1, 1)]);
+/// assert_eq!(corners(&previous, &straight).count(), 0);
+/// ```
+pub fn corners<'a>(
+    previous: &'a [GridPos],
+    current: &'a [GridPos],
+) -> impl Iterator<Item = usize> + 'a {
+    // The path the body slides along: where it is now, and then where its tail was.
+    let path = move |i: usize| current.get(i).or(previous.last()).copied();
+    (1..current.len()).filter(move |&i| {
+        let step = |from: Option<GridPos>, to: Option<GridPos>| {
+            from.zip(to)
+                .map(|(from, to)| (to.x - from.x, to.y - from.y))
+        };
+        let (behind, ahead) = (step(path(i + 1), path(i)), step(path(i), path(i - 1)));
+        behind.is_some_and(|behind| behind != (0, 0) && Some(behind) != ahead)
+    })
+}
+
+/// The cells of every snake before the latest tick of a game, and the ones they are on now,
+/// kept from frame to frame so that the snakes can be drawn sliding from one to the other.
+///
+/// ```
+/// use snake_game::game::Game;
+/// use snake_game::motion::Trails;
+///
+/// let mut game = Game::new(10, 10);
+/// let mut trails = Trails::new();
+/// trails.update(&game);
+/// // Before any tick, the snakes stay where they are.
+/// let start: Vec<_> = game.snake().cells().copied().collect();
+/// assert_eq!(trails.previous(0), Some(&start[..]));
+///
+/// game.step();
+/// trails.update(&game);
+/// assert_eq!(trails.previous(0), Some(&start[..]));
+/// assert_eq!(trails.previous(1), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Trails {
+    /// The tick the game was on at the last update, if there was one.
+    tick: Option<u64>,
+    previous: Vec<Vec<GridPos>>,
+    current: Vec<Vec<GridPos>>,
+}
+
+impl Trails {
+    pub fn new() -> Trails {
+        Trails::default()
+    }
+
+    /// Catches up with `game`, once a frame. A game that went back in time, such as a new
+    /// round or a rewound one, starts the snakes over from where they are.
+    pub fn update(&mut self, game: &Game) {
+        let ticks = game.ticks();
+        let players = game.players();
+        let moved = self.tick.is_some_and(|tick| ticks > tick);
+        let same = self.tick == Some(ticks) && self.current.len() == players.len();
+        if same {
+            return;
+        }
+        let cells = |i: usize| players[i].snake().cells().copied();
+        if moved && self.current.len() == players.len() {
+            mem::swap(&mut self.previous, &mut self.current);
+        } else {
+            self.previous.resize_with(players.len(), Vec::new);
+            for (i, previous) in self.previous.iter_mut().enumerate() {
+                previous.clear();
+                previous.extend(cells(i));
+            }
+        }
+        self.current.resize_with(players.len(), Vec::new);
+        for (i, current) in self.current.iter_mut().enumerate() {
+            current.clear();
+            current.extend(cells(i));
+        }
+        self.tick = Some(ticks);
+    }
+
+    /// The cells the snake of `player` was on before the latest tick, from the head to the
+    /// tail.
+    pub fn previous(&self, player: usize) -> Option<&[GridPos]> {
+        self.previous.get(player).map(Vec::as_slice)
+    }
+}
@@ -0,0 +1,501 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::game::{Direction, Game};
+
+/// Bumped whenever a message changes shape. The boards are sent as the whole [`Game`], so this
+/// goes up along with the version of the save format too.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The port hosting listens on when none is given.
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// The longest message either side accepts. Anything longer is taken as the other side
+/// talking something else than this protocol.
+pub const MAX_MESSAGE_LEN: usize = 1 << 24;
+
+/// How long joining waits for the host to answer, and the host for someone who connected to
+/// say hello, before giving up on them.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long sending a message can take before the other side is taken as gone. Sending happens
+/// on the window's thread, which must not hang on a guest that stopped reading.
+const SEND_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Everything said over a connection, each sent as its length in four bytes, big-endian, and
+/// then the message itself in bincode.
+#[derive(Serialize, Deserialize)]
+pub enum Message {
+    /// The first thing the guest says, with the [`PROTOCOL_VERSION`] it speaks.
+    Hello { version: u32 },
+    /// The host's answer to [`Message::Hello`], with the version it speaks. The host hangs up
+    /// right after it if the versions differ.
+    Welcome { version: u32 },
+    /// The guest asks for its snake to turn.
+    Turn(Direction),
+    /// The round as it stands, sent after every tick and whenever a new round starts.
+    Board(Box<Game>),
+    /// What the host is doing, sent whenever it changes.
+    Phase(Phase),
+}
+
+/// [`Message::Board`] as it is sent, borrowing the game since there is no copying one. It is
+/// written out the same way, so it reads back as a `Message::Board`.
+struct BoardMessage<'a>(&'a Game);
+
+/// Where `Board` is among the variants of [`Message`], which is all bincode writes of which
+/// one it is.
+const BOARD_VARIANT: u32 = 3;
+
+impl Serialize for BoardMessage<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_variant("Message", BOARD_VARIANT, "Board", self.0)
+    }
+}
+
+/// Where the host is at, for the guest to show.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Phase {
+    /// Not playing yet, or back from a round that was given up on.
+    Menu,
+    /// Counting down to the round, with this many seconds left, rounded up.
+    Countdown(u32),
+    Playing,
+    Paused,
+    GameOver,
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    /// A message that couldn't be read or written in bincode.
+    Format(bincode::Error),
+    /// A message longer than [`MAX_MESSAGE_LEN`].
+    TooLong(usize),
+    /// The other side speaks another version of the protocol.
+    Version {
+        theirs: u32,
+    },
+    /// The other side said something the protocol doesn't allow at this point.
+    Unexpected,
+    /// The other side hung up.
+    Closed,
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetError::Io(err) => write!(f, "connection failed: {}", err),
+            NetError::Format(err) => write!(f, "malformed message: {}", err),
+            NetError::TooLong(len) => write!(f, "message of {} bytes is too long", len),
+            NetError::Version { theirs } => write!(
+                f,
+                "the other side speaks protocol version {}, but this game only speaks version {}",
+                theirs, PROTOCOL_VERSION
+            ),
+            NetError::Unexpected => write!(f, "unexpected message"),
+            NetError::Closed => write!(f, "connection closed"),
+        }
+    }
+}
+
+impl Error for NetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NetError::Io(err) => Some(err),
+            NetError::Format(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    fn from(err: io::Error) -> NetError {
+        match err.kind() {
+            io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe => NetError::Closed,
+            _ => NetError::Io(err),
+        }
+    }
+}
+
+impl From<bincode::Error> for NetError {
+    fn from(err: bincode::Error) -> NetError {
+        NetError::Format(err)
+    }
+}
+
+/// Writes `message` to `writer`, framed so that [`read_message`] reads back exactly it.
+///
+/// ```
+/// use snake_game::game::Direction;
+/// use snake_game::net::{read_message, write_message, Message};
+///
+/// let mut wire = Vec::new();
+/// write_message(&mut wire, &Message::Turn(Direction::Left)).unwrap();
+/// write_message(&mut wire, &Message::Hello { version: 7 }).unwrap();
+/// // Each message is its length and then that many bytes.
+/// let len = u32::from_be_bytes([wire[0], wire[1], wire[2], wire[3]]) as usize;
+/// let second = &wire[4 + len..];
+/// let second_len = u32::from_be_bytes([second[0], second[1], second[2], second[3]]) as usize;
+/// assert_eq!(second.len(), 4 + second_len);
+///
+/// let mut reader = &wire[..];
+/// assert!(matches!(read_message(&mut reader), Ok(Message::Turn(Direction::Left))));
+/// assert!(matches!(read_message(&mut reader), Ok(Message::Hello { version: 7 })));
+/// assert!(reader.is_empty());
+/// ```
+pub fn write_message(writer: &mut impl Write, message: &Message) -> Result<(), NetError> {
+    write_frame(writer, bincode::serialize(message)?)
+}
+
+/// Writes `game` as a [`Message::Board`], without the copy of it the message would hold.
+///
+/// ```
+/// use snake_game::game::{Game, GameConfig};
+/// use snake_game::net::{read_message, write_board, write_message, Message};
+///
+/// // Games can't be cloned, but the same seed plays out the same.
+/// let play = || {
+///     let mut game = Game::with_config(GameConfig::new(16, 12).with_poison(3).with_seed(5));
+///     for _ in 0..5 {
+///         game.step();
+///     }
+///     game
+/// };
+///
+/// let game = play();
+/// let (mut borrowed, mut owned) = (Vec::new(), Vec::new());
+/// write_board(&mut borrowed, &game).unwrap();
+/// write_message(&mut owned, &Message::Board(Box::new(play()))).unwrap();
+/// assert_eq!(borrowed, owned);
+/// match read_message(&mut &borrowed[..]) {
+///     Ok(Message::Board(read)) => assert_eq!(read.ticks(), game.ticks()),
+///     _ => panic!("not read back as a board"),
+/// }
+/// ```
+pub fn write_board(writer: &mut impl Write, game: &Game) -> Result<(), NetError> {
+    write_frame(writer, bincode::serialize(&BoardMessage(game))?)
+}
+
+fn write_frame(writer: &mut impl Write, body: Vec<u8>) -> Result<(), NetError> {
+    if body.len() > MAX_MESSAGE_LEN {
+        return Err(NetError::TooLong(body.len()));
+    }
+    // Sent in one go, so that the length doesn't leave on its own.
+    let mut frame = Vec::with_capacity(4 + body.len());
+    frame.extend((body.len() as u32).to_be_bytes());
+    frame.extend(body);
+    writer.write_all(&frame)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the next message [`write_message`] wrote, waiting for all of it to come in.
+///
+/// The end of the input between two messages is [`NetError::Closed`], and so is one in the
+/// middle of a message. A length past [`MAX_MESSAGE_LEN`] is refused before anything is read
+/// into it.
+///
+/// ```
+/// use snake_game::game::Direction;
+/// use snake_game::net::{read_message, write_message, Message, NetError, MAX_MESSAGE_LEN};
+///
+/// let mut wire = Vec::new();
+/// write_message(&mut wire, &Message::Turn(Direction::Up)).unwrap();
+/// assert!(matches!(read_message(&mut &wire[..wire.len() - 1]), Err(NetError::Closed)));
+/// assert!(matches!(read_message(&mut &[][..]), Err(NetError::Closed)));
+///
+/// let huge = (MAX_MESSAGE_LEN as u32 + 1).to_be_bytes();
+/// assert!(matches!(read_message(&mut &huge[..]), Err(NetError::TooLong(_))));
+///
+/// // No message has a variant this far along.
+/// let garbage = [0, 0, 0, 4, 0xff, 0xff, 0xff, 0xff];
+/// assert!(matches!(read_message(&mut &garbage[..]), Err(NetError::Format(_))));
+/// ```
+pub fn read_message(reader: &mut impl Read) -> Result<Message, NetError> {
+    Ok(bincode::deserialize(&read_frame(reader)?)?)
+}
+
+/// The body of the next message, left as it came in.
+fn read_frame(reader: &mut impl Read) -> Result<Vec<u8>, NetError> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(NetError::TooLong(len));
+    }
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
+
+/// One end of a connection between the host and the guest, past the handshake.
+///
+/// Messages are read on a thread of their own, so that waiting for the other side never holds
+/// up the window: [`Connection::poll`] only hands out what has come in already. The thread
+/// only cuts them apart, as a [`Game`] can't leave the thread it is on, and they are read into
+/// messages by `poll`. Dropping the connection hangs up, which the other side sees as
+/// [`NetError::Closed`].
+///
+/// Over the loopback:
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snake_game::game::{Direction, Game, GameConfig};
+/// use snake_game::net::{Connection, Host, Message, NetError};
+///
+/// let host = Host::listen("127.0.0.1:0").unwrap();
+/// let mut guest = Connection::join(host.local_addr()).unwrap();
+/// let mut on_host = loop {
+///     match host.poll() {
+///         Some(connection) => break connection,
+///         None => std::thread::sleep(Duration::from_millis(10)),
+///     }
+/// };
+///
+/// guest.send(&Message::Turn(Direction::Down)).unwrap();
+/// guest.send(&Message::Turn(Direction::Left)).unwrap();
+/// let mut turns = Vec::new();
+/// while turns.len() < 2 {
+///     for message in on_host.poll().unwrap() {
+///         if let Message::Turn(direction) = message {
+///             turns.push(direction);
+///         }
+///     }
+/// }
+/// assert_eq!(turns, [Direction::Down, Direction::Left]);
+///
+/// let mut game = Game::with_config(GameConfig::default().with_seed(9));
+/// game.step();
+/// on_host.send_board(&game).unwrap();
+/// let board = loop {
+///     if let Some(Message::Board(board)) = guest.poll().unwrap().pop() {
+///         break board;
+///     }
+/// };
+/// assert_eq!((board.ticks(), board.seed()), (1, 9));
+///
+/// drop(guest);
+/// let lost = loop {
+///     if let Err(e) = on_host.poll() {
+///         break e;
+///     }
+/// };
+/// assert!(matches!(lost, NetError::Closed));
+/// ```
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Receiver<Result<Vec<u8>, NetError>>,
+    /// Why the connection was lost, once it was, kept until the messages that came in before
+    /// are handed out.
+    lost: Option<NetError>,
+    peer: SocketAddr,
+}
+
+impl Connection {
+    /// Connects to the host at `address` and says hello, waiting for the answer.
+    pub fn join(address: impl ToSocketAddrs) -> Result<Connection, NetError> {
+        let mut last_error = None;
+        for address in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, HANDSHAKE_TIMEOUT) {
+                Ok(mut stream) => {
+                    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+                    write_message(
+                        &mut stream,
+                        &Message::Hello {
+                            version: PROTOCOL_VERSION,
+                        },
+                    )?;
+                    return match read_message(&mut stream)? {
+                        Message::Welcome { version } if version == PROTOCOL_VERSION => {
+                            Connection::new(stream)
+                        }
+                        Message::Welcome { version } => Err(NetError::Version { theirs: version }),
+                        _ => Err(NetError::Unexpected),
+                    };
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(match last_error {
+            Some(e) => e.into(),
+            None => NetError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no address to connect to",
+            )),
+        })
+    }
+
+    /// Takes over `stream` once the handshake went through.
+    fn new(stream: TcpStream) -> Result<Connection, NetError> {
+        stream.set_read_timeout(None)?;
+        stream.set_write_timeout(Some(SEND_TIMEOUT))?;
+        // The messages are small and each of them is wanted right away.
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr()?;
+
+        let mut reader = stream.try_clone()?;
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || loop {
+            let frame = read_frame(&mut reader);
+            let failed = frame.is_err();
+            if sender.send(frame).is_err() || failed {
+                break;
+            }
+        });
+
+        Ok(Connection {
+            stream,
+            incoming,
+            lost: None,
+            peer,
+        })
+    }
+
+    /// Who is at the other end.
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), NetError> {
+        write_message(&mut self.stream, message)
+    }
+
+    /// Sends `game` as a [`Message::Board`].
+    pub fn send_board(&mut self, game: &Game) -> Result<(), NetError> {
+        write_board(&mut self.stream, game)
+    }
+
+    /// Every message that came in since the last call, oldest first, without waiting for more.
+    /// Fails once the connection is lost, after handing out what came in before that.
+    pub fn poll(&mut self) -> Result<Vec<Message>, NetError> {
+        let mut messages = Vec::new();
+        while self.lost.is_none() {
+            match self.incoming.try_recv() {
+                Ok(Ok(frame)) => match bincode::deserialize(&frame) {
+                    Ok(message) => messages.push(message),
+                    Err(e) => self.lost = Some(e.into()),
+                },
+                Ok(Err(e)) => self.lost = Some(e),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.lost = Some(NetError::Closed),
+            }
+        }
+        match self.lost.take() {
+            Some(e) if messages.is_empty() => Err(e),
+            lost => {
+                self.lost = lost;
+                Ok(messages)
+            }
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // Wakes the reading thread up too, so that it ends.
+        self.stream.shutdown(std::net::Shutdown::Both).ok();
+    }
+}
+
+/// Two windows playing the same round over the local network: the host runs the game and
+/// steers the first snake, the guest steers the second one. The guest sends its turns to the
+/// host, which sends back the board after every tick for the guest to draw. The turns reach
+/// the host between two ticks like its own keys do, so they count from the next tick on.
+///
+/// This waits for the guest to join. Whoever connects is greeted on a thread of its own, so
+/// that neither the window nor anyone else connecting waits while they do, and handed out by
+/// [`Host::poll`] once the versions are found to match.
+///
+/// Someone who connects and never says hello holds up nobody else:
+///
+/// ```
+/// use std::net::TcpStream;
+/// use std::time::{Duration, Instant};
+///
+/// use snake_game::net::{Connection, Host};
+///
+/// let host = Host::listen("127.0.0.1:0").unwrap();
+/// let _silent = TcpStream::connect(host.local_addr()).unwrap();
+/// let started = Instant::now();
+/// let _guest = Connection::join(host.local_addr()).unwrap();
+/// while host.poll().is_none() {
+///     std::thread::sleep(Duration::from_millis(10));
+/// }
+/// // Well before the silent one's handshake times out.
+/// assert!(started.elapsed() < Duration::from_secs(2));
+/// ```
+pub struct Host {
+    address: SocketAddr,
+    joined: Receiver<Connection>,
+}
+
+impl Host {
+    /// Starts listening on `address`. It goes on until the program ends.
+    pub fn listen(address: impl ToSocketAddrs) -> io::Result<Host> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let (sender, joined) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        eprintln!("Failed to accept a connection: {}", e);
+                        continue;
+                    }
+                };
+                let sender = sender.clone();
+                thread::spawn(move || match greet(stream) {
+                    Ok(connection) => {
+                        // Nobody takes it once the host is gone, and dropping it hangs up.
+                        sender.send(connection).ok();
+                    }
+                    Err(e) => eprintln!("A player failed to join: {}", e),
+                });
+            }
+        });
+        Ok(Host { address, joined })
+    }
+
+    /// Where guests connect to, with the port picked if listening on port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// The next guest that joined, if any did since the last call.
+    pub fn poll(&self) -> Option<Connection> {
+        self.joined.try_recv().ok()
+    }
+}
+
+/// The host's side of the handshake with someone who just connected.
+fn greet(mut stream: TcpStream) -> Result<Connection, NetError> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_write_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    let version = match read_message(&mut stream)? {
+        Message::Hello { version } => version,
+        _ => return Err(NetError::Unexpected),
+    };
+    write_message(
+        &mut stream,
+        &Message::Welcome {
+            version: PROTOCOL_VERSION,
+        },
+    )?;
+    if version != PROTOCOL_VERSION {
+        return Err(NetError::Version { theirs: version });
+    }
+    Connection::new(stream)
+}
@@ -0,0 +1,423 @@
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Effect, FoodKind};
+use crate::mesh::QuadKind;
+
+/// How much of a cell is left empty along each of its edges, so that the segments of a snake
+/// can be told apart.
+const CELL_INSET: f32 = 0.04;
+
+/// Poison, power-ups and golden food are drawn smaller than the rest in the palettes that
+/// don't lean on hue alone, so they can be told apart by their shape too.
+const POISON_INSET: f32 = 0.28;
+const POWER_UP_INSET: f32 = 0.16;
+const GOLDEN_INSET: f32 = 0.1;
+
+/// How far the head of a snake is lightened towards white, and how much further it spills over
+/// its cell than the rest of the snake, so the head stands out.
+const HEAD_LIGHTEN: f32 = 0.35;
+const HEAD_GROWTH: f32 = 0.04;
+
+/// Laid over the head of a snake that ran into something in zen mode, spilling a little over
+/// the edges of its cell.
+const BUMP_INSET: f32 = -0.08;
+
+/// How far in from the sides of a cell its rounded corners start, as a share of its side.
+const CORNER_RADIUS: f32 = 0.3;
+
+/// How wide the edge of a soft cell fades out over, as a share of its side.
+const SOFT_EDGE: f32 = 0.1;
+
+/// Something drawn on the board, which the palette picks a look for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    /// The snake of the player at this index, still in the round.
+    Snake(usize),
+    /// A snake that is out of the round, or every snake once the round is over.
+    DeadSnake,
+    /// A snake that filled the board.
+    WonSnake,
+    /// The snakes of the best round so far, drawn see-through under everything else.
+    Ghost,
+    Obstacle,
+    Portal,
+    /// Drawn faintly, under anything that is on the same cell.
+    Checkpoint,
+    Food(FoodKind),
+    Poison,
+    PowerUp(Effect),
+    Chaser,
+    /// Laid over the head of a snake that is blocked in zen mode.
+    Bump,
+    /// The bars counting down the seconds before the game starts ticking.
+    Countdown,
+    /// Laid over the cell under the mouse in the level editor.
+    Cursor,
+    /// Written over the window, such as the score.
+    Text,
+    /// The lines between the cells, when they are shown.
+    Grid,
+}
+
+/// The color of a tile, and how much of a cell it leaves empty along each edge. A negative
+/// inset spills over the edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Look {
+    pub color: [f32; 4],
+    pub inset: f32,
+}
+
+impl Look {
+    /// How the head of a snake that looks like this is drawn: lighter, and a little bigger.
+    ///
+    /// ```
+    /// use snake_game::palette::{Palette, Tile};
+    ///
+    /// let body = Palette::Default.look(Tile::Snake(0));
+    /// let head = body.head();
+    /// assert!(head.inset < body.inset);
+    /// assert!((0..3).all(|i| head.color[i] > body.color[i]));
+    /// assert_eq!(head.color[3], body.color[3]);
+    /// ```
+    pub fn head(self) -> Look {
+        let [r, g, b, a] = self.color;
+        let lighten = |c: f32| c + (1.0 - c) * HEAD_LIGHTEN;
+        Look {
+            color: [lighten(r), lighten(g), lighten(b), a],
+            inset: self.inset - HEAD_GROWTH,
+        }
+    }
+}
+
+/// The colors and shapes everything on the board is drawn with. Every color the window shows
+/// comes from here, so that a palette covers the whole board whatever gets drawn on it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Default,
+    /// Keeps clear of telling things apart by red against green, and gives the things that
+    /// could still be mixed up shapes of their own.
+    Deuteranopia,
+    /// Bright colors on a black background, with the same shapes as the deuteranopia palette.
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [
+        Palette::Default,
+        Palette::Deuteranopia,
+        Palette::HighContrast,
+    ];
+
+    /// The names the palettes go by on the command line, in the order of [`Palette::ALL`].
+    pub const NAMES: [&'static str; 3] = ["default", "deuteranopia", "high-contrast"];
+
+    /// The palette called `name`, one of the [`Palette::NAMES`].
+    pub fn named(name: &str) -> Option<Palette> {
+        Palette::NAMES
+            .iter()
+            .position(|&other| other == name)
+            .map(|i| Palette::ALL[i])
+    }
+
+    pub fn name(self) -> &'static str {
+        Palette::NAMES[self as usize]
+    }
+
+    /// The palette after this one, going back to the first after the last.
+    pub fn next(self) -> Palette {
+        Palette::ALL[(self as usize + 1) % Palette::ALL.len()]
+    }
+
+    /// How `tile` is drawn.
+    pub fn look(self, tile: Tile) -> Look {
+        let color = self.color(tile);
+        let inset = match (self, tile) {
+            (_, Tile::Bump | Tile::Cursor) => BUMP_INSET,
+            (Palette::Default, _) => CELL_INSET,
+            (_, Tile::Poison) => POISON_INSET,
+            (_, Tile::PowerUp(_)) => POWER_UP_INSET,
+            (_, Tile::Food(FoodKind::Golden)) => GOLDEN_INSET,
+            (_, Tile::Obstacle) => 0.0,
+            _ => CELL_INSET,
+        };
+        Look { color, inset }
+    }
+
+    fn color(self, tile: Tile) -> [f32; 4] {
+        match self {
+            Palette::Default => default_color(tile),
+            Palette::Deuteranopia => deuteranopia_color(tile),
+            Palette::HighContrast => high_contrast_color(tile),
+        }
+    }
+
+    /// What the board is cleared to.
+    pub fn background(self) -> [f32; 4] {
+        match self {
+            Palette::Default | Palette::Deuteranopia => [0.1, 0.1, 0.1, 1.0],
+            Palette::HighContrast => [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+
+    /// What the background flashes to when a snake dies, fading back from it.
+    pub fn death_flash(self) -> [f32; 4] {
+        match self {
+            Palette::Default => [0.45, 0.04, 0.04, 1.0],
+            Palette::Deuteranopia => [0.45, 0.15, 0.0, 1.0],
+            Palette::HighContrast => [0.6, 0.0, 0.0, 1.0],
+        }
+    }
+
+    /// What the background flashes to behind a snake that filled the board.
+    pub fn won_flash(self) -> [f32; 4] {
+        match self {
+            Palette::Default => [0.1, 0.35, 0.15, 1.0],
+            Palette::Deuteranopia => [0.05, 0.25, 0.4, 1.0],
+            Palette::HighContrast => [0.35, 0.35, 0.35, 1.0],
+        }
+    }
+}
+
+/// The shape the cells are drawn in, cut out of their quads by the fragment shaders. Food is
+/// drawn round in any of them but the sharp one.
+///
+/// ```
+/// use snake_game::mesh::{QuadKind, UNSHAPED};
+/// use snake_game::palette::CellShape;
+///
+/// let (corner, edge, diagonal) = ([0.02, 0.02], [0.5, 0.01], [0.12, 0.12]);
+/// // Sharp cells are the whole of their quads, as for pixel art.
+/// for local in [corner, edge, diagonal] {
+///     assert_eq!(CellShape::Sharp.coverage(local, QuadKind::Food), 1.0);
+/// }
+///
+/// // Rounded ones lose their corners, but not the middle of their sides...
+/// assert_eq!(CellShape::Rounded.coverage(corner, QuadKind::Plain), 0.0);
+/// assert_eq!(CellShape::Rounded.coverage(edge, QuadKind::Plain), 1.0);
+/// assert_eq!(CellShape::Rounded.coverage(diagonal, QuadKind::Plain), 1.0);
+/// // ...and food is round, losing more of them.
+/// assert_eq!(CellShape::Rounded.coverage(diagonal, QuadKind::Food), 0.0);
+///
+/// // Soft ones fade out towards their edges rather than stopping short.
+/// let fading = CellShape::Soft.coverage([0.5, 0.03], QuadKind::Plain);
+/// assert!(0.0 < fading && fading < 1.0);
+///
+/// // Quads that aren't cells are never cut.
+/// for shape in CellShape::ALL {
+///     assert_eq!(shape.coverage(UNSHAPED, QuadKind::Food), 1.0);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CellShape {
+    /// Square cells with hard edges, the look of pixel art.
+    #[default]
+    Sharp,
+    /// Cells with their corners rounded off, and hard edges.
+    Rounded,
+    /// Rounded cells whose edges fade out, blending into what is under them.
+    Soft,
+}
+
+impl CellShape {
+    pub const ALL: [CellShape; 3] = [CellShape::Sharp, CellShape::Rounded, CellShape::Soft];
+
+    /// The names the shapes go by on the command line, in the order of [`CellShape::ALL`].
+    pub const NAMES: [&'static str; 3] = ["sharp", "rounded", "soft"];
+
+    /// The shape called `name`, one of the [`CellShape::NAMES`].
+    pub fn named(name: &str) -> Option<CellShape> {
+        CellShape::NAMES
+            .iter()
+            .position(|&other| other == name)
+            .map(|i| CellShape::ALL[i])
+    }
+
+    pub fn name(self) -> &'static str {
+        CellShape::NAMES[self as usize]
+    }
+
+    /// The shape after this one, going back to the first after the last.
+    pub fn next(self) -> CellShape {
+        CellShape::ALL[(self as usize + 1) % CellShape::ALL.len()]
+    }
+
+    /// How far in from the sides of a quad of `kind` its rounded corners start, as a share of
+    /// its side: 0 for square corners, and a half for a circle. The renderer pushes the one of
+    /// [`QuadKind::Plain`], and the vertex shaders make food round from there.
+    pub fn corner_radius(self, kind: QuadKind) -> f32 {
+        match (self, kind) {
+            (CellShape::Sharp, _) => 0.0,
+            (_, QuadKind::Food) => 0.5,
+            (_, QuadKind::Plain) => CORNER_RADIUS,
+        }
+    }
+
+    /// How wide the edge of a cell fades out over, as a share of its side, or 0 for a hard
+    /// edge.
+    pub fn softness(self) -> f32 {
+        match self {
+            CellShape::Sharp | CellShape::Rounded => 0.0,
+            CellShape::Soft => SOFT_EDGE,
+        }
+    }
+
+    /// How much of a quad of `kind` is drawn at `local`, where it is in its cell from `(0, 0)`
+    /// at its top left corner to `(1, 1)` at its bottom right: 1 inside the shape, 0 outside
+    /// it, and in between over a soft edge. The same as `coverage` in the fragment shaders,
+    /// which multiply the alpha of the quad by it.
+    pub fn coverage(self, local: [f32; 2], kind: QuadKind) -> f32 {
+        let radius = self.corner_radius(kind);
+        // How far `local` is outside the rounded square, negative inside it.
+        let [x, y] = local.map(|c| (c - 0.5).abs() - (0.5 - radius));
+        let outside = x.max(0.0).hypot(y.max(0.0)) + x.max(y).min(0.0) - radius;
+        let softness = self.softness();
+        if softness > 0.0 {
+            (-outside / softness).clamp(0.0, 1.0)
+        } else if outside <= 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// `color`, given in linear light the way the palettes give their colors, encoded for an
+/// image that shows its values as sRGB as they are. Images with an sRGB format encode what is
+/// drawn into them on their own, so this is only for the ones without. Alpha is left alone.
+///
+/// ```
+/// use snake_game::palette::linear_to_srgb;
+///
+/// let [black, white, _, alpha] = linear_to_srgb([0.0, 1.0, 0.0, 0.5]);
+/// assert_eq!((black, alpha), (0.0, 0.5));
+/// assert!((white - 1.0).abs() < 0.0001);
+/// let [grey, ..] = linear_to_srgb([0.2, 0.2, 0.2, 1.0]);
+/// assert!((grey - 0.4845).abs() < 0.001);
+/// // The darkest shades are on a straight line.
+/// let [dark, ..] = linear_to_srgb([0.001, 0.0, 0.0, 1.0]);
+/// assert!((dark - 0.01292).abs() < 0.00001);
+/// ```
+pub fn linear_to_srgb(color: [f32; 4]) -> [f32; 4] {
+    let encode = |c: f32| {
+        if c <= 0.003_130_8 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        }
+    };
+    [
+        encode(color[0]),
+        encode(color[1]),
+        encode(color[2]),
+        color[3],
+    ]
+}
+
+/// Blends `amount` of `b` into `a`.
+pub(crate) fn mix(a: [f32; 4], b: [f32; 4], amount: f32) -> [f32; 4] {
+    let mut mixed = a;
+    for (channel, target) in mixed.iter_mut().zip(b) {
+        *channel += (target - *channel) * amount;
+    }
+    mixed
+}
+
+fn default_color(tile: Tile) -> [f32; 4] {
+    const SNAKES: [[f32; 4]; 4] = [
+        [0.2, 0.8, 0.3, 1.0],
+        [0.2, 0.5, 0.9, 1.0],
+        [0.9, 0.8, 0.2, 1.0],
+        [0.7, 0.3, 0.8, 1.0],
+    ];
+    match tile {
+        Tile::Snake(i) => SNAKES[i % SNAKES.len()],
+        Tile::DeadSnake => [0.5, 0.5, 0.5, 1.0],
+        Tile::WonSnake => [0.3, 1.0, 0.4, 1.0],
+        Tile::Ghost => [0.8, 0.8, 0.8, 0.3],
+        Tile::Obstacle => [0.35, 0.3, 0.25, 1.0],
+        Tile::Portal => [0.3, 0.4, 1.0, 1.0],
+        Tile::Checkpoint => [0.3, 0.8, 0.7, 0.35],
+        Tile::Food(FoodKind::Normal) => [0.9, 0.2, 0.2, 1.0],
+        Tile::Food(FoodKind::Golden) => [1.0, 0.75, 0.1, 1.0],
+        Tile::Food(FoodKind::Mega) => [0.95, 0.45, 0.1, 1.0],
+        Tile::Poison => [0.9, 0.2, 0.7, 1.0],
+        Tile::PowerUp(Effect::SpeedBoost) => [0.2, 0.9, 0.9, 1.0],
+        Tile::PowerUp(Effect::SlowDown) => [0.6, 0.3, 0.9, 1.0],
+        Tile::PowerUp(Effect::Invincible) => [1.0, 1.0, 1.0, 1.0],
+        Tile::PowerUp(Effect::ReversedControls) => [0.7, 0.6, 0.1, 1.0],
+        Tile::Chaser => [0.6, 0.05, 0.1, 1.0],
+        Tile::Bump => [1.0, 1.0, 1.0, 0.35],
+        Tile::Countdown => [0.9, 0.9, 0.9, 0.8],
+        Tile::Cursor => [1.0, 1.0, 1.0, 0.25],
+        Tile::Text => [0.9, 0.9, 0.9, 0.9],
+        Tile::Grid => [1.0, 1.0, 1.0, 0.08],
+    }
+}
+
+/// Built on blues against oranges and yellows, which deuteranopia leaves apart, with lightness
+/// telling apart the rest.
+fn deuteranopia_color(tile: Tile) -> [f32; 4] {
+    const SNAKES: [[f32; 4]; 4] = [
+        [0.35, 0.7, 0.9, 1.0],
+        [0.95, 0.9, 0.25, 1.0],
+        [0.8, 0.6, 0.7, 1.0],
+        [0.0, 0.45, 0.7, 1.0],
+    ];
+    match tile {
+        Tile::Snake(i) => SNAKES[i % SNAKES.len()],
+        Tile::DeadSnake => [0.45, 0.45, 0.45, 1.0],
+        Tile::WonSnake => [0.6, 0.85, 1.0, 1.0],
+        Tile::Ghost => [0.8, 0.8, 0.8, 0.3],
+        Tile::Obstacle => [0.3, 0.3, 0.35, 1.0],
+        Tile::Portal => [0.0, 0.3, 0.9, 1.0],
+        Tile::Checkpoint => [0.6, 0.85, 1.0, 0.35],
+        Tile::Food(FoodKind::Normal) => [0.9, 0.6, 0.0, 1.0],
+        Tile::Food(FoodKind::Golden) => [1.0, 0.95, 0.5, 1.0],
+        Tile::Food(FoodKind::Mega) => [0.8, 0.4, 0.0, 1.0],
+        Tile::Poison => [0.55, 0.3, 0.65, 1.0],
+        Tile::PowerUp(Effect::SpeedBoost) => [0.6, 0.85, 1.0, 1.0],
+        Tile::PowerUp(Effect::SlowDown) => [0.3, 0.35, 0.8, 1.0],
+        Tile::PowerUp(Effect::Invincible) => [1.0, 1.0, 1.0, 1.0],
+        Tile::PowerUp(Effect::ReversedControls) => [0.6, 0.5, 0.2, 1.0],
+        Tile::Chaser => [0.5, 0.25, 0.0, 1.0],
+        Tile::Bump => [1.0, 1.0, 1.0, 0.35],
+        Tile::Countdown => [0.9, 0.9, 0.9, 0.8],
+        Tile::Cursor => [1.0, 1.0, 1.0, 0.25],
+        Tile::Text => [0.9, 0.9, 0.9, 0.9],
+        Tile::Grid => [1.0, 1.0, 1.0, 0.08],
+    }
+}
+
+/// Saturated colors, far apart in lightness, on a black background.
+fn high_contrast_color(tile: Tile) -> [f32; 4] {
+    const SNAKES: [[f32; 4]; 4] = [
+        [1.0, 1.0, 1.0, 1.0],
+        [0.0, 0.9, 1.0, 1.0],
+        [1.0, 1.0, 0.0, 1.0],
+        [1.0, 0.5, 1.0, 1.0],
+    ];
+    match tile {
+        Tile::Snake(i) => SNAKES[i % SNAKES.len()],
+        Tile::DeadSnake => [0.4, 0.4, 0.4, 1.0],
+        Tile::WonSnake => [0.0, 1.0, 0.0, 1.0],
+        Tile::Ghost => [1.0, 1.0, 1.0, 0.25],
+        Tile::Obstacle => [0.55, 0.55, 0.55, 1.0],
+        Tile::Portal => [0.2, 0.4, 1.0, 1.0],
+        Tile::Checkpoint => [1.0, 1.0, 1.0, 0.3],
+        Tile::Food(FoodKind::Normal) => [1.0, 0.1, 0.1, 1.0],
+        Tile::Food(FoodKind::Golden) => [1.0, 0.85, 0.0, 1.0],
+        Tile::Food(FoodKind::Mega) => [1.0, 0.5, 0.0, 1.0],
+        Tile::Poison => [0.8, 0.0, 1.0, 1.0],
+        Tile::PowerUp(Effect::SpeedBoost) => [0.0, 1.0, 1.0, 1.0],
+        Tile::PowerUp(Effect::SlowDown) => [0.5, 0.5, 1.0, 1.0],
+        Tile::PowerUp(Effect::Invincible) => [1.0, 1.0, 1.0, 1.0],
+        Tile::PowerUp(Effect::ReversedControls) => [1.0, 0.7, 0.3, 1.0],
+        Tile::Chaser => [1.0, 0.0, 0.4, 1.0],
+        Tile::Bump => [1.0, 1.0, 1.0, 0.5],
+        Tile::Countdown => [1.0, 1.0, 1.0, 0.9],
+        Tile::Cursor => [1.0, 1.0, 1.0, 0.4],
+        Tile::Text => [1.0, 1.0, 1.0, 1.0],
+        Tile::Grid => [1.0, 1.0, 1.0, 0.2],
+    }
+}
@@ -0,0 +1,199 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use crate::game::{GridPos, Rng};
+use crate::mesh::Mesh;
+
+/// How long a particle flies before it is gone.
+pub const PARTICLE_LIFETIME: Duration = Duration::from_millis(400);
+
+/// The most particles there are at once. Bursts past it are cut short, so a system never
+/// needs more room than it starts with.
+pub const MAX_PARTICLES: usize = 256;
+
+/// How many particles a burst has at the least and at the most.
+pub const BURST_MIN: usize = 20;
+pub const BURST_MAX: usize = 40;
+
+/// How wide a particle is as it starts out, in cells. It shrinks to nothing over its life.
+pub const PARTICLE_SIZE: f32 = 0.2;
+
+/// How fast particles fly out, at the slowest and the fastest, in cells a second.
+const MIN_SPEED: f32 = 1.5;
+const MAX_SPEED: f32 = 4.0;
+
+/// A speck flying out of a burst, in a straight line, shrinking and fading as it goes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Particle {
+    /// Where its middle is, in cells like everything on the board.
+    position: [f32; 2],
+    /// How far it flies a second, in cells.
+    velocity: [f32; 2],
+    /// The color it starts out in.
+    color: [f32; 4],
+    age: Duration,
+}
+
+impl Particle {
+    pub fn position(&self) -> [f32; 2] {
+        self.position
+    }
+
+    /// How far through its life it is, from 0 as it starts to 1 as it is gone.
+    pub fn progress(&self) -> f32 {
+        (self.age.as_secs_f32() / PARTICLE_LIFETIME.as_secs_f32()).min(1.0)
+    }
+
+    /// How wide it is now, in cells.
+    pub fn size(&self) -> f32 {
+        PARTICLE_SIZE * (1.0 - self.progress())
+    }
+
+    /// The color it is drawn in now, fading out along with its alpha.
+    pub fn color(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.color;
+        [r, g, b, a * (1.0 - self.progress())]
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.age < PARTICLE_LIFETIME
+    }
+}
+
+/// The particles bursting out of whatever was just eaten, moved along with the time frames
+/// take rather than with the ticks of the game, so they fly the same however fast it goes.
+///
+/// Particles live in a pool made as big as [`MAX_PARTICLES`] up front, and the ones that are
+/// gone are taken out of it in place, so bursts never allocate.
+///
+/// ```
+/// use snake_game::game::GridPos;
+/// use snake_game::mesh::{Mesh, MeshKind};
+/// use snake_game::particles::{
+///     ParticleSystem, BURST_MAX, BURST_MIN, MAX_PARTICLES, PARTICLE_LIFETIME, PARTICLE_SIZE,
+/// };
+///
+/// let red = [1.0, 0.2, 0.2, 1.0];
+/// let mut particles = ParticleSystem::new();
+/// particles.burst(GridPos::new(3, 2), red);
+/// let spawned = particles.len();
+/// assert!((BURST_MIN..=BURST_MAX).contains(&spawned));
+/// // They start out in the middle of the cell, as big and as bright as they get.
+/// for particle in particles.particles() {
+///     assert_eq!(particle.position(), [3.5, 2.5]);
+///     assert_eq!((particle.size(), particle.color()), (PARTICLE_SIZE, red));
+/// }
+///
+/// // Halfway through their lives they are all still there, further out, smaller and fainter.
+/// particles.update(PARTICLE_LIFETIME / 2);
+/// assert_eq!(particles.len(), spawned);
+/// for particle in particles.particles() {
+///     assert_ne!(particle.position(), [3.5, 2.5]);
+///     assert!(particle.size() < PARTICLE_SIZE);
+///     assert!(particle.color()[3] < red[3]);
+/// }
+///
+/// // Each is drawn as a quad of its own.
+/// let mut mesh = Mesh::new(MeshKind::Instanced);
+/// particles.push_to(&mut mesh);
+/// assert_eq!(mesh.len() as usize, spawned);
+///
+/// // Once their time is up, they are gone.
+/// particles.update(PARTICLE_LIFETIME / 2);
+/// assert!(particles.is_empty());
+///
+/// // However many bursts there are, there are never more particles than the pool holds, and
+/// // it never grows.
+/// let capacity = particles.capacity();
+/// for x in 0..100 {
+///     particles.burst(GridPos::new(x, 0), red);
+/// }
+/// assert_eq!(particles.len(), MAX_PARTICLES);
+/// assert_eq!(particles.capacity(), capacity);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParticleSystem {
+    /// The particles still alive, in no particular order.
+    particles: Vec<Particle>,
+    rng: Rng,
+}
+
+impl ParticleSystem {
+    /// A system with no particles yet, and room for [`MAX_PARTICLES`] of them.
+    pub fn new() -> ParticleSystem {
+        ParticleSystem {
+            particles: Vec::with_capacity(MAX_PARTICLES),
+            rng: Rng::from_time(),
+        }
+    }
+
+    /// Bursts [`BURST_MIN`] to [`BURST_MAX`] particles in `color` out of the middle of `cell`,
+    /// flying every way. Only as many as there is room for are let out.
+    pub fn burst(&mut self, cell: GridPos, color: [f32; 4]) {
+        let count = BURST_MIN + self.rng.below(BURST_MAX - BURST_MIN + 1);
+        let room = MAX_PARTICLES - self.particles.len();
+        for _ in 0..count.min(room) {
+            let angle = self.unit() * TAU;
+            let speed = MIN_SPEED + self.unit() * (MAX_SPEED - MIN_SPEED);
+            self.particles.push(Particle {
+                position: [cell.x as f32 + 0.5, cell.y as f32 + 0.5],
+                velocity: [angle.cos() * speed, angle.sin() * speed],
+                color,
+                age: Duration::ZERO,
+            });
+        }
+    }
+
+    /// Moves every particle on by `elapsed`, taking out the ones whose time is up.
+    pub fn update(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f32();
+        for particle in &mut self.particles {
+            particle.age += elapsed;
+            particle.position[0] += particle.velocity[0] * seconds;
+            particle.position[1] += particle.velocity[1] * seconds;
+        }
+        self.particles.retain(Particle::is_alive);
+    }
+
+    /// Adds a quad to `mesh` for every particle, as big and in the color it is now, after
+    /// whatever is in it already.
+    pub fn push_to(&self, mesh: &mut Mesh) {
+        for particle in &self.particles {
+            let [x, y] = particle.position;
+            let half = particle.size() / 2.0;
+            let (left, top, right, bottom) = (x - half, y - half, x + half, y + half);
+            mesh.push_quad(
+                [[left, top], [right, top], [right, bottom], [left, bottom]],
+                particle.color(),
+            );
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// How many particles the pool has room for, which stays what it started at.
+    pub fn capacity(&self) -> usize {
+        self.particles.capacity()
+    }
+
+    /// A number from 0 up to, but not including, 1.
+    fn unit(&mut self) -> f32 {
+        (self.rng.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+impl Default for ParticleSystem {
+    fn default() -> ParticleSystem {
+        ParticleSystem::new()
+    }
+}
@@ -0,0 +1,180 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The start of what a Vulkan pipeline cache saves: which device and driver made it. A cache
+/// is only any use to the same ones, and some drivers don't take well to being handed one
+/// that isn't theirs, so it is checked before the cache is.
+///
+/// ```
+/// use snake_game::pipeline_cache::CacheHeader;
+///
+/// let header = CacheHeader {
+///     vendor_id: 0x10de,
+///     device_id: 0x2484,
+///     uuid: [7; 16],
+/// };
+/// let mut data = header.to_bytes().to_vec();
+/// data.extend_from_slice(b"whatever the driver keeps");
+/// assert_eq!(CacheHeader::parse(&data), Some(header));
+///
+/// // Another version of the header, or one cut short, isn't one.
+/// assert_eq!(CacheHeader::parse(&data[..CacheHeader::LEN - 1]), None);
+/// data[4] = 2;
+/// assert_eq!(CacheHeader::parse(&data), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CacheHeader {
+    pub vendor_id: u32,
+    pub device_id: u32,
+    /// Tells the builds of the driver apart, along with whatever else changes what it caches.
+    pub uuid: [u8; 16],
+}
+
+impl CacheHeader {
+    /// How many bytes the header takes, the only length it has in its only version.
+    pub const LEN: usize = 32;
+
+    /// The version of the header, the only one there is.
+    const VERSION: u32 = 1;
+
+    /// The header at the start of `data`, as Vulkan writes it, if there is one.
+    pub fn parse(data: &[u8]) -> Option<CacheHeader> {
+        let data = data.get(..CacheHeader::LEN)?;
+        let word = |i: usize| u32::from_le_bytes(data[i * 4..i * 4 + 4].try_into().unwrap());
+        if word(0) as usize != CacheHeader::LEN || word(1) != CacheHeader::VERSION {
+            return None;
+        }
+        Some(CacheHeader {
+            vendor_id: word(2),
+            device_id: word(3),
+            uuid: data[16..].try_into().unwrap(),
+        })
+    }
+
+    /// The header the way Vulkan writes it: its length, its version, then the fields, with the
+    /// least significant byte of each number first.
+    pub fn to_bytes(&self) -> [u8; CacheHeader::LEN] {
+        let mut bytes = [0; CacheHeader::LEN];
+        let words = [
+            CacheHeader::LEN as u32,
+            CacheHeader::VERSION,
+            self.vendor_id,
+            self.device_id,
+        ];
+        for (i, word) in words.into_iter().enumerate() {
+            bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        bytes[16..].copy_from_slice(&self.uuid);
+        bytes
+    }
+}
+
+/// Why a saved pipeline cache isn't used, starting with an empty one instead.
+#[derive(Debug)]
+pub enum CacheError {
+    /// It couldn't be read, or there is none yet.
+    Io(io::Error),
+    /// It doesn't start with a header.
+    Corrupt,
+    /// Another device or driver made it.
+    OtherDevice,
+}
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheError::Io(err) => write!(f, "{}", err),
+            CacheError::Corrupt => write!(f, "it is corrupt"),
+            CacheError::OtherDevice => write!(f, "another GPU or driver made it"),
+        }
+    }
+}
+
+impl Error for CacheError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CacheError::Io(err) => Some(err),
+            CacheError::Corrupt | CacheError::OtherDevice => None,
+        }
+    }
+}
+
+impl From<io::Error> for CacheError {
+    fn from(err: io::Error) -> CacheError {
+        CacheError::Io(err)
+    }
+}
+
+/// The file the pipeline cache is kept in from one launch to the next, so the pipelines are
+/// built faster after the first.
+///
+/// ```
+/// use snake_game::pipeline_cache::{CacheError, CacheFile, CacheHeader};
+///
+/// let dir = std::env::temp_dir().join(format!("snake-pipeline-cache-{}", std::process::id()));
+/// let file = CacheFile::at(dir.join("pipelines.bin"));
+/// let header = CacheHeader {
+///     vendor_id: 1,
+///     device_id: 2,
+///     uuid: [3; 16],
+/// };
+/// assert!(matches!(file.load(&header), Err(CacheError::Io(_))));
+///
+/// let mut data = header.to_bytes().to_vec();
+/// data.extend_from_slice(&[4, 5, 6]);
+/// file.save(&data).unwrap();
+/// assert_eq!(file.load(&header).unwrap(), data);
+///
+/// // A cache of another driver, or one that isn't a cache at all, isn't used.
+/// let updated = CacheHeader {
+///     uuid: [9; 16],
+///     ..header
+/// };
+/// assert!(matches!(file.load(&updated), Err(CacheError::OtherDevice)));
+/// file.save(b"not a cache").unwrap();
+/// assert!(matches!(file.load(&header), Err(CacheError::Corrupt)));
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheFile {
+    path: PathBuf,
+}
+
+impl CacheFile {
+    /// The file in the user's data directory, if there is one.
+    pub fn new() -> Option<CacheFile> {
+        dirs::data_dir().map(|dir| CacheFile::at(dir.join("snake_game").join("pipelines.bin")))
+    }
+
+    pub fn at(path: impl Into<PathBuf>) -> CacheFile {
+        CacheFile { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The cache saved in the file, as long as the device and driver `header` is of made it.
+    pub fn load(&self, header: &CacheHeader) -> Result<Vec<u8>, CacheError> {
+        let data = fs::read(&self.path)?;
+        match CacheHeader::parse(&data) {
+            Some(saved) if saved == *header => Ok(data),
+            Some(_) => Err(CacheError::OtherDevice),
+            None => Err(CacheError::Corrupt),
+        }
+    }
+
+    /// Saves `data` in the file. It is written next to it first, then moved over it, so the
+    /// game closing halfway through leaves the last one whole.
+    pub fn save(&self, data: &[u8]) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let written = self.path.with_extension("tmp");
+        fs::write(&written, data)?;
+        fs::rename(&written, &self.path)
+    }
+}
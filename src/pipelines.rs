@@ -0,0 +1,269 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::sync::Arc;
+use std::time::Instant;
+
+use vulkano::device::Device;
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::graphics::rasterization::PolygonMode;
+use vulkano::pipeline::graphics::GraphicsPipelineCreationError;
+use vulkano::pipeline::layout::PipelineLayout;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline};
+use vulkano::render_pass::RenderPass;
+use vulkano::shader::ShaderModule;
+
+use crate::mesh::MeshKind;
+use crate::pipeline_cache::{CacheError, CacheFile, CacheHeader};
+use crate::renderer::MaterialKind;
+use crate::vulkan::{build_line_pipeline, build_pipeline, check_push_constants, embedded_shaders};
+
+/// Why the pipelines couldn't be built again. The ones built before are kept.
+#[derive(Debug)]
+pub enum PipelineError {
+    /// A pipeline couldn't be built, the shaders not fitting together or the vertices drawn.
+    Build(GraphicsPipelineCreationError),
+    /// The shaders take other descriptor sets or push constants than the game hands them.
+    Incompatible,
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::Build(err) => write!(f, "could not build a pipeline: {}", err),
+            PipelineError::Incompatible => write!(
+                f,
+                "the shaders take other descriptor sets or push constants than the game's"
+            ),
+        }
+    }
+}
+
+impl Error for PipelineError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PipelineError::Build(err) => Some(err),
+            PipelineError::Incompatible => None,
+        }
+    }
+}
+
+impl From<GraphicsPipelineCreationError> for PipelineError {
+    fn from(err: GraphicsPipelineCreationError) -> PipelineError {
+        PipelineError::Build(err)
+    }
+}
+
+/// The pipelines everything is drawn with, one for each [`MaterialKind`], handed out while
+/// frames are recorded. Each is only built the first time it is asked for.
+///
+/// They are all built again together when the render pass or the shaders change, so frames
+/// never draw with some pipelines of the old ones and some of the new.
+pub struct PipelineRegistry {
+    device: Arc<Device>,
+    render_pass: Arc<RenderPass>,
+    mesh_kind: MeshKind,
+    cache: Arc<PipelineCache>,
+    /// The vertex and fragment shaders meshes are drawn with, when they aren't the ones built
+    /// into the game. Lines are always drawn with those.
+    shaders: Option<[Arc<ShaderModule>; 2]>,
+    /// The pipeline of each of the [`MaterialKind::ALL`], once it was built.
+    pipelines: [Option<Arc<GraphicsPipeline>>; MaterialKind::ALL.len()],
+}
+
+impl PipelineRegistry {
+    /// Gets ready to build pipelines drawing `mesh_kind` meshes in the first subpass of
+    /// `render_pass`, through a pipeline cache of their own. Panics if the device can't take
+    /// as many push constants as drawing pushes.
+    pub fn new(
+        device: Arc<Device>,
+        render_pass: Arc<RenderPass>,
+        mesh_kind: MeshKind,
+    ) -> PipelineRegistry {
+        check_push_constants(&device);
+        PipelineRegistry {
+            cache: PipelineCache::empty(device.clone()).unwrap(),
+            device,
+            render_pass,
+            mesh_kind,
+            shaders: None,
+            pipelines: Default::default(),
+        }
+    }
+
+    /// The same registry, building its pipelines through `cache`, such as one loaded with
+    /// [`load_pipeline_cache`].
+    pub fn with_cache(self, cache: Arc<PipelineCache>) -> PipelineRegistry {
+        PipelineRegistry { cache, ..self }
+    }
+
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    pub fn mesh_kind(&self) -> MeshKind {
+        self.mesh_kind
+    }
+
+    /// The cache the pipelines are built through, holding every one built so far.
+    pub fn cache(&self) -> &Arc<PipelineCache> {
+        &self.cache
+    }
+
+    /// Whether the device can draw `material` at all. Only a device with the
+    /// `fill_mode_non_solid` feature turned on draws wireframes.
+    pub fn supports(&self, material: MaterialKind) -> bool {
+        match material {
+            MaterialKind::Wireframe => self.device.enabled_features().fill_mode_non_solid,
+            MaterialKind::Textured | MaterialKind::Text | MaterialKind::Lines => true,
+        }
+    }
+
+    /// The pipeline drawing `material`, built if it wasn't yet. Panics if the device doesn't
+    /// [support](PipelineRegistry::supports) it.
+    pub fn get(&mut self, material: MaterialKind) -> Arc<GraphicsPipeline> {
+        assert!(
+            self.supports(material),
+            "the device can't draw {:?}",
+            material
+        );
+        if let Some(pipeline) = &self.pipelines[material as usize] {
+            return pipeline.clone();
+        }
+        // Shaders other than the built in ones were built into every pipeline when they were
+        // swapped in, so only the built in ones are ever built here.
+        let started = Instant::now();
+        let pipeline = self.build(material).unwrap();
+        println!(
+            "Built the {:?} pipeline in {:.1} ms",
+            material,
+            started.elapsed().as_secs_f64() * 1000.0
+        );
+        self.pipelines[material as usize] = Some(pipeline.clone());
+        pipeline
+    }
+
+    /// Draws meshes with the vertex and fragment `shaders`, or with the ones built into the
+    /// game when they are `None`, building every pipeline again. The shaders are kept as they
+    /// were if [`PipelineRegistry::rebuild_all`] fails.
+    pub fn set_shaders(
+        &mut self,
+        shaders: Option<[Arc<ShaderModule>; 2]>,
+    ) -> Result<(), PipelineError> {
+        let old = mem::replace(&mut self.shaders, shaders);
+        self.rebuild_all().inspect_err(|_| self.shaders = old)
+    }
+
+    /// Draws in the first subpass of `render_pass` from now on, building every pipeline
+    /// again. The render pass is kept as it was if [`PipelineRegistry::rebuild_all`] fails.
+    pub fn set_render_pass(&mut self, render_pass: Arc<RenderPass>) -> Result<(), PipelineError> {
+        let old = mem::replace(&mut self.render_pass, render_pass);
+        self.rebuild_all().inspect_err(|_| self.render_pass = old)
+    }
+
+    /// Builds a pipeline for every material the device supports, swapping them all in only if
+    /// all of them can be built. The descriptor sets made for the pipelines so far have to fit
+    /// the new ones, so each has to take the same sets and push constants as the one it
+    /// replaces.
+    ///
+    /// Nothing drawn with the old ones may still be in flight once they are dropped.
+    pub fn rebuild_all(&mut self) -> Result<(), PipelineError> {
+        let started = Instant::now();
+        let mut rebuilt: [Option<Arc<GraphicsPipeline>>; MaterialKind::ALL.len()] =
+            Default::default();
+        for material in MaterialKind::ALL {
+            if !self.supports(material) {
+                continue;
+            }
+            let pipeline = self.build(material)?;
+            let old = &self.pipelines[material as usize];
+            if !old.as_ref().is_none_or(|old| fits(&pipeline, old.layout())) {
+                return Err(PipelineError::Incompatible);
+            }
+            rebuilt[material as usize] = Some(pipeline);
+        }
+        self.pipelines = rebuilt;
+        println!(
+            "Built every pipeline in {:.1} ms",
+            started.elapsed().as_secs_f64() * 1000.0
+        );
+        Ok(())
+    }
+
+    fn build(
+        &self,
+        material: MaterialKind,
+    ) -> Result<Arc<GraphicsPipeline>, GraphicsPipelineCreationError> {
+        let polygon_mode = match material {
+            MaterialKind::Lines => {
+                return build_line_pipeline(&self.device, &self.render_pass, &self.cache)
+            }
+            MaterialKind::Wireframe => PolygonMode::Line,
+            MaterialKind::Textured | MaterialKind::Text => PolygonMode::Fill,
+        };
+        let [vs, fs] = self
+            .shaders
+            .clone()
+            .unwrap_or_else(|| embedded_shaders(&self.device, self.mesh_kind));
+        build_pipeline(
+            &self.device,
+            &self.render_pass,
+            self.mesh_kind,
+            polygon_mode,
+            &vs,
+            &fs,
+            &self.cache,
+        )
+    }
+}
+
+/// Whether descriptor sets and push constants made for `layout` can be used with `pipeline`.
+fn fits(pipeline: &GraphicsPipeline, layout: &PipelineLayout) -> bool {
+    let sets = pipeline.layout().descriptor_set_layouts().len();
+    sets == layout.descriptor_set_layouts().len()
+        && pipeline.layout().is_compatible_with(layout, sets as u32)
+}
+
+/// The pipeline cache `device` saved in `file` the last time the game ran, or an empty one
+/// when there is none it can use, saying why.
+pub fn load_pipeline_cache(device: &Arc<Device>, file: &CacheFile) -> Arc<PipelineCache> {
+    let properties = device.physical_device().properties();
+    let header = CacheHeader {
+        vendor_id: properties.vendor_id,
+        device_id: properties.device_id,
+        uuid: properties.pipeline_cache_uuid,
+    };
+    match file.load(&header) {
+        Ok(data) => {
+            println!("Building pipelines through a cache of {} bytes", data.len());
+            // The data is only checked to be of this device and driver; the driver is left to
+            // check the rest, as it has to for any cache it is handed.
+            return unsafe { PipelineCache::with_data(device.clone(), &data) }.unwrap();
+        }
+        Err(CacheError::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+            println!("Building pipelines through a new cache");
+        }
+        Err(err) => eprintln!(
+            "warning: building pipelines through a new cache, not the one in {}: {}",
+            file.path().display(),
+            err
+        ),
+    }
+    PipelineCache::empty(device.clone()).unwrap()
+}
+
+/// Saves `cache` in `file` for the next time the game runs, only warning if it can't.
+pub fn save_pipeline_cache(cache: &PipelineCache, file: &CacheFile) {
+    let saved = cache
+        .get_data()
+        .map_err(|err| io::Error::other(err.to_string()))
+        .and_then(|data| file.save(&data));
+    if let Err(err) = saved {
+        eprintln!(
+            "warning: could not save the pipeline cache to {}: {}",
+            file.path().display(),
+            err
+        );
+    }
+}
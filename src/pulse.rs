@@ -0,0 +1,62 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+/// How long food takes to brighten and dim back once. The shaders have the same.
+pub const PULSE_PERIOD: Duration = Duration::from_millis(1200);
+
+/// How far food brightens and dims as it pulses, as a share of its color. The shaders have the
+/// same.
+pub const PULSE_STRENGTH: f32 = 0.2;
+
+/// The clock food pulses by, handed to the shaders every frame so they can brighten and dim it
+/// there.
+///
+/// It only ever counts up to [`PULSE_PERIOD`] and starts over, as that is all the shaders
+/// need. A clock that kept counting would lose its precision as a float after a few hours,
+/// and the pulse would stutter.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use snake_game::pulse::{Pulse, PULSE_PERIOD, PULSE_STRENGTH};
+///
+/// let mut pulse = Pulse::new();
+/// assert_eq!(pulse.time(), 0.0);
+/// assert_eq!(pulse.brightness(), 1.0);
+///
+/// // A quarter of the way through, food is at its brightest.
+/// pulse.advance(PULSE_PERIOD / 4);
+/// assert!((pulse.brightness() - (1.0 + PULSE_STRENGTH)).abs() < 1e-6);
+///
+/// // Hours later, the clock is no further than one period in.
+/// pulse.advance(Duration::from_secs(5 * 60 * 60) + PULSE_PERIOD / 2);
+/// assert!((pulse.time() - PULSE_PERIOD.as_secs_f32() * 3.0 / 4.0).abs() < 1e-6);
+/// assert!((pulse.brightness() - (1.0 - PULSE_STRENGTH)).abs() < 1e-6);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Pulse {
+    /// How far into the current period it is.
+    phase: Duration,
+}
+
+impl Pulse {
+    pub fn new() -> Pulse {
+        Pulse::default()
+    }
+
+    /// Moves the clock on by `elapsed`, going around once it gets to the end of the period.
+    pub fn advance(&mut self, elapsed: Duration) {
+        let nanos = (self.phase + elapsed).as_nanos() % PULSE_PERIOD.as_nanos();
+        self.phase = Duration::from_nanos(nanos as u64);
+    }
+
+    /// How far into the period the clock is, in seconds, as the shaders take it.
+    pub fn time(&self) -> f32 {
+        self.phase.as_secs_f32()
+    }
+
+    /// What food's color is multiplied by now, the way the shaders work it out.
+    pub fn brightness(&self) -> f32 {
+        1.0 + PULSE_STRENGTH * (self.time() / PULSE_PERIOD.as_secs_f32() * TAU).sin()
+    }
+}
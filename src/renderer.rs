@@ -0,0 +1,268 @@
+use std::path::PathBuf;
+
+use crate::mesh::MeshKind;
+
+/// The numbers of samples a pixel the board can be drawn with. Drawing with more than one
+/// smooths the edges of whatever isn't lined up with the pixels, such as the sprites of a
+/// board that doesn't fill the window to the pixel.
+pub const SAMPLE_COUNTS: [u32; 4] = [1, 2, 4, 8];
+
+/// How finished frames go to the screen.
+///
+/// ```
+/// use snake_game::renderer::PresentMode;
+///
+/// assert_eq!(PresentMode::named("mailbox"), Some(PresentMode::Mailbox));
+/// assert_eq!(PresentMode::Immediate.name(), "immediate");
+///
+/// // Turning vsync off asks for the least latency, and turning it on waits again.
+/// assert_eq!(PresentMode::Fifo.toggled(), PresentMode::Mailbox);
+/// assert_eq!(PresentMode::Immediate.toggled(), PresentMode::Fifo);
+///
+/// // A mode the device doesn't have falls back on the other one that doesn't wait, and then
+/// // on the one every device has.
+/// let supported = [PresentMode::Fifo, PresentMode::Immediate];
+/// assert_eq!(PresentMode::Mailbox.within(&supported), PresentMode::Immediate);
+/// assert_eq!(PresentMode::Mailbox.within(&[PresentMode::Fifo]), PresentMode::Fifo);
+/// assert_eq!(PresentMode::Immediate.within(&[]), PresentMode::Fifo);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PresentMode {
+    /// Waits for the screen to refresh before showing each frame, one after the other: vsync.
+    /// Every device has it.
+    #[default]
+    Fifo,
+    /// Shows the latest frame each time the screen refreshes, skipping any older ones: no
+    /// tearing, with less latency than waiting.
+    Mailbox,
+    /// Shows each frame as soon as it is done, tearing through the one on the screen.
+    Immediate,
+}
+
+impl PresentMode {
+    pub const ALL: [PresentMode; 3] = [
+        PresentMode::Fifo,
+        PresentMode::Mailbox,
+        PresentMode::Immediate,
+    ];
+
+    /// The names the modes go by on the command line, in the order of [`PresentMode::ALL`].
+    pub const NAMES: [&'static str; 3] = ["fifo", "mailbox", "immediate"];
+
+    /// The mode called `name`, one of the [`PresentMode::NAMES`].
+    pub fn named(name: &str) -> Option<PresentMode> {
+        PresentMode::NAMES
+            .iter()
+            .position(|&other| other == name)
+            .map(|i| PresentMode::ALL[i])
+    }
+
+    pub fn name(self) -> &'static str {
+        PresentMode::NAMES[self as usize]
+    }
+
+    /// Vsync turned the other way: [`PresentMode::Mailbox`] without it, and
+    /// [`PresentMode::Fifo`] with it.
+    pub fn toggled(self) -> PresentMode {
+        match self {
+            PresentMode::Fifo => PresentMode::Mailbox,
+            PresentMode::Mailbox | PresentMode::Immediate => PresentMode::Fifo,
+        }
+    }
+
+    /// The mode to present with on a device that has the `supported` ones: this one if it is
+    /// there, or else the other one that doesn't wait for the screen, or else
+    /// [`PresentMode::Fifo`], which every device has.
+    pub fn within(self, supported: &[PresentMode]) -> PresentMode {
+        let fallback = match self {
+            PresentMode::Fifo => PresentMode::Fifo,
+            PresentMode::Mailbox => PresentMode::Immediate,
+            PresentMode::Immediate => PresentMode::Mailbox,
+        };
+        [self, fallback]
+            .into_iter()
+            .find(|mode| supported.contains(mode))
+            .unwrap_or(PresentMode::Fifo)
+    }
+}
+
+/// What something is drawn as, each with a pipeline of its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MaterialKind {
+    /// The meshes of the board: quads in colors of their own, tinted by the sprites they show.
+    /// Flat colored ones show none.
+    Textured,
+    /// The text over the board, tinted by the glyphs of the font.
+    Text,
+    /// The lines between the cells of the board.
+    Lines,
+    /// Meshes drawn as only the edges of their triangles, to see what they are made of. Not
+    /// every device can.
+    Wireframe,
+}
+
+impl MaterialKind {
+    pub const ALL: [MaterialKind; 4] = [
+        MaterialKind::Textured,
+        MaterialKind::Text,
+        MaterialKind::Lines,
+        MaterialKind::Wireframe,
+    ];
+}
+
+/// Where the shaders the board is drawn with come from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum ShaderSource {
+    /// The ones compiled into the game when it was built.
+    #[default]
+    Embedded,
+    /// GLSL sources compiled as the game starts, falling back on the embedded shaders if they
+    /// don't compile. Only a game built with the `runtime-shaders` feature can.
+    Files { vert: PathBuf, frag: PathBuf },
+}
+
+/// How the window is drawn, as picked on the command line.
+///
+/// ```
+/// use snake_game::mesh::MeshKind;
+/// use snake_game::renderer::{RenderConfig, ShaderSource};
+///
+/// let config = RenderConfig::default();
+/// assert_eq!(config.mesh_kind, MeshKind::Instanced);
+/// assert_eq!(config.samples, 1);
+/// assert_eq!(config.shaders, ShaderSource::Embedded);
+///
+/// // As many samples as asked for, as long as the device takes them...
+/// let config = config.with_samples(4);
+/// assert_eq!(config.samples_within(8), 4);
+/// // ...or as many as it does take.
+/// assert_eq!(config.samples_within(2), 2);
+/// assert_eq!(config.samples_within(1), 1);
+///
+/// // Only powers of two go.
+/// assert_eq!(RenderConfig::default().with_samples(6).samples_within(8), 4);
+/// assert_eq!(RenderConfig::default().with_samples(0).samples_within(8), 1);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderConfig {
+    pub mesh_kind: MeshKind,
+    /// How many samples to take a pixel, one of the [`SAMPLE_COUNTS`].
+    pub samples: u32,
+    /// The mode asked for, which the device may not have.
+    pub present_mode: PresentMode,
+    /// The most frames a second to draw when presenting doesn't wait for the screen, if there
+    /// is a limit.
+    pub fps_limit: Option<u32>,
+    pub shaders: ShaderSource,
+}
+
+impl Default for RenderConfig {
+    fn default() -> RenderConfig {
+        RenderConfig {
+            mesh_kind: MeshKind::default(),
+            samples: 1,
+            present_mode: PresentMode::default(),
+            fps_limit: None,
+            shaders: ShaderSource::default(),
+        }
+    }
+}
+
+impl RenderConfig {
+    pub fn with_mesh_kind(self, mesh_kind: MeshKind) -> RenderConfig {
+        RenderConfig { mesh_kind, ..self }
+    }
+
+    pub fn with_samples(self, samples: u32) -> RenderConfig {
+        RenderConfig { samples, ..self }
+    }
+
+    pub fn with_present_mode(self, present_mode: PresentMode) -> RenderConfig {
+        RenderConfig {
+            present_mode,
+            ..self
+        }
+    }
+
+    pub fn with_fps_limit(self, fps_limit: u32) -> RenderConfig {
+        RenderConfig {
+            fps_limit: Some(fps_limit),
+            ..self
+        }
+    }
+
+    pub fn with_shaders(self, shaders: ShaderSource) -> RenderConfig {
+        RenderConfig { shaders, ..self }
+    }
+
+    /// The samples a pixel to draw with on a device that takes up to `max`: the most of the
+    /// [`SAMPLE_COUNTS`] that is no more than either, and one sample when nothing more goes.
+    pub fn samples_within(&self, max: u32) -> u32 {
+        SAMPLE_COUNTS
+            .into_iter()
+            .filter(|&count| count <= self.samples && count <= max)
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+/// Where a window was and how big, in physical pixels. Some window systems don't say where
+/// windows are, leaving `position` out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub position: Option<[i32; 2]>,
+    pub size: [u32; 2],
+}
+
+/// Whether the window fills the screen, remembering where it was before it did so it can go
+/// back there.
+///
+/// ```
+/// use snake_game::renderer::{Placement, WindowMode};
+///
+/// let mut mode = WindowMode::new();
+/// let windowed = Placement {
+///     position: Some([100, 50]),
+///     size: [800, 600],
+/// };
+/// // Going fullscreen has nowhere to go back to yet.
+/// assert_eq!(mode.toggle(windowed), None);
+/// assert!(mode.is_fullscreen());
+///
+/// // Leaving it goes back to where the window was, whatever size the screen is.
+/// let fullscreen = Placement {
+///     position: Some([0, 0]),
+///     size: [1920, 1080],
+/// };
+/// assert_eq!(mode.toggle(fullscreen), Some(windowed));
+/// assert!(!mode.is_fullscreen());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowMode {
+    /// Where the window was before it went fullscreen, while it is.
+    windowed: Option<Placement>,
+}
+
+impl WindowMode {
+    /// A window that doesn't fill the screen.
+    pub fn new() -> WindowMode {
+        WindowMode::default()
+    }
+
+    pub fn is_fullscreen(&self) -> bool {
+        self.windowed.is_some()
+    }
+
+    /// Switches between filling the screen and not, for a window at `current` now. Returns
+    /// where the window goes back to when it leaves fullscreen, and `None` when it goes
+    /// fullscreen.
+    pub fn toggle(&mut self, current: Placement) -> Option<Placement> {
+        match self.windowed.take() {
+            Some(windowed) => Some(windowed),
+            None => {
+                self.windowed = Some(current);
+                None
+            }
+        }
+    }
+}
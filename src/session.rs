@@ -0,0 +1,1333 @@
+use std::cell::RefCell;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+
+use winit::event::{MouseButton, VirtualKeyCode};
+
+use crate::death::DEATH_ANIMATION;
+use crate::game::{
+    Achievement, Achievements, BoardView, ConfigError, Controller, DailyChallenge, DailyRecords,
+    Difficulty, Direction, Editor, FoodKind, Game, GameConfig, GameEvent, GameMode, GreedyAi,
+    GridPos, HighScores, Level, Playback, Replay, ReplayError, RoundEnd, Stats, StepResult, Tool,
+    Tutorial, TutorialProgress,
+};
+use crate::layout::BoardViewport;
+use crate::net::{Connection, Host, Message, NetError, Phase};
+use crate::palette::{CellShape, Palette};
+use crate::settings::Settings;
+use crate::spectate::Spectators;
+use crate::state::{AppEvent, AppState, Key};
+use crate::ticker::Ticker;
+
+/// Time scales, stepped through with `-` and `+`.
+const TIME_SCALES: [f64; 4] = [0.5, 1.0, 2.0, 4.0];
+
+/// How many ticks a newly unlocked achievement stays in the title for.
+const ACHIEVEMENT_NOTICE_TICKS: u64 = 40;
+
+/// How long the menu is left alone before the demo starts.
+const DEMO_AFTER: Duration = Duration::from_secs(15);
+
+/// How many of the latest ticks can be taken back in the rounds that allow it.
+const REWIND_TICKS: usize = 30;
+
+/// What the window shows.
+// Only one of these is ever built, so the size of the variants doesn't matter.
+#[allow(clippy::large_enum_variant)]
+pub enum Mode {
+    /// Rounds built from `config`. `controllers` is indexed by player, and the keys pressed go
+    /// to all of them. When `record` is set, every round is saved there as a replay
+    /// once it ends.
+    ///
+    /// The first round continues `resume` instead when it is set. A round still going when the
+    /// window closes is saved to `save`. The scores of the keyboard players go into
+    /// `high_scores` at the end of every round, tagged with `difficulty`, which `config` is set up
+    /// for. A round that beats the table is kept next to it, and plays along with the rounds
+    /// after it as a ghost. The keyboard players unlock `achievements` too, and what they do
+    /// adds up in `stats`, saved at the end of every round.
+    ///
+    /// When `tutorial` is set, the tutorial runs before anything else, and is marked done in it
+    /// once it is played through or skipped.
+    ///
+    /// When `daily` is set, `config` is set up for that day's challenge. Its scores then go into
+    /// the daily records instead of `high_scores`, and there is no ghost to race.
+    ///
+    /// When `editor` is set, the level it holds is edited first, to be saved to the path next to
+    /// it and played on.
+    ///
+    /// When `host` is set, player two is steered from another window, by a guest who joins
+    /// through it. Rounds only start once the guest is there.
+    ///
+    /// When `spectators` is set, every tick of the rounds is published to them.
+    Play {
+        config: GameConfig,
+        difficulty: Difficulty,
+        controllers: Vec<Box<dyn Controller>>,
+        record: Option<PathBuf>,
+        resume: Option<Box<Game>>,
+        save: Option<PathBuf>,
+        high_scores: HighScores,
+        achievements: Achievements,
+        stats: Stats,
+        tutorial: Option<TutorialProgress>,
+        daily: Option<(DailyChallenge, DailyRecords)>,
+        editor: Option<(Editor, PathBuf)>,
+        host: Option<Host>,
+        spectators: Option<Spectators>,
+    },
+    /// Plays a recorded round back.
+    Watch(Replay),
+    /// Steers player two of the rounds the host at the other end of the connection plays.
+    Join(Connection),
+}
+
+/// The game on screen, along with whatever drives it and the state of the application.
+pub(crate) struct Session {
+    state: AppState,
+    round: Round,
+    /// What the last tick run did to player one, `None` until the round's first tick.
+    last_step: Option<StepResult>,
+    /// Whether a snake died since [`Session::take_died`] was last asked.
+    died: bool,
+    /// The food eaten since [`Session::take_eaten`] was last asked, and where it was.
+    eaten: Vec<(GridPos, FoodKind)>,
+    /// The tutorial while it runs, and where to mark it done.
+    tutorial: Option<(Tutorial, TutorialProgress)>,
+    /// Saved whenever the player changes them.
+    settings: Settings,
+    editing: Option<Editing>,
+    /// How long it has been since the last key or mouse input.
+    idle: Duration,
+    /// The round the computer plays, while the demo runs.
+    demo: Option<Demo>,
+    hosting: Option<Hosting>,
+}
+
+/// Rounds played with a guest in another window, see [`Host`].
+struct Hosting {
+    host: Host,
+    /// The guest, once one joined.
+    guest: Option<Connection>,
+    /// What the guest was sent last: the phase, and the tick the board was on.
+    sent: Option<(Phase, u64)>,
+    /// Why the last guest left, until another one joins.
+    lost: Option<String>,
+}
+
+/// The level editor, along with what the mouse is doing on it.
+struct Editing {
+    editor: Editor,
+    /// Where the level is saved before it is played.
+    path: PathBuf,
+    /// The cell under the mouse, if it is over the board.
+    cursor: Option<GridPos>,
+    /// Whether the mouse button held down paints with the tool, or erases.
+    held: Option<bool>,
+    /// Why the level couldn't be played, the last time it was tried.
+    error: Option<String>,
+}
+
+/// A round the computer plays on its own, behind the menu. It goes nowhere: no listener is
+/// registered on it, so none of it counts towards the high scores, the stats or the
+/// achievements, and leaving the demo just drops it.
+struct Demo {
+    /// What each new demo round is built from.
+    config: GameConfig,
+    game: Game,
+}
+
+// There is only ever one of these, so the size of the variants doesn't matter.
+#[allow(clippy::large_enum_variant)]
+enum Round {
+    Play {
+        config: GameConfig,
+        difficulty: Difficulty,
+        controllers: Vec<Box<dyn Controller>>,
+        record: Option<PathBuf>,
+        save: Option<PathBuf>,
+        high_scores: HighScores,
+        game: Game,
+        /// `None` for a resumed round, whose start wasn't seen.
+        replay: Option<Replay>,
+        /// The best round so far, played back in step with `game`, until it ends.
+        ghost: Option<Playback>,
+        achievements: Achievements,
+        stats: Stats,
+        /// What `game` told its listener about during the tick, not looked at yet.
+        events: Rc<RefCell<Vec<GameEvent>>>,
+        /// The achievement unlocked last, and the tick it stops being shown on.
+        notice: Option<(&'static Achievement, u64)>,
+        /// The challenge being played, if any, and the best score of each day.
+        daily: Option<(DailyChallenge, DailyRecords)>,
+        spectators: Option<Spectators>,
+    },
+    Watch {
+        replay: Replay,
+        playback: Playback,
+    },
+    /// The rounds of a host, as it sends them. `connection` is `None` once it is lost.
+    Join {
+        connection: Option<Connection>,
+        game: Game,
+    },
+}
+
+impl Session {
+    pub fn new(mode: Mode, settings: Settings) -> Session {
+        let mut tutorial = None;
+        let mut editing = None;
+        let mut hosting = None;
+        let round = match mode {
+            Mode::Play {
+                config,
+                difficulty,
+                controllers,
+                record,
+                resume,
+                save,
+                high_scores,
+                achievements,
+                stats,
+                tutorial: progress,
+                daily,
+                editor,
+                host,
+                spectators,
+            } => {
+                tutorial = progress.map(|progress| (Tutorial::new(), progress));
+                hosting = host.map(|host| Hosting {
+                    host,
+                    guest: None,
+                    sent: None,
+                    lost: None,
+                });
+                editing = editor.map(|(editor, path)| Editing {
+                    editor,
+                    path,
+                    cursor: None,
+                    held: None,
+                    error: None,
+                });
+                let (mut game, replay) = match resume {
+                    Some(game) => (*game, None),
+                    None => {
+                        let game = new_game(&config);
+                        let replay = Replay::new(&game);
+                        (game, Some(replay))
+                    }
+                };
+                keep_history(&mut game, &difficulty);
+                let mut ghost = match daily {
+                    Some(_) => None,
+                    None => high_scores
+                        .best_run_path(&score_table(&difficulty, config.mode))
+                        .and_then(|path| load_ghost(&path, &config)),
+                };
+                keep_up(&mut ghost, &game);
+                let events = Rc::default();
+                watch(&mut game, &controllers, &achievements, &events);
+                Round::Play {
+                    config,
+                    difficulty,
+                    controllers,
+                    record,
+                    save,
+                    high_scores,
+                    game,
+                    replay,
+                    ghost,
+                    achievements,
+                    stats,
+                    events,
+                    notice: None,
+                    daily,
+                    spectators,
+                }
+            }
+            Mode::Watch(replay) => Round::Watch {
+                playback: replay.play(),
+                replay,
+            },
+            // Shown until the host sends its board, which it does as soon as the guest joins.
+            Mode::Join(connection) => Round::Join {
+                connection: Some(connection),
+                game: Game::with_config(GameConfig::default()),
+            },
+        };
+
+        Session {
+            state: match (&tutorial, &editing) {
+                (Some(_), _) => AppState::Tutorial,
+                (None, Some(_)) => AppState::Editor,
+                (None, None) => AppState::Menu,
+            },
+            round,
+            last_step: None,
+            died: false,
+            eaten: Vec::new(),
+            tutorial,
+            settings,
+            editing,
+            idle: Duration::ZERO,
+            demo: None,
+            hosting,
+        }
+    }
+
+    pub fn state(&self) -> AppState {
+        self.state
+    }
+
+    pub fn game(&self) -> &Game {
+        if let Some((tutorial, _)) = &self.tutorial {
+            return tutorial.game();
+        }
+        if let Some(demo) = &self.demo {
+            return &demo.game;
+        }
+        match &self.round {
+            Round::Play { game, .. } | Round::Join { game, .. } => game,
+            Round::Watch { playback, .. } => playback.game(),
+        }
+    }
+
+    /// The level being edited, while the editor is open.
+    pub fn editor(&self) -> Option<&Editor> {
+        match (self.state, &self.editing) {
+            (AppState::Editor, Some(editing)) => Some(&editing.editor),
+            _ => None,
+        }
+    }
+
+    /// The cell of the level being edited under the mouse, while the editor is open.
+    pub fn cursor(&self) -> Option<GridPos> {
+        match (self.state, &self.editing) {
+            (AppState::Editor, Some(editing)) => editing.cursor,
+            _ => None,
+        }
+    }
+
+    /// Where the board goes in a window `window` pixels wide and high: the level being edited
+    /// while the editor is open, the game otherwise. Its cells are a whole number of pixels
+    /// wide with integer scaling.
+    pub fn viewport(&self, window: [u32; 2]) -> BoardViewport {
+        let fit = if self.settings.integer_scaling {
+            BoardViewport::integer
+        } else {
+            BoardViewport::new
+        };
+        match self.editor() {
+            Some(editor) => fit([editor.width(), editor.height()], window),
+            None => {
+                let game = self.game();
+                fit([game.width(), game.height()], window).with_mirror(game.config().mirrored)
+            }
+        }
+    }
+
+    /// What everything is drawn with.
+    pub fn palette(&self) -> Palette {
+        self.settings.palette
+    }
+
+    /// What shape the cells are drawn in.
+    pub fn cell_shape(&self) -> CellShape {
+        self.settings.cells
+    }
+
+    /// Whether to draw lines between the cells of the board.
+    pub fn grid(&self) -> bool {
+        self.settings.grid
+    }
+
+    /// Whether snakes slide from cell to cell between ticks.
+    pub fn smooth(&self) -> bool {
+        self.settings.smooth
+    }
+
+    /// Whether food is kept from pulsing.
+    pub fn reduced_motion(&self) -> bool {
+        self.settings.reduced_motion
+    }
+
+    /// Whether the window fills the screen, as it did last time.
+    pub fn fullscreen(&self) -> bool {
+        self.settings.fullscreen
+    }
+
+    /// Keeps whether the window fills the screen for the next time.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.settings.fullscreen = fullscreen;
+        self.settings.save();
+    }
+
+    /// How far into a snake dying the round is, while it is: all the way through once a round
+    /// that ended with a death is over, so that the snake stays gone. A guest doesn't see it,
+    /// as the host only tells it the round is still being played meanwhile.
+    pub fn dying(&self) -> Option<Duration> {
+        match self.state {
+            AppState::Dying(left) => Some(DEATH_ANIMATION.saturating_sub(left)),
+            AppState::GameOver if !matches!(self.round, Round::Join { .. }) => self
+                .game()
+                .round_end()
+                .filter(|&end| ends_in_death(end))
+                .map(|_| DEATH_ANIMATION),
+            _ => None,
+        }
+    }
+
+    pub fn last_step(&self) -> Option<StepResult> {
+        self.last_step
+    }
+
+    /// Whether a snake lost a life or died since the last time this was asked.
+    pub fn take_died(&mut self) -> bool {
+        mem::take(&mut self.died)
+    }
+
+    /// The food eaten since the last time this was asked, and the cell it was on.
+    pub fn take_eaten(&mut self) -> impl Iterator<Item = (GridPos, FoodKind)> + '_ {
+        self.eaten.drain(..)
+    }
+
+    /// The best round so far, as far as the current one has got, while it lasts.
+    pub fn ghost(&self) -> Option<&Game> {
+        if self.tutorial.is_some() || self.demo.is_some() {
+            return None;
+        }
+        match &self.round {
+            Round::Play { ghost, .. } => ghost.as_ref().map(Playback::game),
+            Round::Watch { .. } | Round::Join { .. } => None,
+        }
+    }
+
+    pub fn key_pressed(&mut self, key: VirtualKeyCode, ticker: &mut Ticker) {
+        if self.wake(ticker) {
+            return;
+        }
+        // A snake dying can only be left or cut short, nothing else happens meanwhile.
+        if let AppState::Dying(_) = self.state {
+            let event = match app_key(key).map(Key::event) {
+                Some(AppEvent::Quit) => AppEvent::Quit,
+                _ => AppEvent::Skip,
+            };
+            self.handle(event, ticker);
+            return;
+        }
+        if let Some(key) = app_key(key) {
+            self.handle(key.event(), ticker);
+        }
+
+        // Player two is the guest's to steer when hosting.
+        if let (true, Some((player, direction))) = (self.state.is_steering(), key_direction(key)) {
+            if player == 0 || self.hosting.is_none() {
+                self.round.steer(player, direction);
+            }
+        }
+        if let (Some((tutorial, _)), Some((0, direction))) =
+            (&mut self.tutorial, key_direction(key))
+        {
+            tutorial.press(direction);
+        }
+        let scale = match key {
+            VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract | VirtualKeyCode::LBracket => {
+                TIME_SCALES
+                    .iter()
+                    .rev()
+                    .find(|&&scale| scale < ticker.scale())
+            }
+            VirtualKeyCode::Equals
+            | VirtualKeyCode::Plus
+            | VirtualKeyCode::NumpadAdd
+            | VirtualKeyCode::RBracket => TIME_SCALES.iter().find(|&&scale| scale > ticker.scale()),
+            _ => None,
+        };
+        if let Some(&scale) = scale {
+            ticker.set_scale(scale);
+        }
+        if key == VirtualKeyCode::P {
+            self.settings.palette = self.settings.palette.next();
+            self.settings.save();
+            println!("Palette: {}", self.settings.palette.name());
+        }
+        if key == VirtualKeyCode::C {
+            self.settings.cells = self.settings.cells.next();
+            self.settings.save();
+            println!("Cells: {}", self.settings.cells.name());
+        }
+        if key == VirtualKeyCode::G {
+            self.settings.grid = !self.settings.grid;
+            self.settings.save();
+        }
+        if key == VirtualKeyCode::M {
+            self.settings.smooth = !self.settings.smooth;
+            self.settings.save();
+        }
+        if let (AppState::Editor, Some(editing)) = (self.state, &mut self.editing) {
+            editing.key_pressed(key);
+        }
+    }
+
+    /// Called when the mouse moves onto `cell` of the board, or off the board when it is
+    /// `None`. Paints the cell in the editor while a button is held.
+    pub fn cursor_moved(&mut self, cell: Option<GridPos>, ticker: &mut Ticker) {
+        if self.wake(ticker) {
+            return;
+        }
+        if let (AppState::Editor, Some(editing)) = (self.state, &mut self.editing) {
+            if editing.cursor != cell {
+                editing.cursor = cell;
+                editing.paint();
+            }
+        }
+    }
+
+    /// Called when a mouse button goes down, or back up. In the editor, the left button paints
+    /// with the current tool and the right one erases, for as long as they are held.
+    pub fn mouse_input(&mut self, button: MouseButton, pressed: bool, ticker: &mut Ticker) {
+        if self.wake(ticker) {
+            return;
+        }
+        if let (AppState::Editor, Some(editing)) = (self.state, &mut self.editing) {
+            editing.held = match (button, pressed) {
+                (MouseButton::Left, true) => Some(false),
+                (MouseButton::Right, true) => Some(true),
+                (_, false) => None,
+                _ => editing.held,
+            };
+            editing.paint();
+        }
+    }
+
+    /// Starts the idle time over, as there was some input, and leaves the demo if it is running.
+    /// Returns whether it was, in which case the input did nothing else.
+    fn wake(&mut self, ticker: &mut Ticker) -> bool {
+        self.idle = Duration::ZERO;
+        let demo = self.state == AppState::Demo;
+        self.handle(AppEvent::Wake, ticker);
+        demo
+    }
+
+    /// Runs the ticks `elapsed` is worth, as long as the game or the demo is running, counts it
+    /// off the countdown, or towards starting the demo while the menu is left alone.
+    pub fn update(&mut self, elapsed: Duration, ticker: &mut Ticker) {
+        if let Round::Join { .. } = self.round {
+            self.follow();
+            return;
+        }
+        if let Some((tutorial, _)) = &mut self.tutorial {
+            ticker.advance(elapsed);
+            while !tutorial.is_over() && ticker.tick(tutorial.game().tick_interval()) {
+                tutorial.tick();
+            }
+            if tutorial.is_over() {
+                self.handle(AppEvent::TutorialOver, ticker);
+            }
+        } else if let AppState::Countdown(left) = self.state {
+            match left.checked_sub(elapsed) {
+                Some(left) if !left.is_zero() => self.state = AppState::Countdown(left),
+                _ => self.handle(AppEvent::CountdownOver, ticker),
+            }
+        } else if let AppState::Dying(left) = self.state {
+            match left.checked_sub(elapsed) {
+                Some(left) if !left.is_zero() => self.state = AppState::Dying(left),
+                _ => self.handle(AppEvent::DyingOver, ticker),
+            }
+        } else if self.state.is_running()
+            && self.round.run(
+                elapsed,
+                ticker,
+                &mut self.last_step,
+                &mut self.died,
+                &mut self.eaten,
+            )
+        {
+            let event = match self.game().round_end() {
+                Some(end) if ends_in_death(end) => AppEvent::SnakeDied,
+                _ => AppEvent::RoundOver,
+            };
+            self.handle(event, ticker);
+        } else if self.state == AppState::Menu {
+            self.idle += elapsed;
+            if self.idle >= DEMO_AFTER {
+                self.handle(AppEvent::Idle, ticker);
+            }
+        } else if let Some(demo) = &mut self.demo {
+            demo.run(elapsed, ticker);
+        }
+        self.serve(ticker);
+    }
+
+    /// Lets a guest join while there is none, passes on the turns of the one there is, and
+    /// sends it the board and the phase whenever they changed. Losing the guest ends the round.
+    fn serve(&mut self, ticker: &mut Ticker) {
+        let hosting = match &mut self.hosting {
+            Some(hosting) => hosting,
+            None => return,
+        };
+        while let Some(guest) = hosting.host.poll() {
+            // There is only room for one, the others are hung up on.
+            if hosting.guest.is_none() {
+                println!("{} joined", guest.peer());
+                hosting.guest = Some(guest);
+                hosting.sent = None;
+                hosting.lost = None;
+            }
+        }
+        let guest = match &mut hosting.guest {
+            Some(guest) => guest,
+            None => return,
+        };
+
+        let served = guest.poll().and_then(|messages| {
+            for message in messages {
+                match message {
+                    Message::Turn(direction) if self.state.is_steering() => {
+                        self.round.steer(1, direction)
+                    }
+                    Message::Turn(_) => {}
+                    _ => return Err(NetError::Unexpected),
+                }
+            }
+            let game = match &self.round {
+                Round::Play { game, .. } => game,
+                Round::Watch { .. } | Round::Join { .. } => return Ok(()),
+            };
+            let now = (phase(self.state), game.ticks());
+            if hosting.sent != Some(now) {
+                guest.send_board(game)?;
+                guest.send(&Message::Phase(now.0))?;
+                hosting.sent = Some(now);
+            }
+            Ok(())
+        });
+        if let Err(e) = served {
+            eprintln!("Lost the connection to the other player: {}", e);
+            hosting.guest = None;
+            hosting.lost = Some(e.to_string());
+            self.handle(AppEvent::Disconnected, ticker);
+        }
+    }
+
+    /// Takes in what the host sent: the board to show, and the state to be in. The state of a
+    /// guest only ever follows the host's, so none of it goes through [`AppState::update`].
+    /// Once the connection is lost, the guest is left at the menu.
+    fn follow(&mut self) {
+        let (connection, game) = match &mut self.round {
+            Round::Join { connection, game } => (connection, game),
+            Round::Play { .. } | Round::Watch { .. } => return,
+        };
+        let messages = match connection.as_mut().map(Connection::poll) {
+            Some(Ok(messages)) => messages,
+            Some(Err(e)) => {
+                eprintln!("Lost the connection to the host: {}", e);
+                *connection = None;
+                self.state = AppState::Menu;
+                return;
+            }
+            None => {
+                self.state = AppState::Menu;
+                return;
+            }
+        };
+        for message in messages {
+            match message {
+                Message::Board(board) => *game = *board,
+                Message::Phase(phase) => self.state = phase_state(phase),
+                Message::Hello { .. } | Message::Welcome { .. } | Message::Turn(_) => {}
+            }
+        }
+    }
+
+    /// Called when the window loses the focus.
+    pub fn focus_lost(&mut self, ticker: &mut Ticker) {
+        self.handle(AppEvent::FocusLost, ticker);
+    }
+
+    /// Whether this window hosts and nobody joined it yet, so there is no round to start.
+    fn waiting_for_guest(&self) -> bool {
+        matches!(&self.hosting, Some(hosting) if hosting.guest.is_none())
+    }
+
+    /// Moves to the state `event` leads to, if any. A guest only ever follows the host, so
+    /// nothing moves it.
+    fn handle(&mut self, event: AppEvent, ticker: &mut Ticker) {
+        if let Round::Join { .. } = self.round {
+            return;
+        }
+        let next = match self.state.update(event) {
+            Some(next) => next,
+            None => return,
+        };
+
+        match (self.state, next) {
+            (_, AppState::Editor) if self.editing.is_none() => return,
+            (AppState::Menu | AppState::Editor, AppState::Countdown(_))
+                if self.waiting_for_guest() =>
+            {
+                return
+            }
+            // Nothing is started over or given up on, the round just goes back a tick.
+            _ if event == AppEvent::Rewind => {
+                if !self.round.rewind() {
+                    return;
+                }
+                self.last_step = None;
+            }
+            // The demo would take the board away from the guest.
+            (_, AppState::Demo) => {
+                self.demo = self.round.demo().filter(|_| self.hosting.is_none());
+                if self.demo.is_none() {
+                    return;
+                }
+                ticker.reset();
+            }
+            // The demo is only ever looked at between two of its ticks, so there is nothing
+            // half done to it when it is dropped.
+            (AppState::Demo, _) => {
+                self.demo = None;
+                ticker.reset();
+            }
+            // The level has to check out before it can be played.
+            (AppState::Editor, _) => {
+                let editing = match &mut self.editing {
+                    Some(editing) => editing,
+                    None => return,
+                };
+                let round = &mut self.round;
+                let played = editing
+                    .editor
+                    .save(&editing.path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|level| round.set_level(&level).map_err(|e| e.to_string()));
+                if let Err(e) = played {
+                    eprintln!("Can't play the level: {}", e);
+                    editing.error = Some(e);
+                    return;
+                }
+                println!("Saved the level to {}", editing.path.display());
+                editing.error = None;
+                editing.held = None;
+                self.last_step = None;
+                ticker.reset();
+            }
+            // Going back to the menu gives up on the round, and a round that is over is only
+            // left for a new one. A round only goes back to the menu without a pause when the
+            // guest left.
+            (
+                AppState::Countdown(_) | AppState::Playing | AppState::Paused | AppState::Dying(_),
+                AppState::Menu,
+            )
+            | (AppState::GameOver, _) => {
+                self.round.restart();
+                self.last_step = None;
+                ticker.reset();
+            }
+            // The first tick takes a whole interval after the countdown, like on any other tick.
+            (AppState::Countdown(_), AppState::Playing) => ticker.reset(),
+            (AppState::Tutorial, _) => {
+                if let Some((_, mut progress)) = self.tutorial.take() {
+                    progress.finish();
+                    progress.save();
+                }
+                ticker.reset();
+            }
+            _ => {}
+        }
+        self.state = next;
+    }
+
+    /// Called when the window is about to close. Saves the round if it is under way.
+    pub fn close(&self) {
+        if let Round::Play {
+            save: Some(path),
+            game,
+            ..
+        } = &self.round
+        {
+            if game.ticks() > 0 && !game.is_over() {
+                match game.save_to(path) {
+                    Ok(()) => println!("Saved the game to {}", path.display()),
+                    Err(e) => eprintln!("Failed to save the game to {}: {}", path.display(), e),
+                }
+            }
+        }
+    }
+
+    /// The best score so far, an achievement just unlocked, whether this is a replay, how fast
+    /// `ticker` runs the game and what state it is in, for the window title.
+    pub fn title_suffix(&self, ticker: &Ticker) -> String {
+        let mut suffix = match &self.round {
+            Round::Play {
+                config,
+                high_scores,
+                difficulty,
+                game,
+                notice,
+                daily,
+                ..
+            } => {
+                let table = score_table(difficulty, config.mode);
+                let mut suffix = match daily {
+                    Some((challenge, records)) => daily_summary(challenge, records, game),
+                    None => match high_scores.best_on(&table) {
+                        Some(best) => {
+                            format!(" - Best ({}): {}", table, config.mode.format_score(best))
+                        }
+                        None => String::new(),
+                    },
+                };
+                if let Some((achievement, until)) = notice {
+                    if game.ticks() < *until {
+                        suffix += &format!(" - Achievement unlocked: {}", achievement.name);
+                    }
+                }
+                if game.was_rewound() {
+                    suffix += " - Rewound, not ranked";
+                }
+                suffix
+            }
+            Round::Watch { .. } => format!(" (replay, {}x)", ticker.scale()),
+            Round::Join {
+                connection: Some(connection),
+                ..
+            } => format!(" - Playing with {}", connection.peer()),
+            Round::Join {
+                connection: None, ..
+            } => " - Lost the connection to the host".to_string(),
+        };
+        if matches!(self.round, Round::Play { .. }) && ticker.scale() != 1.0 {
+            suffix += &format!(" ({}x)", ticker.scale());
+        }
+
+        match self.state {
+            AppState::Tutorial => {
+                if let Some(step) = self.tutorial.as_ref().and_then(|(t, _)| t.current()) {
+                    suffix = format!(" - Tutorial: {} (Escape to skip)", step.prompt);
+                }
+            }
+            AppState::Menu => suffix += &self.menu_prompt(),
+            AppState::Demo => suffix += " - Demo, press any key",
+            AppState::Countdown(left) => {
+                suffix += &format!(" - Starting in {}", left.as_secs_f32().ceil())
+            }
+            AppState::Paused if self.game().rewindable_ticks() > 0 => {
+                suffix += " (paused, Backspace to rewind)"
+            }
+            AppState::Paused => suffix += " (paused)",
+            AppState::Editor => {
+                if let Some(editing) = &self.editing {
+                    suffix = editing.title();
+                }
+            }
+            AppState::Playing | AppState::Dying(_) | AppState::GameOver => {}
+        }
+        suffix
+    }
+}
+
+impl Session {
+    /// What to do at the menu, which is waiting for someone else with a round on the network.
+    fn menu_prompt(&self) -> String {
+        match (&self.round, &self.hosting) {
+            (
+                Round::Join {
+                    connection: None, ..
+                },
+                _,
+            ) => String::new(),
+            (Round::Join { .. }, _) => " - Waiting for the host to start".to_string(),
+            (_, Some(hosting)) if hosting.guest.is_none() => {
+                let waiting = format!(
+                    " - Waiting for a player to join on port {}",
+                    hosting.host.local_addr().port()
+                );
+                match &hosting.lost {
+                    Some(lost) => format!(" - The other player left ({}){}", lost, waiting),
+                    None => waiting,
+                }
+            }
+            _ => " - Press Enter to start".to_string(),
+        }
+    }
+}
+
+impl Round {
+    /// Starts over with a new round, or from the beginning of the replay.
+    fn restart(&mut self) {
+        match self {
+            Round::Play {
+                config,
+                difficulty,
+                controllers,
+                high_scores,
+                game,
+                replay,
+                ghost,
+                achievements,
+                events,
+                daily,
+                ..
+            } => {
+                for controller in controllers.iter_mut() {
+                    controller.round_ended();
+                }
+                *game = new_game(config);
+                keep_history(game, difficulty);
+                *replay = Some(Replay::new(game));
+                watch(game, controllers, achievements, events);
+                if daily.is_none() {
+                    *ghost = high_scores
+                        .best_run_path(&score_table(difficulty, config.mode))
+                        .and_then(|path| load_ghost(&path, config));
+                }
+            }
+            Round::Watch {
+                replay, playback, ..
+            } => *playback = replay.play(),
+            Round::Join { .. } => {}
+        }
+    }
+
+    /// Takes back the latest tick of a round that keeps its history. The ghost only ever plays
+    /// forwards, so it is dropped. Returns whether there was a tick to take back.
+    fn rewind(&mut self) -> bool {
+        if let Round::Play { game, ghost, .. } = self {
+            if game.rewind() {
+                *ghost = None;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// A demo of rounds like the ones played, all of its snakes steered by the computer. A
+    /// replay has none.
+    fn demo(&self) -> Option<Demo> {
+        match self {
+            Round::Play { config, .. } => {
+                // Unlike the rounds played, each demo round is a new one.
+                let config = GameConfig {
+                    seed: None,
+                    ..config.clone()
+                };
+                Some(Demo {
+                    game: Game::with_config(config.clone()),
+                    config,
+                })
+            }
+            Round::Watch { .. } | Round::Join { .. } => None,
+        }
+    }
+
+    /// Moves the rounds onto `level`, starting a new one, as long as the config still works on
+    /// it.
+    fn set_level(&mut self, level: &Level) -> Result<(), ConfigError> {
+        if let Round::Play { config, .. } = self {
+            let on_level = config.clone().with_level(level);
+            on_level.validate()?;
+            *config = on_level;
+        }
+        self.restart();
+        Ok(())
+    }
+
+    /// Passes a turn on to the controller of `player`, or to the host for a guest, whichever
+    /// player it is for.
+    fn steer(&mut self, player: usize, direction: Direction) {
+        match self {
+            Round::Play { controllers, .. } => {
+                if let Some(controller) = controllers.get_mut(player) {
+                    controller.key_pressed(direction);
+                }
+            }
+            Round::Join { connection, .. } => {
+                let sent = connection
+                    .as_mut()
+                    .map(|connection| connection.send(&Message::Turn(direction)));
+                if let Some(Err(e)) = sent {
+                    eprintln!("Lost the connection to the host: {}", e);
+                    *connection = None;
+                }
+            }
+            Round::Watch { .. } => {}
+        }
+    }
+
+    /// Feeds `elapsed` into the ticker and runs the ticks it has time for, keeping what the last
+    /// of them did to player one in `last_step`, setting `died` if any snake died and adding
+    /// the food eaten to `eaten`, along with where it was. Returns whether the round ended.
+    fn run(
+        &mut self,
+        elapsed: Duration,
+        ticker: &mut Ticker,
+        last_step: &mut Option<StepResult>,
+        died: &mut bool,
+        eaten: &mut Vec<(GridPos, FoodKind)>,
+    ) -> bool {
+        match self {
+            Round::Play {
+                difficulty,
+                controllers,
+                record,
+                high_scores,
+                game,
+                replay,
+                ghost,
+                achievements,
+                stats,
+                events,
+                notice,
+                daily,
+                spectators,
+                ..
+            } => {
+                ticker.advance(elapsed);
+                while !game.is_over() && ticker.tick(game.tick_interval()) {
+                    let players = game.players().len();
+                    for (player, controller) in controllers.iter_mut().enumerate().take(players) {
+                        for direction in controller.next_moves(&BoardView::new(game, player)) {
+                            if let Some(replay) = replay {
+                                replay.record(game.ticks(), player, direction);
+                            }
+                            game.turn_player(player, direction);
+                        }
+                        if let Some(failure) = controller.failure() {
+                            eprintln!("Player {} stopped steering: {}", player + 1, failure);
+                            game.controller_failed(player);
+                        }
+                    }
+                    *last_step = Some(game.step());
+                    keep_up(ghost, game);
+                    if let Some(spectators) = spectators {
+                        spectators.publish(game);
+                    }
+
+                    let humans = humans(game, controllers);
+                    for event in events.borrow_mut().drain(..) {
+                        stats.record(game, &event, &humans);
+                        if matches!(event, GameEvent::LifeLost { .. } | GameEvent::Died { .. }) {
+                            *died = true;
+                        }
+                        if let GameEvent::Ate { pos, kind, .. } = event {
+                            eaten.push((pos, kind));
+                        }
+                        if let GameEvent::AchievementUnlocked { id, .. } = event {
+                            if achievements.unlock(id) {
+                                achievements.save();
+                                *notice = Achievement::find(id).map(|achievement| {
+                                    (achievement, game.ticks() + ACHIEVEMENT_NOTICE_TICKS)
+                                });
+                            }
+                        }
+                    }
+                }
+
+                if !game.is_over() {
+                    return false;
+                }
+                for controller in controllers.iter_mut() {
+                    controller.round_ended();
+                }
+                stats.save();
+
+                // A round that was rewound could have been played over until it went well.
+                if game.was_rewound() {
+                    return true;
+                }
+
+                // Daily rounds are only ranked against the same day's.
+                if let Some((challenge, records)) = daily {
+                    for player in humans(game, controllers) {
+                        records.record(challenge.date, game.players()[player].score());
+                    }
+                    records.save();
+                } else {
+                    let table = score_table(difficulty, game.config().mode);
+                    let mut new_best = false;
+                    for player in humans(game, controllers) {
+                        let score = game.players()[player].score();
+                        new_best |= high_scores.record(score, &table) == Some(0);
+                    }
+                    high_scores.save();
+
+                    if let (true, Some(path), Some(replay)) =
+                        (new_best, high_scores.best_run_path(&table), replay.as_ref())
+                    {
+                        if let Err(e) = replay.save(&path) {
+                            eprintln!("Failed to save the best run to {}: {}", path.display(), e);
+                        }
+                    }
+                }
+
+                if let (Some(path), Some(replay)) = (record.as_ref(), replay.as_ref()) {
+                    if let Err(e) = replay.save(path) {
+                        eprintln!("Failed to save replay to {}: {}", path.display(), e);
+                    }
+                }
+                true
+            }
+            Round::Watch { playback, .. } => {
+                ticker.advance(elapsed);
+                while !playback.is_over() && ticker.tick(playback.game().tick_interval()) {
+                    *last_step = Some(playback.step());
+                }
+                playback.is_over()
+            }
+            Round::Join { .. } => false,
+        }
+    }
+}
+
+impl Demo {
+    /// Feeds `elapsed` into the ticker and runs the ticks it has time for, starting a new round
+    /// once one is over.
+    fn run(&mut self, elapsed: Duration, ticker: &mut Ticker) {
+        ticker.advance(elapsed);
+        while ticker.tick(self.game.tick_interval()) {
+            for player in 0..self.game.players().len() {
+                let view = BoardView::new(&self.game, player);
+                for direction in GreedyAi.next_moves(&view) {
+                    self.game.turn_player(player, direction);
+                }
+            }
+            self.game.step();
+            if self.game.is_over() {
+                self.game = Game::with_config(self.config.clone());
+            }
+        }
+    }
+}
+
+impl Editing {
+    /// Picks a tool from the toolbar with the number keys, or takes back the last edit.
+    fn key_pressed(&mut self, key: VirtualKeyCode) {
+        let tool = match key {
+            VirtualKeyCode::Key1 => Some(0),
+            VirtualKeyCode::Key2 => Some(1),
+            VirtualKeyCode::Key3 => Some(2),
+            VirtualKeyCode::Key4 => Some(3),
+            VirtualKeyCode::Key5 => Some(4),
+            VirtualKeyCode::Key6 => Some(5),
+            _ => None,
+        };
+        if let Some(&tool) = tool.and_then(|i| Tool::ALL.get(i)) {
+            self.editor.set_tool(tool);
+        }
+        if matches!(key, VirtualKeyCode::Z | VirtualKeyCode::Back) {
+            self.editor.undo();
+        }
+    }
+
+    /// Paints the cell under the mouse, if a button is held down over the board.
+    fn paint(&mut self) {
+        if let (Some(erase), Some(cell)) = (self.held, self.cursor) {
+            if erase {
+                self.editor.erase(cell);
+            } else {
+                self.editor.paint(cell);
+            }
+        }
+    }
+
+    /// The toolbar with the current tool in brackets, how to use the editor, and what is wrong
+    /// with the level if playing it failed.
+    fn title(&self) -> String {
+        let tools = Tool::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, &tool)| {
+                if tool == self.editor.tool() {
+                    format!("[{} {}]", i + 1, tool.name())
+                } else {
+                    format!("{} {}", i + 1, tool.name())
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut title = format!(
+            " - Editing {}: {} - Z to undo, Enter to save and play",
+            self.path.display(),
+            tools.join(" ")
+        );
+        if let Some(error) = &self.error {
+            title += &format!(" - Can't play: {}", error);
+        }
+        title
+    }
+}
+
+/// Lets the last [`REWIND_TICKS`] of `game` be taken back if it is a casual round, played on
+/// easy or in zen mode.
+fn keep_history(game: &mut Game, difficulty: &Difficulty) {
+    if matches!(difficulty, Difficulty::Easy) || matches!(game.config().mode, GameMode::Zen) {
+        game.keep_history(REWIND_TICKS);
+    }
+}
+
+/// Builds the game for a new round, telling the terminal its seed so the round can be played
+/// again with `--seed`.
+fn new_game(config: &GameConfig) -> Game {
+    let game = Game::with_config(config.clone());
+    println!("Round seed: {}", game.seed());
+    game
+}
+
+/// The players of `game` steered from the keyboard.
+fn humans(game: &Game, controllers: &[Box<dyn Controller>]) -> Vec<usize> {
+    let players = game.players().len();
+    (0..players)
+        .filter(|&player| controllers.get(player).is_some_and(|c| c.is_human()))
+        .collect()
+}
+
+/// Has `game` track the achievements still locked for each keyboard player, and pass on
+/// everything that happens to `events`.
+fn watch(
+    game: &mut Game,
+    controllers: &[Box<dyn Controller>],
+    achievements: &Achievements,
+    events: &Rc<RefCell<Vec<GameEvent>>>,
+) {
+    for player in humans(game, controllers) {
+        game.track_achievements(player, achievements);
+    }
+
+    let events = Rc::clone(events);
+    game.on_event(move |event| events.borrow_mut().push(event));
+}
+
+/// Loads the best round from `path` to race against rounds built from `config`. A best round
+/// played on another board is left out, since the ghost would go through walls that aren't
+/// there.
+fn load_ghost(path: &Path, config: &GameConfig) -> Option<Playback> {
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(ReplayError::Io(e)) if e.kind() == io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            eprintln!(
+                "warning: could not load the best run from {}: {}",
+                path.display(),
+                e
+            );
+            return None;
+        }
+    };
+
+    let best = replay.config();
+    if (best.width, best.height) != (config.width, config.height) {
+        eprintln!(
+            "Not racing the best run: it was played on a {}x{} board, not {}x{}",
+            best.width, best.height, config.width, config.height
+        );
+        return None;
+    }
+    if best.wall_mode != config.wall_mode
+        || best.obstacles != config.obstacles
+        || best.portals != config.portals
+    {
+        eprintln!("Not racing the best run: it was played with other walls, obstacles or portals");
+        return None;
+    }
+    Some(replay.play())
+}
+
+/// The date of `challenge` for the title, with the final score and the best one of the day
+/// once `game` is over.
+fn daily_summary(challenge: &DailyChallenge, records: &DailyRecords, game: &Game) -> String {
+    let mode = game.config().mode;
+    let mut summary = format!(" - Daily challenge {}", challenge.date);
+    if game.is_over() {
+        summary += &format!(" - Final score: {}", mode.format_score(game.score()));
+    }
+    if let Some(best) = records.best(challenge.date) {
+        summary += &format!(" - Best today: {}", mode.format_score(best));
+    }
+    summary
+}
+
+/// Plays `ghost` up to the tick `game` is at, and drops it once its round is over.
+fn keep_up(ghost: &mut Option<Playback>, game: &Game) {
+    if let Some(playback) = ghost {
+        while !playback.is_over() && playback.game().ticks() < game.ticks() {
+            playback.step();
+        }
+        if playback.is_over() {
+            *ghost = None;
+        }
+    }
+}
+
+/// What high scores of a round on `difficulty` in `mode` are tagged with. Survival scores count
+/// seconds rather than food, and zen rounds can't be lost, so both are kept apart from the
+/// others.
+fn score_table(difficulty: &Difficulty, mode: GameMode) -> String {
+    match mode {
+        GameMode::Survival { .. } => format!("{} survival", difficulty.name()),
+        GameMode::Zen => format!("{} zen", difficulty.name()),
+        GameMode::Classic | GameMode::Timed { .. } => difficulty.name().to_string(),
+    }
+}
+
+/// Whether a round that ended with `end` ended with a snake dying, which is shown before the
+/// round is over.
+fn ends_in_death(end: RoundEnd) -> bool {
+    match end {
+        RoundEnd::Died | RoundEnd::Winner(_) | RoundEnd::Tie => true,
+        RoundEnd::TimeUp | RoundEnd::Won => false,
+    }
+}
+
+/// What the guest is told about `state`. Whatever the host does away from the round is the
+/// menu to the guest, and a snake dying is still the round being played.
+fn phase(state: AppState) -> Phase {
+    match state {
+        AppState::Countdown(left) => Phase::Countdown(left.as_secs_f32().ceil() as u32),
+        AppState::Playing | AppState::Dying(_) => Phase::Playing,
+        AppState::Paused => Phase::Paused,
+        AppState::GameOver => Phase::GameOver,
+        AppState::Tutorial | AppState::Menu | AppState::Demo | AppState::Editor => Phase::Menu,
+    }
+}
+
+/// The state a guest is in while the host is in `phase`.
+fn phase_state(phase: Phase) -> AppState {
+    match phase {
+        Phase::Menu => AppState::Menu,
+        Phase::Countdown(seconds) => AppState::Countdown(Duration::from_secs(seconds.into())),
+        Phase::Playing => AppState::Playing,
+        Phase::Paused => AppState::Paused,
+        Phase::GameOver => AppState::GameOver,
+    }
+}
+
+/// The [`Key`] standing for an [`AppEvent`] that a key of the window is, if any.
+fn app_key(key: VirtualKeyCode) -> Option<Key> {
+    match key {
+        VirtualKeyCode::Return => Some(Key::Enter),
+        VirtualKeyCode::Space => Some(Key::Space),
+        VirtualKeyCode::R => Some(Key::R),
+        VirtualKeyCode::Escape => Some(Key::Escape),
+        VirtualKeyCode::Tab => Some(Key::Tab),
+        VirtualKeyCode::Back => Some(Key::Backspace),
+        _ => None,
+    }
+}
+
+/// Which player a key steers and in which direction. Player one uses the arrow keys and player
+/// two WASD.
+fn key_direction(key: VirtualKeyCode) -> Option<(usize, Direction)> {
+    match key {
+        VirtualKeyCode::Up => Some((0, Direction::Up)),
+        VirtualKeyCode::Down => Some((0, Direction::Down)),
+        VirtualKeyCode::Left => Some((0, Direction::Left)),
+        VirtualKeyCode::Right => Some((0, Direction::Right)),
+        VirtualKeyCode::W => Some((1, Direction::Up)),
+        VirtualKeyCode::S => Some((1, Direction::Down)),
+        VirtualKeyCode::A => Some((1, Direction::Left)),
+        VirtualKeyCode::D => Some((1, Direction::Right)),
+        _ => None,
+    }
+}
@@ -0,0 +1,104 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::background::BackgroundMode;
+use crate::palette::{CellShape, Palette};
+
+/// The player's choices about how the game looks, kept in a file next to the high scores so
+/// they carry over from one launch to the next.
+///
+/// Like with [`HighScores`](crate::game::HighScores), problems with the file are only warned
+/// about.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    pub palette: Palette,
+    /// The shape the cells are drawn in, going with the palette.
+    #[serde(default)]
+    pub cells: CellShape,
+    /// Whether lines are drawn between the cells of the board.
+    #[serde(default)]
+    pub grid: bool,
+    /// Whether snakes slide from cell to cell between ticks, rather than jumping.
+    #[serde(default)]
+    pub smooth: bool,
+    /// Whether the background slowly changes color.
+    #[serde(default)]
+    pub background: BackgroundMode,
+    /// Whether things that move only for looks are kept still, such as the pulsing of food.
+    #[serde(default)]
+    pub reduced_motion: bool,
+    /// Whether the window fills the screen.
+    #[serde(default)]
+    pub fullscreen: bool,
+    /// Whether cells are drawn a whole number of pixels wide, leaving wider bars around the
+    /// board rather than seams between cells.
+    #[serde(default)]
+    pub integer_scaling: bool,
+}
+
+impl Settings {
+    /// Loads the settings from the user's data directory.
+    pub fn load() -> Settings {
+        match dirs::data_dir() {
+            Some(dir) => Settings::load_from(dir.join("snake_game").join("settings.json")),
+            None => {
+                eprintln!("warning: no data directory, settings won't be kept");
+                Settings::default()
+            }
+        }
+    }
+
+    /// Loads the settings from `path`, which is also where [`Settings::save`] writes them. A
+    /// missing file means everything is left as it comes.
+    pub fn load_from(path: impl Into<PathBuf>) -> Settings {
+        let path = path.into();
+        let mut settings = match read(&path) {
+            Ok(settings) => settings,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Settings::default(),
+            Err(e) => {
+                eprintln!(
+                    "warning: could not read settings from {}: {}",
+                    path.display(),
+                    e
+                );
+                Settings::default()
+            }
+        };
+
+        settings.path = Some(path);
+        settings
+    }
+
+    pub fn save(&self) {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if let Err(e) = write(self, path) {
+            eprintln!(
+                "warning: could not save settings to {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
+fn read(path: &Path) -> io::Result<Settings> {
+    let source = fs::read_to_string(path)?;
+    serde_json::from_str(&source).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn write(settings: &Settings, path: &Path) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string(settings)?)
+}
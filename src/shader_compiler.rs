@@ -0,0 +1,239 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use shaderc::{Compiler, ShaderKind};
+
+use crate::hot_reload::{shader_module, spirv_words, ReloadError};
+use crate::pipelines::PipelineRegistry;
+
+/// Why GLSL shaders couldn't be drawn with. The ones built into the game are drawn with
+/// instead.
+#[derive(Debug)]
+pub enum CompileError {
+    /// A shader couldn't be read.
+    Io(PathBuf, io::Error),
+    /// The compiler couldn't be started.
+    NoCompiler,
+    /// A shader has a nul character in it, which the compiler can't take.
+    Nul(String),
+    /// A shader didn't compile. The message says which file and line went wrong.
+    Glsl(shaderc::Error),
+    /// The compiled shaders didn't make a pipeline the game can draw with.
+    Pipeline(ReloadError),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::Io(path, err) => write!(f, "could not read {}: {}", path.display(), err),
+            CompileError::NoCompiler => write!(f, "could not start the shader compiler"),
+            CompileError::Nul(name) => write!(f, "{} has a nul character in it", name),
+            CompileError::Glsl(err) => write!(f, "{}", err),
+            CompileError::Pipeline(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for CompileError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CompileError::Io(_, err) => Some(err),
+            CompileError::Glsl(err) => Some(err),
+            CompileError::Pipeline(err) => Some(err),
+            CompileError::NoCompiler | CompileError::Nul(_) => None,
+        }
+    }
+}
+
+impl From<shaderc::Error> for CompileError {
+    fn from(err: shaderc::Error) -> CompileError {
+        CompileError::Glsl(err)
+    }
+}
+
+impl From<ReloadError> for CompileError {
+    fn from(err: ReloadError) -> CompileError {
+        CompileError::Pipeline(err)
+    }
+}
+
+/// Compiles the GLSL `source` of a shader of `kind` into SPIR-V words. `name` stands for the
+/// file the source came from in the errors, which say the line that went wrong.
+///
+/// ```
+/// use shaderc::ShaderKind;
+/// use snake_game::shader_compiler::compile;
+///
+/// let good = "#version 450
+/// layout(location = 0) out vec4 f_color;
+/// void main() { f_color = vec4(1.0); }
+/// ";
+/// let words = compile(good, "good.frag", ShaderKind::Fragment).unwrap();
+/// assert_eq!(words[0], 0x0723_0203);
+///
+/// let bad = "#version 450
+/// void main() { f_color = vec4(1.0); }
+/// ";
+/// let err = compile(bad, "bad.frag", ShaderKind::Fragment).unwrap_err();
+/// assert!(err.to_string().contains("bad.frag:2"), "{}", err);
+///
+/// // Not even what the compiler can't be handed at all makes it panic.
+/// assert!(compile("#version 450\0", "nul.frag", ShaderKind::Fragment).is_err());
+/// ```
+pub fn compile(source: &str, name: &str, kind: ShaderKind) -> Result<Vec<u32>, CompileError> {
+    if source.contains('\0') || name.contains('\0') {
+        return Err(CompileError::Nul(name.to_string()));
+    }
+    let mut compiler = Compiler::new().ok_or(CompileError::NoCompiler)?;
+    let artifact = compiler.compile_into_spirv(source, kind, name, "main", None)?;
+    Ok(artifact.as_binary().to_vec())
+}
+
+/// A hash of `source` as a shader of `kind` that comes out the same whatever machine and
+/// build of the game works it out, unlike the standard library's: 64 bit FNV-1a.
+///
+/// ```
+/// use shaderc::ShaderKind;
+/// use snake_game::shader_compiler::source_hash;
+///
+/// let source = "void main() {}";
+/// assert_eq!(source_hash(source, ShaderKind::Vertex), source_hash(source, ShaderKind::Vertex));
+/// assert_ne!(source_hash(source, ShaderKind::Vertex), source_hash(source, ShaderKind::Fragment));
+/// assert_ne!(source_hash(source, ShaderKind::Vertex), source_hash("", ShaderKind::Vertex));
+/// ```
+pub fn source_hash(source: &str, kind: ShaderKind) -> u64 {
+    let kind = kind as u8;
+    std::iter::once(&kind)
+        .chain(source.as_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+}
+
+/// Where shaders compiled before are kept, so they aren't compiled again every time the game
+/// starts. Each is kept under the [`source_hash`] of its source, so one that changed is
+/// compiled again.
+///
+/// ```
+/// use shaderc::ShaderKind;
+/// use snake_game::shader_compiler::ShaderCache;
+///
+/// let dir = std::env::temp_dir().join(format!("snake-shader-cache-{}", std::process::id()));
+/// let cache = ShaderCache::in_dir(&dir);
+/// let source = "#version 450
+/// layout(location = 0) out vec4 f_color;
+/// void main() { f_color = vec4(1.0); }
+/// ";
+/// let words = cache.compile(source, "a.frag", ShaderKind::Fragment).unwrap();
+/// assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+///
+/// // The second time, it comes out of the cache.
+/// assert_eq!(cache.compile(source, "a.frag", ShaderKind::Fragment).unwrap(), words);
+/// assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+///
+/// // Shaders that don't compile aren't kept.
+/// assert!(cache.compile("void", "b.frag", ShaderKind::Fragment).is_err());
+/// assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShaderCache {
+    /// `None` when there is nowhere to keep them, so they are compiled every time.
+    dir: Option<PathBuf>,
+}
+
+impl ShaderCache {
+    /// The cache in the user's cache directory.
+    pub fn new() -> ShaderCache {
+        let dir = dirs::cache_dir().map(|dir| dir.join("snake_game").join("shaders"));
+        if dir.is_none() {
+            eprintln!("warning: no cache directory, shaders will be compiled every time");
+        }
+        ShaderCache { dir }
+    }
+
+    /// A cache kept in `dir`, made when the first shader goes into it.
+    pub fn in_dir(dir: impl Into<PathBuf>) -> ShaderCache {
+        ShaderCache {
+            dir: Some(dir.into()),
+        }
+    }
+
+    /// The SPIR-V words of the GLSL `source`, the way [`compile`] compiles them: out of the
+    /// cache if they are in it, and put in it otherwise. A cache that can't be written to is
+    /// only warned about.
+    pub fn compile(
+        &self,
+        source: &str,
+        name: &str,
+        kind: ShaderKind,
+    ) -> Result<Vec<u32>, CompileError> {
+        let path = match &self.dir {
+            Some(dir) => dir.join(format!("{:016x}.spv", source_hash(source, kind))),
+            None => return compile(source, name, kind),
+        };
+        if let Some(words) = fs::read(&path).ok().and_then(|bytes| spirv_words(&bytes)) {
+            return Ok(words);
+        }
+
+        let words = compile(source, name, kind)?;
+        let bytes: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let written =
+            fs::create_dir_all(path.parent().unwrap()).and_then(|()| fs::write(&path, bytes));
+        if let Err(err) = written {
+            eprintln!(
+                "warning: could not cache {} in {}: {}",
+                name,
+                path.display(),
+                err
+            );
+        }
+        Ok(words)
+    }
+}
+
+impl Default for ShaderCache {
+    fn default() -> ShaderCache {
+        ShaderCache::new()
+    }
+}
+
+/// Compiles the GLSL shader at `path`, of `kind`, through `cache`.
+fn compile_file(
+    path: &Path,
+    kind: ShaderKind,
+    cache: &ShaderCache,
+) -> Result<Vec<u32>, CompileError> {
+    let source =
+        fs::read_to_string(path).map_err(|err| CompileError::Io(path.to_path_buf(), err))?;
+    cache.compile(&source, &path.display().to_string(), kind)
+}
+
+/// Builds every pipeline in `pipelines` again with the GLSL shaders at `vert` and `frag`. They
+/// are kept as they are, drawing with the shaders built into the game, and what went wrong
+/// logged, unless all of them can be built.
+pub fn use_file_shaders(pipelines: &mut PipelineRegistry, [vert, frag]: [&Path; 2]) {
+    let cache = ShaderCache::new();
+    let compiled = compile_file(vert, ShaderKind::Vertex, &cache)
+        .and_then(|vs| Ok([vs, compile_file(frag, ShaderKind::Fragment, &cache)?]))
+        .and_then(|[vs, fs]| {
+            let device = pipelines.device();
+            let shaders = [
+                shader_module(device, vert, &vs)?,
+                shader_module(device, frag, &fs)?,
+            ];
+            pipelines
+                .set_shaders(Some(shaders))
+                .map_err(|err| CompileError::Pipeline(err.into()))
+        });
+    match compiled {
+        Ok(()) => println!("Drawing with {} and {}", vert.display(), frag.display()),
+        Err(err) => eprintln!(
+            "warning: drawing with the shaders built into the game instead: {}",
+            err
+        ),
+    }
+}
@@ -0,0 +1,42 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    vec4 color;
+    vec2 offset;
+    vec2 scale;
+    float time;
+    float depth;
+    float corner_radius;
+    float softness;
+} pc;
+
+layout(location = 0) in vec4 v_color;
+layout(location = 1) in vec2 v_tex_coords;
+layout(location = 2) in vec2 v_local;
+layout(location = 3) flat in vec2 v_shape;
+
+layout(set = 1, binding = 0) uniform sampler2D sprite;
+
+layout(location = 0) out vec4 f_color;
+
+// How much of the quad covers the fragment at `local` in its cell, with its corners rounded
+// off by `shape`: 1 inside, 0 outside, and in between over a soft edge. The same as
+// CellShape::coverage in palette.rs.
+float coverage(vec2 local, vec2 shape) {
+    float radius = shape.x;
+    vec2 q = abs(local - 0.5) - (0.5 - radius);
+    float outside = length(max(q, 0.0)) + min(max(q.x, q.y), 0.0) - radius;
+    return shape.y > 0.0 ? clamp(-outside / shape.y, 0.0, 1.0) : step(outside, 0.0);
+}
+
+void main() {
+    // Negative texture coordinates leave the color as it is.
+    vec4 texel = v_tex_coords.x < 0.0 ? vec4(1.0) : texture(sprite, v_tex_coords);
+    f_color = v_color * texel * pc.color;
+    f_color.a *= coverage(v_local, v_shape);
+    // Whatever can't be seen is left out of the depth buffer too, so it doesn't hide what is
+    // drawn behind it afterwards.
+    if (f_color.a == 0.0) {
+        discard;
+    }
+}
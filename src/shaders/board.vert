@@ -0,0 +1,51 @@
+#version 450
+
+layout(location = 0) in vec2 position;
+layout(location = 1) in vec4 color;
+layout(location = 2) in vec2 tex_coords;
+// One for food, which pulses and is round, and zero for everything else.
+layout(location = 3) in uint quad_kind;
+// Where the corner is in its cell, or the middle of it for quads that aren't cells.
+layout(location = 4) in vec2 local;
+
+layout(set = 0, binding = 0) uniform Projection {
+    vec2 scale;
+    vec2 offset;
+} projection;
+
+layout(push_constant) uniform PushConstants {
+    vec4 color;
+    vec2 offset;
+    vec2 scale;
+    // Seconds into the pulse, starting over every period.
+    float time;
+    // How far back the layer is drawn, from 0 at the front to 1 at the back.
+    float depth;
+    // How far in from the sides of a cell its corners are rounded from, and how wide its edge
+    // fades out over, both as shares of its side.
+    float corner_radius;
+    float softness;
+} pc;
+
+layout(location = 0) out vec4 v_color;
+layout(location = 1) out vec2 v_tex_coords;
+layout(location = 2) out vec2 v_local;
+// The corner radius and the softness of the quad.
+layout(location = 3) flat out vec2 v_shape;
+
+// The same as PULSE_PERIOD and PULSE_STRENGTH in pulse.rs.
+const float PULSE_PERIOD = 1.2;
+const float PULSE_STRENGTH = 0.2;
+const float TAU = 6.28318531;
+
+void main() {
+    vec2 cell = position * pc.scale + pc.offset;
+    gl_Position = vec4(cell * projection.scale + projection.offset, pc.depth, 1.0);
+    float pulse = quad_kind == 1u ? 1.0 + PULSE_STRENGTH * sin(pc.time / PULSE_PERIOD * TAU) : 1.0;
+    v_color = vec4(color.rgb * pulse, color.a);
+    v_tex_coords = tex_coords;
+    v_local = local;
+    // Food is round, unless the cells are square.
+    float radius = quad_kind == 1u && pc.corner_radius > 0.0 ? 0.5 : pc.corner_radius;
+    v_shape = vec2(radius, pc.softness);
+}
@@ -0,0 +1,240 @@
+use std::io::{self, BufWriter, Write};
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::{Direction, Game, GridPos, RoundEnd};
+
+/// The port spectating listens on when none is given.
+pub const DEFAULT_SPECTATE_PORT: u16 = 7879;
+
+/// How many snapshots can wait for a spectator that is slow to read them. The ones that come
+/// after that are dropped for it, until it catches up.
+pub const SPECTATOR_BACKLOG: usize = 8;
+
+/// The round as spectators get it, once every tick, each as a line of JSON:
+///
+/// ```json
+/// {"tick":12,"width":20,"height":20,"over":null,
+///  "snakes":[{"cells":[{"x":5,"y":3},{"x":4,"y":3}],"direction":"Right","alive":true,
+///             "score":3,"lives":1}],
+///  "foods":[{"x":12,"y":7,"poison":false}],
+///  "obstacles":[{"x":0,"y":0}]}
+/// ```
+///
+/// Every snake's `cells` go from the head to the tail, counted from the top left corner, and
+/// `over` says why the round ended once it did.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub tick: u64,
+    pub width: u32,
+    pub height: u32,
+    pub over: Option<RoundEnd>,
+    pub snakes: Vec<SnapshotSnake>,
+    pub foods: Vec<SnapshotFood>,
+    pub obstacles: Vec<GridPos>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSnake {
+    pub cells: Vec<GridPos>,
+    pub direction: Direction,
+    pub alive: bool,
+    pub score: u32,
+    pub lives: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFood {
+    pub x: i32,
+    pub y: i32,
+    pub poison: bool,
+}
+
+impl Snapshot {
+    /// `game` as it stands.
+    pub fn of(game: &Game) -> Snapshot {
+        Snapshot {
+            tick: game.ticks(),
+            width: game.width(),
+            height: game.height(),
+            over: game.round_end(),
+            snakes: game
+                .players()
+                .iter()
+                .map(|player| SnapshotSnake {
+                    cells: player.snake().cells().copied().collect(),
+                    direction: player.snake().direction(),
+                    alive: player.is_alive(),
+                    score: player.score(),
+                    lives: player.lives(),
+                })
+                .collect(),
+            foods: game
+                .foods()
+                .iter()
+                .map(|food| SnapshotFood {
+                    x: food.pos.x,
+                    y: food.pos.y,
+                    poison: food.is_poison(),
+                })
+                .collect(),
+            obstacles: game.obstacles().iter().copied().collect(),
+        }
+    }
+}
+
+/// Hands out a [`Snapshot`] of the round to whoever connects to watch it, for overlays and the
+/// like. Anyone can connect, any number of times; they are only ever sent to and never listened
+/// to.
+///
+/// Publishing never waits on a spectator: each one is written to on a thread of its own, which
+/// is handed the snapshots through a queue [`SPECTATOR_BACKLOG`] long. A spectator that reads
+/// slower than the round goes skips the snapshots that don't fit, and one that hangs up is
+/// forgotten. With nobody watching, publishing doesn't even take the snapshot.
+///
+/// ```
+/// use std::io::{BufRead, BufReader};
+/// use std::net::TcpStream;
+/// use std::sync::mpsc;
+/// use std::thread;
+/// use std::time::Duration;
+///
+/// use snake_game::game::{Game, GameConfig, GameMode, WallMode};
+/// use snake_game::spectate::{Snapshot, Spectators};
+///
+/// let mut spectators = Spectators::listen("127.0.0.1:0").unwrap();
+/// let stream = TcpStream::connect(spectators.local_addr()).unwrap();
+/// stream.set_read_timeout(Some(Duration::from_millis(50))).unwrap();
+/// let mut lines = BufReader::new(stream);
+///
+/// // A round that goes on for as long as it is stepped.
+/// let config = GameConfig::new(20, 20)
+///     .with_mode(GameMode::Zen)
+///     .with_wall_mode(WallMode::Wrap);
+/// let mut game = Game::with_config(config);
+/// // The spectator is only taken in once the listening thread got to it.
+/// let mut line = String::new();
+/// while line.is_empty() {
+///     spectators.publish(&game);
+///     lines.read_line(&mut line).ok();
+/// }
+/// let snapshot: Snapshot = serde_json::from_str(&line).unwrap();
+/// assert_eq!(snapshot, Snapshot::of(&game));
+/// assert_eq!(snapshot.snakes[0].cells.len(), game.players()[0].snake().len());
+///
+/// // From now on the spectator reads every snapshot as it comes.
+/// lines.get_ref().set_read_timeout(None).unwrap();
+/// let (sender, snapshots) = mpsc::channel();
+/// thread::spawn(move || {
+///     let mut line = String::new();
+///     while lines.read_line(&mut line).is_ok_and(|read| read > 0) {
+///         let snapshot: Snapshot = serde_json::from_str(&line).unwrap();
+///         if sender.send(snapshot).is_err() {
+///             break;
+///         }
+///         line.clear();
+///     }
+/// });
+///
+/// // Someone who never reads doesn't hold the round up, they just miss snapshots. The one
+/// // still reading is waited for after every snapshot, so it never falls behind, and it gets
+/// // each of them, down to the latest.
+/// let _asleep = TcpStream::connect(spectators.local_addr()).unwrap();
+/// while spectators.dropped() == 0 {
+///     game.step();
+///     spectators.publish(&game);
+///     let mut snapshot = snapshots.recv_timeout(Duration::from_secs(5)).unwrap();
+///     // Snapshots sent while it was being taken in may still be on their way.
+///     while snapshot.tick < game.ticks() {
+///         snapshot = snapshots.recv_timeout(Duration::from_secs(5)).unwrap();
+///     }
+///     assert_eq!(snapshot, Snapshot::of(&game));
+/// }
+/// ```
+pub struct Spectators {
+    address: SocketAddr,
+    /// The spectators that connected, each as the queue to its writing thread.
+    joined: Receiver<SyncSender<Arc<String>>>,
+    watching: Vec<SyncSender<Arc<String>>>,
+    dropped: u64,
+}
+
+impl Spectators {
+    /// Starts listening on `addr` for spectators, on a thread of its own.
+    pub fn listen(addr: impl ToSocketAddrs) -> io::Result<Spectators> {
+        let listener = TcpListener::bind(addr)?;
+        let address = listener.local_addr()?;
+        let (sender, joined) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                let (queue, snapshots) = mpsc::sync_channel::<Arc<String>>(SPECTATOR_BACKLOG);
+                thread::spawn(move || {
+                    let mut writer = BufWriter::new(stream);
+                    for snapshot in snapshots {
+                        let written = writer.write_all(snapshot.as_bytes());
+                        if written.and_then(|()| writer.flush()).is_err() {
+                            break;
+                        }
+                    }
+                });
+                if sender.send(queue).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Spectators {
+            address,
+            joined,
+            watching: Vec::new(),
+            dropped: 0,
+        })
+    }
+
+    /// Where it listens, with the port picked if it was asked to listen on port 0.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// How many snapshots were skipped for spectators that fell behind, all of them together.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Sends a snapshot of `game` to everyone watching.
+    pub fn publish(&mut self, game: &Game) {
+        while let Ok(queue) = self.joined.try_recv() {
+            self.watching.push(queue);
+        }
+        if self.watching.is_empty() {
+            return;
+        }
+
+        let mut line = match serde_json::to_string(&Snapshot::of(game)) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("Failed to write a snapshot for the spectators: {}", e);
+                return;
+            }
+        };
+        line.push('\n');
+        let line = Arc::new(line);
+        let dropped = &mut self.dropped;
+        self.watching
+            .retain(|queue| match queue.try_send(Arc::clone(&line)) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    *dropped += 1;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+    }
+}
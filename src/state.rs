@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use crate::death::DEATH_ANIMATION;
+
+/// How long the countdown before the game starts or resumes ticking lasts.
+pub const COUNTDOWN: Duration = Duration::from_secs(3);
+
+/// Where the application is at, which decides what input does and whether the game ticks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppState {
+    /// Walking a new player through the game on boards of its own, see
+    /// [`Tutorial`](crate::game::Tutorial). Leads to the menu once it is over or skipped.
+    Tutorial,
+    /// Waiting for the player to start. The board is shown but nothing moves.
+    Menu,
+    /// The menu left alone for a while, with a round the computer plays drawn dimmed behind
+    /// it. Any input goes back to the menu.
+    Demo,
+    /// Counting down to the game ticking, with this much time left. The board is shown and the
+    /// keys pressed are queued up for the snakes, but nothing moves yet.
+    Countdown(Duration),
+    /// The game ticks and the snakes can be steered.
+    Playing,
+    /// The game is frozen and drawn dimmed.
+    Paused,
+    /// The round ended with a snake dying, which is shown flashing and crumbling away for this
+    /// much longer, see [`segment_state`](crate::death::segment_state). Only leaving does
+    /// anything, and any other key skips to the end.
+    Dying(Duration),
+    /// The round ended, waiting for a restart.
+    GameOver,
+    /// Drawing a level with the mouse, see [`Editor`](crate::game::Editor). Starting saves the
+    /// level and plays it.
+    Editor,
+}
+
+/// Something that may move the application to another state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppEvent {
+    Start,
+    TogglePause,
+    /// Sent once the countdown has run out.
+    CountdownOver,
+    /// Sent when the window stops getting the keys pressed.
+    FocusLost,
+    /// Sent by the game when the current round ends.
+    RoundOver,
+    /// Sent by the game when the current round ends with a snake dying, instead of
+    /// [`AppEvent::RoundOver`].
+    SnakeDied,
+    /// Sent once a snake is done dying.
+    DyingOver,
+    /// Sent on any key but the one to leave while a snake is dying.
+    Skip,
+    /// Sent once the last step of the tutorial is done.
+    TutorialOver,
+    Restart,
+    /// Leave for the menu, giving up on the current round, or skipping the tutorial.
+    Quit,
+    /// Go back to editing the level, giving up on the current round.
+    Edit,
+    /// Sent once the menu has been left alone for long enough.
+    Idle,
+    /// Sent on any key or mouse input while the demo plays.
+    Wake,
+    /// Take back the latest tick, in the rounds that allow it, and stay paused there.
+    Rewind,
+    /// Sent when the other player of a round over the network is gone.
+    Disconnected,
+}
+
+/// The keys that stand for an [`AppEvent`], whatever the window calls them. The window maps
+/// the keys it gets to these, and steers the snakes with the others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Enter,
+    Space,
+    R,
+    Escape,
+    Tab,
+    Backspace,
+}
+
+impl Key {
+    /// The event the key stands for.
+    pub fn event(self) -> AppEvent {
+        match self {
+            Key::Enter => AppEvent::Start,
+            Key::Space => AppEvent::TogglePause,
+            Key::R => AppEvent::Restart,
+            Key::Escape => AppEvent::Quit,
+            Key::Tab => AppEvent::Edit,
+            Key::Backspace => AppEvent::Rewind,
+        }
+    }
+}
+
+impl AppState {
+    /// The state `event` leads to from this one, or `None` if the event means nothing here.
+    /// Every transition of the application goes through this.
+    ///
+    /// ```
+    /// use snake_game::state::{AppEvent, AppState, Key, COUNTDOWN};
+    ///
+    /// // The menu waits for the start: the arrow keys don't steer anything, and neither pausing
+    /// // nor restarting means anything there.
+    /// let menu = AppState::Menu;
+    /// assert!(!menu.is_steering());
+    /// assert_eq!(menu.update(Key::Space.event()), None);
+    /// assert_eq!(menu.update(Key::R.event()), None);
+    /// assert_eq!(menu.update(Key::Enter.event()), Some(AppState::Countdown(COUNTDOWN)));
+    ///
+    /// // A round that is over restarts with a countdown the snakes can be steered during, then
+    /// // plays, or goes back to the menu.
+    /// let restarted = AppState::GameOver.update(Key::R.event()).unwrap();
+    /// assert_eq!(restarted, AppState::Countdown(COUNTDOWN));
+    /// assert!(restarted.is_steering() && !restarted.is_running());
+    /// assert_eq!(restarted.update(AppEvent::CountdownOver), Some(AppState::Playing));
+    /// assert_eq!(AppState::GameOver.update(Key::Escape.event()), Some(AppState::Menu));
+    /// ```
+    pub fn update(self, event: AppEvent) -> Option<AppState> {
+        match (self, event) {
+            (AppState::Tutorial, AppEvent::TutorialOver | AppEvent::Quit) => Some(AppState::Menu),
+            (AppState::Menu, AppEvent::Start) => Some(AppState::Countdown(COUNTDOWN)),
+            (AppState::Menu, AppEvent::Idle) => Some(AppState::Demo),
+            (AppState::Demo, AppEvent::Wake) => Some(AppState::Menu),
+            (AppState::Countdown(_), AppEvent::CountdownOver) => Some(AppState::Playing),
+            // Whoever was looking away would miss the start, so the countdown starts over.
+            (AppState::Countdown(_), AppEvent::FocusLost) => Some(AppState::Countdown(COUNTDOWN)),
+            (AppState::Countdown(_) | AppState::Playing, AppEvent::TogglePause) => {
+                Some(AppState::Paused)
+            }
+            (AppState::Playing, AppEvent::RoundOver) => Some(AppState::GameOver),
+            (AppState::Playing, AppEvent::SnakeDied) => Some(AppState::Dying(DEATH_ANIMATION)),
+            (AppState::Dying(_), AppEvent::DyingOver | AppEvent::Skip) => Some(AppState::GameOver),
+            (AppState::Paused, AppEvent::TogglePause) => Some(AppState::Countdown(COUNTDOWN)),
+            (AppState::GameOver, AppEvent::Start | AppEvent::Restart) => {
+                Some(AppState::Countdown(COUNTDOWN))
+            }
+            (AppState::Paused | AppState::Dying(_) | AppState::GameOver, AppEvent::Quit) => {
+                Some(AppState::Menu)
+            }
+            (AppState::Playing | AppState::Paused | AppState::GameOver, AppEvent::Rewind) => {
+                Some(AppState::Paused)
+            }
+            (
+                AppState::Countdown(_)
+                | AppState::Playing
+                | AppState::Paused
+                | AppState::Dying(_)
+                | AppState::GameOver,
+                AppEvent::Disconnected,
+            ) => Some(AppState::Menu),
+            (AppState::Editor, AppEvent::Start) => Some(AppState::Countdown(COUNTDOWN)),
+            (AppState::Menu | AppState::Paused | AppState::GameOver, AppEvent::Edit) => {
+                Some(AppState::Editor)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the game ticks in this state.
+    pub fn is_running(self) -> bool {
+        self == AppState::Playing
+    }
+
+    /// Whether the keys pressed steer the snakes in this state.
+    pub fn is_steering(self) -> bool {
+        matches!(self, AppState::Countdown(_) | AppState::Playing)
+    }
+}
@@ -0,0 +1,215 @@
+use crate::layout::Bounds;
+use crate::mesh::{Mesh, TexRect};
+
+/// How many of its texels a glyph is drawn over, on a screen that doesn't scale things up.
+pub const TEXT_SCALE: f32 = 3.0;
+
+/// How far text stays from the edges of what it is laid out in, in texels of the font.
+const MARGIN: f32 = 3.0;
+
+/// A bitmap font: an image of `image` texels cut into cells of `glyph` texels, row after row,
+/// one for each character from `first` on. Each cell has the blank space that goes after its
+/// glyph and below it, so they can be drawn right next to each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Font {
+    pub image: [u32; 2],
+    pub glyph: [u32; 2],
+    pub first: char,
+    pub count: u32,
+}
+
+impl Font {
+    /// The font the game comes with, `sprites/font.png`: the characters from the space to the
+    /// underscore, which take in the digits, the capitals and the usual punctuation.
+    pub const BUNDLED: Font = Font {
+        image: [96, 32],
+        glyph: [6, 8],
+        first: ' ',
+        count: 64,
+    };
+
+    /// Where the glyph for `c` is in the image. Lowercase letters are drawn as capitals, and
+    /// anything else the font doesn't have as a question mark.
+    pub fn glyph(&self, c: char) -> TexRect {
+        let index = |c: char| {
+            (c as u32)
+                .checked_sub(self.first as u32)
+                .filter(|&index| index < self.count)
+        };
+        let index = index(c.to_ascii_uppercase())
+            .or_else(|| index('?'))
+            .unwrap_or(0);
+
+        let columns = self.image[0] / self.glyph[0];
+        let (column, row) = (index % columns, index / columns);
+        TexRect {
+            origin: [
+                (column * self.glyph[0]) as f32 / self.image[0] as f32,
+                (row * self.glyph[1]) as f32 / self.image[1] as f32,
+            ],
+            extent: [
+                self.glyph[0] as f32 / self.image[0] as f32,
+                self.glyph[1] as f32 / self.image[1] as f32,
+            ],
+            transposed: false,
+        }
+    }
+}
+
+/// The corner of the [`Bounds`] text is laid out in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// A character laid out on the window: `position` is its top left corner and `size` how big it
+/// is, both in pixels, and `tex` where its glyph is in the font.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Glyph {
+    pub position: [f32; 2],
+    pub size: [f32; 2],
+    pub tex: TexRect,
+}
+
+/// Lays text out in a part of the window in a [`Font`], as quads measured in pixels from the
+/// window's top left corner, apart from the board and wherever it is drawn.
+///
+/// Each texel of the font covers a whole number of pixels, [`TEXT_SCALE`] times the window's
+/// scale factor, so that the glyphs stay crisp. Text that wouldn't fit across its bounds is
+/// drawn smaller, down to one pixel a texel.
+///
+/// ```
+/// use snake_game::layout::Bounds;
+/// use snake_game::text::{Corner, Font, TextRenderer};
+///
+/// let text = TextRenderer::new(Font::BUNDLED, 2.0);
+/// let window = Bounds::window([800, 600]);
+/// let glyphs = text.layout("Hi 7", Corner::TopLeft, window, 1.0);
+/// // The space takes room, but has nothing to draw.
+/// assert_eq!(glyphs.len(), 3);
+/// assert_eq!(glyphs[0].position, [6.0, 6.0]);
+/// assert_eq!(glyphs[0].size, [12.0, 16.0]);
+/// assert_eq!(glyphs[1].position, [18.0, 6.0]);
+/// assert_eq!(glyphs[2].position, [42.0, 6.0]);
+/// // Lowercase letters are drawn as capitals.
+/// assert_eq!(glyphs[1].tex, Font::BUNDLED.glyph('I'));
+///
+/// // On the right, each line ends at the margin.
+/// let glyphs = text.layout("12\n3", Corner::TopRight, window, 1.0);
+/// assert_eq!(glyphs[1].position, [800.0 - 6.0 - 12.0, 6.0]);
+/// assert_eq!(glyphs[2].position, [800.0 - 6.0 - 12.0, 22.0]);
+///
+/// // A screen that scales things up by half again draws each texel over three pixels...
+/// let glyphs = text.layout("1", Corner::BottomLeft, window, 1.5);
+/// assert_eq!(glyphs[0].size, [18.0, 24.0]);
+/// assert_eq!(glyphs[0].position, [9.0, 600.0 - 9.0 - 24.0]);
+/// // ...unless the text wouldn't fit.
+/// let glyphs = text.layout("12345", Corner::TopLeft, Bounds::window([40, 30]), 1.0);
+/// assert_eq!(glyphs[0].size, [6.0, 8.0]);
+///
+/// // Laid out over the board, it keeps to the corner of the board.
+/// let board = Bounds {
+///     origin: [100.0, 0.0],
+///     size: [600.0, 600.0],
+/// };
+/// let glyphs = text.layout("1", Corner::TopRight, board, 1.0);
+/// assert_eq!(glyphs[0].position, [700.0 - 6.0 - 12.0, 6.0]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextRenderer {
+    font: Font,
+    scale: f32,
+}
+
+impl TextRenderer {
+    /// Lays text out in `font`, each of its texels `scale` pixels wide before the window's
+    /// scale factor.
+    pub fn new(font: Font, scale: f32) -> TextRenderer {
+        TextRenderer { font, scale }
+    }
+
+    pub fn font(&self) -> Font {
+        self.font
+    }
+
+    /// Every character of `text` to draw, laid out in `corner` of `bounds`, on a window that
+    /// scales things up by `scale_factor`. Lines are broken at newlines, and lined up on the
+    /// side of the bounds the corner is on.
+    pub fn layout(
+        &self,
+        text: &str,
+        corner: Corner,
+        bounds: Bounds,
+        scale_factor: f64,
+    ) -> Vec<Glyph> {
+        let lines: Vec<&str> = text.lines().collect();
+        let columns = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as f32;
+        let cell = [self.font.glyph[0] as f32, self.font.glyph[1] as f32];
+
+        // Whole pixels a texel, as many as asked for, but no more than fit.
+        let wanted = (self.scale * scale_factor as f32).round().max(1.0);
+        let room = bounds.size[0] / (columns * cell[0] + 2.0 * MARGIN);
+        let texel = wanted.min(room.floor()).max(1.0);
+
+        let size = [cell[0] * texel, cell[1] * texel];
+        let margin = MARGIN * texel;
+        let height = lines.len() as f32 * size[1];
+        let top = match corner {
+            Corner::TopLeft | Corner::TopRight => bounds.origin[1] + margin,
+            Corner::BottomLeft | Corner::BottomRight => {
+                bounds.origin[1] + bounds.size[1] - margin - height
+            }
+        };
+
+        let mut glyphs = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            let width = line.chars().count() as f32 * size[0];
+            let left = match corner {
+                Corner::TopLeft | Corner::BottomLeft => bounds.origin[0] + margin,
+                Corner::TopRight | Corner::BottomRight => {
+                    bounds.origin[0] + bounds.size[0] - margin - width
+                }
+            };
+            let y = top + row as f32 * size[1];
+            for (column, c) in line.chars().enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+                glyphs.push(Glyph {
+                    position: [left + column as f32 * size[0], y],
+                    size,
+                    tex: self.font.glyph(c),
+                });
+            }
+        }
+        glyphs
+    }
+
+    /// Adds `text` to `mesh` in `color`, laid out the way [`TextRenderer::layout`] does.
+    pub fn push(
+        &self,
+        mesh: &mut Mesh,
+        text: &str,
+        corner: Corner,
+        bounds: Bounds,
+        scale_factor: f64,
+        color: [f32; 4],
+    ) {
+        for glyph in self.layout(text, corner, bounds, scale_factor) {
+            let [left, top] = glyph.position;
+            let [right, bottom] = [left + glyph.size[0], top + glyph.size[1]];
+            mesh.push_textured_quad(
+                [[left, top], [right, top], [right, bottom], [left, bottom]],
+                color,
+                glyph.tex,
+            );
+        }
+    }
+}
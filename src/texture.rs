@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use image::ImageFormat;
+
+/// The sprites everything is drawn with, white and see-through around them so that the
+/// palette's colors tint them. Where each is comes from
+/// [`Atlas::bundled`](crate::atlas::Atlas::bundled).
+const ATLAS: &[u8] = include_bytes!("../sprites/atlas.png");
+
+/// The glyphs text is drawn with, white on see-through, laid out as
+/// [`Font::BUNDLED`](crate::text::Font::BUNDLED) says.
+const FONT: &[u8] = include_bytes!("../sprites/font.png");
+
+/// An image ready to go to the GPU: `width` by `height` texels of four bytes each, red, green,
+/// blue and alpha, row after row from the top. The colors are in sRGB, and the alpha isn't
+/// premultiplied.
+///
+/// Any size goes, powers of two or not: textures are only ever sampled at their full size,
+/// never mipmapped, so the GPU doesn't care.
+///
+/// ```
+/// use snake_game::atlas::Atlas;
+/// use snake_game::text::Font;
+/// use snake_game::texture::{Texture, TextureError};
+///
+/// let atlas = Texture::atlas();
+/// assert_eq!((atlas.width(), atlas.height()), (128, 48));
+/// assert_eq!(atlas.pixels().len(), 128 * 48 * 4);
+/// assert_eq!(Atlas::bundled().size(), [atlas.width(), atlas.height()]);
+/// // The corners of the first sprite are see-through, its middle isn't.
+/// assert_eq!(atlas.texel(0, 0)[3], 0);
+/// assert_eq!(atlas.texel(7, 12)[3], 255);
+///
+/// let font = Texture::font();
+/// assert_eq!([font.width(), font.height()], Font::BUNDLED.image);
+///
+/// assert!(matches!(
+///     Texture::load("no/such/sprite.png"),
+///     Err(TextureError::Io(_))
+/// ));
+/// assert!(matches!(
+///     Texture::decode(b"not a png"),
+///     Err(TextureError::Decode(_))
+/// ));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Texture {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum TextureError {
+    Io(io::Error),
+    /// The file isn't a PNG, or is a broken one.
+    Decode(image::ImageError),
+    /// The image has no texels at all.
+    Empty,
+    /// The image is bigger than the GPU takes, which is `limit` texels on either side.
+    TooLarge {
+        width: u32,
+        height: u32,
+        limit: u32,
+    },
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureError::Io(err) => write!(f, "could not read image: {}", err),
+            TextureError::Decode(err) => write!(f, "could not decode image: {}", err),
+            TextureError::Empty => write!(f, "image is empty"),
+            TextureError::TooLarge {
+                width,
+                height,
+                limit,
+            } => write!(
+                f,
+                "image is {}x{}, but the GPU only takes images up to {} on a side",
+                width, height, limit
+            ),
+        }
+    }
+}
+
+impl Error for TextureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TextureError::Io(err) => Some(err),
+            TextureError::Decode(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TextureError {
+    fn from(err: io::Error) -> TextureError {
+        TextureError::Io(err)
+    }
+}
+
+impl Texture {
+    /// Reads the PNG at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Texture, TextureError> {
+        Texture::decode(&fs::read(path)?)
+    }
+
+    /// Decodes `bytes` as a PNG, in whatever color type it was saved with.
+    pub fn decode(bytes: &[u8]) -> Result<Texture, TextureError> {
+        let image = image::load_from_memory_with_format(bytes, ImageFormat::Png)
+            .map_err(TextureError::Decode)?
+            .into_rgba8();
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return Err(TextureError::Empty);
+        }
+        Ok(Texture {
+            width,
+            height,
+            pixels: image.into_raw(),
+        })
+    }
+
+    /// The image of the bundled atlas.
+    pub fn atlas() -> Texture {
+        Texture::decode(ATLAS).expect("the bundled atlas is a PNG")
+    }
+
+    /// The image of the bundled font.
+    pub fn font() -> Texture {
+        Texture::decode(FONT).expect("the bundled font is a PNG")
+    }
+
+    /// A single white texel, which leaves whatever it tints as it is.
+    pub fn white() -> Texture {
+        Texture {
+            width: 1,
+            height: 1,
+            pixels: vec![255; 4],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The texel `x` across and `y` down from the top left corner.
+    pub fn texel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y * self.width + x) as usize * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Checks that the texture is no more than `limit` texels on either side.
+    pub fn fits(&self, limit: u32) -> Result<(), TextureError> {
+        if self.width > limit || self.height > limit {
+            return Err(TextureError::TooLarge {
+                width: self.width,
+                height: self.height,
+                limit,
+            });
+        }
+        Ok(())
+    }
+}
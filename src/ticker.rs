@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+/// The most ticks a single frame may run. Past that the backlog is dropped, so that after a
+/// stall the game picks up where it was rather than fast-forwarding through it.
+pub const MAX_TICKS_PER_FRAME: u32 = 5;
+
+/// The slowest the game can be made to run, as a multiple of its normal speed.
+pub const MIN_TIME_SCALE: f64 = 0.25;
+
+/// The fastest the game can be made to run, as a multiple of its normal speed.
+pub const MAX_TIME_SCALE: f64 = 4.0;
+
+/// Accumulates frame time and hands it out as game ticks.
+///
+/// Frames and ticks run at different rates, so the render loop feeds every frame's duration in
+/// and then asks for ticks until the accumulated time runs out. The game thus runs at the same
+/// speed whatever the refresh rate of the display.
+///
+/// The frame time is scaled on the way in, so that the game can run in slow motion or fast
+/// forward while frames keep coming at the same rate.
+#[derive(Clone, Debug)]
+pub struct Ticker {
+    accumulated: Duration,
+    /// Ticks handed out since the last call to `advance`.
+    ticks_this_frame: u32,
+    scale: f64,
+}
+
+impl Default for Ticker {
+    fn default() -> Ticker {
+        Ticker {
+            accumulated: Duration::ZERO,
+            ticks_this_frame: 0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Ticker {
+    pub fn new() -> Ticker {
+        Ticker::default()
+    }
+
+    /// How many times faster than normal the game runs.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Makes the game run `scale` times faster than normal, within [`MIN_TIME_SCALE`] and
+    /// [`MAX_TIME_SCALE`]. Anything that isn't a number puts it back to normal.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snake_game::ticker::{Ticker, MAX_TIME_SCALE, MIN_TIME_SCALE};
+    ///
+    /// // How many 100 ms ticks ten seconds of 20 ms frames run at `scale`.
+    /// fn ticks_at(scale: f64) -> (f64, usize) {
+    ///     let interval = Duration::from_millis(100);
+    ///     let mut ticker = Ticker::new();
+    ///     ticker.set_scale(scale);
+    ///     let mut ticks = 0;
+    ///     for _ in 0..500 {
+    ///         ticker.advance(Duration::from_millis(20));
+    ///         ticks += std::iter::from_fn(|| ticker.tick(interval).then_some(())).count();
+    ///     }
+    ///     (ticker.scale(), ticks)
+    /// }
+    ///
+    /// assert_eq!(ticks_at(1.0), (1.0, 100));
+    /// assert_eq!(ticks_at(0.5), (0.5, 50));
+    /// assert_eq!(ticks_at(2.0), (2.0, 200));
+    /// // Past the limits, it runs at the limit.
+    /// assert_eq!(ticks_at(0.01), (MIN_TIME_SCALE, 25));
+    /// assert_eq!(ticks_at(-3.0), (MIN_TIME_SCALE, 25));
+    /// assert_eq!(ticks_at(100.0), (MAX_TIME_SCALE, 400));
+    /// assert_eq!(ticks_at(f64::INFINITY), (MAX_TIME_SCALE, 400));
+    /// assert_eq!(ticks_at(f64::NAN), (1.0, 100));
+    ///
+    /// let mut ticker = Ticker::new();
+    /// ticker.set_scale(3.0);
+    /// ticker.set_scale(f64::NAN);
+    /// assert_eq!(ticker.scale(), 1.0);
+    /// ```
+    pub fn set_scale(&mut self, scale: f64) {
+        self.scale = if scale.is_nan() {
+            1.0
+        } else {
+            scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE)
+        };
+    }
+
+    /// Starts a new frame that took `elapsed`.
+    pub fn advance(&mut self, elapsed: Duration) {
+        self.accumulated += elapsed.mul_f64(self.scale);
+        self.ticks_this_frame = 0;
+    }
+
+    /// Consumes `interval` worth of time if that much has accumulated.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use snake_game::ticker::{Ticker, MAX_TICKS_PER_FRAME};
+    ///
+    /// let interval = Duration::from_millis(100);
+    /// let mut ticker = Ticker::new();
+    ///
+    /// // Whatever is left over once a frame ran its ticks carries on to the next one.
+    /// ticker.advance(Duration::from_millis(250));
+    /// assert!(ticker.tick(interval) && ticker.tick(interval));
+    /// assert!(!ticker.tick(interval));
+    /// ticker.advance(Duration::from_millis(50));
+    /// assert!(ticker.tick(interval));
+    /// assert!(!ticker.tick(interval));
+    ///
+    /// // After a stall, a frame runs no more than its share of ticks and drops the rest.
+    /// ticker.advance(Duration::from_secs(2));
+    /// let ticks = std::iter::from_fn(|| ticker.tick(interval).then_some(())).count();
+    /// assert_eq!(ticks, MAX_TICKS_PER_FRAME as usize);
+    /// ticker.advance(Duration::from_millis(50));
+    /// assert!(!ticker.tick(interval));
+    /// assert!((ticker.progress(interval) - 0.5).abs() < 1e-6);
+    /// ```
+    pub fn tick(&mut self, interval: Duration) -> bool {
+        if self.ticks_this_frame >= MAX_TICKS_PER_FRAME {
+            self.accumulated = Duration::ZERO;
+            return false;
+        }
+
+        if self.accumulated >= interval {
+            self.accumulated -= interval;
+            self.ticks_this_frame += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// How far into the next tick of length `interval` the accumulated time reaches, from 0 to
+    /// 1. Frames drawn between two ticks can use it to interpolate.
+    pub fn progress(&self, interval: Duration) -> f32 {
+        if interval.is_zero() {
+            return 0.0;
+        }
+        (self.accumulated.as_secs_f32() / interval.as_secs_f32()).min(1.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulated = Duration::ZERO;
+        self.ticks_this_frame = 0;
+    }
+}
@@ -0,0 +1,1829 @@
+use std::mem;
+#[cfg(feature = "dev-tools")]
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use vulkano::buffer::cpu_pool::CpuBufferPoolChunk;
+use vulkano::buffer::{
+    BufferUsage, CpuAccessibleBuffer, CpuBufferPool, DeviceLocalBuffer, TypedBufferAccess,
+};
+use vulkano::command_buffer::{
+    AutoCommandBufferBuilder, CommandBufferUsage, PrimaryCommandBuffer, SecondaryAutoCommandBuffer,
+    SubpassContents,
+};
+use vulkano::descriptor_set::PersistentDescriptorSet;
+use vulkano::device::physical::{PhysicalDevice, PhysicalDeviceType};
+use vulkano::device::{Device, DeviceExtensions, DeviceOwned, Features, Queue};
+use vulkano::format::{ClearValue, Format, NumericType};
+use vulkano::image::view::ImageView;
+use vulkano::image::{
+    AttachmentImage, ImageAccess, ImageDimensions, ImageUsage, ImmutableImage, MipmapsCount,
+    SwapchainImage,
+};
+use vulkano::instance::{Instance as VulkanInstance, InstanceCreationError, InstanceExtensions};
+use vulkano::memory::pool::StdMemoryPool;
+use vulkano::pipeline::cache::PipelineCache;
+use vulkano::pipeline::graphics::color_blend::ColorBlendState;
+use vulkano::pipeline::graphics::depth_stencil::{CompareOp, DepthState, DepthStencilState};
+use vulkano::pipeline::graphics::input_assembly::{InputAssemblyState, PrimitiveTopology};
+use vulkano::pipeline::graphics::rasterization::{PolygonMode, RasterizationState};
+use vulkano::pipeline::graphics::vertex_input::BuffersDefinition;
+use vulkano::pipeline::graphics::viewport::{Viewport, ViewportState};
+use vulkano::pipeline::graphics::GraphicsPipelineCreationError;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline, PipelineBindPoint, StateMode};
+use vulkano::render_pass::{Framebuffer, RenderPass, Subpass};
+use vulkano::sampler::{Filter, MipmapMode, Sampler, SamplerAddressMode};
+use vulkano::shader::ShaderModule;
+use vulkano::swapchain::{
+    self, AcquireError, Capabilities, ColorSpace, PresentMode as VulkanPresentMode, Surface,
+    Swapchain, SwapchainCreationError,
+};
+use vulkano::sync::{self, FlushError, GpuFuture};
+use vulkano::{DeviceSize, Version};
+use vulkano_win::VkSurfaceBuild;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+use winit::window::{Fullscreen, Window, WindowBuilder};
+
+use crate::atlas::Atlas;
+use crate::background::Background;
+use crate::board::{board_vertices, tile_sprite};
+use crate::frame::{FrameCache, FrameRecorder, Layer, RecordedCache};
+use crate::frame_stats::FrameStats;
+use crate::game::{Editor, EditorTile, Effect, FoodKind, Game, GridPos, RoundEnd, StepResult};
+#[cfg(feature = "dev-tools")]
+use crate::hot_reload::{reload_pipelines, shader_files, ShaderWatcher, SHADER_DIR};
+use crate::layout::{min_window_size, Projection, WindowSize};
+use crate::limiter::FrameLimiter;
+use crate::mesh::{grid_lines, DrawParams, Instance, Mesh, MeshKind, Vertex, UNIT_QUAD, WHITE};
+use crate::motion::Trails;
+use crate::palette::{linear_to_srgb, mix, CellShape, Palette, Tile};
+use crate::particles::ParticleSystem;
+use crate::pipeline_cache::CacheFile;
+use crate::pipelines::{load_pipeline_cache, save_pipeline_cache, PipelineRegistry};
+use crate::pulse::Pulse;
+use crate::renderer::{
+    MaterialKind, Placement, PresentMode, RenderConfig, ShaderSource, WindowMode, SAMPLE_COUNTS,
+};
+use crate::session::{Mode, Session};
+use crate::settings::Settings;
+#[cfg(feature = "runtime-shaders")]
+use crate::shader_compiler::use_file_shaders;
+use crate::state::{AppState, COUNTDOWN};
+use crate::text::{Corner as TextCorner, Font, TextRenderer, TEXT_SCALE};
+use crate::texture::{Texture, TextureError};
+use crate::ticker::Ticker;
+
+/// How much of the board's width is left between two countdown bars, in cells.
+const COUNTDOWN_GAP: f32 = 0.5;
+
+/// How far everything fades into the background while the game is paused, or the demo plays.
+const PAUSED_DIM: f32 = 0.6;
+
+/// The formats the window is drawn in, best first. The sRGB ones encode the colors the shaders
+/// write, which are in linear light, on their own; the others show them as they are, so colors
+/// are encoded before they are drawn into those.
+pub const PREFERRED_FORMATS: [Format; 4] = [
+    Format::B8G8R8A8_SRGB,
+    Format::R8G8B8A8_SRGB,
+    Format::B8G8R8A8_UNORM,
+    Format::R8G8B8A8_UNORM,
+];
+
+/// How often the frame statistics in the title bar change, so it doesn't flicker.
+const FRAME_STATS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How wide the lines between cells are, in pixels on a screen that doesn't scale things up.
+const GRID_LINE_WIDTH: f32 = 1.0;
+
+vulkano::impl_vertex!(Vertex, position, color, tex_coords, quad_kind, local);
+vulkano::impl_vertex!(
+    Instance,
+    origin,
+    extent,
+    color,
+    tex_origin,
+    tex_extent,
+    tex_transposed,
+    quad_kind,
+    local_origin,
+    local_extent
+);
+
+/// A corner of [`UNIT_QUAD`], as it is read alongside the instances. Shader inputs are
+/// matched to fields by name, so it has no color to be mistaken for theirs.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+struct Corner {
+    position: [f32; 2],
+}
+vulkano::impl_vertex!(Corner, position);
+
+// The code generated by `shader!` trips a couple of lints we don't control.
+#[allow(deprecated, clippy::needless_question_mark)]
+mod vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/board.vert"
+    }
+}
+
+#[allow(deprecated, clippy::needless_question_mark)]
+mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/board.frag"
+    }
+}
+
+#[allow(deprecated, clippy::needless_question_mark)]
+mod instanced_vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "src/shaders/instanced.vert"
+    }
+}
+
+#[allow(deprecated, clippy::needless_question_mark)]
+mod instanced_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "src/shaders/instanced.frag"
+    }
+}
+
+pub struct Vulkan {
+    surface: Arc<Surface<Window>>,
+    device: Arc<Device>,
+    queue: Arc<Queue>,
+    swapchain: Arc<Swapchain<Window>>,
+    images: Vec<Arc<SwapchainImage<Window>>>,
+    render_pass: Arc<RenderPass>,
+    pipelines: PipelineRegistry,
+    /// Where the pipeline cache is saved when the window is closed, if there is anywhere.
+    cache_file: Option<CacheFile>,
+    mesh_kind: MeshKind,
+    samples: u32,
+    depth_format: Format,
+    present_mode: PresentMode,
+    fps_limit: Option<u32>,
+}
+
+impl Vulkan {
+    /// Opens the window, set up to draw the way `config` says. A device that can't take as
+    /// many samples a pixel as asked for draws with as many as it can, and one without the
+    /// present mode asked for presents the way [`PresentMode::within`] falls back on.
+    pub fn new(event_loop: &EventLoop<()>, config: RenderConfig) -> Vulkan {
+        let instance = create_instance(&vulkano_win::required_extensions()).unwrap();
+        let surface = WindowBuilder::new()
+            .with_title("Snake")
+            .build_vk_surface(event_loop, instance.clone())
+            .unwrap();
+
+        let (device, queue) =
+            create_logical_device(&instance, Some(&surface)).expect("no GPU draws to the window");
+        let (swapchain, images) = create_swapchain(&device, &queue, &surface, config.present_mode);
+        let samples = config.samples_within(max_samples(device.physical_device()));
+        if samples < config.samples {
+            eprintln!(
+                "warning: the device only takes {} samples a pixel, not {}",
+                samples, config.samples
+            );
+        }
+        let depth_format = choose_depth_format(device.physical_device());
+        println!("Depth format: {:?}", depth_format);
+        let render_pass = create_render_pass(&device, swapchain.format(), samples, depth_format);
+        let mesh_kind = config.mesh_kind;
+        let mut pipelines = PipelineRegistry::new(device.clone(), render_pass.clone(), mesh_kind);
+        let cache_file = CacheFile::new();
+        match &cache_file {
+            Some(file) => pipelines = pipelines.with_cache(load_pipeline_cache(&device, file)),
+            None => eprintln!("warning: no data directory, pipelines will be built from scratch"),
+        }
+        match &config.shaders {
+            ShaderSource::Embedded => {}
+            #[cfg(feature = "runtime-shaders")]
+            ShaderSource::Files { vert, frag } => use_file_shaders(&mut pipelines, [vert, frag]),
+            #[cfg(not(feature = "runtime-shaders"))]
+            ShaderSource::Files { .. } => eprintln!(
+                "warning: drawing with the shaders built into the game, as it was built \
+                 without the runtime-shaders feature"
+            ),
+        }
+
+        Vulkan {
+            surface,
+            device,
+            queue,
+            swapchain,
+            images,
+            render_pass,
+            pipelines,
+            cache_file,
+            mesh_kind,
+            samples,
+            depth_format,
+            present_mode: config.present_mode,
+            fps_limit: config.fps_limit,
+        }
+    }
+
+    /// The most samples a pixel the device draws with, one of the [`SAMPLE_COUNTS`].
+    pub fn max_samples(&self) -> u32 {
+        max_samples(self.device.physical_device())
+    }
+
+    /// The samples a pixel the window is drawn with.
+    pub fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Runs the render loop, ticking the game on a timer and drawing it every frame.
+    pub fn setup(self, event_loop: EventLoop<()>, mode: Mode, settings: Settings) -> ! {
+        let Vulkan {
+            surface,
+            device,
+            queue,
+            mut swapchain,
+            images,
+            render_pass,
+            mut pipelines,
+            cache_file,
+            mesh_kind,
+            samples,
+            depth_format,
+            mut present_mode,
+            fps_limit,
+        } = self;
+
+        // The pipelines are built again whenever the shaders on disk change.
+        #[cfg(feature = "dev-tools")]
+        let mut shader_watcher = ShaderWatcher::new(Path::new(SHADER_DIR), shader_files(mesh_kind));
+
+        let mut viewport = Viewport {
+            origin: [0.0, 0.0],
+            dimensions: [0.0, 0.0],
+            depth_range: 0.0..1.0,
+        };
+        let mut framebuffers = window_size_dependent_setup(
+            &images,
+            render_pass.clone(),
+            samples,
+            depth_format,
+            &mut viewport,
+        );
+
+        let mut window_size = WindowSize::new(
+            surface.window().inner_size().into(),
+            surface.window().scale_factor(),
+        );
+        let mut background = Background::new(settings.background);
+        // Whether everything is drawn as the edges of its triangles, to see what the meshes
+        // are made of.
+        let mut wireframe = false;
+        let mut pulse = Pulse::new();
+        let mut session = Session::new(mode, settings);
+        let mut window_mode = WindowMode::new();
+        if session.fullscreen() {
+            toggle_fullscreen(surface.window(), &mut window_mode);
+        }
+
+        let encode = swapchain.format().type_color() != Some(NumericType::SRGB);
+        let output = move |color| if encode { linear_to_srgb(color) } else { color };
+
+        // The sprites and the font are uploaded along with the first frame. The font is
+        // sampled texel by texel, so its glyphs stay sharp however big they are drawn.
+        let atlas = Atlas::bundled();
+        let (sprite_set, sprites_uploaded) = texture_set(
+            &queue,
+            &pipelines.get(MaterialKind::Textured),
+            &Texture::atlas(),
+            Filter::Linear,
+        );
+        let text = TextRenderer::new(Font::BUNDLED, TEXT_SCALE);
+        let (font_set, font_uploaded) = texture_set(
+            &queue,
+            &pipelines.get(MaterialKind::Text),
+            &Texture::font(),
+            Filter::Nearest,
+        );
+
+        let mut recreate_swapchain = false;
+        // Set when vsync is switched, so the swapchain is made again presenting another way.
+        let mut switch_present_mode = false;
+        let mut previous_frame_end = Some(sprites_uploaded.join(font_uploaded).boxed());
+        let mut ticker = Ticker::new();
+        let mut limiter = FrameLimiter::new(fps_limit);
+        let mut last_frame = Instant::now();
+        let mut title = String::new();
+        let mut frame_stats = FrameStats::new();
+        let mut frame_stats_shown = last_frame;
+        let mut frame_stats_text = String::new();
+        let mut shown_title = String::new();
+
+        let geometry = FrameGeometry::new(&device, &queue);
+        // The board only moves when the window is resized or the board changes size, and the
+        // text only when the window is resized, so their projections are only sent over again
+        // then.
+        let projection_pool = CpuBufferPool::<Projection>::uniform_buffer(device.clone());
+        let mut board_projection = None;
+        let mut screen_projection = None;
+        let mut mesh = Mesh::new(mesh_kind);
+        let mut hud = Mesh::new(mesh_kind);
+        let mut particles = ParticleSystem::new();
+        let mut particle_mesh = Mesh::new(mesh_kind);
+        let mut trails = Trails::new();
+        // The grid is made again only when the board changes size or the palette its color.
+        let mut grid: Option<(([u32; 2], Palette), Lines)> = None;
+        // The board the window was last kept from getting too small for.
+        let mut min_size_for = None;
+        let subpass = Subpass::from(render_pass.clone(), 0).unwrap();
+        // The text is recorded again only when it changes, and executed as it is otherwise.
+        let mut ui_commands = RecordedCache::new();
+        // Whole frames are kept for each swapchain image, and the board for all of them, for
+        // as long as nothing drawn changes.
+        let mut frames = FrameCache::new(images.len());
+        let mut board_commands = RecordedCache::new();
+        let mut particle_commands = RecordedCache::new();
+        let mut drawn: Option<FrameKey> = None;
+
+        event_loop.run(move |event, _, control_flow| match event {
+            Event::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                session.close();
+                if let Some(file) = &cache_file {
+                    save_pipeline_cache(pipelines.cache(), file);
+                }
+                *control_flow = ControlFlow::Exit;
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Resized(size),
+                ..
+            } => {
+                recreate_swapchain |= window_size.resized(size.into());
+            }
+            // Moving to a screen that scales things up another way resizes the window without
+            // a resize of its own.
+            Event::WindowEvent {
+                event:
+                    WindowEvent::ScaleFactorChanged {
+                        scale_factor,
+                        new_inner_size,
+                    },
+                ..
+            } => {
+                recreate_swapchain |=
+                    window_size.scale_factor_changed(scale_factor, (*new_inner_size).into());
+            }
+            Event::WindowEvent {
+                event: WindowEvent::Focused(false),
+                ..
+            } => {
+                session.focus_lost(&mut ticker);
+            }
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(key),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => {
+                if key == VirtualKeyCode::V {
+                    present_mode = present_mode.toggled();
+                    switch_present_mode = true;
+                    recreate_swapchain = true;
+                }
+                if key == VirtualKeyCode::F3 && pipelines.supports(MaterialKind::Wireframe) {
+                    wireframe = !wireframe;
+                }
+                // The swapchain is only made again for the new size between two frames, so
+                // switching again before that waits.
+                if key == VirtualKeyCode::F11 && !recreate_swapchain {
+                    toggle_fullscreen(surface.window(), &mut window_mode);
+                    session.set_fullscreen(window_mode.is_fullscreen());
+                    recreate_swapchain = true;
+                }
+                session.key_pressed(key, &mut ticker);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorMoved { position, .. },
+                ..
+            } => {
+                let window = [viewport.dimensions[0] as u32, viewport.dimensions[1] as u32];
+                let cell = session
+                    .viewport(window)
+                    .window_to_cell([position.x as f32, position.y as f32]);
+                session.cursor_moved(cell, &mut ticker);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::CursorLeft { .. },
+                ..
+            } => {
+                session.cursor_moved(None, &mut ticker);
+            }
+            Event::WindowEvent {
+                event: WindowEvent::MouseInput { state, button, .. },
+                ..
+            } => {
+                session.mouse_input(button, state == ElementState::Pressed, &mut ticker);
+            }
+            Event::RedrawEventsCleared => {
+                previous_frame_end.as_mut().unwrap().cleanup_finished();
+
+                // Frames are measured from the start of one to the start of the next, waiting
+                // for a swapchain image and for the frame limiter included.
+                let now = Instant::now();
+                frame_stats.record_frame(now - last_frame);
+                session.update(now - last_frame, &mut ticker);
+                background.advance(now - last_frame);
+                // Food stops pulsing along with the game when it is paused.
+                if !session.reduced_motion() && session.state() != AppState::Paused {
+                    pulse.advance(now - last_frame);
+                }
+                if session.take_died() {
+                    background.flash_death();
+                }
+                // Particles fly along with the time frames take, whatever speed the game goes at,
+                // and only burst out of food when things may move only for looks.
+                particles.update(now - last_frame);
+                let palette = session.palette();
+                let burst = !session.reduced_motion();
+                for (cell, kind) in session.take_eaten() {
+                    if burst {
+                        particles.burst(cell, palette.look(Tile::Food(kind)).color);
+                    }
+                }
+                last_frame = now;
+                #[cfg(feature = "dev-tools")]
+                if shader_watcher.poll(now) {
+                    // The frames in flight still draw with the pipelines being swapped out.
+                    queue.wait().unwrap();
+                    previous_frame_end.as_mut().unwrap().cleanup_finished();
+                    reload_pipelines(&mut pipelines);
+                }
+                if now - frame_stats_shown >= FRAME_STATS_INTERVAL {
+                    frame_stats_text = frame_stats.summary();
+                    frame_stats_shown = now;
+                }
+
+                let game = session.game();
+                let new_title = match session.state() {
+                    AppState::Editor => "Snake".to_string() + &session.title_suffix(&ticker),
+                    _ => window_title(game) + &session.title_suffix(&ticker),
+                };
+                if new_title != title {
+                    if game.is_over() {
+                        println!("{}", new_title);
+                    }
+                    title = new_title;
+                }
+                let mut full_title = match frame_stats_text.as_str() {
+                    "" => title.clone(),
+                    stats => format!("{} - {}", title, stats),
+                };
+                // What the last frame drew, which is what there is to see in a wireframe.
+                if wireframe {
+                    full_title += &format!(
+                        " - Wireframe: {} on the board, {} in the text",
+                        mesh_counts(&mesh),
+                        mesh_counts(&hud)
+                    );
+                }
+                if full_title != shown_title {
+                    surface.window().set_title(&full_title);
+                    shown_title = full_title;
+                }
+
+                if recreate_swapchain {
+                    let dimensions = window_size.physical();
+                    let caps = surface.capabilities(device.physical_device()).unwrap();
+                    let chosen = choose_present_mode(&caps, present_mode);
+                    let (new_swapchain, new_images) = match swapchain
+                        .recreate()
+                        .dimensions(dimensions)
+                        .present_mode(chosen)
+                        .build()
+                    {
+                        Ok(r) => r,
+                        Err(SwapchainCreationError::UnsupportedDimensions) => return,
+                        Err(e) => panic!("Failed to recreate swapchain: {:?}", e),
+                    };
+                    if switch_present_mode {
+                        println!("Present mode: {:?}", chosen);
+                        switch_present_mode = false;
+                    }
+
+                    swapchain = new_swapchain;
+                    framebuffers = window_size_dependent_setup(
+                        &new_images,
+                        render_pass.clone(),
+                        samples,
+                        depth_format,
+                        &mut viewport,
+                    );
+                    frames.invalidate(new_images.len());
+                    recreate_swapchain = false;
+                }
+
+                let acquiring = Instant::now();
+                let (image_num, suboptimal, acquire_future) =
+                    match swapchain::acquire_next_image(swapchain.clone(), None) {
+                        Ok(r) => r,
+                        Err(AcquireError::OutOfDate) => {
+                            recreate_swapchain = true;
+                            return;
+                        }
+                        Err(e) => panic!("Failed to acquire next image: {:?}", e),
+                    };
+
+                if suboptimal {
+                    recreate_swapchain = true;
+                }
+                // Waiting for an image is time the CPU spends idle.
+                let acquired = acquiring.elapsed();
+
+                let window = [viewport.dimensions[0] as u32, viewport.dimensions[1] as u32];
+                let board = session.viewport(window);
+                // Wireframes are drawn with a pipeline of their own, text included.
+                let [mesh_pipeline, text_pipeline] = if wireframe {
+                    [MaterialKind::Wireframe; 2]
+                } else {
+                    [MaterialKind::Textured, MaterialKind::Text]
+                }
+                .map(|material| pipelines.get(material));
+                let line_pipeline = pipelines.get(MaterialKind::Lines);
+                let board_set = projection_set(
+                    &mut board_projection,
+                    board.projection(),
+                    &projection_pool,
+                    &mesh_pipeline,
+                );
+                let screen_set = projection_set(
+                    &mut screen_projection,
+                    Projection::screen(window),
+                    &projection_pool,
+                    &text_pipeline,
+                );
+                if min_size_for != Some(board.grid()) {
+                    let [width, height] = min_window_size(board.grid());
+                    surface
+                        .window()
+                        .set_min_inner_size(Some(PhysicalSize::new(width, height)));
+                    min_size_for = Some(board.grid());
+                }
+                let wanted = (board.grid(), palette);
+                if grid.as_ref().map(|(made_for, _)| *made_for) != Some(wanted) {
+                    let lines = grid_lines(board.grid(), output(palette.look(Tile::Grid).color));
+                    grid = Some((wanted, upload_static(&device, &queue, &lines)));
+                }
+                match session.editor() {
+                    Some(editor) => {
+                        editor_vertices(editor, session.cursor(), palette, &atlas, &mut mesh)
+                    }
+                    None => {
+                        trails.update(game);
+                        // A round that is over stays where it ended.
+                        let motion = (session.smooth() && !game.is_over())
+                            .then(|| (&trails, ticker.progress(game.tick_interval())));
+                        board_vertices(
+                            game,
+                            session.ghost(),
+                            motion,
+                            session.dying(),
+                            palette,
+                            &atlas,
+                            &mut mesh,
+                        )
+                    }
+                }
+                if session.last_step() == Some(StepResult::Blocked) && session.editor().is_none() {
+                    let look = palette.look(Tile::Bump);
+                    let head = game.snake().head();
+                    mesh.push_cell(head, look.inset, look.color);
+                }
+                match session.state() {
+                    AppState::Paused | AppState::Demo => {
+                        mesh.recolor(|color| {
+                            let mut dimmed = mix(color, palette.background(), PAUSED_DIM);
+                            dimmed[3] = color[3];
+                            dimmed
+                        });
+                    }
+                    AppState::Countdown(left) => {
+                        countdown_vertices(left, [game.width(), game.height()], palette, &mut mesh)
+                    }
+                    AppState::Tutorial
+                    | AppState::Menu
+                    | AppState::Playing
+                    | AppState::Dying(_)
+                    | AppState::GameOver
+                    | AppState::Editor => {}
+                }
+                hud.clear();
+                if session.editor().is_none() {
+                    text.push(
+                        &mut hud,
+                        &score_text(game),
+                        TextCorner::TopLeft,
+                        board.bounds(),
+                        window_size.scale_factor(),
+                        palette.look(Tile::Text).color,
+                    );
+                }
+
+                particle_mesh.clear();
+                particles.push_to(&mut particle_mesh);
+
+                mesh.recolor(output);
+                particle_mesh.recolor(output);
+                hud.recolor(output);
+
+                let won = game.round_end() == Some(RoundEnd::Won);
+                let background_color = output(background.color(palette, won));
+
+                // Wide lines take a feature not every device has; without it they are as thin
+                // as they come.
+                let line_width = device.enabled_features().wide_lines.then(|| {
+                    let [least, most] = device.physical_device().properties().line_width_range;
+                    let wanted = GRID_LINE_WIDTH * window_size.scale_factor() as f32;
+                    wanted.clamp(least, most)
+                });
+                let mut frame = FrameRecorder::new();
+                let shown_lines = session.grid().then_some(grid.as_ref()).flatten();
+                if let Some((_, lines)) = shown_lines {
+                    frame.push(
+                        Layer::Board,
+                        Batch {
+                            pipeline: &line_pipeline,
+                            sets: (board_set.clone(), sprite_set.clone()),
+                            depth: Layer::Board.depth(),
+                            shape: CellShape::Sharp,
+                            draw: Draw::Lines {
+                                lines: lines.clone(),
+                                width: line_width,
+                            },
+                        },
+                    );
+                }
+                frame.push(
+                    Layer::Board,
+                    Batch {
+                        pipeline: &mesh_pipeline,
+                        sets: (board_set.clone(), sprite_set.clone()),
+                        depth: Layer::Board.depth(),
+                        shape: session.cell_shape(),
+                        draw: Draw::Mesh(&mesh),
+                    },
+                );
+                frame.push(
+                    Layer::Particles,
+                    Batch {
+                        pipeline: &mesh_pipeline,
+                        sets: (board_set.clone(), sprite_set.clone()),
+                        depth: Layer::Particles.depth(),
+                        shape: CellShape::Sharp,
+                        draw: Draw::Mesh(&particle_mesh),
+                    },
+                );
+                // The text goes over the board, measured in pixels rather than in cells.
+                frame.push(
+                    Layer::Ui,
+                    Batch {
+                        pipeline: &text_pipeline,
+                        sets: (screen_set.clone(), font_set.clone()),
+                        depth: Layer::Ui.depth(),
+                        shape: CellShape::Sharp,
+                        draw: Draw::Mesh(&hud),
+                    },
+                );
+
+                let record = |layer, usage| {
+                    record_layer(
+                        &queue,
+                        &geometry,
+                        subpass.clone(),
+                        usage,
+                        &viewport,
+                        frame.layer(layer),
+                        pulse.time(),
+                    )
+                };
+                let ui_key = UiKey {
+                    hud: hud.clone(),
+                    dimensions: viewport.dimensions,
+                    pipeline: text_pipeline.clone(),
+                    projection: screen_set,
+                };
+                // Frames still in flight may be executing the text while the next one is
+                // submitted with it, and so for the board and the frames kept.
+                let ui_commands = ui_commands.get_or_record(ui_key, || {
+                    record(Layer::Ui, CommandBufferUsage::SimultaneousUse)
+                });
+
+                let key = FrameKey {
+                    mesh: mesh.clone(),
+                    particles: particle_mesh.clone(),
+                    lines: shown_lines.map(|(_, lines)| (lines.clone(), line_width)),
+                    mesh_pipeline: mesh_pipeline.clone(),
+                    line_pipeline: line_pipeline.clone(),
+                    projection: board_set,
+                    time: pulse.time(),
+                    shape: session.cell_shape(),
+                    dimensions: viewport.dimensions,
+                    background: background_color,
+                    ui: ui_commands.clone(),
+                };
+                if drawn.as_ref() != Some(&key) {
+                    frames.bump();
+                    drawn = Some(key);
+                }
+                let generation = frames.generation();
+                let command_buffer = frames.get_or_record(image_num, || {
+                    let board_commands = board_commands.get_or_record(generation, || {
+                        record(Layer::Board, CommandBufferUsage::SimultaneousUse)
+                    });
+                    let particle_commands = particle_commands.get_or_record(generation, || {
+                        record(Layer::Particles, CommandBufferUsage::SimultaneousUse)
+                    });
+                    let mut builder = AutoCommandBufferBuilder::primary(
+                        device.clone(),
+                        queue.family(),
+                        CommandBufferUsage::SimultaneousUse,
+                    )
+                    .unwrap();
+                    builder
+                        .begin_render_pass(
+                            framebuffers[image_num].clone(),
+                            SubpassContents::SecondaryCommandBuffers,
+                            clear_values(background_color, samples),
+                        )
+                        .unwrap();
+                    for layer in Layer::ALL {
+                        let commands = match layer {
+                            Layer::Board => board_commands.clone(),
+                            Layer::Particles => particle_commands.clone(),
+                            Layer::Ui => ui_commands.clone(),
+                        };
+                        builder.execute_commands(commands).unwrap();
+                    }
+                    builder.end_render_pass().unwrap();
+                    Arc::new(builder.build().unwrap())
+                });
+
+                let future = previous_frame_end
+                    .take()
+                    .unwrap()
+                    .join(acquire_future)
+                    .then_execute(queue.clone(), command_buffer)
+                    .unwrap()
+                    .then_swapchain_present(queue.clone(), swapchain.clone(), image_num)
+                    .then_signal_fence_and_flush();
+
+                match future {
+                    Ok(future) => {
+                        previous_frame_end = Some(future.boxed());
+                    }
+                    Err(FlushError::OutOfDate) => {
+                        recreate_swapchain = true;
+                        previous_frame_end = Some(sync::now(device.clone()).boxed());
+                    }
+                    Err(e) => {
+                        println!("Failed to flush future: {:?}", e);
+                        previous_frame_end = Some(sync::now(device.clone()).boxed());
+                    }
+                }
+
+                frame_stats.record_cpu_time(now.elapsed().saturating_sub(acquired));
+                // Frames wait for the screen already with vsync. The time spent here still
+                // counts towards the next frame, so the game keeps ticking.
+                limiter.end_frame(swapchain.present_mode() == VulkanPresentMode::Fifo);
+            }
+            _ => (),
+        })
+    }
+}
+
+/// Starts Vulkan with `extensions`, which are the ones a window needs when drawing to one.
+pub(crate) fn create_instance(
+    extensions: &InstanceExtensions,
+) -> Result<Arc<VulkanInstance>, InstanceCreationError> {
+    VulkanInstance::new(None, Version::V1_1, extensions, None)
+}
+
+/// The device to draw with, the best one that can draw to `surface`, or any that draws at all
+/// without one. `None` if there is none.
+pub(crate) fn create_logical_device(
+    instance: &Arc<VulkanInstance>,
+    surface: Option<&Arc<Surface<Window>>>,
+) -> Option<(Arc<Device>, Arc<Queue>)> {
+    let device_extensions = DeviceExtensions {
+        khr_swapchain: surface.is_some(),
+        ..DeviceExtensions::none()
+    };
+    let presents = |q| surface.is_none_or(|surface| surface.is_supported(q).unwrap_or(false));
+
+    let (physical_device, queue_family) = PhysicalDevice::enumerate(instance)
+        .filter(|&p| p.supported_extensions().is_superset_of(&device_extensions))
+        .filter_map(|p| {
+            p.queue_families()
+                .find(|&q| q.supports_graphics() && presents(q))
+                .map(|q| (p, q))
+        })
+        .min_by_key(|(p, _)| match p.properties().device_type {
+            PhysicalDeviceType::DiscreteGpu => 0,
+            PhysicalDeviceType::IntegratedGpu => 1,
+            PhysicalDeviceType::VirtualGpu => 2,
+            PhysicalDeviceType::Cpu => 3,
+            PhysicalDeviceType::Other => 4,
+        })?;
+
+    println!(
+        "Using device: {} (type: {:?})",
+        physical_device.properties().device_name,
+        physical_device.properties().device_type,
+    );
+
+    // Lines between cells look the same at any scale when they can be drawn wider. Drawing
+    // only the edges of triangles is for looking into the meshes, and left out without it.
+    let supported = physical_device.supported_features();
+    let features = Features {
+        wide_lines: supported.wide_lines,
+        fill_mode_non_solid: supported.fill_mode_non_solid,
+        ..Features::none()
+    };
+    let (device, mut queues) = Device::new(
+        physical_device,
+        &features,
+        &physical_device
+            .required_extensions()
+            .union(&device_extensions),
+        [(queue_family, 0.5)].iter().cloned(),
+    )
+    .unwrap();
+
+    Some((device, queues.next().unwrap()))
+}
+
+fn create_swapchain(
+    device: &Arc<Device>,
+    queue: &Arc<Queue>,
+    surface: &Arc<Surface<Window>>,
+    present_mode: PresentMode,
+) -> (Arc<Swapchain<Window>>, Vec<Arc<SwapchainImage<Window>>>) {
+    let caps = surface.capabilities(device.physical_device()).unwrap();
+    let composite_alpha = caps.supported_composite_alpha.iter().next().unwrap();
+    let (format, color_space) = choose_surface_format(&caps.supported_formats)
+        .expect("the surface takes at least one format");
+    println!("Using format: {:?} ({:?})", format, color_space);
+    let present_mode = choose_present_mode(&caps, present_mode);
+    println!("Present mode: {:?}", present_mode);
+    let dimensions: [u32; 2] = surface.window().inner_size().into();
+
+    Swapchain::start(device.clone(), surface.clone())
+        .num_images(caps.min_image_count)
+        .format(format)
+        .color_space(color_space)
+        .present_mode(present_mode)
+        .dimensions(dimensions)
+        .usage(ImageUsage::color_attachment())
+        .sharing_mode(queue)
+        .composite_alpha(composite_alpha)
+        .build()
+        .unwrap()
+}
+
+/// The format and color space to draw the window in, out of the `supported` ones: the first of
+/// the [`PREFERRED_FORMATS`] in the sRGB color space, or else any format in that color space,
+/// or else the first the surface takes at all.
+///
+/// ```
+/// use snake_game::vulkan::choose_surface_format;
+/// use vulkano::format::Format;
+/// use vulkano::swapchain::ColorSpace;
+///
+/// let srgb = ColorSpace::SrgbNonLinear;
+/// // Many drivers list a UNORM format first.
+/// let supported = [
+///     (Format::B8G8R8A8_UNORM, srgb),
+///     (Format::B8G8R8A8_SRGB, srgb),
+/// ];
+/// assert_eq!(choose_surface_format(&supported), Some((Format::B8G8R8A8_SRGB, srgb)));
+///
+/// // Without an sRGB format, a UNORM one is taken.
+/// let supported = [
+///     (Format::A2B10G10R10_UNORM_PACK32, srgb),
+///     (Format::R8G8B8A8_UNORM, srgb),
+/// ];
+/// assert_eq!(choose_surface_format(&supported), Some((Format::R8G8B8A8_UNORM, srgb)));
+///
+/// // The color space counts for more than the format.
+/// let p3 = ColorSpace::DisplayP3NonLinear;
+/// let supported = [
+///     (Format::B8G8R8A8_SRGB, p3),
+///     (Format::A2B10G10R10_UNORM_PACK32, srgb),
+/// ];
+/// assert_eq!(
+///     choose_surface_format(&supported),
+///     Some((Format::A2B10G10R10_UNORM_PACK32, srgb))
+/// );
+///
+/// let only = [(Format::B8G8R8A8_SRGB, p3)];
+/// assert_eq!(choose_surface_format(&only), Some(only[0]));
+/// assert_eq!(choose_surface_format(&[]), None);
+/// ```
+pub fn choose_surface_format(supported: &[(Format, ColorSpace)]) -> Option<(Format, ColorSpace)> {
+    let in_srgb =
+        |&&(_, color_space): &&(Format, ColorSpace)| color_space == ColorSpace::SrgbNonLinear;
+    PREFERRED_FORMATS
+        .iter()
+        .find_map(|&preferred| {
+            supported
+                .iter()
+                .filter(in_srgb)
+                .find(|&&(format, _)| format == preferred)
+        })
+        .or_else(|| supported.iter().find(in_srgb))
+        .or_else(|| supported.first())
+        .copied()
+}
+
+/// How to present for `wanted`, out of the modes in `caps`.
+fn choose_present_mode(caps: &Capabilities, wanted: PresentMode) -> VulkanPresentMode {
+    let modes = caps.present_modes;
+    let supported: Vec<PresentMode> = PresentMode::ALL
+        .into_iter()
+        .filter(|mode| match mode {
+            PresentMode::Fifo => modes.fifo,
+            PresentMode::Mailbox => modes.mailbox,
+            PresentMode::Immediate => modes.immediate,
+        })
+        .collect();
+    match wanted.within(&supported) {
+        PresentMode::Fifo => VulkanPresentMode::Fifo,
+        PresentMode::Mailbox => VulkanPresentMode::Mailbox,
+        PresentMode::Immediate => VulkanPresentMode::Immediate,
+    }
+}
+
+/// The render pass drawing into swapchain images of `format`, with `samples` samples a pixel
+/// and a depth buffer of `depth_format`, such as [`choose_depth_format`] picks. More than one
+/// sample draws into a multisampled image first, which is then resolved into the swapchain's.
+pub fn create_render_pass(
+    device: &Arc<Device>,
+    format: Format,
+    samples: u32,
+    depth_format: Format,
+) -> Arc<RenderPass> {
+    if samples == 1 {
+        return vulkano::single_pass_renderpass!(
+            device.clone(),
+            attachments: {
+                color: {
+                    load: Clear,
+                    store: Store,
+                    format: format,
+                    samples: 1,
+                },
+                depth: {
+                    load: Clear,
+                    store: DontCare,
+                    format: depth_format,
+                    samples: 1,
+                }
+            },
+            pass: {
+                color: [color],
+                depth_stencil: {depth}
+            }
+        )
+        .unwrap();
+    }
+
+    vulkano::single_pass_renderpass!(
+        device.clone(),
+        attachments: {
+            multisampled: {
+                load: Clear,
+                store: DontCare,
+                format: format,
+                samples: samples,
+            },
+            color: {
+                load: DontCare,
+                store: Store,
+                format: format,
+                samples: 1,
+            },
+            depth: {
+                load: Clear,
+                store: DontCare,
+                format: depth_format,
+                samples: samples,
+            }
+        },
+        pass: {
+            color: [multisampled],
+            depth_stencil: {depth},
+            resolve: [color],
+        }
+    )
+    .unwrap()
+}
+
+/// The depth formats the depth buffer can have, best first. Every device takes the last for a
+/// depth buffer.
+const DEPTH_FORMATS: [Format; 3] = [
+    Format::D32_SFLOAT,
+    Format::X8_D24_UNORM_PACK32,
+    Format::D16_UNORM,
+];
+
+/// The first of the [`DEPTH_FORMATS`] that `physical` takes for a depth buffer.
+pub fn choose_depth_format(physical: PhysicalDevice) -> Format {
+    DEPTH_FORMATS
+        .into_iter()
+        .find(|format| {
+            format
+                .properties(physical)
+                .optimal_tiling_features
+                .depth_stencil_attachment
+        })
+        .unwrap_or(Format::D16_UNORM)
+}
+
+/// The clear values of the attachments of a render pass made by [`create_render_pass`] with
+/// `samples` samples a pixel. What is drawn into is cleared to `background`: multisampled,
+/// that is an image of its own, resolved into the swapchain's, which is left as it is before.
+/// The depth buffer is cleared to the back.
+pub(crate) fn clear_values(background: [f32; 4], samples: u32) -> Vec<ClearValue> {
+    let mut values = vec![background.into()];
+    if samples > 1 {
+        values.push(ClearValue::None);
+    }
+    values.push(ClearValue::Depth(1.0));
+    values
+}
+
+/// The most of the [`SAMPLE_COUNTS`] that `physical` can draw with, into color and depth
+/// alike.
+pub fn max_samples(physical: PhysicalDevice) -> u32 {
+    let color = physical.properties().framebuffer_color_sample_counts;
+    let depth = physical.properties().framebuffer_depth_sample_counts;
+    let supported = [
+        color.sample1 && depth.sample1,
+        color.sample2 && depth.sample2,
+        color.sample4 && depth.sample4,
+        color.sample8 && depth.sample8,
+    ];
+    SAMPLE_COUNTS
+        .into_iter()
+        .zip(supported)
+        .filter(|&(_, supported)| supported)
+        .map(|(count, _)| count)
+        .max()
+        .unwrap_or(1)
+}
+
+/// Panics if `device` can't take as many bytes of push constants as drawing pushes.
+pub(crate) fn check_push_constants(device: &Arc<Device>) {
+    let pushed = mem::size_of::<vs::ty::PushConstants>() as u32;
+    let limit = device
+        .physical_device()
+        .properties()
+        .max_push_constants_size;
+    if pushed > limit {
+        panic!(
+            "The device takes {} bytes of push constants, drawing needs {}",
+            limit, pushed
+        );
+    }
+}
+
+/// The shaders built into the game that draw `mesh_kind` meshes: the vertex shader, then the
+/// fragment one.
+pub(crate) fn embedded_shaders(
+    device: &Arc<Device>,
+    mesh_kind: MeshKind,
+) -> [Arc<ShaderModule>; 2] {
+    match mesh_kind {
+        MeshKind::Triangles | MeshKind::Indexed => [
+            vs::load(device.clone()).unwrap(),
+            fs::load(device.clone()).unwrap(),
+        ],
+        MeshKind::Instanced => [
+            instanced_vs::load(device.clone()).unwrap(),
+            instanced_fs::load(device.clone()).unwrap(),
+        ],
+    }
+}
+
+/// The pipeline drawing `mesh_kind` meshes with the shaders `vs` and `fs`, which may not be the
+/// ones built into the game, filling their triangles or only drawing their edges as
+/// `polygon_mode` says. Both shaders have to have a `main` entry point. The vertices of the
+/// kinds other than instances are drawn with the [`DrawParams`] pushed for them, and instances
+/// only take their time.
+///
+/// Whatever of it was built before is found in `cache`, and what wasn't is left there.
+pub(crate) fn build_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<RenderPass>,
+    mesh_kind: MeshKind,
+    polygon_mode: PolygonMode,
+    vs: &ShaderModule,
+    fs: &ShaderModule,
+    cache: &Arc<PipelineCache>,
+) -> Result<Arc<GraphicsPipeline>, GraphicsPipelineCreationError> {
+    let buffers = match mesh_kind {
+        MeshKind::Triangles | MeshKind::Indexed => BuffersDefinition::new().vertex::<Vertex>(),
+        MeshKind::Instanced => BuffersDefinition::new()
+            .vertex::<Corner>()
+            .instance::<Instance>(),
+    };
+
+    GraphicsPipeline::start()
+        .vertex_input_state(buffers)
+        .vertex_shader(vs.entry_point("main").unwrap(), ())
+        .input_assembly_state(InputAssemblyState::new())
+        .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+        .rasterization_state(RasterizationState {
+            polygon_mode,
+            ..RasterizationState::new()
+        })
+        .fragment_shader(fs.entry_point("main").unwrap(), ())
+        .color_blend_state(ColorBlendState::new(1).blend_alpha())
+        .depth_stencil_state(depth_test())
+        // Takes as many samples a pixel as the subpass draws with.
+        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .build_with_cache(cache.clone())
+        .build(device.clone())
+}
+
+/// The pipeline drawing lines between the cells of the board, given as the two ends of each
+/// line in turn. Their width is set while drawing, if the device can draw them any wider than
+/// a pixel. It is built through `cache`, like the others.
+pub(crate) fn build_line_pipeline(
+    device: &Arc<Device>,
+    render_pass: &Arc<RenderPass>,
+    cache: &Arc<PipelineCache>,
+) -> Result<Arc<GraphicsPipeline>, GraphicsPipelineCreationError> {
+    let line_width = if device.enabled_features().wide_lines {
+        StateMode::Dynamic
+    } else {
+        StateMode::Fixed(1.0)
+    };
+
+    GraphicsPipeline::start()
+        .vertex_input_state(BuffersDefinition::new().vertex::<Vertex>())
+        .vertex_shader(
+            vs::load(device.clone())
+                .unwrap()
+                .entry_point("main")
+                .unwrap(),
+            (),
+        )
+        .input_assembly_state(InputAssemblyState::new().topology(PrimitiveTopology::LineList))
+        .viewport_state(ViewportState::viewport_dynamic_scissor_irrelevant())
+        .rasterization_state(RasterizationState {
+            line_width,
+            ..RasterizationState::new()
+        })
+        .fragment_shader(
+            fs::load(device.clone())
+                .unwrap()
+                .entry_point("main")
+                .unwrap(),
+            (),
+        )
+        .color_blend_state(ColorBlendState::new(1).blend_alpha())
+        .depth_stencil_state(depth_test())
+        .render_pass(Subpass::from(render_pass.clone(), 0).unwrap())
+        .build_with_cache(cache.clone())
+        .build(device.clone())
+}
+
+/// How the pipelines test depth: what is as near as or nearer than what was drawn there before
+/// goes over it, so that within a layer, what is drawn later goes over what was drawn before.
+fn depth_test() -> DepthStencilState {
+    DepthStencilState {
+        depth: Some(DepthState {
+            enable_dynamic: false,
+            compare_op: StateMode::Fixed(CompareOp::LessOrEqual),
+            write_enable: StateMode::Fixed(true),
+        }),
+        ..DepthStencilState::disabled()
+    }
+}
+
+/// Lines on the GPU, as the two ends of each in turn.
+pub(crate) type Lines = Arc<DeviceLocalBuffer<[Vertex]>>;
+
+/// Sends `data` over to memory of the GPU's own, to be drawn from as vertices, and waits until
+/// it is there. The GPU reads vertices faster from its own memory, which the CPU can't write
+/// to, so they are written into a staging buffer first and copied over from it.
+///
+/// This is for vertices drawn frame after frame that don't change, such as the grid. Those
+/// that change from one frame to the next are better off left where the CPU writes them.
+pub(crate) fn upload_static<T>(
+    device: &Arc<Device>,
+    queue: &Arc<Queue>,
+    data: &[T],
+) -> Arc<DeviceLocalBuffer<[T]>>
+where
+    T: Copy + Send + Sync + 'static,
+{
+    let staging = CpuAccessibleBuffer::from_iter(
+        device.clone(),
+        BufferUsage::transfer_source(),
+        false,
+        data.iter().copied(),
+    )
+    .unwrap();
+    let buffer = DeviceLocalBuffer::array(
+        device.clone(),
+        data.len() as DeviceSize,
+        BufferUsage::vertex_buffer_transfer_destination(),
+        [queue.family()],
+    )
+    .unwrap();
+
+    let mut builder = AutoCommandBufferBuilder::primary(
+        device.clone(),
+        queue.family(),
+        CommandBufferUsage::OneTimeSubmit,
+    )
+    .unwrap();
+    builder.copy_buffer(staging, buffer.clone()).unwrap();
+    builder
+        .build()
+        .unwrap()
+        .execute(queue.clone())
+        .unwrap()
+        .then_signal_fence_and_flush()
+        .unwrap()
+        .wait(None)
+        .unwrap();
+    buffer
+}
+
+/// Something drawn in a frame, with `pipeline` and the descriptor sets `sets`: first the
+/// projection, then the texture. It is drawn `depth` back, the [`Layer::depth`] of the layer
+/// it is in.
+pub(crate) struct Batch<'a> {
+    pub(crate) pipeline: &'a Arc<GraphicsPipeline>,
+    pub(crate) sets: (Arc<PersistentDescriptorSet>, Arc<PersistentDescriptorSet>),
+    pub(crate) depth: f32,
+    /// The shape of the cells of its meshes.
+    pub(crate) shape: CellShape,
+    pub(crate) draw: Draw<'a>,
+}
+
+pub(crate) enum Draw<'a> {
+    Mesh(&'a Mesh),
+    /// Lines `width` pixels wide, or as wide as the pipeline has them when it is `None`.
+    Lines {
+        lines: Lines,
+        width: Option<f32>,
+    },
+}
+
+/// The buffers meshes are drawn from. Meshes change from frame to frame, as the snake changes
+/// length as it eats, so vertices, indices and instances are sub-allocated from a pool each
+/// frame rather than living in a buffer of a fixed size. Only the square every instance is
+/// drawn as stays the same, and is uploaded to the GPU's own memory once.
+///
+/// What a frame takes from the pools is kept by the command buffers drawing from it, and so by
+/// the future of the frame, and goes back to the pools to be used again once the GPU is done
+/// with it. The pools only grow when a frame needs more than is free, so once the biggest
+/// meshes were drawn, they stay the same size: `cargo run --example geometry` draws a thousand
+/// frames of a big board to check that they do.
+pub(crate) struct FrameGeometry {
+    vertices: CpuBufferPool<Vertex>,
+    indices: CpuBufferPool<u32>,
+    instances: CpuBufferPool<Instance>,
+    unit_quad: Arc<DeviceLocalBuffer<[Corner]>>,
+}
+
+impl FrameGeometry {
+    /// The buffers of `device`, with the square uploaded through `queue`.
+    pub(crate) fn new(device: &Arc<Device>, queue: &Arc<Queue>) -> FrameGeometry {
+        FrameGeometry {
+            vertices: CpuBufferPool::vertex_buffer(device.clone()),
+            indices: CpuBufferPool::new(device.clone(), BufferUsage::index_buffer()),
+            instances: CpuBufferPool::vertex_buffer(device.clone()),
+            unit_quad: upload_static(
+                device,
+                queue,
+                &UNIT_QUAD.map(|position| Corner { position }),
+            ),
+        }
+    }
+
+    /// Copies `data` into the next free chunk of `pool`, for a frame to draw from.
+    pub(crate) fn upload<T>(
+        pool: &CpuBufferPool<T>,
+        data: &[T],
+    ) -> Arc<CpuBufferPoolChunk<T, Arc<StdMemoryPool>>>
+    where
+        T: Copy + Send + Sync + 'static,
+    {
+        pool.chunk(data.iter().copied()).unwrap()
+    }
+
+    /// How many bytes the pools take altogether, free or not.
+    pub(crate) fn capacity(&self) -> DeviceSize {
+        fn bytes<T>(pool: &CpuBufferPool<T>) -> DeviceSize {
+            pool.capacity() * mem::size_of::<T>() as DeviceSize
+        }
+        bytes(&self.vertices) + bytes(&self.indices) + bytes(&self.instances)
+    }
+
+    /// Records drawing `batches`, one after the other, so that each is drawn over the ones
+    /// before it, with food `time` seconds into its [`Pulse`](crate::pulse::Pulse). A pipeline
+    /// is only bound again when it changes from one batch to the next.
+    pub(crate) fn record<L>(
+        &self,
+        builder: &mut AutoCommandBufferBuilder<L>,
+        batches: &[Batch],
+        time: f32,
+    ) {
+        let mut bound: Option<&Arc<GraphicsPipeline>> = None;
+        for batch in batches {
+            if !bound.is_some_and(|pipeline| Arc::ptr_eq(pipeline, batch.pipeline)) {
+                builder.bind_pipeline_graphics(batch.pipeline.clone());
+                bound = Some(batch.pipeline);
+            }
+            builder.bind_descriptor_sets(
+                PipelineBindPoint::Graphics,
+                batch.pipeline.layout().clone(),
+                0,
+                batch.sets.clone(),
+            );
+            let params = DrawParams::new(WHITE)
+                .with_time(time)
+                .with_depth(batch.depth)
+                .with_shape(batch.shape);
+            match &batch.draw {
+                Draw::Mesh(mesh) => self.draw(builder, batch.pipeline, mesh, params),
+                Draw::Lines { lines, width } => {
+                    if let Some(width) = width {
+                        builder.set_line_width(*width);
+                    }
+                    builder
+                        .bind_vertex_buffers(0, lines.clone())
+                        .push_constants(batch.pipeline.layout().clone(), 0, push_constants(params))
+                        .draw(lines.len() as u32, 1, 0, 0)
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    /// Records drawing `mesh` with `pipeline`, whose descriptor sets are bound already, in one
+    /// draw call with `params`.
+    fn draw<L>(
+        &self,
+        builder: &mut AutoCommandBufferBuilder<L>,
+        pipeline: &Arc<GraphicsPipeline>,
+        mesh: &Mesh,
+        params: DrawParams,
+    ) {
+        let Some(count) = mesh.draw_count() else {
+            return;
+        };
+        match mesh.kind() {
+            MeshKind::Triangles | MeshKind::Indexed => {
+                let vertex_buffer = FrameGeometry::upload(&self.vertices, mesh.vertices());
+                builder
+                    .bind_vertex_buffers(0, vertex_buffer)
+                    .push_constants(pipeline.layout().clone(), 0, push_constants(params));
+                if mesh.kind() == MeshKind::Indexed {
+                    let index_buffer = FrameGeometry::upload(&self.indices, mesh.indices());
+                    builder
+                        .bind_index_buffer(index_buffer)
+                        .draw_indexed(count, 1, 0, 0, 0)
+                        .unwrap();
+                } else {
+                    builder.draw(count, 1, 0, 0).unwrap();
+                }
+            }
+            MeshKind::Instanced => {
+                let instance_buffer = FrameGeometry::upload(&self.instances, mesh.instances());
+                builder
+                    .bind_vertex_buffers(0, (self.unit_quad.clone(), instance_buffer))
+                    .push_constants(pipeline.layout().clone(), 0, push_constants(params))
+                    .draw(UNIT_QUAD.len() as u32, count, 0, 0)
+                    .unwrap();
+            }
+        }
+    }
+}
+
+/// Records drawing `batches` in `subpass` into a secondary command buffer of their own, the
+/// way [`FrameGeometry::record`] does, to be executed from the frame's primary one.
+fn record_layer(
+    queue: &Arc<Queue>,
+    geometry: &FrameGeometry,
+    subpass: Subpass,
+    usage: CommandBufferUsage,
+    viewport: &Viewport,
+    batches: &[Batch],
+    time: f32,
+) -> Arc<SecondaryAutoCommandBuffer> {
+    let mut builder = AutoCommandBufferBuilder::secondary_graphics(
+        queue.device().clone(),
+        queue.family(),
+        usage,
+        subpass,
+    )
+    .unwrap();
+    // Secondary command buffers start with no dynamic state of their own.
+    builder.set_viewport(0, [viewport.clone()]);
+    geometry.record(&mut builder, batches, time);
+    Arc::new(builder.build().unwrap())
+}
+
+/// What the text over the board is recorded from: while it stays the same, so does what is
+/// drawn.
+struct UiKey {
+    hud: Mesh,
+    dimensions: [f32; 2],
+    pipeline: Arc<GraphicsPipeline>,
+    projection: Arc<PersistentDescriptorSet>,
+}
+
+impl PartialEq for UiKey {
+    fn eq(&self, other: &UiKey) -> bool {
+        // The pipeline and the projection are made again whenever they change, and the ones
+        // kept here are kept alive, so they are the same only if they are the same objects.
+        self.hud == other.hud
+            && self.dimensions == other.dimensions
+            && Arc::ptr_eq(&self.pipeline, &other.pipeline)
+            && Arc::ptr_eq(&self.projection, &other.projection)
+    }
+}
+
+/// Everything a whole frame is recorded from but the swapchain image it is drawn into: while it
+/// stays the same, so does what is drawn.
+struct FrameKey {
+    mesh: Mesh,
+    particles: Mesh,
+    /// The grid and how wide its lines are, when it is shown.
+    lines: Option<(Lines, Option<f32>)>,
+    mesh_pipeline: Arc<GraphicsPipeline>,
+    line_pipeline: Arc<GraphicsPipeline>,
+    projection: Arc<PersistentDescriptorSet>,
+    time: f32,
+    shape: CellShape,
+    dimensions: [f32; 2],
+    background: [f32; 4],
+    ui: Arc<SecondaryAutoCommandBuffer>,
+}
+
+impl PartialEq for FrameKey {
+    fn eq(&self, other: &FrameKey) -> bool {
+        // As with the text, everything kept behind an `Arc` is made again when it changes.
+        let same_lines = match (&self.lines, &other.lines) {
+            (Some((lines, width)), Some((other_lines, other_width))) => {
+                Arc::ptr_eq(lines, other_lines) && width == other_width
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        self.mesh == other.mesh
+            && self.particles == other.particles
+            && same_lines
+            && Arc::ptr_eq(&self.mesh_pipeline, &other.mesh_pipeline)
+            && Arc::ptr_eq(&self.line_pipeline, &other.line_pipeline)
+            && Arc::ptr_eq(&self.projection, &other.projection)
+            && self.time == other.time
+            && self.shape == other.shape
+            && self.dimensions == other.dimensions
+            && self.background == other.background
+            && Arc::ptr_eq(&self.ui, &other.ui)
+    }
+}
+
+/// The descriptor set handing `wanted` to the vertex shaders, made again only when `current`
+/// was made for another projection.
+pub(crate) fn projection_set(
+    current: &mut Option<(Projection, Arc<PersistentDescriptorSet>)>,
+    wanted: Projection,
+    pool: &CpuBufferPool<Projection>,
+    pipeline: &Arc<GraphicsPipeline>,
+) -> Arc<PersistentDescriptorSet> {
+    match current {
+        Some((projection, set)) if *projection == wanted => set.clone(),
+        _ => {
+            let buffer = pool.next(wanted).unwrap();
+            let mut set = PersistentDescriptorSet::start(
+                pipeline.layout().descriptor_set_layouts()[0].clone(),
+            );
+            set.add_buffer(buffer).unwrap();
+            let set = set.build().unwrap();
+            *current = Some((wanted, set.clone()));
+            set
+        }
+    }
+}
+
+/// The descriptor set handing `texture` to the fragment shaders, sampled with `filter`, and
+/// the future it is uploaded by. A texture the GPU can't take is left out, drawing everything
+/// that would use it in its color alone, from a texture of a single white texel.
+pub(crate) fn texture_set(
+    queue: &Arc<Queue>,
+    pipeline: &Arc<GraphicsPipeline>,
+    texture: &Texture,
+    filter: Filter,
+) -> (Arc<PersistentDescriptorSet>, impl GpuFuture) {
+    let (image, uploaded) = match upload_texture(queue, texture) {
+        Ok(uploaded) => uploaded,
+        Err(e) => {
+            eprintln!("Failed to load a texture: {}", e);
+            upload_texture(queue, &Texture::white()).unwrap()
+        }
+    };
+    let sampler = Sampler::new(
+        queue.device().clone(),
+        filter,
+        filter,
+        MipmapMode::Nearest,
+        SamplerAddressMode::ClampToEdge,
+        SamplerAddressMode::ClampToEdge,
+        SamplerAddressMode::ClampToEdge,
+        0.0,
+        1.0,
+        0.0,
+        0.0,
+    )
+    .unwrap();
+    let mut set =
+        PersistentDescriptorSet::start(pipeline.layout().descriptor_set_layouts()[1].clone());
+    set.add_sampled_image(image, sampler).unwrap();
+    (set.build().unwrap(), uploaded)
+}
+
+/// Sends `texture` over to the GPU, as an image the shaders can sample. It is only there once
+/// the future returned along with it is done.
+fn upload_texture(
+    queue: &Arc<Queue>,
+    texture: &Texture,
+) -> Result<
+    (
+        Arc<ImageView<ImmutableImage>>,
+        impl GpuFuture + Send + Sync + 'static,
+    ),
+    TextureError,
+> {
+    let limit = queue
+        .device()
+        .physical_device()
+        .properties()
+        .max_image_dimension2_d;
+    texture.fits(limit)?;
+
+    let (image, uploaded) = ImmutableImage::from_iter(
+        texture.pixels().iter().copied(),
+        ImageDimensions::Dim2d {
+            width: texture.width(),
+            height: texture.height(),
+            array_layers: 1,
+        },
+        MipmapsCount::One,
+        Format::R8G8B8A8_SRGB,
+        queue.clone(),
+    )
+    .unwrap();
+    Ok((ImageView::new(image).unwrap(), uploaded))
+}
+
+/// `params` the way the shaders take them.
+fn push_constants(params: DrawParams) -> vs::ty::PushConstants {
+    vs::ty::PushConstants {
+        color: params.color,
+        offset: params.offset,
+        scale: params.scale,
+        time: params.time,
+        depth: params.depth,
+        corner_radius: params.corner_radius,
+        softness: params.softness,
+    }
+}
+
+/// Writes the level being edited into `mesh`, the way [`board_vertices`] draws the board the
+/// level makes, with `cursor` lit up.
+///
+/// The mesh is cleared first so the same allocations can be reused every frame.
+fn editor_vertices(
+    editor: &Editor,
+    cursor: Option<GridPos>,
+    palette: Palette,
+    atlas: &Atlas,
+    mesh: &mut Mesh,
+) {
+    mesh.clear();
+
+    // Which of the cells of the level each tile is drawn on.
+    type Drawn = fn(EditorTile) -> bool;
+    let tiles: [(Tile, Drawn); 5] = [
+        (Tile::Obstacle, |kind| kind == EditorTile::Wall),
+        (Tile::Portal, |kind| matches!(kind, EditorTile::Portal(_))),
+        (Tile::Checkpoint, |kind| kind == EditorTile::Checkpoint),
+        (Tile::Food(FoodKind::Normal), |kind| {
+            kind == EditorTile::FoodHint
+        }),
+        (Tile::Snake(0), |kind| {
+            matches!(kind, EditorTile::Start | EditorTile::Body)
+        }),
+    ];
+    for (tile, drawn) in tiles {
+        let look = palette.look(tile);
+        for (cell, _) in editor.cells().filter(|&(_, kind)| drawn(kind)) {
+            mesh.push_sprite(cell, look.inset, look.color, tile_sprite(atlas, tile));
+        }
+    }
+
+    if let Some(cell) = cursor {
+        let look = palette.look(Tile::Cursor);
+        mesh.push_cell(cell, look.inset, look.color);
+    }
+}
+
+/// Adds the time `left` on the countdown to `mesh`, as bars across the middle
+/// of a board of `grid` cells: one for each second to go, the last of which shrinks as its
+/// second runs out. Their color comes from `palette`.
+fn countdown_vertices(left: Duration, grid: [u32; 2], palette: Palette, mesh: &mut Mesh) {
+    let slot = grid[0] as f32 / COUNTDOWN.as_secs_f32().ceil();
+    let (top, bottom) = (grid[1] as f32 / 2.0 - 0.5, grid[1] as f32 / 2.0 + 0.5);
+    let seconds = left.as_secs_f32();
+
+    let color = palette.look(Tile::Countdown).color;
+    let mut second = 0.0;
+    while second < seconds {
+        let fill = (seconds - second).min(1.0);
+        let left = second * slot + COUNTDOWN_GAP / 2.0;
+        let right = left + (slot - COUNTDOWN_GAP) * fill;
+        mesh.push_quad(
+            [[left, top], [right, top], [right, bottom], [left, bottom]],
+            color,
+        );
+        second += 1.0;
+    }
+}
+
+/// The score, or scores, written in the corner of the window.
+fn score_text(game: &Game) -> String {
+    let mode = game.config().mode;
+    let scores = game
+        .players()
+        .iter()
+        .map(|player| mode.format_score(player.score()))
+        .collect::<Vec<_>>()
+        .join("  ");
+    format!("{}: {}", mode.score_label(), scores)
+}
+
+/// The score, or scores, with any combo going and the lives left when there is more than one,
+/// followed by how the round ended once it has.
+fn window_title(game: &Game) -> String {
+    let mode = game.config().mode;
+    let scores = game
+        .players()
+        .iter()
+        .map(|player| match player.multiplier() {
+            1 => mode.format_score(player.score()),
+            multiplier => format!("{} (x{})", mode.format_score(player.score()), multiplier),
+        })
+        .collect::<Vec<_>>()
+        .join(" - ");
+    let label = mode.score_label();
+    let mut lives = String::new();
+    if game.config().lives > 1 {
+        let left = game
+            .players()
+            .iter()
+            .map(|player| player.lives().to_string())
+            .collect::<Vec<_>>();
+        lives = format!(" - Lives: {}", left.join(" - "));
+    }
+    if let Some(tick) = game.players()[0].respawn_at() {
+        lives += &format!(" - Life lost! Back in {}", tick - game.ticks());
+    }
+    let reversed = game.effect_ticks_left(0, Effect::ReversedControls);
+    if reversed > 0 {
+        lives += &format!(" - Controls reversed for {}", reversed);
+    }
+
+    match game.round_end() {
+        None => match game.time_left() {
+            Some(left) => {
+                format!(
+                    "Snake - {}: {}{} - Time: {}s",
+                    label,
+                    scores,
+                    lives,
+                    left.as_secs_f32().ceil()
+                )
+            }
+            None => format!("Snake - {}: {}{}", label, scores, lives),
+        },
+        Some(RoundEnd::Died) => format!("Snake - Game over! {}: {}", label, scores),
+        Some(RoundEnd::Winner(i)) => {
+            format!("Snake - Player {} wins! {}: {}", i + 1, label, scores)
+        }
+        Some(RoundEnd::Tie) => format!("Snake - It's a tie! {}: {}", label, scores),
+        Some(RoundEnd::TimeUp) => format!("Snake - Time's up! {}: {}", label, scores),
+        Some(RoundEnd::Won) => format!("Snake - You filled the board! {}: {}", label, scores),
+    }
+}
+
+/// How many vertices, indices or instances `mesh` has, whichever it is drawn from.
+fn mesh_counts(mesh: &Mesh) -> String {
+    let counted = match mesh.kind() {
+        MeshKind::Triangles => "vertices",
+        MeshKind::Indexed => "indices",
+        MeshKind::Instanced => "instances",
+    };
+    format!("{} {}", mesh.len(), counted)
+}
+
+/// Makes `window` fill the screen, borderless, or puts it back where it was before it did.
+fn toggle_fullscreen(window: &Window, mode: &mut WindowMode) {
+    let current = Placement {
+        position: window
+            .outer_position()
+            .ok()
+            .map(|position| [position.x, position.y]),
+        size: window.inner_size().into(),
+    };
+    match mode.toggle(current) {
+        None => window.set_fullscreen(Some(Fullscreen::Borderless(None))),
+        Some(windowed) => {
+            window.set_fullscreen(None);
+            window.set_inner_size(PhysicalSize::new(windowed.size[0], windowed.size[1]));
+            if let Some([x, y]) = windowed.position {
+                window.set_outer_position(PhysicalPosition::new(x, y));
+            }
+        }
+    }
+}
+
+fn window_size_dependent_setup(
+    images: &[Arc<SwapchainImage<Window>>],
+    render_pass: Arc<RenderPass>,
+    samples: u32,
+    depth_format: Format,
+    viewport: &mut Viewport,
+) -> Vec<Arc<Framebuffer>> {
+    let dimensions = images[0].dimensions().width_height();
+    viewport.dimensions = [dimensions[0] as f32, dimensions[1] as f32];
+
+    // Every frame draws into the same multisampled image, as it is only needed until it is
+    // resolved.
+    let multisampled = (samples > 1).then(|| {
+        let image = AttachmentImage::transient_multisampled(
+            render_pass.device().clone(),
+            dimensions,
+            samples.try_into().unwrap(),
+            images[0].format(),
+        )
+        .unwrap();
+        ImageView::new(image).unwrap()
+    });
+    // And into the same depth buffer, cleared at the start of every frame.
+    let depth = AttachmentImage::transient_multisampled(
+        render_pass.device().clone(),
+        dimensions,
+        samples.try_into().unwrap(),
+        depth_format,
+    )
+    .unwrap();
+    let depth = ImageView::new(depth).unwrap();
+
+    images
+        .iter()
+        .map(|image| {
+            let view = ImageView::new(image.clone()).unwrap();
+            let mut framebuffer = Framebuffer::start(render_pass.clone());
+            if let Some(multisampled) = &multisampled {
+                framebuffer = framebuffer.add(multisampled.clone()).unwrap();
+            }
+            framebuffer
+                .add(view)
+                .unwrap()
+                .add(depth.clone())
+                .unwrap()
+                .build()
+                .unwrap()
+        })
+        .collect::<Vec<_>>()
+}